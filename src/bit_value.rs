@@ -0,0 +1,160 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::value::convert::{FromValue, FromValueError};
+
+use std::convert::TryFrom;
+
+use crate::Value;
+
+/// Value of a `BIT(n)` column as an `u64`.
+///
+/// MySql gives out `BIT` values as bytes in both protocols. Bytes are big-endian, i.e.
+/// the first byte contains the most significant bits, and there are `(n + 7) / 8` of them.
+/// This wrapper takes care of the byte order, so that bit `0` of the `u64` is the least
+/// significant bit of the column (e.g. `b'110'` is `BitValue(6)`).
+///
+/// Columns up to `BIT(64)` (the maximum width supported by MySql) are supported – conversion
+/// from a value of more than eight bytes will fail. `BitValue` is bound as an eight byte
+/// big-endian string, that is accepted by a column of any width, if the value fits.
+///
+/// ```rust
+/// # use mysql_async::test_misc::get_opts;
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// use mysql_async::{prelude::*, BitValue, Conn};
+///
+/// let mut conn = Conn::new(get_opts()).await?;
+///
+/// let flags: Option<BitValue> = conn.query_first("SELECT b'1000000011'").await?;
+/// let flags = flags.unwrap();
+/// assert_eq!(flags, BitValue(0b10_0000_0011));
+/// assert!(flags.bit(0) && flags.bit(9) && !flags.bit(2));
+/// # conn.disconnect().await }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitValue(pub u64);
+
+impl BitValue {
+    /// Returns the wrapped `u64`.
+    pub fn into_inner(self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if the given bit is set (bit `0` is the least significant one).
+    ///
+    /// Returns `false` for bits outside of the `0..64` range.
+    pub fn bit(&self, index: u32) -> bool {
+        index < u64::BITS && self.0 & (1 << index) != 0
+    }
+}
+
+impl From<u64> for BitValue {
+    fn from(value: u64) -> Self {
+        BitValue(value)
+    }
+}
+
+impl From<BitValue> for u64 {
+    fn from(value: BitValue) -> Self {
+        value.0
+    }
+}
+
+impl From<BitValue> for Value {
+    fn from(value: BitValue) -> Self {
+        Value::Bytes(value.0.to_be_bytes().to_vec())
+    }
+}
+
+impl TryFrom<Value> for BitValue {
+    type Error = FromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(ref bytes) if bytes.len() <= 8 => Ok(BitValue(
+                bytes
+                    .iter()
+                    .fold(0_u64, |acc, byte| (acc << 8) | u64::from(*byte)),
+            )),
+            // e.g. `b'101' + 0`
+            Value::UInt(x) => Ok(BitValue(x)),
+            Value::Int(x) if x >= 0 => Ok(BitValue(x as u64)),
+            _ => Err(FromValueError(value)),
+        }
+    }
+}
+
+impl FromValue for BitValue {
+    type Intermediate = BitValue;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        from_value, prelude::*, test_misc::get_opts, BitValue, Conn, FromValueError, Value,
+    };
+
+    #[test]
+    fn should_convert_bit_value() {
+        assert_eq!(from_value::<BitValue>(Value::Bytes(vec![])), BitValue(0));
+        assert_eq!(
+            from_value::<BitValue>(Value::Bytes(vec![0b110])),
+            BitValue(6)
+        );
+        assert_eq!(
+            from_value::<BitValue>(Value::Bytes(vec![0x01, 0x02])),
+            BitValue(0x0102)
+        );
+        assert_eq!(
+            from_value::<BitValue>(Value::Bytes(vec![0xff; 8])),
+            BitValue(u64::MAX)
+        );
+        assert_eq!(from_value::<BitValue>(Value::UInt(5)), BitValue(5));
+
+        for invalid in [Value::Bytes(vec![0; 9]), Value::Int(-1), Value::NULL] {
+            assert_eq!(
+                BitValue::get_intermediate(invalid.clone()),
+                Err(FromValueError(invalid))
+            );
+        }
+
+        assert_eq!(
+            Value::from(BitValue(0x0102)),
+            Value::Bytes(vec![0, 0, 0, 0, 0, 0, 0x01, 0x02])
+        );
+
+        let value = BitValue(0b101);
+        assert!(value.bit(0) && !value.bit(1) && value.bit(2));
+        assert!(!value.bit(64));
+        assert!(BitValue(u64::MAX).bit(63));
+    }
+
+    #[tokio::test]
+    async fn should_roundtrip_bit_value() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_bit (b3 BIT(3), b12 BIT(12), b64 BIT(64))")
+            .await?;
+
+        let values = (BitValue(0b101), BitValue(0x801), BitValue(u64::MAX - 1));
+        conn.exec_drop("INSERT INTO tmp_bit VALUES (?, ?, ?)", values)
+            .await?;
+        conn.query_drop("INSERT INTO tmp_bit VALUES (b'101', b'100000000001', ~1)")
+            .await?;
+
+        let query = "SELECT b3, b12, b64 FROM tmp_bit";
+        let binary: Vec<(BitValue, BitValue, BitValue)> = conn.exec(query, ()).await?;
+        assert_eq!(binary, vec![values, values]);
+
+        let text: Vec<(BitValue, BitValue, BitValue)> = conn.query(query).await?;
+        assert_eq!(text, vec![values, values]);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
@@ -13,7 +13,8 @@ use std::{mem::take, ops::Deref, sync::Arc};
 pub struct BufferPool {
     buffer_size_cap: usize,
     buffer_init_cap: usize,
-    pool: ArrayQueue<Vec<u8>>,
+    /// `None` if pooling is disabled (i.e. `MYSQL_ASYNC_BUFFER_POOL_CAP` is `0`).
+    pool: Option<ArrayQueue<Vec<u8>>>,
 }
 
 impl BufferPool {
@@ -33,8 +34,17 @@ impl BufferPool {
             .and_then(|x| x.parse().ok())
             .unwrap_or(0);
 
+        Self::with_caps(pool_cap, buffer_size_cap, buffer_init_cap)
+    }
+
+    /// Creates a pool, that doesn't retain buffers (see [`crate::BufferStrategy::Unpooled`]).
+    pub fn unpooled() -> Self {
+        Self::with_caps(0, 0, 0)
+    }
+
+    fn with_caps(pool_cap: usize, buffer_size_cap: usize, buffer_init_cap: usize) -> Self {
         Self {
-            pool: ArrayQueue::new(pool_cap),
+            pool: (pool_cap > 0).then(|| ArrayQueue::new(pool_cap)),
             buffer_size_cap,
             buffer_init_cap,
         }
@@ -43,7 +53,8 @@ impl BufferPool {
     pub fn get(self: &Arc<Self>) -> PooledBuf {
         let buf = self
             .pool
-            .pop()
+            .as_ref()
+            .and_then(|pool| pool.pop())
             .unwrap_or_else(|| Vec::with_capacity(self.buffer_init_cap));
         debug_assert_eq!(buf.len(), 0);
         PooledBuf(buf, self.clone())
@@ -56,6 +67,11 @@ impl BufferPool {
     }
 
     fn put(self: &Arc<Self>, mut buf: Vec<u8>) {
+        let Some(pool) = self.pool.as_ref() else {
+            // pooling is disabled, so the buffer is freed immediately
+            return;
+        };
+
         // SAFETY:
         // 1. OK – 0 is always within capacity
         // 2. OK - nothing to initialize
@@ -64,7 +80,7 @@ impl BufferPool {
         buf.shrink_to(self.buffer_size_cap);
 
         // ArrayQueue will make sure to drop the buffer if capacity is exceeded
-        let _ = self.pool.push(buf);
+        let _ = pool.push(buf);
     }
 }
 
@@ -96,3 +112,26 @@ impl Drop for PooledBuf {
         self.1.put(take(&mut self.0))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::BufferPool;
+
+    #[test]
+    fn should_reuse_buffers() {
+        let pool = Arc::new(BufferPool::with_caps(1, 1024, 0));
+        pool.get_with(b"foo");
+        assert_eq!(pool.pool.as_ref().unwrap().len(), 1);
+        assert!(pool.get().as_mut().capacity() >= 3);
+    }
+
+    #[test]
+    fn should_not_retain_buffers_if_disabled() {
+        let pool = Arc::new(BufferPool::with_caps(0, 1024, 0));
+        assert!(pool.pool.is_none());
+        assert_eq!(&*pool.get_with(b"foo"), b"foo");
+        assert_eq!(pool.get().as_mut().capacity(), 0);
+    }
+}
@@ -0,0 +1,263 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use futures_util::stream::{BoxStream, StreamExt};
+
+use std::time::Duration;
+
+use crate::{prelude::Queryable, Conn, Result};
+
+/// Returns the current stage, that reports progress, of a thread of the given connection.
+///
+/// There is no row if there is no such connection and the stage columns are `NULL` if there is
+/// no such stage.
+const ALTER_PROGRESS_QUERY: &str = "\
+    SELECT s.EVENT_NAME, s.WORK_COMPLETED, s.WORK_ESTIMATED \
+    FROM performance_schema.threads t \
+    LEFT JOIN performance_schema.events_stages_current s \
+        ON s.THREAD_ID = t.THREAD_ID AND s.WORK_ESTIMATED IS NOT NULL \
+    WHERE t.PROCESSLIST_ID = ?";
+
+/// Progress of a long-running DDL statement (see [`Conn::watch_alter_progress`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterProgress {
+    stage: String,
+    work_completed: u64,
+    work_estimated: u64,
+}
+
+impl AlterProgress {
+    /// Returns the name of the current stage, e.g. `stage/innodb/alter table (read PK and
+    /// internal sort)`.
+    pub fn stage(&self) -> &str {
+        &self.stage
+    }
+
+    /// Returns the amount of work completed (`WORK_COMPLETED` column).
+    pub fn work_completed(&self) -> u64 {
+        self.work_completed
+    }
+
+    /// Returns the estimated amount of work (`WORK_ESTIMATED` column).
+    ///
+    /// The estimate is revised by the server as the statement executes.
+    pub fn work_estimated(&self) -> u64 {
+        self.work_estimated
+    }
+
+    /// Returns the progress percentage (`0.0..=100.0`).
+    pub fn percentage(&self) -> f64 {
+        if self.work_estimated == 0 {
+            return 0.0;
+        }
+        (self.work_completed as f64 * 100.0 / self.work_estimated as f64).min(100.0)
+    }
+}
+
+impl Conn {
+    /// Watches the progress of a DDL statement (e.g. `ALTER TABLE`), that runs on another
+    /// connection with the given id (see [`Conn::id`]), using this connection as a side
+    /// connection.
+    ///
+    /// The stream polls `performance_schema.events_stages_current` every `interval`
+    /// and yields the current stage of the watched connection, if it reports progress.
+    /// The stage is correlated with the connection via `performance_schema.threads`.
+    ///
+    /// The stream ends if the watched connection no longer exists or if its progress stage
+    /// is over, i.e. there is no stage after at least one was seen. Note that it doesn't end
+    /// if the statement finishes before the first poll, so you may want to stop polling it
+    /// as soon as the statement is done (e.g. via `StreamExt::take_until`).
+    ///
+    /// Progress is only reported if the corresponding stage instruments and the stages consumer
+    /// are enabled (requires the `UPDATE` privilege on `performance_schema`), e.g. for InnoDB:
+    ///
+    /// ```sql
+    /// UPDATE performance_schema.setup_instruments SET ENABLED = 'YES'
+    ///     WHERE NAME LIKE 'stage/innodb/alter%';
+    /// UPDATE performance_schema.setup_consumers SET ENABLED = 'YES'
+    ///     WHERE NAME LIKE '%stages%';
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use futures_util::StreamExt;
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let mut watcher = Conn::new(get_opts()).await?;
+    ///
+    /// let id = conn.id();
+    /// let alter = conn.query_drop("ALTER TABLE tbl ENGINE = InnoDB");
+    /// let mut progress = watcher
+    ///     .watch_alter_progress(id, Duration::from_millis(500))
+    ///     .take_until(alter);
+    /// while let Some(progress) = progress.next().await {
+    ///     println!("{:.1}%", progress?.percentage());
+    /// }
+    /// match progress.take_future() {
+    ///     Some(alter) => alter.await?,
+    ///     None => progress.take_result().expect("the statement is done")?,
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn watch_alter_progress(
+        &mut self,
+        connection_id: u32,
+        interval: Duration,
+    ) -> BoxStream<'_, Result<AlterProgress>> {
+        futures_util::stream::try_unfold((self, false, true), move |(conn, seen, first)| {
+            async move {
+                if !first {
+                    tokio::time::sleep(interval).await;
+                }
+
+                loop {
+                    let row: Option<(Option<String>, Option<u64>, Option<u64>)> = conn
+                        .exec_first(ALTER_PROGRESS_QUERY, (connection_id,))
+                        .await?;
+
+                    match row {
+                        Some((Some(stage), work_completed, Some(work_estimated))) => {
+                            let progress = AlterProgress {
+                                stage,
+                                work_completed: work_completed.unwrap_or_default(),
+                                work_estimated,
+                            };
+                            return Ok(Some((progress, (conn, true, false))));
+                        }
+                        // the stage is over
+                        Some(_) if seen => return Ok(None),
+                        // the stage isn't started yet
+                        Some(_) => tokio::time::sleep(interval).await,
+                        // the connection is gone
+                        None => return Ok(None),
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use std::time::Duration;
+
+    use super::AlterProgress;
+    use crate::{prelude::*, test_misc::get_opts, Conn};
+
+    #[test]
+    fn should_compute_alter_progress_percentage() {
+        let progress = |work_completed, work_estimated| AlterProgress {
+            stage: "stage/innodb/alter table (read PK and internal sort)".into(),
+            work_completed,
+            work_estimated,
+        };
+        assert_eq!(progress(0, 0).percentage(), 0.0);
+        assert_eq!(progress(25, 100).percentage(), 25.0);
+        assert_eq!(progress(120, 100).percentage(), 100.0);
+    }
+
+    #[tokio::test]
+    async fn should_watch_alter_progress() -> crate::Result<()> {
+        const INSTRUMENTS: &str = "SELECT NAME, ENABLED FROM performance_schema.setup_instruments \
+            WHERE NAME LIKE 'stage/innodb/alter%'";
+        const CONSUMERS: &str = "SELECT NAME, ENABLED FROM performance_schema.setup_consumers \
+            WHERE NAME LIKE '%stages%'";
+
+        let mut conn = Conn::new(get_opts()).await?;
+        let mut watcher = Conn::new(get_opts()).await?;
+
+        let performance_schema: Option<bool> =
+            watcher.query_first("SELECT @@performance_schema").await?;
+        if performance_schema != Some(true) {
+            // there is nothing to watch
+            conn.disconnect().await?;
+            return watcher.disconnect().await;
+        }
+
+        let instruments: Vec<(String, String)> = watcher.query(INSTRUMENTS).await?;
+        let consumers: Vec<(String, String)> = watcher.query(CONSUMERS).await?;
+        watcher
+            .query_drop(
+                "UPDATE performance_schema.setup_instruments SET ENABLED = 'YES' \
+                WHERE NAME LIKE 'stage/innodb/alter%'",
+            )
+            .await?;
+        watcher
+            .query_drop(
+                "UPDATE performance_schema.setup_consumers SET ENABLED = 'YES' \
+                WHERE NAME LIKE '%stages%'",
+            )
+            .await?;
+
+        let result = watch_alter(&mut conn, &mut watcher).await;
+
+        // restore previous settings
+        watcher
+            .exec_batch(
+                "UPDATE performance_schema.setup_instruments SET ENABLED = ? WHERE NAME = ?",
+                instruments
+                    .into_iter()
+                    .map(|(name, enabled)| (enabled, name)),
+            )
+            .await?;
+        watcher
+            .exec_batch(
+                "UPDATE performance_schema.setup_consumers SET ENABLED = ? WHERE NAME = ?",
+                consumers.into_iter().map(|(name, enabled)| (enabled, name)),
+            )
+            .await?;
+
+        let progress = result?;
+        assert!(!progress.is_empty());
+        for item in progress {
+            assert!(item.stage().starts_with("stage/"));
+            assert!((0.0..=100.0).contains(&item.percentage()));
+        }
+
+        conn.query_drop("DROP TABLE alter_progress").await?;
+        conn.disconnect().await?;
+        watcher.disconnect().await?;
+        Ok(())
+    }
+
+    /// Runs a long enough `ALTER TABLE` on `conn` and collects its progress using `watcher`.
+    async fn watch_alter(conn: &mut Conn, watcher: &mut Conn) -> crate::Result<Vec<AlterProgress>> {
+        conn.query_drop("DROP TABLE IF EXISTS alter_progress")
+            .await?;
+        conn.query_drop("CREATE TABLE alter_progress (id INT AUTO_INCREMENT PRIMARY KEY, x TEXT)")
+            .await?;
+        conn.query_drop("INSERT INTO alter_progress (x) VALUES (REPEAT('x', 100))")
+            .await?;
+        for _ in 0..16 {
+            conn.query_drop("INSERT INTO alter_progress (x) SELECT x FROM alter_progress")
+                .await?;
+        }
+
+        let id = conn.id();
+        let alter = conn.query_drop("ALTER TABLE alter_progress ENGINE = InnoDB");
+        let mut stream = watcher
+            .watch_alter_progress(id, Duration::from_millis(10))
+            .take_until(alter);
+
+        let mut progress = Vec::new();
+        while let Some(item) = stream.next().await {
+            progress.push(item?);
+        }
+        match stream.take_future() {
+            Some(alter) => alter.await?,
+            None => stream.take_result().unwrap()?,
+        }
+
+        Ok(progress)
+    }
+}
@@ -7,10 +7,11 @@
 // modified, or distributed except according to those terms.
 
 use futures_core::ready;
+use futures_sink::Sink;
 use mysql_common::{
     binlog::{
         consts::{BinlogVersion::Version4, EventType},
-        events::{Event, TableMapEvent, TransactionPayloadEvent},
+        events::{Event, EventData, RotateEvent, TableMapEvent, TransactionPayloadEvent},
         EventStreamReader,
     },
     io::ParseBuf,
@@ -24,12 +25,25 @@ use std::{
     task::{Context, Poll},
 };
 
+use crate::{
+    buffer_pool::PooledBuf, error::DriverError, io::ReadPacket, Conn, Error, IoError, Result,
+};
 use crate::{connection_like::Connection, queryable::Queryable};
-use crate::{error::DriverError, io::ReadPacket, Conn, Error, IoError, Result};
 
-use self::request::BinlogStreamRequest;
+use self::request::{BinlogStreamRequest, EventFilter};
 
 pub mod request;
+mod resilient;
+mod row_change;
+
+pub use self::resilient::{BinlogStreamEvent, ReconnectPolicy, ResilientBinlogStream};
+pub use self::row_change::RowChange;
+
+/// First byte of the semi-sync header and of the semi-sync acknowledgement packet.
+const SEMI_SYNC_MAGIC: u8 = 0xef;
+
+/// Semi-sync header flag that requests an acknowledgement.
+const SEMI_SYNC_ACK_REQ: u8 = 0x01;
 
 impl super::Conn {
     /// Turns this connection into a binlog stream.
@@ -38,11 +52,34 @@ impl super::Conn {
     /// If the request’s filename is empty, the server will send the binlog-stream of the first known binlog.
     pub async fn get_binlog_stream(
         mut self,
-        request: BinlogStreamRequest<'_>,
+        mut request: BinlogStreamRequest<'_>,
     ) -> Result<BinlogStream> {
+        let filter = std::mem::take(&mut request.filter);
+        let semi_sync = request.semi_sync && self.enable_semi_sync().await?;
         self.request_binlog(request).await?;
 
-        Ok(BinlogStream::new(self))
+        Ok(BinlogStream::new(self, filter, semi_sync))
+    }
+
+    /// Registers this connection as a semi-sync replica, if the server supports it.
+    ///
+    /// Returns `false` if there is no semi-sync plugin on the server.
+    async fn enable_semi_sync(&mut self) -> crate::Result<bool> {
+        let plugin: Option<(String, String)> = self
+            .query_first(
+                "SHOW VARIABLES WHERE Variable_name IN \
+                    ('rpl_semi_sync_master_enabled', 'rpl_semi_sync_source_enabled')",
+            )
+            .await?;
+
+        if plugin.is_none() {
+            return Ok(false);
+        }
+
+        // `rpl_semi_sync_slave` is checked by the older plugin and by MariaDB
+        self.query_drop("SET @rpl_semi_sync_slave = 1, @rpl_semi_sync_replica = 1")
+            .await?;
+        Ok(true)
     }
 
     async fn register_as_slave(
@@ -74,15 +111,90 @@ pub struct BinlogStream {
     // TODO: Use 'static reader here (requires impl on the mysql_common side).
     /// Uncompressed Transaction_payload_event we are iterating over (if any).
     tpe: Option<Cursor<Vec<u8>>>,
+    filter: EventFilter,
+    /// Whether events are prefixed with the semi-sync header.
+    semi_sync: bool,
+    /// Current binlog file name (tracked for semi-sync acknowledgements).
+    filename: Vec<u8>,
+    /// Semi-sync acknowledgement to send before reading the next packet.
+    ack: Option<PooledBuf>,
+    /// Whether the acknowledgement is sent but not yet flushed.
+    flush_ack: bool,
 }
 
 impl BinlogStream {
     /// `conn` is a `Conn` with `request_binlog` executed on it.
-    pub(super) fn new(conn: Conn) -> Self {
+    pub(super) fn new(conn: Conn, filter: EventFilter, semi_sync: bool) -> Self {
         BinlogStream {
             read_packet: ReadPacket::new(conn),
             esr: EventStreamReader::new(Version4),
             tpe: None,
+            filter,
+            semi_sync,
+            filename: Vec::new(),
+            ack: None,
+            flush_ack: false,
+        }
+    }
+
+    /// Returns the id of the stream's connection.
+    pub(super) fn connection_id(&self) -> u32 {
+        self.read_packet.conn_ref().id()
+    }
+
+    /// Sends a pending semi-sync acknowledgement, if any.
+    fn poll_send_ack(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let stream = self.read_packet.0.stream_mut()?;
+
+        if self.ack.is_some() {
+            ready!(Pin::new(stream.codec.as_mut().expect("must be here")).poll_ready(cx))?;
+            // the server resets the sequence id after an event that requires an acknowledgement
+            stream.reset_seq_id();
+            let ack = self.ack.take().expect("must be here");
+            Pin::new(stream.codec.as_mut().expect("must be here")).start_send(ack)?;
+            self.flush_ack = true;
+        }
+
+        if self.flush_ack {
+            ready!(Pin::new(stream.codec.as_mut().expect("must be here")).poll_flush(cx))?;
+            self.flush_ack = false;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Returns `false` if the given event should be skipped
+    /// (see [`BinlogStreamRequest::with_db_filter`]).
+    fn should_emit(&self, event: &Event) -> bool {
+        use EventType::*;
+
+        if self.filter.is_empty() {
+            return true;
+        }
+
+        let table_id = match event.header().event_type() {
+            Ok(TABLE_MAP_EVENT)
+            | Ok(WRITE_ROWS_EVENT_V1)
+            | Ok(UPDATE_ROWS_EVENT_V1)
+            | Ok(DELETE_ROWS_EVENT_V1)
+            | Ok(WRITE_ROWS_EVENT)
+            | Ok(UPDATE_ROWS_EVENT)
+            | Ok(DELETE_ROWS_EVENT)
+            | Ok(PARTIAL_UPDATE_ROWS_EVENT) => match event.read_data() {
+                Ok(Some(EventData::TableMapEvent(tme))) => tme.table_id(),
+                Ok(Some(EventData::RowsEvent(re))) => re.table_id(),
+                // let the caller deal with broken events
+                _ => return true,
+            },
+            _ => return true,
+        };
+
+        // table map is tracked by the reader even if the event is skipped
+        match self.esr.get_tme(table_id) {
+            Some(tme) => self
+                .filter
+                .matches(tme.database_name_raw(), tme.table_name_raw()),
+            None => true,
         }
     }
 
@@ -91,6 +203,46 @@ impl BinlogStream {
         self.esr.get_tme(table_id)
     }
 
+    /// Decodes rows of the given `WRITE_ROWS_EVENT`, `UPDATE_ROWS_EVENT` or `DELETE_ROWS_EVENT`
+    /// (either version) using the corresponding `TABLE_MAP_EVENT` (see [`RowChange`]).
+    ///
+    /// Returns `None` if the event is not a rows event. Note that the table map must have been
+    /// seen by this stream, so call it before the next event is consumed.
+    ///
+    /// ```rust,no_run
+    /// # use futures_util::StreamExt;
+    /// # use mysql_async::{test_misc::get_opts, BinlogStreamRequest, Conn, RowChange};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let conn = Conn::new(get_opts()).await?;
+    /// let mut stream = conn.get_binlog_stream(BinlogStreamRequest::new(42)).await?;
+    /// while let Some(event) = stream.next().await {
+    ///     for change in stream.decode_rows(&event?)?.unwrap_or_default() {
+    ///         match change {
+    ///             RowChange::Insert(row) => println!("inserted {:?}", row),
+    ///             RowChange::Update { before, after } => println!("{:?} -> {:?}", before, after),
+    ///             RowChange::Delete(row) => println!("deleted {:?}", row),
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn decode_rows(&self, event: &Event) -> Result<Option<Vec<RowChange>>> {
+        let rows_event = match event.read_data()? {
+            Some(EventData::RowsEvent(rows_event)) => rows_event,
+            _ => return Ok(None),
+        };
+
+        let tme = self.get_tme(rows_event.table_id()).ok_or_else(|| {
+            std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("unknown table id {}", rows_event.table_id()),
+            )
+        })?;
+
+        RowChange::decode_all(&rows_event, tme).map(Some)
+    }
+
     /// Closes the stream's `Conn`. Additionally, the connection is dropped, so its associated
     /// pool (if any) will regain a connection slot.
     pub async fn close(self) -> Result<()> {
@@ -114,10 +266,13 @@ impl BinlogStream {
     }
 }
 
-impl futures_core::stream::Stream for BinlogStream {
-    type Item = Result<Event>;
+impl BinlogStream {
+    /// Reads the next event regardless of the filter.
+    fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Event>>> {
+        if let Err(err) = ready!(self.poll_send_ack(cx)) {
+            return Poll::Ready(Some(Err(err)));
+        }
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         {
             let Self {
                 ref mut tpe,
@@ -161,9 +316,44 @@ impl futures_core::stream::Stream for BinlogStream {
         }
 
         if first_byte == Some(0) {
-            let event_data = &packet[1..];
+            let mut event_data = &packet[1..];
+            let mut need_ack = false;
+
+            if self.semi_sync {
+                match event_data {
+                    [SEMI_SYNC_MAGIC, flags, rest @ ..] => {
+                        need_ack = flags & SEMI_SYNC_ACK_REQ != 0;
+                        event_data = rest;
+                    }
+                    _ => {
+                        return Poll::Ready(Some(Err(DriverError::UnexpectedPacket {
+                            payload: packet.to_vec(),
+                        }
+                        .into())))
+                    }
+                }
+            }
+
             match self.esr.read(event_data) {
                 Ok(Some(event)) => {
+                    if self.semi_sync {
+                        if event.header().event_type_raw() == EventType::ROTATE_EVENT as u8 {
+                            if let Ok(e) = event.read_event::<RotateEvent<'_>>() {
+                                self.filename = e.name_raw().to_vec();
+                            }
+                        }
+
+                        if need_ack {
+                            let mut ack = self.read_packet.conn_ref().buffer_pool().get();
+                            ack.as_mut().push(SEMI_SYNC_MAGIC);
+                            ack.as_mut().extend_from_slice(
+                                &u64::from(event.header().log_pos()).to_le_bytes(),
+                            );
+                            ack.as_mut().extend_from_slice(&self.filename);
+                            self.ack = Some(ack);
+                        }
+                    }
+
                     if event.header().event_type_raw() == EventType::TRANSACTION_PAYLOAD_EVENT as u8
                     {
                         #[allow(clippy::single_match)]
@@ -186,6 +376,19 @@ impl futures_core::stream::Stream for BinlogStream {
     }
 }
 
+impl futures_core::stream::Stream for BinlogStream {
+    type Item = Result<Event>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match ready!(self.poll_next_event(cx)) {
+                Some(Ok(event)) if !self.should_emit(&event) => continue,
+                item => return Poll::Ready(item),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -374,4 +577,204 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn should_filter_binlog_events_by_table() -> super::Result<()> {
+        let (mut conn, filename, pos) = create_binlog_stream_conn(None).await?;
+        let db: String = "SELECT DATABASE()".first(&mut conn).await?.unwrap();
+
+        "CREATE TABLE IF NOT EXISTS binlog_filter_in (id int not null)"
+            .ignore(&mut conn)
+            .await?;
+        "CREATE TABLE IF NOT EXISTS binlog_filter_out (id int not null)"
+            .ignore(&mut conn)
+            .await?;
+        "INSERT INTO binlog_filter_in VALUES (1), (2)"
+            .ignore(&mut conn)
+            .await?;
+        "INSERT INTO binlog_filter_out VALUES (3), (4)"
+            .ignore(&mut conn)
+            .await?;
+        "DROP TABLE binlog_filter_in, binlog_filter_out"
+            .ignore(&mut conn)
+            .await?;
+
+        let mut binlog_stream = conn
+            .get_binlog_stream(
+                BinlogStreamRequest::new(18)
+                    .with_filename(&filename)
+                    .with_pos(pos)
+                    .with_non_blocking()
+                    .with_table_filter(db, "binlog_filter_in"),
+            )
+            .await?;
+
+        let mut rows_events = 0;
+        while let Some(event) = binlog_stream.next().await {
+            match event?.read_data()? {
+                Some(EventData::TableMapEvent(tme)) => {
+                    assert_eq!(tme.table_name(), "binlog_filter_in");
+                }
+                Some(EventData::RowsEvent(re)) => {
+                    let tme = binlog_stream.get_tme(re.table_id()).unwrap();
+                    assert_eq!(tme.table_name(), "binlog_filter_in");
+                    rows_events += 1;
+                }
+                _ => (),
+            }
+        }
+        assert_eq!(rows_events, 1);
+
+        binlog_stream.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_read_binlog_with_semi_sync() -> super::Result<()> {
+        // semi-sync acknowledgements are only requested if the server has the plugin enabled,
+        // otherwise this is a regular binlog stream
+        let (mut conn, filename, pos) = create_binlog_stream_conn(None).await?;
+        gen_dummy_data(&mut conn).await?;
+
+        let mut binlog_stream = conn
+            .get_binlog_stream(
+                BinlogStreamRequest::new(20)
+                    .with_filename(&filename)
+                    .with_pos(pos)
+                    .with_non_blocking()
+                    .with_semi_sync(true),
+            )
+            .await?;
+
+        let mut events_num = 0;
+        while let Some(event) = timeout(Duration::from_secs(10), binlog_stream.next())
+            .await
+            .unwrap()
+        {
+            event?.header().event_type().unwrap();
+            events_num += 1;
+        }
+        assert!(events_num > 0);
+
+        binlog_stream.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_decode_row_changes() -> super::Result<()> {
+        let (mut conn, filename, pos) = create_binlog_stream_conn(None).await?;
+        let db: String = "SELECT DATABASE()".first(&mut conn).await?.unwrap();
+
+        "CREATE TABLE IF NOT EXISTS binlog_row_changes (id INT PRIMARY KEY, name VARCHAR(16))"
+            .ignore(&mut conn)
+            .await?;
+        "INSERT INTO binlog_row_changes VALUES (1, 'foo'), (2, NULL)"
+            .ignore(&mut conn)
+            .await?;
+        "UPDATE binlog_row_changes SET name = 'bar' WHERE id = 1"
+            .ignore(&mut conn)
+            .await?;
+        "DELETE FROM binlog_row_changes WHERE id = 2"
+            .ignore(&mut conn)
+            .await?;
+        "DROP TABLE binlog_row_changes".ignore(&mut conn).await?;
+
+        let mut binlog_stream = conn
+            .get_binlog_stream(
+                BinlogStreamRequest::new(19)
+                    .with_filename(&filename)
+                    .with_pos(pos)
+                    .with_non_blocking()
+                    .with_table_filter(db, "binlog_row_changes"),
+            )
+            .await?;
+
+        type Image = (u32, Option<String>);
+        let mut changes: Vec<(Option<Image>, Option<Image>)> = Vec::new();
+        while let Some(event) = binlog_stream.next().await {
+            for change in binlog_stream.decode_rows(&event?)?.unwrap_or_default() {
+                changes.push(match change {
+                    RowChange::Insert(row) => (None, Some(from_row(row))),
+                    RowChange::Update { before, after } => {
+                        (Some(from_row(before)), Some(from_row(after)))
+                    }
+                    RowChange::Delete(row) => (Some(from_row(row)), None),
+                });
+            }
+        }
+
+        assert_eq!(
+            changes,
+            vec![
+                (None, Some((1, Some("foo".into())))),
+                (None, Some((2, None))),
+                (Some((1, Some("foo".into()))), Some((1, Some("bar".into())))),
+                (Some((2, None)), None),
+            ]
+        );
+
+        binlog_stream.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_resume_binlog_stream_after_connection_loss() -> super::Result<()> {
+        use mysql_common::binlog::consts::EventType;
+
+        let (mut conn, filename, pos) = create_binlog_stream_conn(None).await?;
+
+        let mut stream = ResilientBinlogStream::new(
+            get_opts(),
+            BinlogStreamRequest::new(21)
+                .with_filename(&filename)
+                .with_pos(pos),
+        )
+        .with_reconnect_policy(
+            ReconnectPolicy::new(3)
+                .with_backoff(Duration::from_millis(10), Duration::from_millis(100)),
+        );
+
+        // read until the first transaction (`CREATE TABLE`) is seen
+        loop {
+            let item = timeout(Duration::from_secs(10), stream.next())
+                .await
+                .unwrap();
+            if let BinlogStreamEvent::Event(event) = item.unwrap()? {
+                if event.header().event_type_raw() == EventType::QUERY_EVENT as u8 {
+                    break;
+                }
+            }
+        }
+        let resume_pos = stream.pos();
+        assert!(resume_pos > pos);
+
+        let id = stream.connection_id().unwrap();
+        conn.query_drop(format!("KILL {}", id)).await?;
+
+        let mut reconnected = false;
+        loop {
+            let item = timeout(Duration::from_secs(10), stream.next())
+                .await
+                .unwrap();
+            match item.unwrap()? {
+                BinlogStreamEvent::Reconnected => {
+                    assert_eq!(stream.pos(), resume_pos);
+                    assert_ne!(stream.connection_id(), Some(id));
+                    reconnected = true;
+                }
+                BinlogStreamEvent::Event(event)
+                    if reconnected
+                        && event.header().event_type_raw() == EventType::XID_EVENT as u8 =>
+                {
+                    break;
+                }
+                BinlogStreamEvent::Event(_) => (),
+            }
+        }
+        assert!(stream.pos() > resume_pos);
+
+        stream.close().await?;
+        conn.disconnect().await?;
+        Ok(())
+    }
 }
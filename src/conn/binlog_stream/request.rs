@@ -3,9 +3,33 @@ use mysql_common::packets::{
 };
 
 /// Binlog stream request builder.
+#[derive(Debug, Clone)]
 pub struct BinlogStreamRequest<'a> {
     pub(crate) binlog_request: BinlogRequest<'a>,
     pub(crate) register_slave: ComRegisterSlave<'a>,
+    pub(crate) filter: EventFilter,
+    pub(crate) semi_sync: bool,
+}
+
+/// Client-side filter of row-based events (see [`BinlogStreamRequest::with_db_filter`]).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventFilter {
+    dbs: Vec<Vec<u8>>,
+    tables: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl EventFilter {
+    /// Returns `true` if there is no filter.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.dbs.is_empty() && self.tables.is_empty()
+    }
+
+    /// Returns `true` if events of the given table should be emitted.
+    pub(crate) fn matches(&self, db: &[u8], table: &[u8]) -> bool {
+        self.is_empty()
+            || self.dbs.iter().any(|x| x == db)
+            || self.tables.iter().any(|(x, y)| x == db && y == table)
+    }
 }
 
 impl<'a> BinlogStreamRequest<'a> {
@@ -14,6 +38,8 @@ impl<'a> BinlogStreamRequest<'a> {
         Self {
             binlog_request: BinlogRequest::new(server_id),
             register_slave: ComRegisterSlave::new(server_id),
+            filter: EventFilter::default(),
+            semi_sync: false,
         }
     }
 
@@ -52,6 +78,76 @@ impl<'a> BinlogStreamRequest<'a> {
         self
     }
 
+    /// Enables semi-synchronous replication acknowledgements (defaults to `false`).
+    ///
+    /// If enabled, then the stream will register itself as a semi-sync replica
+    /// and acknowledge every event, that the server requests an acknowledgement for.
+    /// Without it a semi-sync enabled server will wait for `rpl_semi_sync_source_timeout`
+    /// (`rpl_semi_sync_master_timeout`) on every commit, unless there are other semi-sync
+    /// replicas.
+    ///
+    /// An event is acknowledged as soon as the stream is polled for the next event, i.e. after
+    /// the event was consumed. This is a no-op if the server has no semi-sync plugin installed.
+    pub fn with_semi_sync(mut self, semi_sync: bool) -> Self {
+        self.semi_sync = semi_sync;
+        self
+    }
+
+    /// Only emit row-based events of the given database (could be called multiple times).
+    ///
+    /// Filtering happens on the client side, because `COM_BINLOG_DUMP` doesn't support it.
+    /// Only `TABLE_MAP_EVENT`s and rows events are filtered, so all the other events
+    /// (e.g. `ROTATE_EVENT`, `QUERY_EVENT` or `XID_EVENT`) are emitted as is and positions
+    /// could still be tracked. Table maps of filtered tables are still processed internally.
+    ///
+    /// If combined with [`BinlogStreamRequest::with_table_filter`], then an event is emitted
+    /// if it matches either filter.
+    pub fn with_db_filter<T: Into<Vec<u8>>>(mut self, db: T) -> Self {
+        self.filter.dbs.push(db.into());
+        self
+    }
+
+    /// Only emit row-based events of the given table (could be called multiple times).
+    ///
+    /// See [`BinlogStreamRequest::with_db_filter`].
+    pub fn with_table_filter<T, U>(mut self, db: T, table: U) -> Self
+    where
+        T: Into<Vec<u8>>,
+        U: Into<Vec<u8>>,
+    {
+        self.filter.tables.push((db.into(), table.into()));
+        self
+    }
+
+    /// Returns an owned copy of this request.
+    pub(crate) fn into_owned(self) -> BinlogStreamRequest<'static> {
+        let binlog_request = &self.binlog_request;
+        let sids = binlog_request
+            .sids()
+            .iter()
+            .map(|sid| Sid::new(sid.uuid()).with_intervals(sid.intervals().to_vec()))
+            .collect::<Vec<_>>();
+        let register_slave = &self.register_slave;
+
+        BinlogStreamRequest {
+            binlog_request: BinlogRequest::new(binlog_request.server_id())
+                .with_use_gtid(binlog_request.use_gtid())
+                .with_flags(binlog_request.flags())
+                .with_filename(binlog_request.filename_raw().to_vec())
+                .with_pos(binlog_request.pos())
+                .with_sids(sids),
+            register_slave: ComRegisterSlave::new(register_slave.server_id())
+                .with_hostname(register_slave.hostname_raw().to_vec())
+                .with_user(register_slave.user_raw().to_vec())
+                .with_password(register_slave.password_raw().to_vec())
+                .with_port(register_slave.port())
+                .with_replication_rank(register_slave.replication_rank())
+                .with_master_id(register_slave.master_id()),
+            filter: self.filter,
+            semi_sync: self.semi_sync,
+        }
+    }
+
     /// This hostname will be reported to the server (max len 255, default to an empty string).
     ///
     /// Usually left default.
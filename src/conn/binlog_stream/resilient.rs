@@ -0,0 +1,592 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use futures_core::{ready, stream::Stream};
+use futures_util::FutureExt;
+use mysql_common::{
+    binlog::{
+        consts::EventType,
+        events::{Event, GtidEvent, QueryEvent, RotateEvent, TableMapEvent},
+    },
+    packets::{GnoInterval, Sid},
+    proto::MySerialize,
+};
+
+use std::{
+    cmp::min,
+    collections::BTreeMap,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use crate::{BoxFuture, Conn, Opts, Result};
+
+use super::{request::BinlogStreamRequest, BinlogStream, RowChange};
+
+/// Event type of a MariaDB `GTID_EVENT` (it starts an event group instead of `BEGIN`).
+const MARIADB_GTID_EVENT: u8 = 0xa2;
+
+/// Flag of a MariaDB `GTID_EVENT`, that marks an event group without `BEGIN`/`COMMIT`
+/// (e.g. a DDL statement).
+const MARIADB_FL_STANDALONE: u8 = 0x01;
+
+/// Reconnection policy of a [`ResilientBinlogStream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReconnectPolicy {
+    max_attempts: usize,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Creates a policy with the given maximum number of consecutive reconnection attempts.
+    ///
+    /// `0` disables reconnection. Backoff defaults to `100ms` doubled after every failed attempt
+    /// up to `10s`.
+    pub fn new(max_attempts: usize) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Defines the delay before the second attempt and the maximum delay between attempts
+    /// (the delay is doubled after every failed attempt).
+    ///
+    /// The first attempt is made right after the connection loss.
+    pub fn with_backoff(mut self, backoff: Duration, max_backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Returns the maximum number of consecutive reconnection attempts.
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Returns the delay before the second attempt.
+    pub fn backoff(&self) -> Duration {
+        self.backoff
+    }
+
+    /// Returns the maximum delay between attempts.
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+}
+
+impl Default for ReconnectPolicy {
+    /// 10 attempts with backoff from `100ms` to `10s`.
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// An item of a [`ResilientBinlogStream`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinlogStreamEvent {
+    /// A binlog event.
+    Event(Event),
+    /// The connection was lost and the stream was resumed using a new connection.
+    ///
+    /// Events that follow are the events the server sends at the beginning of a binlog stream
+    /// (i.e. `ROTATE_EVENT` and `FORMAT_DESCRIPTION_EVENT`) followed by the first transaction,
+    /// that wasn't completely seen before the connection loss.
+    Reconnected,
+}
+
+enum State {
+    Streaming(Box<BinlogStream>),
+    Connecting {
+        fut: BoxFuture<'static, BinlogStream>,
+        is_reconnect: bool,
+    },
+    Done,
+}
+
+/// Binlog stream, that resumes on connection loss.
+///
+/// It is a wrapper over [`BinlogStream`], that tracks the position of the last completely seen
+/// transaction (and the set of seen GTIDs if the request is GTID-based). If the connection is lost
+/// (see [`crate::Error::is_connection_lost`]), then the stream reconnects according to its
+/// [`ReconnectPolicy`], requests the binlog starting from the tracked position, and yields
+/// [`BinlogStreamEvent::Reconnected`]. Events of a transaction, that was interrupted
+/// by the connection loss, are sent again, so the consumer should discard the uncommitted part
+/// of the transaction it has seen when it gets this marker.
+///
+/// The error is yielded and the stream is terminated if the stream fails to reconnect,
+/// or if the error is not a connection loss (e.g. the requested binlog is purged).
+///
+/// Stream initialization is lazy, i.e. the connection won't be established until this stream
+/// is polled. Note that the initial connection is not retried.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use futures_util::StreamExt;
+/// # use mysql_async::{
+/// #     test_misc::get_opts, BinlogStreamEvent, BinlogStreamRequest, ReconnectPolicy,
+/// #     ResilientBinlogStream,
+/// # };
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// let mut stream = ResilientBinlogStream::new(get_opts(), BinlogStreamRequest::new(42).with_gtid())
+///     .with_reconnect_policy(
+///         ReconnectPolicy::new(5).with_backoff(Duration::from_secs(1), Duration::from_secs(30)),
+///     );
+///
+/// while let Some(item) = stream.next().await {
+///     match item? {
+///         BinlogStreamEvent::Event(event) => println!("{:?}", event.header().event_type()),
+///         BinlogStreamEvent::Reconnected => println!("resumed at {}", stream.pos()),
+///     }
+/// }
+/// # Ok(()) }
+/// ```
+pub struct ResilientBinlogStream {
+    opts: Opts,
+    request: BinlogStreamRequest<'static>,
+    policy: ReconnectPolicy,
+    state: State,
+    /// Binlog file name of the last completely seen transaction.
+    filename: Vec<u8>,
+    /// Position after the last completely seen transaction.
+    pos: u64,
+    /// GTIDs of completely seen transactions.
+    gtid_set: GtidSet,
+    /// GTID of the current transaction.
+    pending_gtid: Option<([u8; 16], u64)>,
+    /// Whether a transaction was started and not yet committed (or rolled back).
+    in_transaction: bool,
+}
+
+impl ResilientBinlogStream {
+    /// Creates a new stream with the default [`ReconnectPolicy`].
+    ///
+    /// GTID set of a GTID-based request is extended with GTIDs of seen transactions
+    /// on every reconnect.
+    pub fn new<T: Into<Opts>>(opts: T, request: BinlogStreamRequest<'_>) -> Self {
+        let request = request.into_owned();
+        let filename = request.binlog_request.filename_raw().to_vec();
+        let pos = request.binlog_request.pos();
+        let gtid_set = GtidSet::from_sids(request.binlog_request.sids());
+        let opts = opts.into();
+
+        let fut = connect(opts.clone(), request.clone()).boxed();
+
+        Self {
+            opts,
+            request,
+            policy: ReconnectPolicy::default(),
+            state: State::Connecting {
+                fut,
+                is_reconnect: false,
+            },
+            filename,
+            pos,
+            gtid_set,
+            pending_gtid: None,
+            in_transaction: false,
+        }
+    }
+
+    /// Defines the reconnection policy (defaults to [`ReconnectPolicy::default`]).
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the reconnection policy.
+    pub fn reconnect_policy(&self) -> ReconnectPolicy {
+        self.policy
+    }
+
+    /// Returns the binlog file name of the last completely seen transaction.
+    pub fn filename(&self) -> &[u8] {
+        &self.filename
+    }
+
+    /// Returns the position after the last completely seen transaction.
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+
+    /// Returns GTIDs of completely seen transactions including the GTID set of the initial request.
+    ///
+    /// Only GTID-based requests are resumed using this set.
+    pub fn gtid_set(&self) -> Vec<Sid<'static>> {
+        self.gtid_set.to_sids()
+    }
+
+    /// Returns the id of the current connection (`None` if not connected).
+    pub fn connection_id(&self) -> Option<u32> {
+        match self.state {
+            State::Streaming(ref stream) => Some(stream.connection_id()),
+            _ => None,
+        }
+    }
+
+    /// Returns a table map event for the given table id
+    /// (see [`BinlogStream::get_tme`]).
+    pub fn get_tme(&self, table_id: u64) -> Option<&TableMapEvent<'static>> {
+        match self.state {
+            State::Streaming(ref stream) => stream.get_tme(table_id),
+            _ => None,
+        }
+    }
+
+    /// Decodes rows of the given rows event (see [`BinlogStream::decode_rows`]).
+    ///
+    /// Returns `None` if the event is not a rows event or if the stream is not connected.
+    pub fn decode_rows(&self, event: &Event) -> Result<Option<Vec<RowChange>>> {
+        match self.state {
+            State::Streaming(ref stream) => stream.decode_rows(event),
+            _ => Ok(None),
+        }
+    }
+
+    /// Closes the underlying connection (if any) and terminates the stream.
+    pub async fn close(mut self) -> Result<()> {
+        match std::mem::replace(&mut self.state, State::Done) {
+            State::Streaming(stream) => stream.close().await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Updates the tracked position according to the given event.
+    fn track(&mut self, event: &Event) {
+        let header = event.header();
+        let event_type = header.event_type_raw();
+
+        if event_type == EventType::ROTATE_EVENT as u8 {
+            if let Ok(e) = event.read_event::<RotateEvent<'_>>() {
+                self.filename = e.name_raw().to_vec();
+                self.pos = e.position();
+            }
+        } else if event_type == EventType::GTID_EVENT as u8 {
+            if let Ok(e) = event.read_event::<GtidEvent>() {
+                self.pending_gtid = Some((e.sid(), e.gno()));
+            }
+        } else if event_type == MARIADB_GTID_EVENT {
+            // post-header is `seq_no` (8 bytes), `domain_id` (4 bytes) and `flags` (1 byte)
+            if let Some(flags) = event.data().get(12) {
+                self.in_transaction = flags & MARIADB_FL_STANDALONE == 0;
+            }
+        } else if event_type == EventType::QUERY_EVENT as u8 {
+            if let Ok(e) = event.read_event::<QueryEvent<'_>>() {
+                let query = e.query_raw();
+                if query.eq_ignore_ascii_case(b"BEGIN") {
+                    self.in_transaction = true;
+                } else if !self.in_transaction
+                    || query.eq_ignore_ascii_case(b"COMMIT")
+                    || query.eq_ignore_ascii_case(b"ROLLBACK")
+                {
+                    // either the end of a transaction or a statement outside of a transaction
+                    // (e.g. DDL statement)
+                    self.end_transaction(event);
+                }
+            }
+        } else if event_type == EventType::XID_EVENT as u8
+            || event_type == EventType::TRANSACTION_PAYLOAD_EVENT as u8
+        {
+            self.end_transaction(event);
+        }
+    }
+
+    /// Moves the tracked position past the transaction, that ends with the given event.
+    fn end_transaction(&mut self, event: &Event) {
+        self.in_transaction = false;
+        // events of a transaction payload are positioned at zero
+        if event.header().log_pos() > 0 {
+            self.pos = u64::from(event.header().log_pos());
+        }
+        if let Some((sid, gno)) = self.pending_gtid.take() {
+            self.gtid_set.add(sid, gno);
+        }
+    }
+
+    /// Returns a request, that resumes the stream at the tracked position.
+    fn resume_request(&self) -> BinlogStreamRequest<'static> {
+        let mut request = self.request.clone();
+        let mut binlog_request = request
+            .binlog_request
+            .with_filename(self.filename.clone())
+            .with_pos(self.pos);
+        if binlog_request.use_gtid() {
+            binlog_request = binlog_request.with_sids(self.gtid_set.to_sids());
+        }
+        request.binlog_request = binlog_request;
+        request
+    }
+
+    /// Returns a future, that reconnects according to the reconnection policy.
+    fn reconnect(&self) -> BoxFuture<'static, BinlogStream> {
+        let opts = self.opts.clone();
+        let request = self.resume_request();
+        let policy = self.policy;
+
+        async move {
+            let mut backoff = policy.backoff;
+            let mut attempt = 1;
+
+            loop {
+                match connect(opts.clone(), request.clone()).await {
+                    Err(err) if attempt < policy.max_attempts && err.is_connection_lost() => {
+                        tokio::time::sleep(backoff).await;
+                        backoff = min(backoff * 2, policy.max_backoff);
+                        attempt += 1;
+                    }
+                    result => return result,
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Establishes a new connection and requests the binlog.
+async fn connect(opts: Opts, request: BinlogStreamRequest<'static>) -> Result<BinlogStream> {
+    Conn::new(opts).await?.get_binlog_stream(request).await
+}
+
+impl Stream for ResilientBinlogStream {
+    type Item = Result<BinlogStreamEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.state {
+                State::Streaming(ref mut stream) => {
+                    match ready!(Pin::new(&mut **stream).poll_next(cx)) {
+                        Some(Ok(event)) => {
+                            self.track(&event);
+                            return Poll::Ready(Some(Ok(BinlogStreamEvent::Event(event))));
+                        }
+                        Some(Err(err))
+                            if err.is_connection_lost() && self.policy.max_attempts > 0 =>
+                        {
+                            self.pending_gtid = None;
+                            self.in_transaction = false;
+                            let fut = self.reconnect();
+                            self.state = State::Connecting {
+                                fut,
+                                is_reconnect: true,
+                            };
+                        }
+                        Some(Err(err)) => {
+                            self.state = State::Done;
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                        None => {
+                            self.state = State::Done;
+                            return Poll::Ready(None);
+                        }
+                    }
+                }
+                State::Connecting {
+                    ref mut fut,
+                    is_reconnect,
+                } => match ready!(fut.as_mut().poll(cx)) {
+                    Ok(stream) => {
+                        self.state = State::Streaming(Box::new(stream));
+                        if is_reconnect {
+                            return Poll::Ready(Some(Ok(BinlogStreamEvent::Reconnected)));
+                        }
+                    }
+                    Err(err) => {
+                        self.state = State::Done;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                },
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Set of GTIDs as half-open intervals of transaction numbers per server UUID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct GtidSet(BTreeMap<[u8; 16], Vec<(u64, u64)>>);
+
+impl GtidSet {
+    fn from_sids(sids: &[Sid<'_>]) -> Self {
+        let mut set = Self::default();
+        for sid in sids {
+            for interval in sid.intervals() {
+                let (start, end) = interval_bounds(interval);
+                set.add_interval(sid.uuid(), start, end);
+            }
+        }
+        set
+    }
+
+    fn add(&mut self, uuid: [u8; 16], gno: u64) {
+        self.add_interval(uuid, gno, gno + 1);
+    }
+
+    fn add_interval(&mut self, uuid: [u8; 16], start: u64, end: u64) {
+        let intervals = self.0.entry(uuid).or_default();
+        intervals.push((start, end));
+        intervals.sort_unstable();
+
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(intervals.len());
+        for &(start, end) in intervals.iter() {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        *intervals = merged;
+    }
+
+    fn to_sids(&self) -> Vec<Sid<'static>> {
+        self.0
+            .iter()
+            .map(|(uuid, intervals)| {
+                Sid::new(*uuid).with_intervals(
+                    intervals
+                        .iter()
+                        .map(|&(start, end)| GnoInterval::new(start, end))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns `[start, end)` bounds of the given interval.
+fn interval_bounds(interval: &GnoInterval) -> (u64, u64) {
+    // there are no accessors, so take them from the wire representation
+    let mut buf = Vec::with_capacity(16);
+    interval.serialize(&mut buf);
+    let mut start = [0_u8; 8];
+    let mut end = [0_u8; 8];
+    start.copy_from_slice(&buf[..8]);
+    end.copy_from_slice(&buf[8..16]);
+    (u64::from_le_bytes(start), u64::from_le_bytes(end))
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql_common::{
+        binlog::{
+            consts::{BinlogVersion, EventType},
+            events::{Event, FormatDescriptionEvent, QueryEvent},
+        },
+        packets::{GnoInterval, Sid},
+        proto::MySerialize,
+    };
+
+    use super::{GtidSet, ResilientBinlogStream, MARIADB_GTID_EVENT};
+    use crate::{test_misc::get_opts, BinlogStreamRequest};
+
+    fn event(event_type: u8, log_pos: u32, data: &[u8]) -> Event {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&0_u32.to_le_bytes());
+        buf.push(event_type);
+        buf.extend_from_slice(&1_u32.to_le_bytes());
+        buf.extend_from_slice(&(19 + data.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&log_pos.to_le_bytes());
+        buf.extend_from_slice(&0_u16.to_le_bytes());
+        buf.extend_from_slice(data);
+        Event::read(
+            &FormatDescriptionEvent::new(BinlogVersion::Version4),
+            &buf[..],
+        )
+        .unwrap()
+    }
+
+    fn query(log_pos: u32, query: &str) -> Event {
+        let mut data = Vec::new();
+        QueryEvent::new(&b""[..], &b"test"[..])
+            .with_query(query.as_bytes())
+            .serialize(&mut data);
+        event(EventType::QUERY_EVENT as u8, log_pos, &data)
+    }
+
+    fn xid(log_pos: u32) -> Event {
+        event(EventType::XID_EVENT as u8, log_pos, &42_u64.to_le_bytes())
+    }
+
+    fn mariadb_gtid(log_pos: u32, standalone: bool) -> Event {
+        let mut data = Vec::new();
+        data.extend_from_slice(&1_u64.to_le_bytes());
+        data.extend_from_slice(&0_u32.to_le_bytes());
+        data.push(standalone as u8);
+        event(MARIADB_GTID_EVENT, log_pos, &data)
+    }
+
+    #[test]
+    fn should_track_transaction_boundaries() {
+        let mut stream = ResilientBinlogStream::new(get_opts(), BinlogStreamRequest::new(42));
+        let initial_pos = stream.pos();
+
+        // statement-based transaction
+        stream.track(&query(100, "BEGIN"));
+        stream.track(&query(200, "INSERT INTO foo VALUES (1)"));
+        assert_eq!(stream.pos(), initial_pos);
+        stream.track(&query(300, "COMMIT"));
+        assert_eq!(stream.pos(), 300);
+
+        // DDL statement
+        stream.track(&query(400, "CREATE TABLE bar (id INT)"));
+        assert_eq!(stream.pos(), 400);
+
+        // row-based transaction
+        stream.track(&query(500, "BEGIN"));
+        assert_eq!(stream.pos(), 400);
+        stream.track(&xid(600));
+        assert_eq!(stream.pos(), 600);
+
+        // rolled back transaction (keywords are case-insensitive)
+        stream.track(&query(700, "begin"));
+        stream.track(&query(800, "UPDATE foo SET id = 2"));
+        assert_eq!(stream.pos(), 600);
+        stream.track(&query(900, "rollback"));
+        assert_eq!(stream.pos(), 900);
+
+        // MariaDB transaction (starts with GTID instead of BEGIN)
+        stream.track(&mariadb_gtid(1000, false));
+        stream.track(&query(1100, "INSERT INTO foo VALUES (3)"));
+        assert_eq!(stream.pos(), 900);
+        stream.track(&xid(1200));
+        assert_eq!(stream.pos(), 1200);
+
+        // MariaDB standalone event group
+        stream.track(&mariadb_gtid(1300, true));
+        stream.track(&query(1400, "DROP TABLE bar"));
+        assert_eq!(stream.pos(), 1400);
+    }
+
+    #[test]
+    fn should_track_gtid_set() {
+        let a = [1_u8; 16];
+        let b = [2_u8; 16];
+
+        let mut set = GtidSet::from_sids(&[
+            Sid::new(a).with_interval(GnoInterval::new(1, 5)),
+            Sid::new(b).with_interval(GnoInterval::new(10, 11)),
+        ]);
+        set.add(a, 5);
+        set.add(a, 7);
+        set.add(b, 9);
+        set.add(a, 6);
+
+        assert_eq!(
+            set.to_sids(),
+            vec![
+                Sid::new(a).with_interval(GnoInterval::new(1, 8)),
+                Sid::new(b).with_interval(GnoInterval::new(9, 11)),
+            ]
+        );
+    }
+}
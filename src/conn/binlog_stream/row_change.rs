@@ -0,0 +1,76 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::{
+    binlog::{
+        events::{RowsEventData, TableMapEvent},
+        row::BinlogRow,
+    },
+    row::Row,
+};
+
+use std::{
+    convert::TryFrom,
+    io::{Error as IoError, ErrorKind},
+};
+
+use crate::Result;
+
+/// A decoded row of a binlog rows event (see [`crate::BinlogStream::decode_rows`]).
+///
+/// Rows are decoded using the corresponding `TABLE_MAP_EVENT`, so column names are only
+/// available if the server is configured with `binlog_row_metadata=FULL`.
+///
+/// Row images are not necessarily full – with `binlog_row_image` set to `MINIMAL` or `NOBLOB`
+/// columns, that are absent in the image, are represented as taken values of a [`Row`],
+/// i.e. [`Row::as_ref`] returns `None` for them (unlike `NULL` values, that are present
+/// as [`crate::Value::NULL`]).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowChange {
+    /// A row of a `WRITE_ROWS_EVENT`.
+    Insert(Row),
+    /// Before and after images of a row of an `UPDATE_ROWS_EVENT`.
+    Update {
+        /// Row image before the update.
+        before: Row,
+        /// Row image after the update.
+        after: Row,
+    },
+    /// A row of a `DELETE_ROWS_EVENT`.
+    Delete(Row),
+}
+
+impl RowChange {
+    /// Decodes all rows of the given rows event.
+    pub(super) fn decode_all(
+        rows_event: &RowsEventData<'_>,
+        tme: &TableMapEvent<'_>,
+    ) -> Result<Vec<RowChange>> {
+        let mut changes = Vec::new();
+        for row in rows_event.rows(tme) {
+            let change = match row? {
+                (None, Some(after)) => RowChange::Insert(to_row(after)?),
+                (Some(before), Some(after)) => RowChange::Update {
+                    before: to_row(before)?,
+                    after: to_row(after)?,
+                },
+                (Some(before), None) => RowChange::Delete(to_row(before)?),
+                (None, None) => {
+                    return Err(IoError::new(ErrorKind::InvalidData, "empty rows event row").into())
+                }
+            };
+            changes.push(change);
+        }
+        Ok(changes)
+    }
+}
+
+/// Converts a binlog row to a [`Row`] (fails on partial JSON updates).
+fn to_row(row: BinlogRow) -> Result<Row> {
+    Row::try_from(row).map_err(|err| IoError::new(ErrorKind::InvalidData, err).into())
+}
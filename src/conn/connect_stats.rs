@@ -0,0 +1,109 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::time::{Duration, Instant};
+
+/// Timing breakdown of connection establishment.
+///
+/// It's only collected if [`Opts::collect_connect_stats`] is enabled
+/// (see [`Conn::connect_stats`]).
+///
+/// [`Opts::collect_connect_stats`]: crate::Opts::collect_connect_stats
+/// [`Conn::connect_stats`]: crate::Conn::connect_stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectStats {
+    dns_resolution: Option<Duration>,
+    connect: Duration,
+    tls_handshake: Option<Duration>,
+    auth: Duration,
+    total: Duration,
+}
+
+impl ConnectStats {
+    /// Time spent resolving the server hostname (`None` for socket connections).
+    pub fn dns_resolution(&self) -> Option<Duration> {
+        self.dns_resolution
+    }
+
+    /// Time spent establishing a TCP connection (or a socket connection).
+    pub fn connect(&self) -> Duration {
+        self.connect
+    }
+
+    /// Time spent on TLS handshake (`None` if TLS is not used).
+    pub fn tls_handshake(&self) -> Option<Duration> {
+        self.tls_handshake
+    }
+
+    /// Time spent on MySql handshake and authentication (excluding TLS handshake).
+    pub fn auth(&self) -> Duration {
+        self.auth
+    }
+
+    /// Total time spent establishing the connection.
+    ///
+    /// Besides the phases above it includes reading server settings,
+    /// reconnecting via socket (see [`crate::Opts::prefer_socket`])
+    /// and running `init` and `setup` commands.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+}
+
+/// Measures consecutive phases of connection establishment, if enabled.
+#[derive(Debug)]
+pub(crate) struct Stopwatch {
+    start: Option<Instant>,
+    last: Option<Instant>,
+    stats: ConnectStats,
+}
+
+impl Stopwatch {
+    pub(crate) fn new(enabled: bool) -> Self {
+        let start = if enabled { Some(Instant::now()) } else { None };
+        Self {
+            start,
+            last: start,
+            stats: ConnectStats::default(),
+        }
+    }
+
+    /// Returns time elapsed since the previous lap (`None` if disabled).
+    fn lap(&mut self) -> Option<Duration> {
+        let last = self.last.as_mut()?;
+        let now = Instant::now();
+        let elapsed = now.duration_since(*last);
+        *last = now;
+        Some(elapsed)
+    }
+
+    pub(crate) fn dns_resolved(&mut self) {
+        self.stats.dns_resolution = self.lap();
+    }
+
+    pub(crate) fn connected(&mut self) {
+        self.stats.connect = self.lap().unwrap_or_default();
+    }
+
+    pub(crate) fn tls_handshake_done(&mut self) {
+        self.stats.tls_handshake = self.lap();
+    }
+
+    /// Adds time elapsed since the previous lap to the auth time.
+    pub(crate) fn auth_step_done(&mut self) {
+        let elapsed = self.lap().unwrap_or_default();
+        self.stats.auth += elapsed;
+    }
+
+    /// Returns collected stats (`None` if disabled).
+    pub(crate) fn finish(mut self) -> Option<ConnectStats> {
+        let start = self.start?;
+        self.stats.total = start.elapsed();
+        Some(self.stats)
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+/// Flavor of the MySql-compatible server (see [`crate::Conn::flavor`]).
+///
+/// It's derived from the server version string given in the handshake and
+/// from `@@version_comment`:
+///
+/// * `MariaDb` – the version string contains `MariaDB` (MariaDb prefixes it with `5.5.5-`);
+/// * `TiDb` – the version string contains `TiDB`;
+/// * `Percona` – `@@version_comment` contains `Percona`;
+/// * `MySql` – any other server, including MySql-compatible servers that identify themselves
+///   as MySql.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServerFlavor {
+    /// Oracle MySql (or a compatible server that identifies itself as MySql).
+    MySql,
+    /// MariaDb.
+    MariaDb,
+    /// Percona Server for MySql.
+    Percona,
+    /// TiDb.
+    TiDb,
+}
+
+impl ServerFlavor {
+    /// Detects the flavor using the server version string given in the handshake.
+    ///
+    /// Returns `None` if `@@version_comment` is required to tell the flavor.
+    pub(crate) fn from_version(version: &[u8]) -> Option<Self> {
+        if contains(version, b"MariaDB") {
+            Some(ServerFlavor::MariaDb)
+        } else if contains(version, b"TiDB") {
+            Some(ServerFlavor::TiDb)
+        } else {
+            None
+        }
+    }
+
+    /// Detects the flavor using `@@version_comment`.
+    pub(crate) fn from_version_comment(version_comment: &[u8]) -> Self {
+        if contains(version_comment, b"Percona") {
+            ServerFlavor::Percona
+        } else if contains(version_comment, b"MariaDB") {
+            ServerFlavor::MariaDb
+        } else if contains(version_comment, b"TiDB") {
+            ServerFlavor::TiDb
+        } else {
+            ServerFlavor::MySql
+        }
+    }
+
+    /// Returns `true` if this is a MariaDb server.
+    pub fn is_mariadb(&self) -> bool {
+        *self == ServerFlavor::MariaDb
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|x| x == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerFlavor;
+    use crate::{test_misc::get_opts, Conn};
+
+    #[test]
+    fn should_detect_server_flavor() {
+        assert_eq!(
+            ServerFlavor::from_version(b"5.5.5-10.11.6-MariaDB-1:10.11.6+maria~ubu2204"),
+            Some(ServerFlavor::MariaDb)
+        );
+        assert_eq!(
+            ServerFlavor::from_version(b"8.0.11-TiDB-v7.5.0"),
+            Some(ServerFlavor::TiDb)
+        );
+        assert_eq!(ServerFlavor::from_version(b"8.0.34-26"), None);
+        assert_eq!(ServerFlavor::from_version(b"8.0.35"), None);
+
+        assert_eq!(
+            ServerFlavor::from_version_comment(
+                b"Percona Server (GPL), Release 26, Revision 0fe62c85"
+            ),
+            ServerFlavor::Percona
+        );
+        assert_eq!(
+            ServerFlavor::from_version_comment(b"MySQL Community Server - GPL"),
+            ServerFlavor::MySql
+        );
+        assert_eq!(
+            ServerFlavor::from_version_comment(b"Source distribution"),
+            ServerFlavor::MySql
+        );
+    }
+
+    #[tokio::test]
+    async fn should_expose_server_flavor() -> crate::Result<()> {
+        let conn = Conn::new(get_opts()).await?;
+        assert_eq!(conn.flavor().is_mariadb(), conn.inner.is_mariadb);
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
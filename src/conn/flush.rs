@@ -0,0 +1,103 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use crate::{prelude::Queryable, Conn, Result};
+
+/// Option of the `FLUSH` statement (see [`Conn::flush`]).
+///
+/// Every option requires the `RELOAD` privilege.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FlushOption {
+    /// `FLUSH TABLES` – closes all open tables and flushes the query cache.
+    Tables,
+    /// `FLUSH PRIVILEGES` – reloads privileges from the grant tables.
+    Privileges,
+    /// `FLUSH LOGS` – closes and reopens the log files.
+    Logs,
+    /// `FLUSH STATUS` – resets session status variables.
+    Status,
+    /// `FLUSH HOSTS` – empties the host cache.
+    ///
+    /// Deprecated since MySql 8.0.23 (and removed in MySql 8.4)
+    /// in favor of `TRUNCATE TABLE performance_schema.host_cache`.
+    Hosts,
+}
+
+impl FlushOption {
+    /// Returns the `FLUSH` statement for this option.
+    fn statement(self) -> &'static str {
+        match self {
+            FlushOption::Tables => "FLUSH TABLES",
+            FlushOption::Privileges => "FLUSH PRIVILEGES",
+            FlushOption::Logs => "FLUSH LOGS",
+            FlushOption::Status => "FLUSH STATUS",
+            FlushOption::Hosts => "FLUSH HOSTS",
+        }
+    }
+}
+
+impl Conn {
+    /// Executes the `FLUSH` statement with the given option.
+    ///
+    /// Returns a server error, for which [`crate::Error::is_access_denied`] is `true`,
+    /// if the user lacks the `RELOAD` privilege.
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, Conn, FlushOption};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// conn.flush(FlushOption::Privileges).await?;
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn flush(&mut self, option: FlushOption) -> Result<()> {
+        self.query_drop(option.statement()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::*, test_misc::get_opts, Conn, FlushOption};
+
+    #[tokio::test]
+    async fn should_flush() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        for option in [
+            FlushOption::Tables,
+            FlushOption::Privileges,
+            FlushOption::Logs,
+            FlushOption::Status,
+        ] {
+            conn.flush(option).await?;
+        }
+
+        // the user lacks the `RELOAD` privilege
+        let _ = conn.query_drop("DROP USER 'flush_user'@'%'").await;
+        conn.query_drop("CREATE USER 'flush_user'@'%' IDENTIFIED BY 'password'")
+            .await?;
+        let opts = get_opts()
+            .user(Some("flush_user"))
+            .pass(Some("password"))
+            .db_name(None::<String>);
+        let result = async {
+            let mut user_conn = Conn::new(opts).await?;
+            let result = user_conn.flush(FlushOption::Tables).await;
+            user_conn.disconnect().await?;
+            Ok::<_, crate::Error>(result)
+        }
+        .await;
+        conn.query_drop("DROP USER 'flush_user'@'%'").await?;
+
+        let err = result?.unwrap_err();
+        assert!(err.is_access_denied(), "{}", err);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
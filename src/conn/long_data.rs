@@ -0,0 +1,245 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::{constants::MAX_PAYLOAD_LEN, packets::ComStmtSendLongData};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use std::{fmt, pin::Pin};
+
+use crate::{
+    conn::routines::{ExecRoutine, StmtResetRoutine},
+    prelude::StatementLike,
+    BinaryProtocol, Conn, DriverError, Params, QueryResult, Result, Value,
+};
+
+/// `COM_STMT_SEND_LONG_DATA` header length (command, statement id and parameter index).
+const LONG_DATA_HEADER_LEN: usize = 7;
+
+/// Statement parameter, that is streamed to the server in chunks
+/// (see [`Conn::exec_long_data`]).
+pub struct LongData<'a> {
+    index: usize,
+    reader: Pin<Box<dyn AsyncRead + Send + 'a>>,
+}
+
+impl<'a> LongData<'a> {
+    /// Creates a long data parameter, that streams the given reader as the value
+    /// of the parameter at the given (zero-based) position.
+    pub fn new<R>(index: usize, reader: R) -> Self
+    where
+        R: AsyncRead + Send + 'a,
+    {
+        Self {
+            index,
+            reader: Box::pin(reader),
+        }
+    }
+
+    /// Returns the parameter position.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Debug for LongData<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LongData")
+            .field("index", &self.index)
+            .field("reader", &"..")
+            .finish()
+    }
+}
+
+impl Conn {
+    /// Executes the given statement streaming values of the given parameters
+    /// using `COM_STMT_SEND_LONG_DATA`.
+    ///
+    /// This is the way to bind a large `BLOB` or `TEXT` value without loading it
+    /// into memory – the value is read from an [`AsyncRead`] and sent in chunks, each of which
+    /// fits into the `max_allowed_packet` of the connection. Note that the server still limits
+    /// the size of the whole value by its `max_allowed_packet`.
+    ///
+    /// `params` must contain a value for every parameter of the statement, but values
+    /// of the parameters streamed via `long_data` are ignored (use e.g. `Value::NULL`).
+    /// Long data parameters are positional, so for a statement with named parameters
+    /// `index` refers to the position of a placeholder in the statement.
+    ///
+    /// Every other `Value::Bytes` parameter is sent as long data as well, the rest
+    /// of the parameters are sent along with `COM_STMT_EXECUTE`.
+    ///
+    /// ```rust
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn, LongData, Value};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// conn.query_drop("CREATE TEMPORARY TABLE files (id INT, data LONGBLOB)").await?;
+    ///
+    /// let file = &b"file contents"[..]; // e.g. `tokio::fs::File`
+    /// conn.exec_long_data(
+    ///     "INSERT INTO files VALUES (?, ?)",
+    ///     (1, Value::NULL),
+    ///     vec![LongData::new(1, file)],
+    /// )
+    /// .await?
+    /// .drop_result()
+    /// .await?;
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn exec_long_data<'a, S, P>(
+        &'a mut self,
+        stmt: S,
+        params: P,
+        long_data: Vec<LongData<'_>>,
+    ) -> Result<QueryResult<'a, 'static, BinaryProtocol>>
+    where
+        S: StatementLike,
+        P: Into<Params>,
+    {
+        let statement = self.get_statement(stmt).await?;
+
+        let mut values = match params.into() {
+            Params::Empty => Vec::new(),
+            Params::Positional(values) => values,
+            named @ Params::Named(_) => {
+                if statement.named_params.is_empty() {
+                    return Err(DriverError::NamedParamsForPositionalQuery.into());
+                }
+                match named.into_positional(&statement.named_params)? {
+                    Params::Positional(values) => values,
+                    _ => Vec::new(),
+                }
+            }
+        };
+
+        if values.len() != statement.num_params() as usize {
+            return Err(DriverError::StmtParamsMismatch {
+                required: statement.num_params(),
+                supplied: values.len() as u16,
+            }
+            .into());
+        }
+
+        for data in &long_data {
+            match values.get_mut(data.index) {
+                // the value is streamed, so it's empty in the execute request
+                Some(value) => *value = Value::Bytes(Vec::new()),
+                None => {
+                    return Err(DriverError::StmtParamsMismatch {
+                        required: statement.num_params(),
+                        supplied: data.index as u16 + 1,
+                    }
+                    .into())
+                }
+            }
+        }
+
+        for data in long_data {
+            if let Err(err) = self.stream_long_data(statement.id(), data).await {
+                // discard long data, that is already sent (the original error is more relevant)
+                let _ = self.routine(StmtResetRoutine::new(statement.id())).await;
+                return Err(err);
+            }
+        }
+
+        self.routine(ExecRoutine::new(&statement, Params::Positional(values)).with_long_data())
+            .await?;
+        Ok(QueryResult::new(self))
+    }
+
+    /// Sends the given long data parameter in chunks.
+    async fn stream_long_data(&mut self, statement_id: u32, mut data: LongData<'_>) -> Result<()> {
+        let max_allowed_packet = self
+            .inner
+            .stream
+            .as_ref()
+            .and_then(|stream| stream.max_allowed_packet())
+            .unwrap_or(MAX_PAYLOAD_LEN);
+        let chunk_len = max_allowed_packet.min(MAX_PAYLOAD_LEN) - LONG_DATA_HEADER_LEN;
+
+        let mut chunk = vec![0_u8; chunk_len];
+        loop {
+            let mut len = 0;
+            while len < chunk_len {
+                match data.reader.read(&mut chunk[len..]).await? {
+                    0 => break,
+                    n => len += n,
+                }
+            }
+
+            if len > 0 {
+                let com = ComStmtSendLongData::new(statement_id, data.index as u16, &chunk[..len]);
+                self.write_command(&com).await?;
+            }
+
+            if len < chunk_len {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::*, test_misc::get_opts, Conn, LongData, OptsBuilder, Value};
+
+    #[tokio::test]
+    async fn should_stream_long_data() -> crate::Result<()> {
+        const LEN: usize = 3 * 1024 * 1024;
+
+        // client side `max_allowed_packet` is smaller than the value
+        let opts = OptsBuilder::from_opts(get_opts()).max_allowed_packet(Some(1024 * 1024));
+        let mut conn = Conn::new(opts).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_long_data (id INT, tag TEXT, data LONGBLOB)")
+            .await?;
+
+        let data = b"0123456789".repeat(LEN / 10);
+        conn.exec_long_data(
+            "INSERT INTO tmp_long_data VALUES (?, ?, ?)",
+            (1, "tag", Value::NULL),
+            vec![LongData::new(2, &data[..])],
+        )
+        .await?
+        .drop_result()
+        .await?;
+
+        let row: Option<(u32, String, usize, bool)> = conn
+            .exec_first(
+                "SELECT id, tag, LENGTH(data), data = REPEAT('0123456789', ?) FROM tmp_long_data",
+                (LEN / 10,),
+            )
+            .await?;
+        assert_eq!(row, Some((1, "tag".into(), data.len(), true)));
+
+        // named params and an empty value
+        conn.exec_long_data(
+            "INSERT INTO tmp_long_data VALUES (:id, :tag, :data)",
+            params! { "id" => 2, "tag" => "empty", "data" => Value::NULL },
+            vec![LongData::new(2, &b""[..])],
+        )
+        .await?
+        .drop_result()
+        .await?;
+        let data: Option<Vec<u8>> = conn
+            .query_first("SELECT data FROM tmp_long_data WHERE id = 2")
+            .await?;
+        assert_eq!(data, Some(vec![]));
+
+        // out of range parameter
+        let result = conn
+            .exec_long_data(
+                "SELECT ?",
+                (Value::NULL,),
+                vec![LongData::new(1, &b"foo"[..])],
+            )
+            .await;
+        assert!(result.is_err());
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+use bytes::Bytes;
 use futures_util::FutureExt;
 pub use mysql_common::named_params;
 
@@ -14,16 +15,21 @@ use mysql_common::{
     crypto,
     io::ParseBuf,
     packets::{
-        AuthPlugin, AuthSwitchRequest, CommonOkPacket, ErrPacket, HandshakePacket,
+        AuthPlugin, AuthSwitchRequest, ComStmtClose, CommonOkPacket, ErrPacket, HandshakePacket,
         HandshakeResponse, OkPacket, OkPacketDeserializer, OldAuthSwitchRequest, OldEofPacket,
         ResultSetTerminator, SslRequest,
     },
     proto::MySerialize,
     row::Row,
+    value::{
+        convert::{FromValue, FromValueError},
+        Value,
+    },
 };
 
 use std::{
     borrow::Cow,
+    collections::HashMap,
     fmt,
     future::Future,
     mem::{self, replace},
@@ -34,30 +40,80 @@ use std::{
 };
 
 use crate::{
-    buffer_pool::PooledBuf,
-    conn::{pool::Pool, stmt_cache::StmtCache},
+    buffer_pool::{BufferPool, PooledBuf},
+    conn::{
+        pool::Pool,
+        stmt_cache::{QueryString, StmtCache},
+    },
     consts::{CapabilityFlags, Command, StatusFlags},
     error::*,
-    io::Stream,
+    io::{EndpointAddr, Stream},
+    local_infile_handler::InfileProgress,
     opts::Opts,
+    prelude::{FromRow, StatementLike},
     queryable::{
         query_result::{QueryResult, ResultSetMeta},
+        stmt::{Statement, StmtInner},
         transaction::TxStatus,
         BinaryProtocol, Queryable, TextProtocol,
     },
-    ChangeUserOpts, InfileData, OptsBuilder,
+    ChangeUserOpts, InfileData, OptsBuilder, Params, SessionStateChange,
 };
 
-use self::routines::Routine;
+use self::{
+    connect_stats::{ConnectStats, Stopwatch},
+    flavor::ServerFlavor,
+    routines::Routine,
+};
 
+pub mod alter_progress;
 #[cfg(feature = "binlog")]
 pub mod binlog_stream;
+pub mod connect_stats;
+pub mod flavor;
+pub mod flush;
+pub mod long_data;
 pub mod pool;
 pub mod routines;
 pub mod stmt_cache;
+pub mod warning;
 
 const DEFAULT_WAIT_TIMEOUT: usize = 28800;
 
+/// Authentication plugins known to exist, but not supported by this driver.
+const UNSUPPORTED_AUTH_PLUGINS: &[&str] = &[
+    "sha256_password",
+    "auth_gssapi_client",
+    "client_ed25519",
+    "dialog",
+    "authentication_kerberos_client",
+    "authentication_ldap_sasl_client",
+    "authentication_oci_client",
+    "authentication_fido_client",
+    "authentication_webauthn_client",
+];
+
+/// Returns an error for an authentication plugin that the driver can't handle.
+fn unsupported_auth_plugin(name: &[u8]) -> DriverError {
+    let name = String::from_utf8_lossy(name).into_owned();
+    if UNSUPPORTED_AUTH_PLUGINS.contains(&name.as_str()) {
+        DriverError::UnsupportedAuthPlugin { name }
+    } else {
+        DriverError::UnknownAuthPlugin { name }
+    }
+}
+
+/// Returns `true` if the given `caching_sha2_password` packet contains a PEM public key.
+fn is_public_key_packet(packet: &[u8]) -> bool {
+    match packet.split_first() {
+        Some((0x01, key)) => {
+            let key = String::from_utf8_lossy(key);
+            key.contains("-----BEGIN ") && key.contains("-----END ")
+        }
+        _ => false,
+    }
+}
+
 /// Helper that asynchronously disconnects the givent connection on the default tokio executor.
 fn disconnect(mut conn: Conn) {
     let disconnected = conn.inner.disconnected;
@@ -95,9 +151,13 @@ pub(crate) enum PendingResult {
 /// Mysql connection
 struct ConnInner {
     stream: Option<Stream>,
+    buffer_pool: Arc<BufferPool>,
     id: u32,
     is_mariadb: bool,
+    flavor: ServerFlavor,
     version: (u16, u16, u16),
+    protocol_version: u8,
+    default_collation: u8,
     socket: Option<String>,
     capabilities: CapabilityFlags,
     status: StatusFlags,
@@ -106,16 +166,34 @@ struct ConnInner {
     pool: Option<Pool>,
     pending_result: std::result::Result<Option<PendingResult>, ServerError>,
     tx_status: TxStatus,
+    /// Id of a statement, which cursor must be closed before the next command.
+    cursor_to_close: Option<u32>,
+    /// Ids of statements prepared within a transaction, if tracked
+    /// (see [`crate::TxOpts::close_prepared_on_end`]).
+    tx_prepared: Option<Vec<u32>>,
+    /// Autocommit mode of a freshly established connection.
+    default_autocommit: bool,
+    connect_stats: Option<ConnectStats>,
     reset_upon_returning_to_a_pool: bool,
     opts: Opts,
     ttl_deadline: Option<Instant>,
     last_io: Instant,
     wait_timeout: Duration,
     stmt_cache: StmtCache,
+    /// Statements pinned via [`Conn::prepare_pinned`].
+    pinned_stmts: HashMap<QueryString, Arc<StmtInner>>,
     nonce: Vec<u8>,
     auth_plugin: AuthPlugin<'static>,
     auth_switched: bool,
     server_key: Option<Vec<u8>>,
+    /// Password fetched from [`Opts::credential_provider`] for the ongoing authentication.
+    provided_pass: Option<String>,
+    /// Character set of the connection (`character_set_client`).
+    charset: Cow<'static, str>,
+    /// Whether `NO_BACKSLASH_ESCAPES` SQL mode is enabled (as reported by the server).
+    no_backslash_escapes: bool,
+    /// Start time and text of a query, that is timed (see [`Opts::slow_query_threshold`]).
+    slow_query: Option<(Instant, Arc<[u8]>)>,
     /// Connection is already disconnected.
     pub(crate) disconnected: bool,
     /// One-time connection-level infile handler.
@@ -149,15 +227,24 @@ impl ConnInner {
             last_ok_packet: None,
             last_err_packet: None,
             stream: None,
+            buffer_pool: opts.buffer_strategy().buffer_pool(),
             is_mariadb: false,
+            flavor: ServerFlavor::MySql,
             version: (0, 0, 0),
+            protocol_version: 0,
+            default_collation: 0,
             id: 0,
             pending_result: Ok(None),
             pool: None,
             tx_status: TxStatus::None,
+            cursor_to_close: None,
+            tx_prepared: None,
+            default_autocommit: true,
+            connect_stats: None,
             last_io: Instant::now(),
             wait_timeout: Duration::from_secs(0),
             stmt_cache: StmtCache::new(opts.stmt_cache_size()),
+            pinned_stmts: HashMap::new(),
             socket: opts.socket().map(Into::into),
             opts,
             ttl_deadline,
@@ -166,6 +253,10 @@ impl ConnInner {
             auth_switched: false,
             disconnected: false,
             server_key: None,
+            provided_pass: None,
+            charset: Cow::Borrowed(""),
+            no_backslash_escapes: false,
+            slow_query: None,
             infile_handler: None,
             reset_upon_returning_to_a_pool: false,
         }
@@ -193,6 +284,28 @@ impl Conn {
         self.inner.id
     }
 
+    /// Returns connection identifier (thread id) as reported in the handshake,
+    /// i.e. the value of `CONNECTION_ID()` and the argument for `KILL`.
+    ///
+    /// This is an alias for [`Conn::id`].
+    pub fn connection_id(&self) -> u32 {
+        self.id()
+    }
+
+    /// Returns the protocol version reported in the handshake (`10` for any supported server).
+    pub fn protocol_version(&self) -> u8 {
+        self.inner.protocol_version
+    }
+
+    /// Returns the id of the server default collation reported in the handshake
+    /// (e.g. `255` for `utf8mb4_0900_ai_ci`).
+    ///
+    /// Note that this is the server default and not necessarily the collation
+    /// of the connection.
+    pub fn server_default_collation(&self) -> u8 {
+        self.inner.default_collation
+    }
+
     /// Returns the ID generated by a query (usually `INSERT`) on a table with a column having the
     /// `AUTO_INCREMENT` attribute. Returns `None` if there was no previous query on the connection
     /// or if the query did not update an AUTO_INCREMENT value.
@@ -236,6 +349,38 @@ impl Conn {
         self.inner.last_ok_packet.as_ref()
     }
 
+    /// Returns `true` if autocommit mode is enabled for this connection
+    /// (derived from [`Conn::status_flags`]).
+    pub fn autocommit(&self) -> bool {
+        self.inner
+            .status
+            .contains(StatusFlags::SERVER_STATUS_AUTOCOMMIT)
+    }
+
+    /// Enables or disables autocommit mode for this connection (`SET autocommit = ..`).
+    ///
+    /// Note that disabling autocommit implicitly starts a transaction, that must be committed
+    /// explicitly (e.g. with `COMMIT` query). Pooled connections are returned to the pool
+    /// in their default autocommit mode with uncommitted changes rolled back.
+    pub async fn set_autocommit(&mut self, autocommit: bool) -> Result<()> {
+        if autocommit {
+            self.query_drop("SET autocommit = 1").await
+        } else {
+            self.query_drop("SET autocommit = 0").await
+        }
+    }
+
+    /// Returns `true` if this connection is in a non-default autocommit mode
+    /// or has an implicitly started transaction (see [`Conn::set_autocommit`]).
+    pub(crate) fn autocommit_is_dirty(&self) -> bool {
+        self.autocommit() != self.inner.default_autocommit
+            || (!self.autocommit()
+                && self
+                    .inner
+                    .status
+                    .contains(StatusFlags::SERVER_STATUS_IN_TRANS))
+    }
+
     /// Turns on/off automatic connection reset (see [`crate::PoolOpts::with_reset_connection`]).
     ///
     /// Only makes sense for pooled connections.
@@ -273,15 +418,42 @@ impl Conn {
     /// Handles OK packet.
     pub(crate) fn handle_ok(&mut self, ok_packet: OkPacket<'static>) {
         self.inner.status = ok_packet.status_flags();
+        self.inner.no_backslash_escapes = self
+            .inner
+            .status
+            .contains(StatusFlags::SERVER_STATUS_NO_BACKSLASH_ESCAPES);
+        self.track_session_state(&ok_packet);
         self.inner.last_err_packet = None;
         self.inner.last_ok_packet = Some(ok_packet);
     }
 
+    /// Tracks connection character set using session state information, if any.
+    fn track_session_state(&mut self, ok_packet: &OkPacket<'_>) {
+        for info in ok_packet.session_state_info().unwrap_or_default() {
+            if let Ok(SessionStateChange::SystemVariables(vars)) = info.decode() {
+                for var in vars {
+                    if var.name_bytes() == b"character_set_client" {
+                        self.inner.charset = Cow::Owned(var.value_str().into_owned());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the character set negotiated during the handshake.
+    fn default_charset(&self) -> &'static str {
+        if self.inner.version >= (5, 5, 3) {
+            "utf8mb4"
+        } else {
+            "utf8"
+        }
+    }
+
     /// Handles ERR packet.
     pub(crate) fn handle_err(&mut self, err_packet: ErrPacket<'_>) -> Result<()> {
         match err_packet {
             ErrPacket::Error(err) => {
-                self.inner.status = StatusFlags::empty();
+                self.inner.status &= StatusFlags::SERVER_STATUS_AUTOCOMMIT;
                 self.inner.last_ok_packet = None;
                 self.inner.last_err_packet = Some(err.clone().into_owned());
                 Err(Error::from(err))
@@ -290,6 +462,48 @@ impl Conn {
         }
     }
 
+    /// Requests to close the cursor of the given statement before the next command.
+    pub(crate) fn set_cursor_to_close(&mut self, stmt_id: u32) {
+        self.inner.cursor_to_close = Some(stmt_id);
+    }
+
+    /// Returns the id of a statement, which cursor must be closed, if any.
+    pub(crate) fn take_cursor_to_close(&mut self) -> Option<u32> {
+        self.inner.cursor_to_close.take()
+    }
+
+    /// Starts tracking of statements prepared on this connection.
+    pub(crate) fn track_prepared_statements(&mut self) {
+        self.inner.tx_prepared = Some(Vec::new());
+    }
+
+    /// Remembers the given statement, if prepared statements are tracked.
+    pub(crate) fn on_statement_prepared(&mut self, stmt_id: u32) {
+        if let Some(ids) = self.inner.tx_prepared.as_mut() {
+            ids.push(stmt_id);
+        }
+    }
+
+    /// Stops tracking of prepared statements without closing tracked statements
+    /// (e.g. if the transaction failed to end or the session is reset).
+    pub(crate) fn untrack_prepared_statements(&mut self) {
+        self.inner.tx_prepared = None;
+    }
+
+    /// Stops tracking of prepared statements and closes tracked statements.
+    ///
+    /// Connection must be clean (it's called right after the transaction end).
+    pub(crate) async fn close_tracked_statements(&mut self) -> Result<()> {
+        if let Some(ids) = self.inner.tx_prepared.take() {
+            for id in ids {
+                self.stmt_cache_mut().remove(id);
+                self.reset_seq_id();
+                self.write_struct(&ComStmtClose::new(id)).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the current transaction status.
     pub(crate) fn get_tx_status(&self) -> TxStatus {
         self.inner.tx_status
@@ -370,6 +584,7 @@ impl Conn {
     where
         F: Routine<T> + 'a,
     {
+        self.reconnect_if_needed().await?;
         self.inner.disconnected = true;
         let result = f.call(&mut *self).await;
         match result {
@@ -387,16 +602,151 @@ impl Conn {
         }
     }
 
+    /// Returns server status flags as reported by the most recent OK or EOF packet
+    /// (or by the handshake packet, if no command was executed yet).
+    ///
+    /// This is the server's notion of the session state, so it could be used,
+    /// for example, to check whether a transaction is actually open on the server
+    /// (see [`StatusFlags::SERVER_STATUS_IN_TRANS`]), which is not the case
+    /// after an implicit commit caused by a DDL statement.
+    ///
+    /// Note that ERR packets do not carry status flags, so only
+    /// [`StatusFlags::SERVER_STATUS_AUTOCOMMIT`] is retained after an error.
+    pub fn server_status_flags(&self) -> StatusFlags {
+        self.inner.status
+    }
+
+    /// Escapes the given string, so that it could be used inside a single-quoted
+    /// SQL string literal (surrounding quotes are not added).
+    ///
+    /// **Prefer parameters (see [`crate::Params`]) whenever possible**, since it is not
+    /// possible to misuse them. This function is meant for the rare cases where dynamic SQL
+    /// is unavoidable.
+    ///
+    /// Escaping depends on the current state of the connection:
+    ///
+    /// *   if `NO_BACKSLASH_ESCAPES` SQL mode is enabled, then only single quotes are escaped
+    ///     (by doubling them), otherwise backslash escapes are used (as in
+    ///     `mysql_real_escape_string`);
+    /// *   multibyte sequences of the connection character set are never broken apart,
+    ///     which is important for charsets such as `big5`, `cp932`, `gbk`, `gb18030` and `sjis`,
+    ///     where backslash (`0x5C`) could be a trailing byte of a multibyte character.
+    ///
+    /// SQL mode is tracked using server status flags. Character set is tracked using
+    /// session state information, so changes made via `SET NAMES` are only taken into account
+    /// if the server tracks `character_set_client` (see `session_track_system_variables`,
+    /// which is the default for MySql >= 5.7 and MariaDb >= 10.2).
+    ///
+    /// ```rust
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    ///
+    /// let input = r"it's a \ backslash";
+    /// let query = format!("SELECT '{}'", conn.escape_string(input));
+    /// let output: Option<String> = conn.query_first(query).await?;
+    /// assert_eq!(output.as_deref(), Some(input));
+    /// # conn.disconnect().await }
+    /// ```
+    pub fn escape_string(&self, input: &str) -> String {
+        crate::escape::escape_with(input, &self.inner.charset, self.inner.no_backslash_escapes)
+    }
+
+    /// Returns a single-quoted SQL string literal for the given string
+    /// (see [`Conn::escape_string`]).
+    ///
+    /// This is [`crate::quote_string`] for the current state of the connection
+    /// (SQL mode and character set).
+    pub fn quote_string(&self, input: &str) -> String {
+        crate::quote_string(input, &self.inner.charset, self.inner.no_backslash_escapes)
+    }
+
+    /// Returns timing breakdown of connection establishment.
+    ///
+    /// Returns `None` unless [`Opts::collect_connect_stats`] is enabled.
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, Conn, OptsBuilder};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let opts = OptsBuilder::from_opts(get_opts()).collect_connect_stats(true);
+    /// let conn = Conn::new(opts).await?;
+    ///
+    /// let stats = conn.connect_stats().expect("enabled");
+    /// println!("connect: {:?}, auth: {:?}", stats.connect(), stats.auth());
+    /// assert!(stats.total() >= stats.connect() + stats.auth());
+    /// # conn.disconnect().await }
+    /// ```
+    pub fn connect_stats(&self) -> Option<&ConnectStats> {
+        self.inner.connect_stats.as_ref()
+    }
+
     /// Returns server version.
     pub fn server_version(&self) -> (u16, u16, u16) {
         self.inner.version
     }
 
+    /// Returns the buffer pool of this connection (see [`crate::BufferStrategy`]).
+    pub(crate) fn buffer_pool(&self) -> &Arc<BufferPool> {
+        &self.inner.buffer_pool
+    }
+
+    /// Returns the server flavor (MySql, MariaDb, Percona, etc.).
+    ///
+    /// It's detected once connected, using the server version string given in the handshake.
+    /// If it's not enough to tell the flavor, then `@@version_comment` is loaded along with
+    /// other server settings (see [`ServerFlavor`] for details).
+    pub fn flavor(&self) -> ServerFlavor {
+        self.inner.flavor
+    }
+
+    /// Returns `true` if the connection is encrypted using TLS.
+    ///
+    /// It reflects the actual state of the stream rather than [`crate::Opts::ssl_opts`].
+    /// Always `false` if the crate is built without TLS support.
+    pub fn is_tls(&self) -> bool {
+        self.is_secure()
+    }
+
+    /// Returns the compression level, if the connection uses the compressed protocol.
+    ///
+    /// It reflects the actual state of the connection, i.e. it's `None` if compression was
+    /// requested via [`crate::OptsBuilder::compression`] but the server doesn't support it.
+    pub fn compression(&self) -> Option<crate::Compression> {
+        self.inner.stream.as_ref().and_then(Stream::compression)
+    }
+
+    /// Returns the address of the server end of the connection.
+    ///
+    /// It's the address this connection is actually connected to, i.e. one of the addresses
+    /// the host name resolved to. For a unix socket (or a named pipe) it's the path of the socket.
+    ///
+    /// Returns `None` if the connection is closed.
+    pub fn peer_addr(&self) -> Option<EndpointAddr> {
+        self.inner.stream.as_ref().and_then(Stream::peer_addr)
+    }
+
+    /// Returns the address of the client end of the connection.
+    ///
+    /// For a unix socket (or a named pipe) it's the path of the socket,
+    /// since the client end of a socket is unnamed.
+    ///
+    /// Returns `None` if the connection is closed.
+    pub fn local_addr(&self) -> Option<EndpointAddr> {
+        self.inner.stream.as_ref().and_then(Stream::local_addr)
+    }
+
     /// Returns connection options.
     pub fn opts(&self) -> &Opts {
         &self.inner.opts
     }
 
+    /// Returns the pool this connection belongs to, if any.
+    pub(crate) fn pool(&self) -> Option<&Pool> {
+        self.inner.pool.as_ref()
+    }
+
     /// Setup _local_ `LOCAL INFILE` handler (see ["LOCAL INFILE Handlers"][2] section
     /// of the crate-level docs).
     ///
@@ -411,6 +761,44 @@ impl Conn {
         self.inner.infile_handler = Some(Box::pin(handler));
     }
 
+    /// Same as [`Conn::set_infile_handler`] but `progress` will be called with the cumulative
+    /// number of bytes sent to the server, as soon as each chunk is written.
+    ///
+    /// `progress` is called synchronously from the upload, so it should be cheap
+    /// (e.g. update a counter or send to a channel), otherwise it'll slow the upload down.
+    /// Errors are reported as for [`Conn::set_infile_handler`].
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, Conn};
+    /// # use mysql_async::prelude::*;
+    /// # use bytes::Bytes;
+    /// # use futures_util::{stream, StreamExt};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// # let mut conn = Conn::new(get_opts()).await?;
+    /// # conn.query_drop("CREATE TEMPORARY TABLE tmp (a TEXT)").await?;
+    /// conn.set_infile_handler_with_progress(
+    ///     async move {
+    ///         let rows = vec![Ok(Bytes::from("foo\n")), Ok(Bytes::from("bar\n"))];
+    ///         Ok(stream::iter(rows).boxed())
+    ///     },
+    ///     |sent| println!("{} bytes uploaded", sent),
+    /// );
+    /// # let _ = conn.query_drop(r#"LOAD DATA LOCAL INFILE "dummy" INTO TABLE tmp"#).await;
+    /// # conn.disconnect().await }
+    /// ```
+    pub fn set_infile_handler_with_progress<T, F>(&mut self, handler: T, progress: F)
+    where
+        T: Future<Output = crate::Result<InfileData>>,
+        T: Send + Sync + 'static,
+        F: FnMut(u64) + Unpin + Send + Sync + 'static,
+    {
+        self.set_infile_handler(async move {
+            let data = handler.await?;
+            Ok(Box::pin(InfileProgress::new(data, progress)) as InfileData)
+        });
+    }
+
     fn take_stream(&mut self) -> Stream {
         self.inner.stream.take().unwrap()
     }
@@ -449,7 +837,7 @@ impl Conn {
 
     /// Returns true if io stream is socket.
     fn is_socket(&self) -> bool {
-        #[cfg(unix)]
+        #[cfg(any(unix, windows))]
         {
             self.inner
                 .stream
@@ -458,7 +846,7 @@ impl Conn {
                 .unwrap_or_default()
         }
 
-        #[cfg(not(unix))]
+        #[cfg(not(any(unix, windows)))]
         false
     }
 
@@ -485,7 +873,12 @@ impl Conn {
     }
 
     async fn handle_handshake(&mut self) -> Result<()> {
-        let packet = self.read_packet().await?;
+        let packet = match self.read_packet().await {
+            Err(Error::Server(error)) if error.is_con_count_error() => {
+                return Err(DriverError::ServerConnectionLimit { error }.into());
+            }
+            result => result?,
+        };
         let handshake = ParseBuf(&packet).parse::<HandshakePacket>(())?;
 
         // Handshake scramble is always 21 bytes length (20 + zero terminator)
@@ -507,8 +900,20 @@ impl Conn {
             })
             .or_else(|| handshake.server_version_parsed())
             .unwrap_or((0, 0, 0));
+        self.inner.flavor = match ServerFlavor::from_version(handshake.server_version_ref()) {
+            Some(flavor) => flavor,
+            None if self.inner.is_mariadb => ServerFlavor::MariaDb,
+            None => ServerFlavor::MySql,
+        };
         self.inner.id = handshake.connection_id();
+        self.inner.protocol_version = handshake.protocol_version();
+        self.inner.default_collation = handshake.default_collation();
         self.inner.status = handshake.status_flags();
+        self.inner.no_backslash_escapes = self
+            .inner
+            .status
+            .contains(StatusFlags::SERVER_STATUS_NO_BACKSLASH_ESCAPES);
+        self.inner.charset = Cow::Borrowed(self.default_charset());
 
         // Allow only CachingSha2Password and MysqlNativePassword here
         // because sha256_password is deprecated and other plugins won't
@@ -562,7 +967,7 @@ impl Conn {
         let auth_data = self
             .inner
             .auth_plugin
-            .gen_data(self.inner.opts.pass(), &self.inner.nonce);
+            .gen_data(self.auth_pass(), &self.inner.nonce);
 
         let handshake_response = HandshakeResponse::new(
             auth_data.as_deref(),
@@ -575,13 +980,36 @@ impl Conn {
         );
 
         // Serialize here to satisfy borrow checker.
-        let mut buf = crate::BUFFER_POOL.get();
+        let mut buf = self.inner.buffer_pool.get();
         handshake_response.serialize(buf.as_mut());
 
         self.write_packet(buf).await?;
         Ok(())
     }
 
+    /// Returns the password to authenticate with, i.e. the one fetched from
+    /// [`Opts::credential_provider`], if any, or [`Opts::pass`].
+    pub(crate) fn auth_pass(&self) -> Option<&str> {
+        match self.inner.opts.credential_provider() {
+            Some(_) => self.inner.provided_pass.as_deref(),
+            None => self.inner.opts.pass(),
+        }
+    }
+
+    /// Fetches the password from [`Opts::credential_provider`], if any.
+    ///
+    /// The password must be dropped via [`Conn::drop_provided_pass`] once authenticated.
+    async fn fetch_provided_pass(&mut self) -> Result<()> {
+        if let Some(provider) = self.inner.opts.credential_provider() {
+            self.inner.provided_pass = Some(provider.fetch_password().await?);
+        }
+        Ok(())
+    }
+
+    fn drop_provided_pass(&mut self) {
+        self.inner.provided_pass = None;
+    }
+
     async fn perform_auth_switch(
         &mut self,
         auth_switch_request: AuthSwitchRequest<'_>,
@@ -602,32 +1030,38 @@ impl Conn {
 
             let plugin_data = match &self.inner.auth_plugin {
                 x @ AuthPlugin::CachingSha2Password => {
-                    x.gen_data(self.inner.opts.pass(), &self.inner.nonce)
+                    x.gen_data(self.auth_pass(), &self.inner.nonce)
                 }
                 x @ AuthPlugin::MysqlNativePassword => {
-                    x.gen_data(self.inner.opts.pass(), &self.inner.nonce)
+                    x.gen_data(self.auth_pass(), &self.inner.nonce)
                 }
                 x @ AuthPlugin::MysqlOldPassword => {
                     if self.inner.opts.secure_auth() {
                         return Err(DriverError::MysqlOldPasswordDisabled.into());
                     } else {
-                        x.gen_data(self.inner.opts.pass(), &self.inner.nonce)
+                        x.gen_data(self.auth_pass(), &self.inner.nonce)
                     }
                 }
                 x @ AuthPlugin::MysqlClearPassword => {
+                    if self.inner.opts.credential_provider().is_some()
+                        && !self.is_secure()
+                        && !self.is_socket()
+                    {
+                        return Err(DriverError::AuthPluginRequiresSecureTransport.into());
+                    }
                     if self.inner.opts.enable_cleartext_plugin() {
-                        x.gen_data(self.inner.opts.pass(), &self.inner.nonce)
+                        x.gen_data(self.auth_pass(), &self.inner.nonce)
                     } else {
                         return Err(DriverError::CleartextPluginDisabled.into());
                     }
                 }
-                x @ AuthPlugin::Other(_) => x.gen_data(self.inner.opts.pass(), &self.inner.nonce),
+                AuthPlugin::Other(name) => return Err(unsupported_auth_plugin(name).into()),
             };
 
             if let Some(plugin_data) = plugin_data {
                 self.write_struct(&plugin_data.into_owned()).await?;
             } else {
-                self.write_packet(crate::BUFFER_POOL.get()).await?;
+                self.write_packet(self.inner.buffer_pool.get()).await?;
             }
 
             self.continue_auth().await?;
@@ -659,10 +1093,7 @@ impl Conn {
                         Err(DriverError::CleartextPluginDisabled.into())
                     }
                 }
-                AuthPlugin::Other(ref name) => Err(DriverError::UnknownAuthPlugin {
-                    name: String::from_utf8_lossy(name.as_ref()).to_string(),
-                }
-                .into()),
+                AuthPlugin::Other(ref name) => Err(unsupported_auth_plugin(name).into()),
             }
         })
     }
@@ -674,7 +1105,7 @@ impl Conn {
         {
             if let Some(compression) = self.inner.opts.compression() {
                 if let Some(stream) = self.inner.stream.as_mut() {
-                    stream.compress(compression);
+                    stream.compress(compression, self.inner.opts.compression_threshold());
                 }
             }
         }
@@ -694,8 +1125,8 @@ impl Conn {
                     self.drop_packet().await
                 }
                 Some(0x04) => {
-                    let pass = self.inner.opts.pass().unwrap_or_default();
-                    let mut pass = crate::BUFFER_POOL.get_with(pass.as_bytes());
+                    let pass = self.auth_pass().unwrap_or_default();
+                    let mut pass = self.inner.buffer_pool.get_with(pass.as_bytes());
                     pass.as_mut().push(0);
 
                     if self.is_secure() || self.is_socket() {
@@ -703,7 +1134,12 @@ impl Conn {
                     } else {
                         if self.inner.server_key.is_none() {
                             self.write_bytes(&[0x02][..]).await?;
+                            // an ERR packet (e.g. if the server has no key pair)
+                            // is returned as a server error by `read_packet`
                             let packet = self.read_packet().await?;
+                            if !is_public_key_packet(&packet) {
+                                return Err(DriverError::AuthPluginRequiresSecureTransport.into());
+                            }
                             self.inner.server_key = Some(packet[1..].to_vec());
                         }
                         for (i, byte) in pass.as_mut().iter_mut().enumerate() {
@@ -762,25 +1198,38 @@ impl Conn {
     /// Returns `true` for ProgressReport packet.
     fn handle_packet(&mut self, packet: &PooledBuf) -> Result<bool> {
         let ok_packet = if self.has_pending_result() {
-            if self
+            if !crate::queryable::is_result_set_terminator(self.capabilities(), packet) {
+                // a row packet may start with 0xFE, so it must not be parsed as an OK packet
+                None
+            } else if self
                 .capabilities()
                 .contains(CapabilityFlags::CLIENT_DEPRECATE_EOF)
             {
                 ParseBuf(packet)
                     .parse::<OkPacketDeserializer<ResultSetTerminator>>(self.capabilities())
                     .map(|x| x.into_inner())
+                    .or_else(|_| {
+                        // the server sends an EOF packet after column definitions
+                        // if a cursor was opened
+                        ParseBuf(packet)
+                            .parse::<OkPacketDeserializer<OldEofPacket>>(self.capabilities())
+                            .map(|x| x.into_inner())
+                    })
+                    .ok()
             } else {
                 ParseBuf(packet)
                     .parse::<OkPacketDeserializer<OldEofPacket>>(self.capabilities())
                     .map(|x| x.into_inner())
+                    .ok()
             }
         } else {
             ParseBuf(packet)
                 .parse::<OkPacketDeserializer<CommonOkPacket>>(self.capabilities())
                 .map(|x| x.into_inner())
+                .ok()
         };
 
-        if let Ok(ok_packet) = ok_packet {
+        if let Some(ok_packet) = ok_packet {
             self.handle_ok(ok_packet.into_owned());
         } else {
             let err_packet = ParseBuf(packet).parse::<ErrPacket>(self.capabilities());
@@ -832,13 +1281,13 @@ impl Conn {
 
     /// Writes bytes to a server.
     pub(crate) async fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
-        let buf = crate::BUFFER_POOL.get_with(bytes);
+        let buf = self.inner.buffer_pool.get_with(bytes);
         self.write_packet(buf).await
     }
 
     /// Sends a serializable structure to a server.
     pub(crate) async fn write_struct<T: MySerialize>(&mut self, x: &T) -> Result<()> {
-        let mut buf = crate::BUFFER_POOL.get();
+        let mut buf = self.inner.buffer_pool.get();
         x.serialize(buf.as_mut());
         self.write_packet(buf).await
     }
@@ -864,7 +1313,7 @@ impl Conn {
         T: AsRef<[u8]>,
     {
         let cmd_data = cmd_data.as_ref();
-        let mut buf = crate::BUFFER_POOL.get();
+        let mut buf = self.inner.buffer_pool.get();
         let body = buf.as_mut();
         body.push(cmd as u8);
         body.extend_from_slice(cmd_data);
@@ -887,6 +1336,17 @@ impl Conn {
     }
 
     async fn run_setup_commands(&mut self) -> Result<()> {
+        if let Some(wait_timeout) = self.inner.opts.session_wait_timeout() {
+            self.query_drop(format!("SET SESSION wait_timeout = {}", wait_timeout))
+                .await?;
+            self.inner.wait_timeout = Duration::from_secs(wait_timeout as u64);
+        }
+
+        if let Some(charset) = self.inner.opts.results_charset() {
+            let query = format!("SET character_set_results = {}", self.quote_string(charset));
+            self.query_drop(query).await?;
+        }
+
         let mut setup = self.inner.opts.setup().to_vec();
 
         while let Some(query) = setup.pop() {
@@ -896,37 +1356,92 @@ impl Conn {
         Ok(())
     }
 
+    /// Prepares statements listed in [`PoolOpts::prepared_statement_warmup`]
+    /// so that they are in the statement cache.
+    ///
+    /// [`PoolOpts::prepared_statement_warmup`]: crate::PoolOpts::prepared_statement_warmup
+    pub(crate) async fn warm_up_stmt_cache(&mut self) -> Result<()> {
+        if self.inner.opts.stmt_cache_size() == 0 {
+            return Ok(());
+        }
+
+        let statements = self
+            .inner
+            .opts
+            .pool_opts()
+            .prepared_statement_warmup()
+            .to_vec();
+        for statement in statements {
+            self.prep(statement).await?;
+        }
+
+        Ok(())
+    }
+
     /// Returns a future that resolves to [`Conn`].
     pub fn new<T: Into<Opts>>(opts: T) -> crate::BoxFuture<'static, Conn> {
         let opts = opts.into();
         async move {
             let mut conn = Conn::empty(opts.clone());
+            let mut stopwatch = Stopwatch::new(opts.collect_connect_stats());
 
             let stream = if let Some(_path) = opts.socket() {
-                #[cfg(unix)]
+                #[cfg(any(unix, windows))]
                 {
-                    Stream::connect_socket(_path.to_owned()).await?
+                    Stream::connect_socket(_path.to_owned(), conn.inner.buffer_pool.clone()).await?
                 }
-                #[cfg(target_os = "windows")]
+                #[cfg(not(any(unix, windows)))]
                 return Err(crate::DriverError::NamedPipesDisabled.into());
             } else {
-                let keepalive = opts
-                    .tcp_keepalive()
-                    .map(|x| std::time::Duration::from_millis(x.into()));
-                Stream::connect_tcp(opts.hostport_or_url(), keepalive).await?
+                // `pipe_name` is only meaningful on Windows, if the pipe can't be opened
+                // (or on other platforms) the connection is established over TCP
+                #[cfg(windows)]
+                let pipe = match opts.pipe_name() {
+                    Some(name) => {
+                        Stream::connect_socket(name.to_owned(), conn.inner.buffer_pool.clone())
+                            .await
+                            .ok()
+                    }
+                    None => None,
+                };
+                #[cfg(not(windows))]
+                let pipe = None;
+
+                match pipe {
+                    Some(stream) => stream,
+                    None => {
+                        let keepalive = opts
+                            .tcp_keepalive()
+                            .map(|x| std::time::Duration::from_millis(x.into()));
+                        let addrs = Stream::resolve(opts.hostport_or_url()).await?;
+                        stopwatch.dns_resolved();
+                        Stream::connect_tcp(&addrs, keepalive, conn.inner.buffer_pool.clone())
+                            .await?
+                    }
+                }
             };
+            stopwatch.connected();
 
             conn.inner.stream = Some(stream);
             conn.setup_stream()?;
             conn.handle_handshake().await?;
+            stopwatch.auth_step_done();
             conn.switch_to_ssl_if_needed().await?;
+            if conn.is_secure() {
+                stopwatch.tls_handshake_done();
+            }
+            conn.fetch_provided_pass().await?;
             conn.do_handshake_response().await?;
             conn.continue_auth().await?;
+            conn.drop_provided_pass();
+            stopwatch.auth_step_done();
             conn.switch_to_compression()?;
             conn.read_settings().await?;
             conn.reconnect_via_socket_if_needed().await?;
             conn.run_init_commands().await?;
             conn.run_setup_commands().await?;
+            conn.inner.default_autocommit = conn.autocommit();
+            conn.inner.connect_stats = stopwatch.finish();
 
             Ok(conn)
         }
@@ -944,7 +1459,7 @@ impl Conn {
     async fn reconnect_via_socket_if_needed(&mut self) -> Result<()> {
         if let Some(socket) = self.inner.socket.as_ref() {
             let opts = self.inner.opts.clone();
-            if opts.socket().is_none() {
+            if opts.socket().is_none() && !self.is_socket() {
                 let opts = OptsBuilder::from_opts(opts).socket(Some(&**socket));
                 if let Ok(conn) = Conn::new(opts).await {
                     let old_conn = std::mem::replace(self, conn);
@@ -956,6 +1471,47 @@ impl Conn {
         Ok(())
     }
 
+    /// Will re-establish a broken connection if [`Opts::auto_reconnect`] is enabled
+    /// and it's safe to do so (see [`Opts::auto_reconnect`] docs).
+    async fn reconnect_if_needed(&mut self) -> Result<()> {
+        if !self.inner.opts.auto_reconnect() || self.inner.pool.is_some() {
+            return Ok(());
+        }
+
+        let in_transaction = match self.inner.tx_status {
+            TxStatus::InTransaction => true,
+            TxStatus::RequiresRollback => false,
+            TxStatus::None => self
+                .inner
+                .status
+                .contains(StatusFlags::SERVER_STATUS_IN_TRANS),
+        };
+
+        if in_transaction {
+            return Ok(());
+        }
+
+        let has_pending_result = self.has_pending_result();
+        if let Some(stream) = self.inner.stream.as_mut() {
+            // unconsumed result set is still in the stream, so we shouldn't check it
+            if has_pending_result || !matches!(stream.check().now_or_never(), Some(Err(_))) {
+                return Ok(());
+            }
+        }
+
+        // Either the stream is broken or it is gone because a previous command failed
+        // with a fatal error (so the loss of a pending result set was already reported).
+        let mut conn = Conn::new(self.inner.opts.clone()).await?;
+        conn.inner.infile_handler = self.inner.infile_handler.take();
+        conn.inner.pinned_stmts = mem::take(&mut self.inner.pinned_stmts);
+        let mut old_conn = replace(self, conn);
+        // the old connection is unusable, so there is nothing to tidy up
+        old_conn.inner.disconnected = true;
+        self.reprepare_pinned_stmts().await?;
+
+        Ok(())
+    }
+
     /// Configures the connection based on server settings. In particular:
     ///
     /// * It reads and stores socket address inside the connection unless if socket address is
@@ -965,6 +1521,8 @@ impl Conn {
     ///
     /// * It reads and stores `wait_timeout` in the connection unless it's already in [`Opts`]
     ///
+    /// * It reads `version_comment` to detect [`ServerFlavor`] unless it's already known
+    ///
     async fn read_settings(&mut self) -> Result<()> {
         enum Action {
             Load(Cfg),
@@ -995,6 +1553,7 @@ impl Conn {
             Socket,
             MaxAllowedPacket,
             WaitTimeout,
+            VersionComment,
         }
 
         impl Cfg {
@@ -1003,6 +1562,7 @@ impl Conn {
                     Self::Socket => "@@socket",
                     Self::MaxAllowedPacket => "@@max_allowed_packet",
                     Self::WaitTimeout => "@@wait_timeout",
+                    Self::VersionComment => "@@version_comment",
                 }
             }
 
@@ -1027,6 +1587,11 @@ impl Conn {
                                 .unwrap_or(DEFAULT_WAIT_TIMEOUT) as u64,
                         );
                     }
+                    Cfg::VersionComment => {
+                        if let Some(crate::Value::Bytes(comment)) = value {
+                            conn.inner.flavor = ServerFlavor::from_version_comment(&comment);
+                        }
+                    }
                 }
             }
         }
@@ -1048,6 +1613,10 @@ impl Conn {
             actions.push(Action::Load(Cfg::Socket))
         }
 
+        if self.inner.flavor == ServerFlavor::MySql {
+            actions.push(Action::Load(Cfg::VersionComment))
+        }
+
         let loads = actions
             .iter()
             .filter_map(|x| match x {
@@ -1106,11 +1675,236 @@ impl Conn {
         self.inner.last_io.elapsed()
     }
 
+    /// Executes `COM_PING`, that must complete within the given `timeout`.
+    ///
+    /// Unlike [`Queryable::ping`] this function won't hang if the server is unresponsive,
+    /// so it is suitable for health checks. On timeout the connection is considered broken
+    /// and it is closed (it won't be returned to a pool), and an [`std::io::ErrorKind::TimedOut`]
+    /// I/O error is returned.
+    pub async fn ping_timeout(&mut self, timeout: Duration) -> Result<()> {
+        match tokio::time::timeout(timeout, self.ping()).await {
+            Ok(result) => result,
+            Err(_) => {
+                // the server is in the middle of a command, so the connection is unusable
+                self.inner.stream.take();
+                self.inner.disconnected = true;
+                Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "ping timed out").into())
+            }
+        }
+    }
+
+    /// Executes `COM_STATISTICS` and returns the server status string.
+    ///
+    /// The string is human-readable and contains e.g. uptime, number of threads
+    /// and queries per second (see [`COM_STATISTICS`][1]).
+    ///
+    /// [1]: https://dev.mysql.com/doc/dev/mysql-server/latest/page_protocol_com_statistics.html
+    pub async fn statistics(&mut self) -> Result<String> {
+        self.routine(routines::StatisticsRoutine).await
+    }
+
+    /// Terminates the connection with the given id (see [`Conn::id`]) using `KILL CONNECTION`.
+    ///
+    /// Returns a server error, for which [`Error::is_no_such_thread`] is `true`,
+    /// if there is no such connection. Note that killing a connection of another user
+    /// requires the `CONNECTION_ADMIN` (or `SUPER`) privilege.
+    pub async fn kill_connection(&mut self, id: u32) -> Result<()> {
+        self.query_drop(format!("KILL CONNECTION {}", id)).await
+    }
+
+    /// Terminates the statement, that the connection with the given id (see [`Conn::id`])
+    /// is currently executing, using `KILL QUERY`. The connection itself stays alive.
+    ///
+    /// Returns a server error, for which [`Error::is_no_such_thread`] is `true`,
+    /// if there is no such connection.
+    pub async fn kill_query(&mut self, id: u32) -> Result<()> {
+        self.query_drop(format!("KILL QUERY {}", id)).await
+    }
+
+    /// Executes the given statement (see [`Queryable::exec`]) and kills it using `KILL QUERY`
+    /// if it doesn't complete within the given `timeout`.
+    ///
+    /// Unlike `max_execution_time`, that only applies to `SELECT` statements, this works
+    /// for any statement. `KILL QUERY` is issued on a sibling connection – an idle one
+    /// taken from the pool of this connection (see [`Pool::try_get_conn`]), or a new one
+    /// established using [`Conn::opts`] if there is no such connection.
+    ///
+    /// Returns [`DriverError::StatementTimeout`] once the statement is killed (i.e. the server
+    /// reports `ER_QUERY_INTERRUPTED`), the connection stays usable. Note that the statement
+    /// may complete before it's killed, in which case its result (or error) is returned.
+    /// If `KILL QUERY` fails, then the connection is closed (because the statement is still
+    /// running) and the error is returned.
+    ///
+    /// Note that a killed `SLEEP()`, that is the only thing the statement does, isn't
+    /// interrupted with an error, but returns `1`, so its result is returned.
+    ///
+    /// ```rust
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn, DriverError, Error};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    ///
+    /// let result = conn
+    ///     .exec_timeout::<u8, _, _>(
+    ///         "SELECT n FROM (SELECT 1 AS n) t WHERE SLEEP(?) = 0",
+    ///         (10,),
+    ///         Duration::from_millis(500),
+    ///     )
+    ///     .await;
+    /// assert!(matches!(result, Err(Error::Driver(DriverError::StatementTimeout { .. }))));
+    ///
+    /// // the connection is still usable
+    /// conn.ping().await?;
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn exec_timeout<T, S, P>(
+        &mut self,
+        stmt: S,
+        params: P,
+        timeout: Duration,
+    ) -> Result<Vec<T>>
+    where
+        S: StatementLike,
+        P: Into<Params> + Send,
+        T: FromRow + Send + 'static,
+    {
+        use futures_util::future::{select, Either};
+
+        let id = self.id();
+        let pool = self.inner.pool.clone();
+        let opts = self.inner.opts.clone();
+
+        let mut exec = self.exec::<T, S, P>(stmt, params);
+        let kill_result = match tokio::time::timeout(timeout, &mut exec).await {
+            Ok(result) => return result,
+            Err(_) => {
+                let kill = async move {
+                    let sibling = match pool {
+                        Some(pool) => pool.try_get_conn(),
+                        None => None,
+                    };
+                    let mut sibling = match sibling {
+                        Some(sibling) => sibling,
+                        None => Conn::new(opts).await?,
+                    };
+                    sibling.kill_query(id).await
+                }
+                .boxed();
+                match select(&mut exec, kill).await {
+                    Either::Left((result, kill)) => {
+                        // the statement completed before it's killed, but `KILL QUERY` is still
+                        // awaited, so that it won't hit a subsequent statement
+                        let _ = kill.await;
+                        return result;
+                    }
+                    Either::Right((kill_result, _)) => kill_result,
+                }
+            }
+        };
+
+        match kill_result {
+            Ok(()) => match exec.await {
+                Err(err) if err.is_query_interrupted() => {
+                    Err(DriverError::StatementTimeout { timeout }.into())
+                }
+                result => result,
+            },
+            Err(err) => {
+                // the statement is still running, so the connection is unusable
+                drop(exec);
+                self.inner.stream.take();
+                self.inner.disconnected = true;
+                Err(err)
+            }
+        }
+    }
+
+    /// Low-level function that sends the given command and returns raw response packets.
+    ///
+    /// Command packet consists of the `cmd` byte (see [`crate::consts::Command`])
+    /// followed by the `payload`. Response packets are collected up to and including
+    /// the first packet that looks like a terminal one, that is:
+    ///
+    /// * an OK packet, if it is the first packet of the response;
+    /// * an EOF packet (or an OK packet with `0xFE` header if `CLIENT_DEPRECATE_EOF`
+    ///   is negotiated).
+    ///
+    /// An ERR packet is returned as an [`Error::Server`].
+    ///
+    /// # Warning
+    ///
+    /// This function knows nothing about the command, so it is the caller's responsibility
+    /// to use it only for commands, whose response is terminated as described above
+    /// (e.g. a result set will only be collected up to the end of column definitions,
+    /// unless `CLIENT_DEPRECATE_EOF` is negotiated). Misuse will desync the connection
+    /// with the server, so that subsequent operations will fail or return garbage.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{consts::Command, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let response = conn.send_command_raw(Command::COM_PING as u8, &[]).await?;
+    /// assert_eq!(response.len(), 1);
+    /// assert_eq!(response[0][0], 0x00); // OK packet
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn send_command_raw(&mut self, cmd: u8, payload: &[u8]) -> Result<Vec<Bytes>> {
+        self.routine(routines::RawCommandRoutine::new(cmd, payload))
+            .await
+    }
+
+    /// Prepares the given statement and pins it to this connection.
+    ///
+    /// Unlike statements prepared via [`Queryable::prep`], a pinned statement is never
+    /// evicted from the statement cache and it is re-prepared automatically whenever
+    /// server-side statements are discarded, i.e. by [`Conn::reset`], [`Conn::change_user`]
+    /// or an automatic reconnect (see [`Opts::auto_reconnect`]). So the returned handle
+    /// stays valid for the lifetime of the connection (including the time it spends in a pool,
+    /// that resets connections upon returning).
+    ///
+    /// Pinning the same query twice returns the same statement. Use [`Queryable::close`]
+    /// to unpin and close the statement.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{prelude::*, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let stmt = conn.prepare_pinned("SELECT ?").await?;
+    ///
+    /// conn.reset().await?;
+    /// let value: Option<u8> = conn.exec_first(&stmt, (42,)).await?;
+    /// assert_eq!(value, Some(42));
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn prepare_pinned<Q: crate::prelude::AsQuery>(
+        &mut self,
+        query: Q,
+    ) -> Result<Statement> {
+        let query = query.as_query();
+        let parsed = named_params::ParsedNamedParams::parse(query.as_ref())?;
+        let inner_stmt = self.pin_stmt(parsed.query()).await?;
+        let named_params = parsed
+            .params()
+            .iter()
+            .map(|x| x.as_ref().to_vec())
+            .collect::<Vec<_>>();
+        Ok(Statement::new(inner_stmt, named_params).pinned())
+    }
+
     /// Executes [`COM_RESET_CONNECTION`][1].
     ///
     /// Returns `false` if command is not supported (requires MySql >5.7.2, MariaDb >10.2.3).
     /// For older versions consider using [`Conn::change_user`].
     ///
+    /// Statements pinned via [`Conn::prepare_pinned`] are re-prepared after the reset.
+    ///
     /// [1]: https://dev.mysql.com/doc/c-api/5.7/en/mysql-reset-connection.html
     pub async fn reset(&mut self) -> Result<bool> {
         let supports_com_reset_connection = if self.inner.is_mariadb {
@@ -1122,8 +1916,11 @@ impl Conn {
 
         if supports_com_reset_connection {
             self.routine(routines::ResetRoutine).await?;
+            self.inner.charset = Cow::Borrowed(self.default_charset());
             self.inner.stmt_cache.clear();
+            self.untrack_prepared_statements();
             self.inner.infile_handler = None;
+            self.reprepare_pinned_stmts().await?;
             self.run_setup_commands().await?;
         }
 
@@ -1163,34 +1960,187 @@ impl Conn {
 
         let conn_opts = &mut self.inner.opts;
         opts.update_opts(conn_opts);
-        self.routine(routines::ChangeUser).await?;
+        self.fetch_provided_pass().await?;
+        let result = self.routine(routines::ChangeUser).await;
+        self.drop_provided_pass();
+        result?;
+        self.inner.charset = Cow::Borrowed(self.default_charset());
         self.inner.stmt_cache.clear();
+        self.untrack_prepared_statements();
         self.inner.infile_handler = None;
+        self.reprepare_pinned_stmts().await?;
         self.run_setup_commands().await?;
         Ok(())
     }
 
-    /// Resets the connection upon returning it to a pool.
+    /// Changes the default database of this connection using [`COM_INIT_DB`][1].
     ///
-    /// Will invoke `COM_CHANGE_USER` if `COM_RESET_CONNECTION` is not supported.
-    async fn reset_for_pool(mut self) -> Result<Self> {
-        if !self.reset().await? {
-            self.change_user(Default::default()).await?;
-        }
-        Ok(self)
+    /// This is a cheaper alternative to the `USE` statement that does not require
+    /// the database name to be quoted or escaped. Connection options will be updated
+    /// accordingly, i.e. [`Opts::db_name`] will return `db_name` after the call.
+    ///
+    /// Returns a server error (`ER_BAD_DB_ERROR`) if the database does not exist.
+    ///
+    /// Note that neither `COM_RESET_CONNECTION` nor returning to a [`Pool`] restores
+    /// the default database, so a pooled connection keeps the selected database
+    /// (and reports it via [`Conn::opts`]) while it's idling. [`Pool::get_conn`] selects
+    /// the default database of the pool back (see [`Pool::get_conn_for_db`]).
+    ///
+    /// [1]: https://dev.mysql.com/doc/dev/mysql-server/latest/page_protocol_com_init_db.html
+    pub async fn select_db<T: AsRef<str>>(&mut self, db_name: T) -> Result<()> {
+        let db_name = db_name.as_ref();
+        self.routine(routines::InitDbRoutine::new(db_name.as_bytes()))
+            .await?;
+        ChangeUserOpts::default()
+            .with_db_name(Some(db_name.into()))
+            .update_opts(&mut self.inner.opts);
+        Ok(())
     }
 
-    /// Requires that `self.inner.tx_status != TxStatus::None`
-    async fn rollback_transaction(&mut self) -> Result<()> {
-        debug_assert_ne!(self.inner.tx_status, TxStatus::None);
-        self.inner.tx_status = TxStatus::None;
-        self.query_drop("ROLLBACK").await
+    /// Sets the user-defined variable `@name` to the given value.
+    ///
+    /// `name` is given without the leading `@` and is quoted as an identifier, so it
+    /// can't be used to inject SQL. The value is sent as a statement parameter, therefore
+    /// it keeps its type (e.g. an integer variable stays an integer).
+    ///
+    /// Note that only user-defined variables are supported, i.e. this won't set
+    /// a session system variable (`@@name`). Use `SET SESSION` for that.
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, Conn};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    ///
+    /// conn.set_var("answer", 42).await?;
+    /// assert_eq!(conn.get_var::<u32>("answer").await?, Some(42));
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn set_var<V: Into<Value>>(&mut self, name: &str, value: V) -> Result<()> {
+        let query = format!("SET @{} = ?", crate::quote_identifier(name));
+        self.exec_drop(query, (value.into(),)).await
     }
 
-    /// Returns `true` if `SERVER_MORE_RESULTS_EXISTS` flag is contained
-    /// in status flags of the connection.
-    pub(crate) fn more_results_exists(&self) -> bool {
-        self.status()
+    /// Returns the value of the user-defined variable `@name` (see [`Conn::set_var`]).
+    ///
+    /// Returns `None` if the variable is not set (or is `NULL`) and
+    /// [`DriverError::FromValue`] if the value can't be converted to `T`.
+    pub async fn get_var<T: FromValue>(&mut self, name: &str) -> Result<Option<T>> {
+        let query = format!("SELECT @{}", crate::quote_identifier(name));
+        match self.query_first::<Value, _>(query).await? {
+            None | Some(Value::NULL) => Ok(None),
+            Some(value) => crate::from_value_opt(value)
+                .map(Some)
+                .map_err(|FromValueError(value)| DriverError::FromValue { value }.into()),
+        }
+    }
+
+    /// Sets the given session system variables using a single `SET` statement.
+    ///
+    /// Names are quoted as identifiers and values are sent as statement parameters, so
+    /// a single round-trip is needed to apply any number of settings, and values keep their
+    /// types. Does nothing if `vars` is empty.
+    ///
+    /// Note that the statement is executed as a whole, so if one of the variables fails
+    /// to be set, none of them is changed.
+    ///
+    /// ```rust
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn, Value};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    ///
+    /// conn.set_session_vars(&[
+    ///     ("sql_select_limit", Value::from(100)),
+    ///     ("wait_timeout", Value::from(600)),
+    /// ])
+    /// .await?;
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn set_session_vars<N: AsRef<str>>(&mut self, vars: &[(N, Value)]) -> Result<()> {
+        if vars.is_empty() {
+            return Ok(());
+        }
+
+        let mut query = String::from("SET ");
+        let mut params = Vec::with_capacity(vars.len());
+        for (i, (name, value)) in vars.iter().enumerate() {
+            if i > 0 {
+                query.push_str(", ");
+            }
+            query.push_str("SESSION ");
+            query.push_str(&crate::quote_identifier(name.as_ref()));
+            query.push_str(" = ?");
+            params.push(value.clone());
+        }
+
+        self.exec_drop(query, params).await
+    }
+
+    /// Resets the connection upon returning it to a pool.
+    ///
+    /// Will invoke `COM_CHANGE_USER` if `COM_RESET_CONNECTION` is not supported.
+    async fn reset_for_pool(mut self) -> Result<Self> {
+        if !self.reset().await? {
+            self.change_user(Default::default()).await?;
+        }
+        self.warm_up_stmt_cache().await?;
+        Ok(self)
+    }
+
+    /// Requires that `self.inner.tx_status != TxStatus::None`
+    ///
+    /// Closes statements prepared within the transaction, if requested
+    /// (see [`crate::TxOpts::close_prepared_on_end`]).
+    async fn rollback_transaction(&mut self) -> Result<()> {
+        debug_assert_ne!(self.inner.tx_status, TxStatus::None);
+        self.inner.tx_status = TxStatus::None;
+        if let Err(err) = self.query_drop("ROLLBACK").await {
+            self.untrack_prepared_statements();
+            return Err(err);
+        }
+        self.close_tracked_statements().await
+    }
+
+    /// Starts timing of the sent query, if slow queries are reported
+    /// (see [`Opts::slow_query_threshold`]).
+    pub(crate) fn start_slow_query_timer(&mut self, query: impl FnOnce() -> Arc<[u8]>) {
+        if self.inner.opts.slow_query_threshold().is_some()
+            && self.inner.opts.slow_query_handler().is_some()
+        {
+            self.inner.slow_query = Some((Instant::now(), query()));
+        }
+    }
+
+    /// Stops timing of a query, that failed with an error.
+    pub(crate) fn cancel_slow_query_timer(&mut self) {
+        self.inner.slow_query = None;
+    }
+
+    /// Stops timing of the query, once its last result set is read, and reports the query
+    /// to [`Opts::slow_query_handler`], if it took longer than the threshold.
+    pub(crate) fn finish_slow_query_timer(&mut self) {
+        if self.inner.slow_query.is_none() || self.more_results_exists() {
+            return;
+        }
+
+        if let Some((started, query)) = self.inner.slow_query.take() {
+            let elapsed = started.elapsed();
+            let threshold = self.inner.opts.slow_query_threshold();
+            if let (Some(threshold), Some(handler)) =
+                (threshold, self.inner.opts.slow_query_handler())
+            {
+                if elapsed > threshold {
+                    handler.handle(&String::from_utf8_lossy(&query), elapsed);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if `SERVER_MORE_RESULTS_EXISTS` flag is contained
+    /// in status flags of the connection.
+    pub(crate) fn more_results_exists(&self) -> bool {
+        self.status()
             .contains(StatusFlags::SERVER_MORE_RESULTS_EXISTS)
     }
 
@@ -1254,6 +2204,19 @@ impl Conn {
                 }
             }
         }
+
+        // the transaction is over, so its statements must not be tracked by the next user
+        self.untrack_prepared_statements();
+
+        if self.autocommit_is_dirty() {
+            // Roll back the implicit transaction first, because changing
+            // the autocommit mode would commit it. Any error here means that
+            // the state is unknown, so the connection shouldn't return to a pool.
+            self.query_drop("ROLLBACK").await?;
+            let default_autocommit = self.inner.default_autocommit;
+            self.set_autocommit(default_autocommit).await?;
+        }
+
         Ok(self)
     }
 }
@@ -1266,10 +2229,63 @@ mod test {
     use rand::Fill;
 
     use crate::{
-        from_row, params, prelude::*, test_misc::get_opts, ChangeUserOpts, Conn, Error,
-        OptsBuilder, Pool, Value, WhiteListFsHandler,
+        from_row, params, prelude::*, test_misc::get_opts, BufferStrategy, ChangeUserOpts, Conn,
+        DriverError, Error, Opts, OptsBuilder, Pool, PoolConstraints, PoolOpts, Value,
+        WhiteListFsHandler,
     };
 
+    /// Writes a packet on behalf of a mock server.
+    async fn write_packet(stream: &mut tokio::net::TcpStream, seq_id: u8, payload: &[u8]) {
+        use tokio::io::AsyncWriteExt;
+
+        let mut packet = (payload.len() as u32).to_le_bytes().to_vec();
+        packet[3] = seq_id;
+        packet.extend_from_slice(payload);
+        stream.write_all(&packet).await.unwrap();
+    }
+
+    /// Reads a packet payload on behalf of a mock server.
+    async fn read_packet(stream: &mut tokio::net::TcpStream) -> Vec<u8> {
+        use tokio::io::AsyncReadExt;
+
+        let mut header = [0_u8; 4];
+        stream.read_exact(&mut header).await.unwrap();
+        let mut payload =
+            vec![0_u8; u32::from_le_bytes([header[0], header[1], header[2], 0]) as usize];
+        stream.read_exact(&mut payload).await.unwrap();
+        payload
+    }
+
+    /// Returns an initial handshake packet of a mock server.
+    fn handshake_packet(server_version: &[u8], auth_plugin: &[u8]) -> Vec<u8> {
+        use mysql_common::{
+            constants::{CapabilityFlags, StatusFlags},
+            packets::HandshakePacket,
+            proto::MySerialize,
+        };
+
+        let mut handshake = Vec::new();
+        HandshakePacket::new(
+            10,
+            server_version,
+            1,
+            [1; 8],
+            Some(&b"123456789012\0"[..]),
+            CapabilityFlags::CLIENT_PROTOCOL_41
+                | CapabilityFlags::CLIENT_SECURE_CONNECTION
+                | CapabilityFlags::CLIENT_PLUGIN_AUTH
+                | CapabilityFlags::CLIENT_LONG_PASSWORD
+                | CapabilityFlags::CLIENT_TRANSACTIONS
+                | CapabilityFlags::CLIENT_PLUGIN_AUTH_LENENC_CLIENT_DATA
+                | CapabilityFlags::CLIENT_DEPRECATE_EOF,
+            45,
+            StatusFlags::SERVER_STATUS_AUTOCOMMIT,
+            Some(auth_plugin),
+        )
+        .serialize(&mut handshake);
+        handshake
+    }
+
     #[tokio::test]
     async fn should_return_found_rows_if_flag_is_set() -> super::Result<()> {
         let opts = get_opts().client_found_rows(true);
@@ -1430,183 +2446,1153 @@ mod test {
             assert!(format!("{:?}", conn).contains("Compression"));
         }
 
-        if crate::test_misc::test_ssl() {
-            assert!(format!("{:?}", conn).contains("Tls"));
-        }
+        if crate::test_misc::test_ssl() {
+            assert!(format!("{:?}", conn).contains("Tls"));
+        }
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[test]
+    fn should_classify_auth_plugin_errors() {
+        assert!(matches!(
+            super::unsupported_auth_plugin(b"sha256_password"),
+            DriverError::UnsupportedAuthPlugin { name } if name == "sha256_password"
+        ));
+        assert!(matches!(
+            super::unsupported_auth_plugin(b"foo_bar"),
+            DriverError::UnknownAuthPlugin { name } if name == "foo_bar"
+        ));
+
+        assert!(super::is_public_key_packet(
+            b"\x01-----BEGIN PUBLIC KEY-----\nMIIB\n-----END PUBLIC KEY-----\n"
+        ));
+        assert!(!super::is_public_key_packet(b""));
+        assert!(!super::is_public_key_packet(b"\x01"));
+        assert!(!super::is_public_key_packet(
+            b"\x02-----BEGIN PUBLIC KEY-----\n-----END PUBLIC KEY-----\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_report_negotiated_tls_and_compression() -> super::Result<()> {
+        let conn = Conn::new(get_opts()).await?;
+        assert_eq!(conn.is_tls(), crate::test_misc::test_ssl());
+        if crate::test_misc::test_compression() {
+            assert_eq!(conn.compression(), Some(crate::Compression::default()));
+        } else {
+            assert_eq!(conn.compression(), None);
+        }
+        conn.disconnect().await?;
+
+        let opts = get_opts().compression(crate::Compression::fast());
+        let conn = Conn::new(opts).await?;
+        assert_eq!(conn.compression(), Some(crate::Compression::fast()));
+        conn.disconnect().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_missing_public_key() -> super::Result<()> {
+        use tokio::{io::AsyncReadExt, net::TcpListener};
+
+        // server that requests a full `caching_sha2_password` authentication over TCP
+        // and answers the public key request with the given packet
+        async fn connect_to_server(public_key_response: Vec<u8>) -> super::Result<Conn> {
+            let listener = TcpListener::bind("127.0.0.1:0").await?;
+            let port = listener.local_addr()?.port();
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let handshake = handshake_packet(b"8.0.34", b"caching_sha2_password");
+                write_packet(&mut stream, 0, &handshake).await;
+                // handshake response
+                read_packet(&mut stream).await;
+                // perform full authentication
+                write_packet(&mut stream, 2, &[0x01, 0x04]).await;
+                assert_eq!(read_packet(&mut stream).await, vec![0x02]);
+                write_packet(&mut stream, 4, &public_key_response).await;
+                let _ = stream.read(&mut [0_u8; 1]).await;
+            });
+
+            let opts = OptsBuilder::default()
+                .ip_or_hostname("127.0.0.1")
+                .tcp_port(port)
+                .prefer_socket(false)
+                .user(Some("root"))
+                .pass(Some("password"));
+            Conn::new(opts).await
+        }
+
+        let result = connect_to_server(b"\x01not a key".to_vec()).await;
+        assert!(matches!(
+            result,
+            Err(Error::Driver(
+                DriverError::AuthPluginRequiresSecureTransport
+            ))
+        ));
+
+        let mut err_packet = vec![0xFF, 0x15, 0x04, b'#'];
+        err_packet.extend_from_slice(b"28000Access denied");
+        let result = connect_to_server(err_packet).await;
+        match result {
+            Err(Error::Server(err)) => assert_eq!(err.code, 1045),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_require_opt_in_for_mysql_old_password() -> super::Result<()> {
+        use tokio::{io::AsyncReadExt, net::TcpListener};
+
+        // pre-4.1 server (MariaDb, so that `@@version_comment` isn't loaded), that switches
+        // authentication to `mysql_old_password`
+        async fn connect_to_server(secure_auth: bool) -> super::Result<Conn> {
+            let listener = TcpListener::bind("127.0.0.1:0").await?;
+            let port = listener.local_addr()?.port();
+            tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let handshake =
+                    handshake_packet(b"5.5.5-10.11.2-MariaDB", b"mysql_native_password");
+                write_packet(&mut stream, 0, &handshake).await;
+                // handshake response
+                read_packet(&mut stream).await;
+                let mut auth_switch = b"\xFEmysql_old_password\0".to_vec();
+                auth_switch.extend_from_slice(b"12345678\0");
+                write_packet(&mut stream, 2, &auth_switch).await;
+                if secure_auth {
+                    return;
+                }
+                // null-terminated 8-byte scramble
+                assert_eq!(read_packet(&mut stream).await.len(), 9);
+                write_packet(&mut stream, 4, &[0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00]).await;
+                let _ = stream.read(&mut [0_u8; 1]).await;
+            });
+
+            let opts = OptsBuilder::default()
+                .ip_or_hostname("127.0.0.1")
+                .tcp_port(port)
+                .prefer_socket(false)
+                .max_allowed_packet(Some(16 * 1024 * 1024))
+                .wait_timeout(Some(28800))
+                .secure_auth(secure_auth)
+                .user(Some("root"))
+                .pass(Some("password"));
+            Conn::new(opts).await
+        }
+
+        let result = connect_to_server(true).await;
+        assert!(
+            matches!(
+                result,
+                Err(Error::Driver(DriverError::MysqlOldPasswordDisabled))
+            ),
+            "{:?}",
+            result.map(|_| ())
+        );
+
+        let conn = connect_to_server(false).await?;
+        assert_eq!(conn.id(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_unsupported_auth_plugin() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        let plugins: Vec<(String, String)> = conn
+            .query("SELECT PLUGIN_NAME, PLUGIN_STATUS FROM information_schema.PLUGINS")
+            .await?;
+        let available = plugins
+            .iter()
+            .any(|(name, status)| name == "sha256_password" && status == "ACTIVE");
+
+        if available && !conn.inner.is_mariadb {
+            let _ = conn.query_drop("DROP USER '__sha256'@'%'").await;
+            conn.query_drop("CREATE USER '__sha256'@'%' IDENTIFIED WITH sha256_password BY 'pass'")
+                .await?;
+
+            let opts = get_opts()
+                .user(Some("__sha256"))
+                .pass(Some("pass"))
+                .db_name(None::<String>);
+            let result = Conn::new(opts).await;
+            conn.query_drop("DROP USER '__sha256'@'%'").await?;
+
+            match result {
+                Err(Error::Driver(DriverError::UnsupportedAuthPlugin { name })) => {
+                    assert_eq!(name, "sha256_password")
+                }
+                other => panic!("unexpected result: {:?}", other.map(|_| ())),
+            }
+        }
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[test]
+    fn should_not_panic_if_dropped_without_tokio_runtime() {
+        let fut = Conn::new(get_opts());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            fut.await.unwrap();
+        });
+        // connection will drop here
+    }
+
+    #[tokio::test]
+    async fn should_execute_init_queries_on_new_connection() -> super::Result<()> {
+        let opts = OptsBuilder::from_opts(get_opts()).init(vec!["SET @a = 42", "SET @b = 'foo'"]);
+        let mut conn = Conn::new(opts).await?;
+        let result: Vec<(u8, String)> = conn.query("SELECT @a, @b").await?;
+        conn.disconnect().await?;
+        assert_eq!(result, vec![(42, "foo".into())]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_execute_setup_queries_on_reset() -> super::Result<()> {
+        let opts = OptsBuilder::from_opts(get_opts()).setup(vec!["SET @a = 42", "SET @b = 'foo'"]);
+        let mut conn = Conn::new(opts).await?;
+
+        // initial run
+        let mut result: Vec<(u8, String)> = conn.query("SELECT @a, @b").await?;
+        assert_eq!(result, vec![(42, "foo".into())]);
+
+        // after reset
+        if conn.reset().await? {
+            result = conn.query("SELECT @a, @b").await?;
+            assert_eq!(result, vec![(42, "foo".into())]);
+        }
+
+        // after change user
+        conn.change_user(Default::default()).await?;
+        result = conn.query("SELECT @a, @b").await?;
+        assert_eq!(result, vec![(42, "foo".into())]);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_set_session_wait_timeout() -> super::Result<()> {
+        let query = "SELECT @@SESSION.wait_timeout";
+
+        // `wait_timeout` only tells the client the server value
+        let opts = OptsBuilder::from_opts(get_opts()).wait_timeout(Some(1234));
+        let mut conn = Conn::new(opts).await?;
+        let global: Option<usize> = conn.query_first("SELECT @@GLOBAL.wait_timeout").await?;
+        assert_eq!(conn.query_first::<usize, _>(query).await?, global);
+        conn.disconnect().await?;
+
+        let opts = OptsBuilder::from_opts(get_opts()).session_wait_timeout(Some(1234));
+        let mut conn = Conn::new(opts).await?;
+        assert_eq!(
+            conn.inner.wait_timeout,
+            std::time::Duration::from_secs(1234)
+        );
+        assert_eq!(conn.query_first::<usize, _>(query).await?, Some(1234));
+
+        conn.query_drop("SET SESSION wait_timeout = 4321").await?;
+        if conn.reset().await? {
+            assert_eq!(conn.query_first::<usize, _>(query).await?, Some(1234));
+        }
+
+        conn.query_drop("SET SESSION wait_timeout = 4321").await?;
+        conn.change_user(Default::default()).await?;
+        assert_eq!(conn.query_first::<usize, _>(query).await?, Some(1234));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_set_results_charset() -> super::Result<()> {
+        let opts = OptsBuilder::from_opts(get_opts()).results_charset(Some("latin1"));
+        let mut conn = Conn::new(opts).await?;
+
+        let query = "SELECT @@character_set_results";
+        assert_eq!(
+            conn.query_first::<String, _>(query).await?.as_deref(),
+            Some("latin1")
+        );
+
+        // results are encoded using latin1, while the query is still utf8
+        let text: Option<Vec<u8>> = conn.query_first("SELECT 'é'").await?;
+        assert_eq!(text, Some(vec![0xE9]));
+        let binary: Option<Vec<u8>> = conn.exec_first("SELECT ?", ("é",)).await?;
+        assert_eq!(binary, Some(vec![0xE9]));
+
+        conn.query_drop("SET character_set_results = utf8mb4")
+            .await?;
+        if conn.reset().await? {
+            assert_eq!(
+                conn.query_first::<String, _>(query).await?.as_deref(),
+                Some("latin1")
+            );
+        }
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_slow_queries() -> super::Result<()> {
+        use std::{
+            sync::{Arc, Mutex},
+            time::Duration,
+        };
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let reported = reported.clone();
+            move |query: &str, elapsed: Duration| {
+                reported.lock().unwrap().push((query.to_owned(), elapsed));
+            }
+        };
+        let opts = OptsBuilder::from_opts(get_opts())
+            .slow_query_threshold(Some(Duration::from_millis(200)))
+            .slow_query_handler(Some(Arc::new(handler) as Arc<dyn crate::SlowQueryHandler>));
+        let mut conn = Conn::new(opts).await?;
+        reported.lock().unwrap().clear();
+
+        conn.query_drop("SELECT 1").await?;
+        conn.query_drop("DO SLEEP(0.3)").await?;
+        conn.exec_drop("SELECT ?", (1,)).await?;
+        let _: Vec<u8> = conn.exec("SELECT SLEEP(?)", (0.3,)).await?;
+        // the last result set of a multi-result set query ends the timer
+        conn.query_drop("SELECT 1; DO SLEEP(0.3)").await?;
+        // failed queries are not reported
+        assert!(conn
+            .query_drop("SELECT SLEEP(0.3) FROM unknown")
+            .await
+            .is_err());
+        assert!(conn.inner.slow_query.is_none());
+        // including the ones that fail on a subsequent result set
+        assert!(conn
+            .query_drop("DO SLEEP(0.3); SELECT 1 FROM unknown")
+            .await
+            .is_err());
+        assert!(conn.inner.slow_query.is_none());
+
+        let reported = reported.lock().unwrap().clone();
+        let queries = reported.iter().map(|x| &*x.0).collect::<Vec<_>>();
+        assert_eq!(
+            queries,
+            [
+                "DO SLEEP(0.3)",
+                "SELECT SLEEP(?)",
+                "SELECT 1; DO SLEEP(0.3)"
+            ]
+        );
+        assert!(reported
+            .iter()
+            .all(|(_, elapsed)| *elapsed >= Duration::from_millis(300)));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_reset_the_connection() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+
+        assert_eq!(
+            conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
+            Value::NULL
+        );
+
+        conn.query_drop("SET @foo = 'foo'").await?;
+
+        assert_eq!(
+            conn.query_first::<String, _>("SELECT @foo").await?.unwrap(),
+            "foo",
+        );
+
+        if conn.reset().await? {
+            assert_eq!(
+                conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
+                Value::NULL
+            );
+        } else {
+            assert_eq!(
+                conn.query_first::<String, _>("SELECT @foo").await?.unwrap(),
+                "foo",
+            );
+        }
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_fetch_password_from_credential_provider() -> super::Result<()> {
+        use futures_util::FutureExt;
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let pass = crate::Opts::from(get_opts()).pass().map(String::from);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = {
+            let calls = calls.clone();
+            move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+                let pass = pass.clone().unwrap_or_default();
+                async move { Ok(pass) }.boxed()
+            }
+        };
+
+        let opts = get_opts()
+            .pass(None::<String>)
+            .credential_provider(Some(Arc::new(provider)));
+        let mut conn = Conn::new(opts).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(conn.inner.provided_pass.is_none());
+
+        conn.change_user(Default::default()).await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(conn.inner.provided_pass.is_none());
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_reprepare_pinned_statements() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+
+        let stmt = conn.prepare_pinned("SELECT :foo").await?;
+        assert!(stmt.is_pinned());
+        assert_eq!(stmt, conn.prepare_pinned("SELECT :foo").await?);
+        assert_eq!(conn.stmt_cache_ref().len(), 0);
+
+        if conn.reset().await? {
+            let value: Option<u8> = conn.exec_first(&stmt, params! { "foo" => 1 }).await?;
+            assert_eq!(value, Some(1));
+        }
+
+        conn.change_user(Default::default()).await?;
+        let value: Option<u8> = conn.exec_first(&stmt, params! { "foo" => 2 }).await?;
+        assert_eq!(value, Some(2));
+
+        // closing unpins the statement
+        conn.close(stmt.clone()).await?;
+        assert!(conn.inner.pinned_stmts.is_empty());
+        assert!(conn.exec_drop(&stmt, params! { "foo" => 3 }).await.is_err());
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_change_user() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        assert_eq!(
+            conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
+            Value::NULL
+        );
+
+        conn.query_drop("SET @foo = 'foo'").await?;
+
+        assert_eq!(
+            conn.query_first::<String, _>("SELECT @foo").await?.unwrap(),
+            "foo",
+        );
+
+        conn.change_user(Default::default()).await?;
+        assert_eq!(
+            conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
+            Value::NULL
+        );
+
+        let plugins: &[&str] = if !conn.inner.is_mariadb && conn.server_version() >= (5, 8, 0) {
+            &["mysql_native_password", "caching_sha2_password"]
+        } else {
+            &["mysql_native_password"]
+        };
+
+        for plugin in plugins {
+            let mut rng = rand::thread_rng();
+            let mut pass = [0u8; 10];
+            pass.try_fill(&mut rng).unwrap();
+            let pass: String = IntoIterator::into_iter(pass)
+                .map(|x| ((x % (123 - 97)) + 97) as char)
+                .collect();
+
+            conn.query_drop("DELETE FROM mysql.user WHERE user = '__mats'")
+                .await
+                .unwrap();
+            conn.query_drop("FLUSH PRIVILEGES").await.unwrap();
+
+            if conn.inner.is_mariadb || conn.server_version() < (5, 7, 0) {
+                if matches!(conn.server_version(), (5, 6, _)) {
+                    conn.query_drop("CREATE USER '__mats'@'%' IDENTIFIED WITH mysql_old_password")
+                        .await
+                        .unwrap();
+                    conn.query_drop(format!(
+                        "SET PASSWORD FOR '__mats'@'%' = OLD_PASSWORD({})",
+                        Value::from(pass.clone()).as_sql(false)
+                    ))
+                    .await
+                    .unwrap();
+                } else {
+                    conn.query_drop("CREATE USER '__mats'@'%'").await.unwrap();
+                    conn.query_drop(format!(
+                        "SET PASSWORD FOR '__mats'@'%' = PASSWORD({})",
+                        Value::from(pass.clone()).as_sql(false)
+                    ))
+                    .await
+                    .unwrap();
+                }
+            } else {
+                conn.query_drop(format!(
+                    "CREATE USER '__mats'@'%' IDENTIFIED WITH {} BY {}",
+                    plugin,
+                    Value::from(pass.clone()).as_sql(false)
+                ))
+                .await
+                .unwrap();
+            };
+
+            conn.query_drop("FLUSH PRIVILEGES").await.unwrap();
+
+            let mut conn2 = Conn::new(get_opts().secure_auth(false)).await.unwrap();
+            conn2
+                .change_user(
+                    ChangeUserOpts::default()
+                        .with_db_name(None)
+                        .with_user(Some("__mats".into()))
+                        .with_pass(Some(pass)),
+                )
+                .await
+                .unwrap();
+            let (db, user) = conn2
+                .query_first::<(Option<String>, String), _>("SELECT DATABASE(), USER();")
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(db, None);
+            assert!(user.starts_with("__mats"));
+
+            conn2.disconnect().await.unwrap();
+        }
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_send_raw_command() -> super::Result<()> {
+        use crate::consts::Command;
+
+        let mut conn = Conn::new(get_opts()).await?;
+
+        let response = conn.send_command_raw(Command::COM_PING as u8, &[]).await?;
+        assert_eq!(response.len(), 1);
+        assert_eq!(response[0][0], 0x00);
+
+        let err = conn
+            .send_command_raw(Command::COM_QUERY as u8, b"BLABLA")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Server(_)));
+
+        // connection is still in sync
+        let response = conn
+            .send_command_raw(Command::COM_INIT_DB as u8, b"mysql")
+            .await?;
+        assert_eq!(response.len(), 1);
+        assert_eq!(
+            conn.query_first::<String, _>("SELECT DATABASE()").await?,
+            Some("mysql".into())
+        );
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_ping_with_timeout() -> super::Result<()> {
+        use std::time::{Duration, Instant};
+        use tokio::{
+            io::copy_bidirectional,
+            net::{TcpListener, TcpStream},
+            sync::oneshot,
+        };
+
+        let opts = crate::Opts::from(get_opts());
+        let mut conn = Conn::new(opts.clone()).await?;
+        conn.ping_timeout(Duration::from_secs(10)).await?;
+        conn.disconnect().await?;
+
+        // proxy that stops forwarding (but keeps sockets open) once `blackhole` is triggered
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let proxy_port = listener.local_addr()?.port();
+        let server_addr = (opts.ip_or_hostname().to_owned(), opts.tcp_port());
+        let (blackhole, blackholed) = oneshot::channel::<()>();
+        let proxy = tokio::spawn(async move {
+            let (mut client, _) = listener.accept().await?;
+            let mut server = TcpStream::connect(server_addr).await?;
+            tokio::select! {
+                _ = copy_bidirectional(&mut client, &mut server) => (),
+                _ = blackholed => (),
+            }
+            Ok::<_, std::io::Error>((client, server))
+        });
+
+        let mut conn = Conn::new(
+            OptsBuilder::from_opts(opts)
+                .ip_or_hostname("127.0.0.1")
+                .tcp_port(proxy_port)
+                .prefer_socket(false),
+        )
+        .await?;
+        conn.ping_timeout(Duration::from_secs(10)).await?;
+
+        blackhole.send(()).unwrap();
+        let _sockets = proxy.await.unwrap()?;
+
+        let start = Instant::now();
+        let err = conn
+            .ping_timeout(Duration::from_millis(500))
+            .await
+            .unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(
+            matches!(err, Error::Io(crate::IoError::Io(ref err)) if err.kind() == std::io::ErrorKind::TimedOut)
+        );
+
+        // connection is closed after the timeout
+        assert!(matches!(
+            conn.ping().await.unwrap_err(),
+            Error::Driver(crate::DriverError::ConnectionClosed)
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_work_without_global_buffer_pool() -> super::Result<()> {
+        let opts = get_opts().buffer_strategy(BufferStrategy::Unpooled);
+        let mut conn = Conn::new(opts).await?;
+        assert!(!std::sync::Arc::ptr_eq(
+            conn.buffer_pool(),
+            &crate::BUFFER_POOL
+        ));
+
+        let data = vec![0x42_u8; 256 * 1024];
+        let result: Option<(u8, Vec<u8>)> = conn.exec_first("SELECT ?, ?", (1, &data)).await?;
+        assert_eq!(result, Some((1, data)));
+        let rows: Vec<u32> = conn.query("SELECT 1 UNION ALL SELECT 2").await?;
+        assert_eq!(rows, vec![1, 2]);
+
+        conn.reset().await?;
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_expose_endpoint_addresses() -> super::Result<()> {
+        let opts = Opts::from(get_opts().prefer_socket(false));
+        let conn = Conn::new(opts.clone()).await?;
+
+        let peer_addr = conn.peer_addr().unwrap().as_tcp().unwrap();
+        assert_eq!(peer_addr.port(), opts.tcp_port());
+        let resolved = tokio::net::lookup_host((opts.ip_or_hostname(), opts.tcp_port()))
+            .await?
+            .collect::<Vec<_>>();
+        assert!(resolved.contains(&peer_addr));
+
+        let local_addr = conn.local_addr().unwrap().as_tcp().unwrap();
+        assert_eq!(local_addr.ip().is_loopback(), peer_addr.ip().is_loopback());
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_kill_connection_and_query() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        let mut victim = Conn::new(get_opts()).await?;
+
+        // query is killed, connection is alive
+        let victim_id = victim.id();
+        let (result, kill_result) =
+            futures_util::future::join(victim.query_drop("DO SLEEP(10)"), async {
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                conn.kill_query(victim_id).await
+            })
+            .await;
+        kill_result?;
+        match result {
+            // MySql reports interrupted `SLEEP` as a successful result
+            Ok(()) | Err(Error::Server(_)) => (),
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+        victim.ping().await?;
+
+        // connection is killed
+        conn.kill_connection(victim_id).await?;
+        assert!(victim.ping().await.is_err());
+
+        let err = conn.kill_connection(victim_id).await.unwrap_err();
+        assert!(err.is_no_such_thread(), "{}", err);
+        let err = conn.kill_query(victim_id).await.unwrap_err();
+        assert!(err.is_no_such_thread(), "{}", err);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_kill_statement_on_timeout() -> super::Result<()> {
+        let timeout = std::time::Duration::from_millis(500);
+
+        // standalone connection
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_exec_timeout (id INT)")
+            .await?;
+        let err = conn
+            .exec_timeout::<u8, _, _>(
+                "SELECT n FROM (SELECT 1 AS n) t WHERE SLEEP(?) = 0",
+                (10,),
+                timeout,
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Driver(DriverError::StatementTimeout { .. })),
+            "{}",
+            err
+        );
+        // a killed `SLEEP`, that is the only thing the statement does, returns `1`
+        let rows = conn
+            .exec_timeout::<u8, _, _>("SELECT SLEEP(?)", (10,), timeout)
+            .await?;
+        assert_eq!(rows, vec![1]);
+        // not only `SELECT`s are killed
+        let err = conn
+            .exec_timeout::<u8, _, _>(
+                "INSERT INTO tmp_exec_timeout SELECT SLEEP(?)",
+                (10,),
+                timeout,
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Driver(DriverError::StatementTimeout { .. })),
+            "{}",
+            err
+        );
+        // statement, that completes in time
+        let rows = conn
+            .exec_timeout::<u8, _, _>("SELECT ?", (42,), timeout)
+            .await?;
+        assert_eq!(rows, vec![42]);
+        // an error of a statement, that isn't killed, is returned as is
+        let err = conn
+            .exec_timeout::<u8, _, _>("SELECT * FROM tmp_exec_timeout_unknown", (), timeout)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Server(ref e) if e.code == 1146),
+            "{}",
+            err
+        );
+        conn.disconnect().await?;
+
+        // pooled connection (the pool is exhausted, so `KILL QUERY` is issued on a new one)
+        let pool_opts = PoolOpts::new().with_constraints(PoolConstraints::new(0, 1).unwrap());
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+        let mut conn = pool.get_conn().await?;
+        let err = conn
+            .exec_timeout::<u8, _, _>(
+                "SELECT n FROM (SELECT 1 AS n) t WHERE SLEEP(?) = 0",
+                (10,),
+                timeout,
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Driver(DriverError::StatementTimeout { .. })),
+            "{}",
+            err
+        );
+        assert_eq!(conn.query_first::<u8, _>("SELECT 42").await?, Some(42));
+        drop(conn);
+        pool.disconnect().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_execute_com_statistics() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        let statistics = conn.statistics().await?;
+        assert!(statistics.starts_with("Uptime: "), "{}", statistics);
+        assert!(statistics.contains("Threads: "), "{}", statistics);
+        // connection is still usable
+        assert_eq!(conn.query_first::<u8, _>("SELECT 42").await?, Some(42));
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_com_statistics_error() -> super::Result<()> {
+        use tokio::{io::AsyncReadExt, net::TcpListener};
+
+        // MariaDb server (so that `@@version_comment` isn't loaded), that answers
+        // `COM_STATISTICS` with an ERR packet
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let handshake = handshake_packet(b"5.5.5-10.11.2-MariaDB", b"mysql_native_password");
+            write_packet(&mut stream, 0, &handshake).await;
+            // handshake response
+            read_packet(&mut stream).await;
+            write_packet(&mut stream, 2, &[0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00]).await;
+            assert_eq!(read_packet(&mut stream).await, vec![0x09]);
+            let mut err_packet = vec![0xFF, 0xCB, 0x04, b'#'];
+            err_packet.extend_from_slice(b"42000Access denied");
+            write_packet(&mut stream, 1, &err_packet).await;
+            let _ = stream.read(&mut [0_u8; 1]).await;
+        });
+
+        let opts = OptsBuilder::default()
+            .ip_or_hostname("127.0.0.1")
+            .tcp_port(port)
+            .prefer_socket(false)
+            .max_allowed_packet(Some(16 * 1024 * 1024))
+            .wait_timeout(Some(28800))
+            .user(Some("root"))
+            .pass(Some("password"));
+        let mut conn = Conn::new(opts).await?;
+        match conn.statistics().await {
+            Err(Error::Server(err)) => assert_eq!(err.code, 1227),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_auto_reconnect() -> super::Result<()> {
+        async fn kill(id: u32) -> super::Result<()> {
+            let mut killer = Conn::new(get_opts()).await?;
+            killer.query_drop(format!("KILL {}", id)).await?;
+            killer.disconnect().await?;
+            // let the server close the socket
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            Ok(())
+        }
+
+        let opts = get_opts().init(vec!["SET @init_value = 42"]);
+
+        // disabled by default
+        let mut conn = Conn::new(opts.clone()).await?;
+        kill(conn.id()).await?;
+        conn.query_drop("SELECT 1").await.unwrap_err();
+        conn.query_drop("SELECT 1").await.unwrap_err();
+
+        let mut conn = Conn::new(opts.auto_reconnect(true)).await?;
+        conn.select_db("information_schema").await?;
+
+        let id = conn.id();
+        kill(id).await?;
+        let (init_value, db): (Option<u8>, Option<String>) = conn
+            .query_first("SELECT @init_value, DATABASE()")
+            .await?
+            .unwrap();
+        assert_ne!(conn.id(), id);
+        assert_eq!(init_value, Some(42));
+        assert_eq!(db.as_deref(), Some("information_schema"));
+
+        // mid-transaction
+        let mut tx = conn.start_transaction(Default::default()).await?;
+        kill(tx.id()).await?;
+        tx.query_drop("SELECT 1").await.unwrap_err();
+        tx.query_drop("SELECT 1").await.unwrap_err();
+        drop(tx);
+        conn.query_drop("SELECT 1").await?;
+
+        // mid-transaction started by a query
+        conn.query_drop("BEGIN").await?;
+        kill(conn.id()).await?;
+        conn.query_drop("SELECT 1").await.unwrap_err();
+
+        let mut conn = Conn::new(get_opts().auto_reconnect(true)).await?;
+
+        // mid-result-set
+        let mut result = conn.query_iter("SELECT 1; SELECT 2").await?;
+        result.next().await?;
+        drop(result);
+        kill(conn.id()).await?;
+        conn.query_drop("SELECT 1").await.unwrap_err();
+        conn.query_drop("SELECT 1").await?;
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_expose_server_status_flags() -> super::Result<()> {
+        use crate::consts::StatusFlags;
+
+        let mut conn = Conn::new(get_opts()).await?;
+        assert!(conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_AUTOCOMMIT));
+        assert!(!conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
+
+        conn.query_drop("START TRANSACTION").await?;
+        assert!(conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
+
+        // DDL causes an implicit commit
+        conn.query_drop("CREATE TABLE IF NOT EXISTS mysql.tmp_status_ddl (id INT)")
+            .await?;
+        assert!(!conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
+        conn.query_drop("DROP TABLE mysql.tmp_status_ddl").await?;
+
+        conn.query_drop("SET autocommit = 0").await?;
+        assert!(!conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_AUTOCOMMIT));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_run_in_explicit_commit_mode() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        assert!(conn.autocommit());
+
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_autocommit (id INT) ENGINE=InnoDB")
+            .await?;
+
+        conn.set_autocommit(false).await?;
+        assert!(!conn.autocommit());
+        conn.query_drop("INSERT INTO tmp_autocommit VALUES (1)")
+            .await?;
+        conn.query_drop("ROLLBACK").await?;
+        conn.query_drop("INSERT INTO tmp_autocommit VALUES (2)")
+            .await?;
+        conn.query_drop("COMMIT").await?;
+
+        conn.set_autocommit(true).await?;
+        assert!(conn.autocommit());
+        let ids: Vec<i32> = conn.query("SELECT id FROM tmp_autocommit").await?;
+        assert_eq!(ids, vec![2]);
 
         conn.disconnect().await?;
         Ok(())
     }
 
-    #[test]
-    fn should_not_panic_if_dropped_without_tokio_runtime() {
-        let fut = Conn::new(get_opts());
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        runtime.block_on(async {
-            fut.await.unwrap();
-        });
-        // connection will drop here
-    }
-
     #[tokio::test]
-    async fn should_execute_init_queries_on_new_connection() -> super::Result<()> {
-        let opts = OptsBuilder::from_opts(get_opts()).init(vec!["SET @a = 42", "SET @b = 'foo'"]);
-        let mut conn = Conn::new(opts).await?;
-        let result: Vec<(u8, String)> = conn.query("SELECT @a, @b").await?;
+    async fn should_select_db() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+
+        conn.select_db("information_schema").await?;
+        let db: Option<String> = conn.query_first("SELECT DATABASE()").await?;
+        assert_eq!(db.as_deref(), Some("information_schema"));
+        assert_eq!(conn.opts().db_name(), Some("information_schema"));
+
+        let err = conn
+            .select_db("mysql_async_nonexistent_db")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Server(ref e) if e.code == 1049));
+        assert_eq!(conn.opts().db_name(), Some("information_schema"));
+
+        // connection should still be usable
+        conn.ping().await?;
         conn.disconnect().await?;
-        assert_eq!(result, vec![(42, "foo".into())]);
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_execute_setup_queries_on_reset() -> super::Result<()> {
-        let opts = OptsBuilder::from_opts(get_opts()).setup(vec!["SET @a = 42", "SET @b = 'foo'"]);
-        let mut conn = Conn::new(opts).await?;
+    async fn should_expose_handshake_info() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
 
-        // initial run
-        let mut result: Vec<(u8, String)> = conn.query("SELECT @a, @b").await?;
-        assert_eq!(result, vec![(42, "foo".into())]);
+        let connection_id: Option<u32> = conn.query_first("SELECT CONNECTION_ID()").await?;
+        assert_eq!(connection_id, Some(conn.connection_id()));
+        assert_eq!(conn.connection_id(), conn.id());
+        assert_eq!(conn.protocol_version(), 10);
 
-        // after reset
-        if conn.reset().await? {
-            result = conn.query("SELECT @a, @b").await?;
-            assert_eq!(result, vec![(42, "foo".into())]);
-        }
+        assert_ne!(conn.server_default_collation(), 0);
 
-        // after change user
-        conn.change_user(Default::default()).await?;
-        result = conn.query("SELECT @a, @b").await?;
-        assert_eq!(result, vec![(42, "foo".into())]);
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_expose_status_flags() -> super::Result<()> {
+        use crate::consts::StatusFlags;
+
+        let mut conn = Conn::new(get_opts()).await?;
+        assert!(conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_AUTOCOMMIT));
+        assert!(!conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
+
+        conn.query_drop("START TRANSACTION").await?;
+        assert!(conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
+
+        let mut result = conn.query_iter("SELECT 1; SELECT 2").await?;
+        result.collect::<u8>().await?;
+        assert!(result
+            .server_status()
+            .contains(StatusFlags::SERVER_MORE_RESULTS_EXISTS));
+        result.collect::<u8>().await?;
+        assert!(!result
+            .server_status()
+            .contains(StatusFlags::SERVER_MORE_RESULTS_EXISTS));
+        assert!(result
+            .server_status()
+            .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
+        drop(result);
+
+        conn.query_drop("COMMIT").await?;
+        assert!(!conn
+            .server_status_flags()
+            .contains(StatusFlags::SERVER_STATUS_IN_TRANS));
 
         conn.disconnect().await?;
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_reset_the_connection() -> super::Result<()> {
+    async fn should_set_and_get_user_variables() -> super::Result<()> {
         let mut conn = Conn::new(get_opts()).await?;
 
-        assert_eq!(
-            conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
-            Value::NULL
-        );
+        assert_eq!(conn.get_var::<i64>("unset_var").await?, None);
 
-        conn.query_drop("SET @foo = 'foo'").await?;
+        conn.set_var("int_var", 42_i64).await?;
+        assert_eq!(conn.get_var::<i64>("int_var").await?, Some(42));
 
+        // names are quoted
+        conn.set_var("weird` name", "it's a string").await?;
         assert_eq!(
-            conn.query_first::<String, _>("SELECT @foo").await?.unwrap(),
-            "foo",
+            conn.get_var::<String>("weird` name").await?.as_deref(),
+            Some("it's a string")
         );
 
-        if conn.reset().await? {
-            assert_eq!(
-                conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
-                Value::NULL
-            );
-        } else {
-            assert_eq!(
-                conn.query_first::<String, _>("SELECT @foo").await?.unwrap(),
-                "foo",
-            );
-        }
+        conn.set_var("int_var", Value::NULL).await?;
+        assert_eq!(conn.get_var::<i64>("int_var").await?, None);
+
+        conn.set_var("str_var", "foo").await?;
+        let err = conn.get_var::<i64>("str_var").await.unwrap_err();
+        assert!(matches!(err, Error::Driver(DriverError::FromValue { .. })));
 
         conn.disconnect().await?;
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_change_user() -> super::Result<()> {
+    async fn should_set_session_vars_at_once() -> super::Result<()> {
         let mut conn = Conn::new(get_opts()).await?;
-        assert_eq!(
-            conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
-            Value::NULL
-        );
-
-        conn.query_drop("SET @foo = 'foo'").await?;
 
-        assert_eq!(
-            conn.query_first::<String, _>("SELECT @foo").await?.unwrap(),
-            "foo",
-        );
+        conn.set_session_vars::<&str>(&[]).await?;
+        conn.set_session_vars(&[
+            ("sql_mode", Value::from("ANSI_QUOTES")),
+            ("wait_timeout", Value::from(1234)),
+            ("sql_select_limit", Value::from(42)),
+        ])
+        .await?;
 
-        conn.change_user(Default::default()).await?;
-        assert_eq!(
-            conn.query_first::<Value, _>("SELECT @foo").await?.unwrap(),
-            Value::NULL
-        );
+        let vars: Option<(String, u64, u64)> = conn
+            .query_first(
+                "SELECT @@SESSION.sql_mode, @@SESSION.wait_timeout, @@SESSION.sql_select_limit",
+            )
+            .await?;
+        assert_eq!(vars, Some(("ANSI_QUOTES".into(), 1234, 42)));
 
-        let plugins: &[&str] = if !conn.inner.is_mariadb && conn.server_version() >= (5, 8, 0) {
-            &["mysql_native_password", "caching_sha2_password"]
-        } else {
-            &["mysql_native_password"]
-        };
+        conn.disconnect().await?;
+        Ok(())
+    }
 
-        for plugin in plugins {
-            let mut rng = rand::thread_rng();
-            let mut pass = [0u8; 10];
-            pass.try_fill(&mut rng).unwrap();
-            let pass: String = IntoIterator::into_iter(pass)
-                .map(|x| ((x % (123 - 97)) + 97) as char)
-                .collect();
+    #[tokio::test]
+    async fn should_collect_connect_stats() -> super::Result<()> {
+        let conn = Conn::new(get_opts()).await?;
+        assert!(conn.connect_stats().is_none());
+        conn.disconnect().await?;
 
-            conn.query_drop("DELETE FROM mysql.user WHERE user = '__mats'")
-                .await
-                .unwrap();
-            conn.query_drop("FLUSH PRIVILEGES").await.unwrap();
+        let opts =
+            crate::Opts::from(OptsBuilder::from_opts(get_opts()).collect_connect_stats(true));
+        let conn = Conn::new(opts.clone()).await?;
+        let stats = *conn.connect_stats().unwrap();
+        assert_eq!(stats.dns_resolution().is_some(), opts.socket().is_none());
+        assert_eq!(stats.tls_handshake().is_some(), opts.ssl_opts().is_some());
+        assert!(
+            stats.total()
+                >= stats.dns_resolution().unwrap_or_default()
+                    + stats.connect()
+                    + stats.tls_handshake().unwrap_or_default()
+                    + stats.auth()
+        );
+        conn.disconnect().await?;
 
-            if conn.inner.is_mariadb || conn.server_version() < (5, 7, 0) {
-                if matches!(conn.server_version(), (5, 6, _)) {
-                    conn.query_drop("CREATE USER '__mats'@'%' IDENTIFIED WITH mysql_old_password")
-                        .await
-                        .unwrap();
-                    conn.query_drop(format!(
-                        "SET PASSWORD FOR '__mats'@'%' = OLD_PASSWORD({})",
-                        Value::from(pass.clone()).as_sql(false)
-                    ))
-                    .await
-                    .unwrap();
-                } else {
-                    conn.query_drop("CREATE USER '__mats'@'%'").await.unwrap();
-                    conn.query_drop(format!(
-                        "SET PASSWORD FOR '__mats'@'%' = PASSWORD({})",
-                        Value::from(pass.clone()).as_sql(false)
-                    ))
-                    .await
-                    .unwrap();
-                }
-            } else {
-                conn.query_drop(format!(
-                    "CREATE USER '__mats'@'%' IDENTIFIED WITH {} BY {}",
-                    plugin,
-                    Value::from(pass.clone()).as_sql(false)
-                ))
-                .await
-                .unwrap();
-            };
+        Ok(())
+    }
 
-            conn.query_drop("FLUSH PRIVILEGES").await.unwrap();
+    #[tokio::test]
+    async fn should_escape_string() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
 
-            let mut conn2 = Conn::new(get_opts().secure_auth(false)).await.unwrap();
-            conn2
-                .change_user(
-                    ChangeUserOpts::default()
-                        .with_db_name(None)
-                        .with_user(Some("__mats".into()))
-                        .with_pass(Some(pass)),
-                )
-                .await
-                .unwrap();
-            let (db, user) = conn2
-                .query_first::<(Option<String>, String), _>("SELECT DATABASE(), USER();")
-                .await
-                .unwrap()
-                .unwrap();
-            assert_eq!(db, None);
-            assert!(user.starts_with("__mats"));
+        let inputs = ["it's a \"test\"", "\\'; --", "\0\n\r\x1A", "縗\\'"];
+        for sql_mode in ["''", "'NO_BACKSLASH_ESCAPES'"] {
+            conn.query_drop(format!("SET sql_mode = {}", sql_mode))
+                .await?;
+            for input in inputs {
+                let query = format!("SELECT '{}'", conn.escape_string(input));
+                let output: Option<String> = conn.query_first(query).await?;
+                assert_eq!(output.as_deref(), Some(input));
+
+                let query = format!("SELECT {}", conn.quote_string(input));
+                let output: Option<String> = conn.query_first(query).await?;
+                assert_eq!(output.as_deref(), Some(input));
+            }
+        }
 
-            conn2.disconnect().await.unwrap();
+        // NUL is not allowed in identifiers
+        conn.query_drop("SET sql_mode = ''").await?;
+        for input in inputs.iter().copied().filter(|x| !x.contains('\0')) {
+            let query = format!(
+                "SELECT {} AS {}",
+                crate::quote_string(input, "utf8mb4", false),
+                crate::quote_identifier(input)
+            );
+            let result = conn.query_iter(query).await?;
+            assert_eq!(result.columns_ref()[0].name_str(), input);
+            let output: Vec<String> = result.collect_and_drop().await?;
+            assert_eq!(output, vec![input.to_owned()]);
         }
 
+        // backslash is a trailing byte of a multibyte character in GBK
+        conn.query_drop("SET sql_mode = ''").await?;
+        conn.query_drop("SET NAMES gbk").await?;
+        let input = "縗\\' OR 1 = 1 -- ";
+        let query = format!("SELECT '{}'", conn.escape_string(input));
+        let output: Vec<Vec<u8>> = conn.query(query).await?;
+        assert_eq!(output.len(), 1);
+
         conn.disconnect().await?;
         Ok(())
     }
@@ -1890,6 +3876,141 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_return_affected_rows_within_transaction() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp (id INT, name TEXT)")
+            .await?;
+        conn.query_drop("INSERT INTO tmp VALUES (1, 'foo'), (2, 'bar'), (3, 'baz')")
+            .await?;
+
+        let mut transaction = conn.start_transaction(Default::default()).await?;
+        let affected = transaction
+            .query_affected("UPDATE tmp SET name = 'quux' WHERE id > 1")
+            .await?;
+        assert_eq!(affected, 2);
+        let affected = transaction
+            .exec_affected("UPDATE tmp SET name = ? WHERE id = ?", ("quux", 1))
+            .await?;
+        assert_eq!(affected, 1);
+        let affected = transaction
+            .exec_affected("UPDATE tmp SET name = ? WHERE id = ?", ("quux", 1))
+            .await?;
+        assert_eq!(affected, 0);
+        transaction.commit().await?;
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_prepare_statement_once_until_closed() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+
+        let stmt = conn.prep("SELECT ?").await?;
+        assert_eq!(conn.prep("SELECT ?").await?.id(), stmt.id());
+        conn.exec_drop("SELECT ?", (1,)).await?;
+        assert_eq!(conn.get_cached_stmt(b"SELECT ?").unwrap().id(), stmt.id());
+
+        conn.close(stmt.clone()).await?;
+        assert!(conn.get_cached_stmt(b"SELECT ?").is_none());
+        let prepared = conn.prep("SELECT ?").await?;
+        assert_ne!(prepared.id(), stmt.id());
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_close_statements_prepared_within_transaction() -> super::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.exec_drop("DO 1", ()).await?;
+
+        let mut tx_opts = crate::TxOpts::default();
+        tx_opts.with_close_prepared_on_end(true);
+
+        for commit in [true, false] {
+            let mut transaction = conn.start_transaction(tx_opts.clone()).await?;
+            transaction.exec_drop("DO 1", ()).await?;
+            transaction.exec_drop("DO 2", ()).await?;
+            transaction.exec_drop("DO 3", ()).await?;
+            assert_eq!(transaction.stmt_cache_ref().len(), 3);
+            if commit {
+                transaction.commit().await?;
+            } else {
+                transaction.rollback().await?;
+            }
+            // statement prepared before the transaction is kept
+            assert_eq!(conn.stmt_cache_ref().len(), 1);
+            assert!(conn.get_cached_stmt(b"DO 1").is_some());
+        }
+
+        // implicit rollback
+        let mut transaction = conn.start_transaction(tx_opts.clone()).await?;
+        transaction.exec_drop("DO 2", ()).await?;
+        drop(transaction);
+        conn.ping().await?;
+        assert_eq!(conn.stmt_cache_ref().len(), 1);
+
+        // statements are no longer tracked
+        conn.exec_drop("DO 2", ()).await?;
+        let transaction = conn.start_transaction(Default::default()).await?;
+        transaction.commit().await?;
+        assert_eq!(conn.stmt_cache_ref().len(), 2);
+
+        // 2 statements per explicit transaction end and 1 on implicit rollback
+        let row_opt = conn
+            .query_first("SHOW SESSION STATUS LIKE 'Com_stmt_close'")
+            .await?;
+        let (_, count): (String, usize) = row_opt.unwrap();
+        assert_eq!(count, 5);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_close_statements_of_transaction_dropped_with_pooled_conn() -> super::Result<()>
+    {
+        let pool_opts = crate::PoolOpts::default()
+            .with_constraints(crate::PoolConstraints::new(1, 1).unwrap())
+            .with_reset_connection(false);
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+
+        let mut tx_opts = crate::TxOpts::default();
+        tx_opts.with_close_prepared_on_end(true);
+
+        let mut conn = pool.get_conn().await?;
+        let id = conn.id();
+        let mut transaction = conn.start_transaction(tx_opts).await?;
+        transaction.exec_drop("DO 1", ()).await?;
+        // neither the transaction, nor the connection is explicitly finished
+        drop(transaction);
+        drop(conn);
+
+        let mut conn = pool.get_conn().await?;
+        assert_eq!(conn.id(), id);
+        assert!(conn.inner.tx_prepared.is_none());
+        assert!(conn.get_cached_stmt(b"DO 1").is_none());
+
+        // statements of the next user are not tracked (and thus not closed)
+        conn.exec_drop("DO 2", ()).await?;
+        conn.start_transaction(Default::default())
+            .await?
+            .commit()
+            .await?;
+        assert!(conn.get_cached_stmt(b"DO 2").is_some());
+        let row_opt = conn
+            .query_first("SHOW SESSION STATUS LIKE 'Com_stmt_close'")
+            .await?;
+        let (_, count): (String, usize) = row_opt.unwrap();
+        assert_eq!(count, 1);
+
+        drop(conn);
+        pool.disconnect().await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_handle_multiresult_set_with_error() -> super::Result<()> {
         const QUERY_FIRST: &str = "SELECT * FROM tmp; SELECT 1; SELECT 2;";
@@ -2134,6 +4255,66 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_report_local_infile_progress() -> super::Result<()> {
+        use std::sync::{Arc, Mutex};
+
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp (a TEXT);")
+            .await?;
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        conn.set_infile_handler_with_progress(
+            async move {
+                Ok(
+                    stream::iter([Bytes::from("AAAAAA\n"), Bytes::from("BBBBBB\nCCCCCC\n")])
+                        .map(Ok)
+                        .boxed(),
+                )
+            },
+            {
+                let reported = reported.clone();
+                move |sent| reported.lock().unwrap().push(sent)
+            },
+        );
+
+        match conn
+            .query_drop(r#"LOAD DATA LOCAL INFILE "dummy" INTO TABLE tmp;"#)
+            .await
+        {
+            Ok(_) => (),
+            Err(super::Error::Server(ref err)) if err.code == 1148 || err.code == 3948 => {
+                // LOCAL INFILE is not allowed or disabled
+                return Ok(());
+            }
+            e @ Err(_) => e.unwrap(),
+        };
+        assert_eq!(*reported.lock().unwrap(), vec![7, 21]);
+
+        // errors are still reported as `LocalInfileError`
+        conn.set_infile_handler_with_progress(
+            async move {
+                Ok(stream::iter([
+                    Ok(Bytes::from("DDDDDD\n")),
+                    Err(std::io::Error::other("broken")),
+                ])
+                .boxed())
+            },
+            |_| (),
+        );
+        let err = conn
+            .query_drop(r#"LOAD DATA LOCAL INFILE "dummy" INTO TABLE tmp;"#)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            super::Error::Driver(DriverError::LocalInfile(_))
+        ));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_handle_local_infile_globally() -> super::Result<()> {
         use std::fs::write;
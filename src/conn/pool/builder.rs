@@ -0,0 +1,345 @@
+// Copyright (c) 2017 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+
+use crate::{
+    conn::pool::{Pool, PoolEventHandler},
+    local_infile_handler::GlobalHandler,
+    opts::{
+        CredentialProvider, Opts, OptsBuilder, PoolConstraints, PoolOpts, SlowQueryHandler, SslOpts,
+    },
+};
+
+/// Provides a way to build a [`Pool`].
+///
+/// It combines connection-level options (see [`OptsBuilder`])
+/// and pool-level options (see [`PoolOpts`]) in a single chain.
+///
+/// ```
+/// # use mysql_async::{Pool, PoolConstraints};
+/// # use std::time::Duration;
+/// let pool = Pool::builder()
+///     .ip_or_hostname("localhost")
+///     .user(Some("root"))
+///     .db_name(Some("mysql"))
+///     .constraints(PoolConstraints::new(5, 20).unwrap())
+///     .inactive_connection_ttl(Duration::from_secs(60))
+///     .init(vec!["SET NAMES utf8mb4"])
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PoolBuilder {
+    opts: OptsBuilder,
+    pool_opts: PoolOpts,
+}
+
+impl PoolBuilder {
+    /// Creates new builder from the given `Opts`.
+    ///
+    /// # Panic
+    ///
+    /// It'll panic if `Opts::try_from(opts)` returns error.
+    pub fn from_opts<T>(opts: T) -> Self
+    where
+        Opts: TryFrom<T>,
+        <Opts as TryFrom<T>>::Error: std::error::Error,
+    {
+        let opts = Opts::try_from(opts).unwrap();
+        PoolBuilder {
+            pool_opts: opts.pool_opts().clone(),
+            opts: OptsBuilder::from_opts::<Opts>(opts),
+        }
+    }
+
+    /// Defines server IP or hostname. See [`Opts::ip_or_hostname`].
+    pub fn ip_or_hostname<T: Into<String>>(mut self, ip_or_hostname: T) -> Self {
+        self.opts = self.opts.ip_or_hostname(ip_or_hostname);
+        self
+    }
+
+    /// Defines TCP port. See [`Opts::tcp_port`].
+    pub fn tcp_port(mut self, tcp_port: u16) -> Self {
+        self.opts = self.opts.tcp_port(tcp_port);
+        self
+    }
+
+    /// Defines user name. See [`Opts::user`].
+    pub fn user<T: Into<String>>(mut self, user: Option<T>) -> Self {
+        self.opts = self.opts.user(user);
+        self
+    }
+
+    /// Defines password. See [`Opts::pass`].
+    pub fn pass<T: Into<String>>(mut self, pass: Option<T>) -> Self {
+        self.opts = self.opts.pass(pass);
+        self
+    }
+
+    /// Defines password provider. See [`Opts::credential_provider`].
+    pub fn credential_provider(mut self, provider: Option<Arc<dyn CredentialProvider>>) -> Self {
+        self.opts = self.opts.credential_provider(provider);
+        self
+    }
+
+    /// Defines database name. See [`Opts::db_name`].
+    pub fn db_name<T: Into<String>>(mut self, db_name: Option<T>) -> Self {
+        self.opts = self.opts.db_name(db_name);
+        self
+    }
+
+    /// Defines initial queries. See [`Opts::init`].
+    pub fn init<T: Into<String>>(mut self, init: Vec<T>) -> Self {
+        self.opts = self.opts.init(init);
+        self
+    }
+
+    /// Defines setup queries. See [`Opts::setup`].
+    pub fn setup<T: Into<String>>(mut self, setup: Vec<T>) -> Self {
+        self.opts = self.opts.setup(setup);
+        self
+    }
+
+    /// Defines `tcp_keepalive` option. See [`Opts::tcp_keepalive`].
+    pub fn tcp_keepalive<T: Into<u32>>(mut self, tcp_keepalive: Option<T>) -> Self {
+        self.opts = self.opts.tcp_keepalive(tcp_keepalive);
+        self
+    }
+
+    /// Defines `tcp_nodelay` option. See [`Opts::tcp_nodelay`].
+    pub fn tcp_nodelay(mut self, nodelay: bool) -> Self {
+        self.opts = self.opts.tcp_nodelay(nodelay);
+        self
+    }
+
+    /// Defines _global_ LOCAL INFILE handler (see crate-level docs).
+    pub fn local_infile_handler<T>(mut self, handler: Option<T>) -> Self
+    where
+        T: GlobalHandler,
+    {
+        self.opts = self.opts.local_infile_handler(handler);
+        self
+    }
+
+    /// Defines connection TTL. See [`Opts::conn_ttl`].
+    pub fn conn_ttl<T: Into<Option<Duration>>>(mut self, conn_ttl: T) -> Self {
+        self.opts = self.opts.conn_ttl(conn_ttl);
+        self
+    }
+
+    /// Defines statement cache size. See [`Opts::stmt_cache_size`].
+    pub fn stmt_cache_size<T>(mut self, cache_size: T) -> Self
+    where
+        T: Into<Option<usize>>,
+    {
+        self.opts = self.opts.stmt_cache_size(cache_size);
+        self
+    }
+
+    /// Defines the number of rows a result set stream fetches ahead of the consumer.
+    /// See [`Opts::row_prefetch`].
+    pub fn row_prefetch<T>(mut self, row_prefetch: T) -> Self
+    where
+        T: Into<Option<usize>>,
+    {
+        self.opts = self.opts.row_prefetch(row_prefetch);
+        self
+    }
+
+    /// Defines SSL options. See [`Opts::ssl_opts`].
+    pub fn ssl_opts<T: Into<Option<SslOpts>>>(mut self, ssl_opts: T) -> Self {
+        self.opts = self.opts.ssl_opts(ssl_opts);
+        self
+    }
+
+    /// Defines `prefer_socket` option. See [`Opts::prefer_socket`].
+    pub fn prefer_socket<T: Into<Option<bool>>>(mut self, prefer_socket: T) -> Self {
+        self.opts = self.opts.prefer_socket(prefer_socket);
+        self
+    }
+
+    /// Defines socket path. See [`Opts::socket`].
+    pub fn socket<T: Into<String>>(mut self, socket: Option<T>) -> Self {
+        self.opts = self.opts.socket(socket);
+        self
+    }
+
+    /// Defines named pipe name. See [`Opts::pipe_name`].
+    pub fn pipe_name<T: Into<String>>(mut self, pipe_name: Option<T>) -> Self {
+        self.opts = self.opts.pipe_name(pipe_name);
+        self
+    }
+
+    /// Defines compression. See [`Opts::compression`].
+    pub fn compression<T: Into<Option<crate::Compression>>>(mut self, compression: T) -> Self {
+        self.opts = self.opts.compression(compression);
+        self
+    }
+
+    /// Defines the size of an outgoing packet, below which it's sent uncompressed.
+    /// See [`Opts::compression_threshold`].
+    pub fn compression_threshold<T: Into<Option<usize>>>(
+        mut self,
+        compression_threshold: T,
+    ) -> Self {
+        self.opts = self.opts.compression_threshold(compression_threshold);
+        self
+    }
+
+    /// Defines `max_allowed_packet` option. See [`Opts::max_allowed_packet`].
+    pub fn max_allowed_packet(mut self, max_allowed_packet: Option<usize>) -> Self {
+        self.opts = self.opts.max_allowed_packet(max_allowed_packet);
+        self
+    }
+
+    /// Defines `wait_timeout` option. See [`Opts::wait_timeout`].
+    pub fn wait_timeout(mut self, wait_timeout: Option<usize>) -> Self {
+        self.opts = self.opts.wait_timeout(wait_timeout);
+        self
+    }
+
+    /// Defines `session_wait_timeout` option. See [`Opts::session_wait_timeout`].
+    pub fn session_wait_timeout(mut self, session_wait_timeout: Option<usize>) -> Self {
+        self.opts = self.opts.session_wait_timeout(session_wait_timeout);
+        self
+    }
+
+    /// Defines `results_charset` option. See [`Opts::results_charset`].
+    pub fn results_charset<T: Into<String>>(mut self, results_charset: Option<T>) -> Self {
+        self.opts = self.opts.results_charset(results_charset);
+        self
+    }
+
+    /// Defines `secure_auth` option. See [`Opts::secure_auth`].
+    pub fn secure_auth(mut self, secure_auth: bool) -> Self {
+        self.opts = self.opts.secure_auth(secure_auth);
+        self
+    }
+
+    /// Defines `client_found_rows` option. See [`Opts::client_found_rows`].
+    pub fn client_found_rows(mut self, client_found_rows: bool) -> Self {
+        self.opts = self.opts.client_found_rows(client_found_rows);
+        self
+    }
+
+    /// Defines `enable_cleartext_plugin` option. See [`Opts::enable_cleartext_plugin`].
+    pub fn enable_cleartext_plugin(mut self, enable_cleartext_plugin: bool) -> Self {
+        self.opts = self.opts.enable_cleartext_plugin(enable_cleartext_plugin);
+        self
+    }
+
+    /// Defines `collect_connect_stats` option. See [`Opts::collect_connect_stats`].
+    pub fn collect_connect_stats(mut self, collect_connect_stats: bool) -> Self {
+        self.opts = self.opts.collect_connect_stats(collect_connect_stats);
+        self
+    }
+
+    /// Defines `validate_param_lengths` option. See [`Opts::validate_param_lengths`].
+    pub fn validate_param_lengths(mut self, validate_param_lengths: bool) -> Self {
+        self.opts = self.opts.validate_param_lengths(validate_param_lengths);
+        self
+    }
+
+    /// Defines `slow_query_threshold` option. See [`Opts::slow_query_threshold`].
+    pub fn slow_query_threshold(mut self, threshold: Option<Duration>) -> Self {
+        self.opts = self.opts.slow_query_threshold(threshold);
+        self
+    }
+
+    /// Defines slow query handler. See [`Opts::slow_query_handler`].
+    pub fn slow_query_handler(mut self, handler: Option<Arc<dyn SlowQueryHandler>>) -> Self {
+        self.opts = self.opts.slow_query_handler(handler);
+        self
+    }
+
+    /// Defines pool constraints. See [`PoolOpts::constraints`].
+    pub fn constraints(mut self, constraints: PoolConstraints) -> Self {
+        self.pool_opts = self.pool_opts.with_constraints(constraints);
+        self
+    }
+
+    /// Defines whether to reset connection upon returning it to the pool.
+    /// See [`PoolOpts::with_reset_connection`].
+    pub fn reset_connection(mut self, reset_connection: bool) -> Self {
+        self.pool_opts = self.pool_opts.with_reset_connection(reset_connection);
+        self
+    }
+
+    /// Defines an absolute connection TTL. See [`PoolOpts::with_abs_conn_ttl`].
+    pub fn abs_conn_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.pool_opts = self.pool_opts.with_abs_conn_ttl(ttl);
+        self
+    }
+
+    /// Defines an absolute connection TTL jitter. See [`PoolOpts::with_abs_conn_ttl_jitter`].
+    pub fn abs_conn_ttl_jitter(mut self, jitter: Option<Duration>) -> Self {
+        self.pool_opts = self.pool_opts.with_abs_conn_ttl_jitter(jitter);
+        self
+    }
+
+    /// Defines inactive connection TTL. See [`PoolOpts::with_inactive_connection_ttl`].
+    pub fn inactive_connection_ttl(mut self, ttl: Duration) -> Self {
+        self.pool_opts = self.pool_opts.with_inactive_connection_ttl(ttl);
+        self
+    }
+
+    /// Defines TTL check interval. See [`PoolOpts::with_ttl_check_interval`].
+    pub fn ttl_check_interval(mut self, interval: Duration) -> Self {
+        self.pool_opts = self.pool_opts.with_ttl_check_interval(interval);
+        self
+    }
+
+    /// Defines whether to retry establishing a connection refused with `ER_CON_COUNT_ERROR`.
+    /// See [`PoolOpts::with_retry_on_con_count_error`].
+    pub fn retry_on_con_count_error(mut self, retry_on_con_count_error: bool) -> Self {
+        self.pool_opts = self
+            .pool_opts
+            .with_retry_on_con_count_error(retry_on_con_count_error);
+        self
+    }
+
+    /// Defines whether pooled connections share prepared statements metadata.
+    /// See [`PoolOpts::with_shared_stmt_metadata`].
+    pub fn shared_stmt_metadata(mut self, shared_stmt_metadata: bool) -> Self {
+        self.pool_opts = self
+            .pool_opts
+            .with_shared_stmt_metadata(shared_stmt_metadata);
+        self
+    }
+
+    /// Defines the pool event handler. See [`PoolOpts::with_event_handler`].
+    pub fn event_handler(mut self, handler: Option<Arc<dyn PoolEventHandler>>) -> Self {
+        self.pool_opts = self.pool_opts.with_event_handler(handler);
+        self
+    }
+
+    /// Defines a query to validate idling connections with.
+    /// See [`PoolOpts::with_validation_query`].
+    pub fn validation_query<T: Into<String>>(mut self, validation_query: Option<T>) -> Self {
+        self.pool_opts = self.pool_opts.with_validation_query(validation_query);
+        self
+    }
+
+    /// Defines statements to prepare on every new pooled connection.
+    /// See [`PoolOpts::with_prepared_statement_warmup`].
+    pub fn prepared_statement_warmup<T: Into<String>>(mut self, statements: Vec<T>) -> Self {
+        self.pool_opts = self.pool_opts.with_prepared_statement_warmup(statements);
+        self
+    }
+
+    /// Defines the pool name. See [`PoolOpts::with_name`].
+    pub fn name<T: Into<String>>(mut self, name: Option<T>) -> Self {
+        self.pool_opts = self.pool_opts.with_name(name);
+        self
+    }
+
+    /// Builds the [`Pool`].
+    pub fn build(self) -> Pool {
+        Pool::new(self.opts.pool_opts(self.pool_opts))
+    }
+}
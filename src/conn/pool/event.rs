@@ -0,0 +1,135 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Reason a pool closes a connection (see [`PoolEvent::ConnectionClosed`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CloseReason {
+    /// The connection is broken, e.g. it failed the liveness check (or the validation query)
+    /// at checkout, or it failed to clean up or to reset upon returning to the pool.
+    Broken,
+    /// The connection outlived its TTL (see [`crate::Opts::conn_ttl`]
+    /// and [`crate::PoolOpts::abs_conn_ttl`]).
+    Expired,
+    /// The connection was idling longer than [`crate::PoolOpts::inactive_connection_ttl`].
+    Inactive,
+    /// The connection was returned to the pool, that already has enough idle connections.
+    Excess,
+    /// The pool is closing (see [`crate::Pool::disconnect`]).
+    PoolClosed,
+}
+
+/// Event of a connection pool (see [`crate::PoolOpts::with_event_handler`]).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PoolEvent {
+    /// A new connection is established by the pool.
+    ConnectionCreated {
+        /// Connection id.
+        id: u32,
+    },
+    /// A connection is closed by the pool.
+    ConnectionClosed {
+        /// Connection id.
+        id: u32,
+        /// The reason to close the connection.
+        reason: CloseReason,
+    },
+    /// [`crate::Pool::get_conn`] failed.
+    CheckoutFailed {
+        /// Description of the error, that is returned to the caller.
+        error: String,
+    },
+    /// Establishing of a connection is going to be retried after a backoff
+    /// (see [`crate::PoolOpts::retry_on_con_count_error`]).
+    ConnectRetry {
+        /// Number of the retry (starting from `1`).
+        attempt: usize,
+        /// The time to wait before the retry.
+        backoff: Duration,
+        /// Description of the error of the failed attempt.
+        error: String,
+    },
+}
+
+/// Receives events of a connection pool (see [`crate::PoolOpts::with_event_handler`]).
+///
+/// The handler is called synchronously by the pool (e.g. within [`crate::Pool::get_conn`]
+/// or by the pool's background task), so it must not block – e.g. log the event,
+/// update a counter or send it to a channel.
+///
+/// It is implemented for every `Fn(PoolEvent)`:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use mysql_async::{PoolEvent, PoolEventHandler, PoolOpts};
+/// let handler = |event: PoolEvent| eprintln!("pool event: {:?}", event);
+/// let pool_opts =
+///     PoolOpts::default().with_event_handler(Some(Arc::new(handler) as Arc<dyn PoolEventHandler>));
+/// ```
+pub trait PoolEventHandler: Send + Sync + 'static {
+    /// Handles a pool event.
+    fn handle(&self, event: PoolEvent);
+}
+
+impl<T> PoolEventHandler for T
+where
+    T: Fn(PoolEvent),
+    T: Send + Sync + 'static,
+{
+    fn handle(&self, event: PoolEvent) {
+        (self)(event)
+    }
+}
+
+/// Pool event handler, that is comparable by identity.
+#[derive(Clone)]
+pub(crate) struct PoolEventHandlerObject(Arc<dyn PoolEventHandler>);
+
+impl PoolEventHandlerObject {
+    pub(crate) fn new(handler: Arc<dyn PoolEventHandler>) -> Self {
+        Self(handler)
+    }
+
+    pub(crate) fn clone_inner(&self) -> Arc<dyn PoolEventHandler> {
+        self.0.clone()
+    }
+
+    pub(crate) fn handle(&self, event: PoolEvent) {
+        self.0.handle(event)
+    }
+}
+
+impl PartialEq for PoolEventHandlerObject {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const () == Arc::as_ptr(&other.0) as *const ()
+    }
+}
+
+impl Eq for PoolEventHandlerObject {}
+
+impl Hash for PoolEventHandlerObject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const ()).hash(state)
+    }
+}
+
+impl fmt::Debug for PoolEventHandlerObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PoolEventHandlerObject")
+            .field(&"..")
+            .finish()
+    }
+}
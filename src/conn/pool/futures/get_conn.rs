@@ -14,6 +14,9 @@ use std::{
 };
 
 use futures_core::ready;
+use futures_util::FutureExt;
+#[cfg(any(feature = "pool-latency", feature = "metrics"))]
+use std::time::Instant;
 #[cfg(feature = "tracing")]
 use {
     std::sync::Arc,
@@ -22,7 +25,7 @@ use {
 
 use crate::{
     conn::{
-        pool::{Pool, QueueId},
+        pool::{Pool, PoolEvent, QueueId},
         Conn,
     },
     error::*,
@@ -36,6 +39,8 @@ pub(crate) enum GetConnInner {
     Connecting(crate::BoxFuture<'static, Conn>),
     /// This future will check, that idling connection is alive.
     Checking(crate::BoxFuture<'static, Conn>),
+    /// This future will select the requested default database.
+    SelectingDb(crate::BoxFuture<'static, Conn>),
 }
 
 impl fmt::Debug for GetConnInner {
@@ -51,6 +56,10 @@ impl fmt::Debug for GetConnInner {
                 .debug_tuple("GetConnInner::Checking")
                 .field(&"<future>")
                 .finish(),
+            GetConnInner::SelectingDb(_) => f
+                .debug_tuple("GetConnInner::SelectingDb")
+                .field(&"<future>")
+                .finish(),
         }
     }
 }
@@ -63,8 +72,14 @@ pub struct GetConn {
     pub(crate) pool: Option<Pool>,
     pub(crate) inner: GetConnInner,
     reset_upon_returning_to_a_pool: bool,
+    /// Default database of the resulting connection (see [`Pool::get_conn_for_db`]).
+    ///
+    /// An idle connection, that uses this database, is preferred.
+    db_name: Option<String>,
     #[cfg(feature = "tracing")]
     span: Arc<Span>,
+    #[cfg(any(feature = "pool-latency", feature = "metrics"))]
+    started: Instant,
 }
 
 impl GetConn {
@@ -74,8 +89,40 @@ impl GetConn {
             pool: Some(pool.clone()),
             inner: GetConnInner::New,
             reset_upon_returning_to_a_pool,
+            db_name: pool.opts.db_name().map(Into::into),
             #[cfg(feature = "tracing")]
-            span: Arc::new(debug_span!("mysql_async::get_conn")),
+            span: Arc::new(debug_span!(
+                "mysql_async::get_conn",
+                mysql_async.pool.name = pool.name()
+            )),
+            #[cfg(any(feature = "pool-latency", feature = "metrics"))]
+            started: Instant::now(),
+        }
+    }
+
+    /// Requests a connection, that uses the given default database
+    /// (defaults to the pool's default database).
+    pub(crate) fn with_db_name(mut self, db_name: String) -> Self {
+        self.db_name = Some(db_name);
+        self
+    }
+
+    /// Returns the given connection back, if it uses the requested default database,
+    /// otherwise moves to [`GetConnInner::SelectingDb`] and returns `None`.
+    fn select_db_if_needed(&mut self, mut conn: Conn) -> Option<Conn> {
+        match self.db_name.take() {
+            Some(db_name) if conn.opts().db_name() != Some(&*db_name) => {
+                // the connection is returned to the pool if it fails
+                self.inner = GetConnInner::SelectingDb(
+                    async move {
+                        conn.select_db(db_name).await?;
+                        Ok(conn)
+                    }
+                    .boxed(),
+                );
+                None
+            }
+            _ => Some(conn),
         }
     }
 
@@ -106,7 +153,17 @@ impl Future for GetConn {
             match self.inner {
                 GetConnInner::New => {
                     let queue_id = self.queue_id;
-                    let next = ready!(self.pool_mut().poll_new_conn(cx, queue_id))?;
+                    let GetConn { pool, db_name, .. } = &mut *self;
+                    let pool = pool
+                        .as_mut()
+                        .expect("GetConn::poll polled after returning Async::Ready");
+                    let next = match ready!(pool.poll_new_conn(cx, queue_id, db_name.as_deref())) {
+                        Ok(next) => next,
+                        Err(err) => {
+                            pool.emit_checkout_failed(&err);
+                            return Poll::Ready(Err(err));
+                        }
+                    };
                     match next {
                         GetConnInner::Connecting(conn_fut) => {
                             self.inner = GetConnInner::Connecting(conn_fut);
@@ -120,6 +177,9 @@ impl Future for GetConn {
                         GetConnInner::New => {
                             unreachable!("Pool::poll_new_conn never gives out GetConnInner::New")
                         }
+                        GetConnInner::SelectingDb(_) => unreachable!(
+                            "Pool::poll_new_conn never gives out GetConnInner::SelectingDb"
+                        ),
                     }
                 }
                 GetConnInner::Done => {
@@ -133,13 +193,23 @@ impl Future for GetConn {
 
                     return match result {
                         Ok(mut c) => {
+                            pool.inner.metrics.conn_created();
+                            pool.opts
+                                .pool_opts()
+                                .emit_event(|| PoolEvent::ConnectionCreated { id: c.id() });
+                            #[cfg(any(feature = "pool-latency", feature = "metrics"))]
+                            pool.inner.metrics.get_conn_done(self.started.elapsed());
                             c.inner.pool = Some(pool);
                             c.inner.reset_upon_returning_to_a_pool =
                                 self.reset_upon_returning_to_a_pool;
-                            Poll::Ready(Ok(c))
+                            match self.select_db_if_needed(c) {
+                                Some(c) => Poll::Ready(Ok(c)),
+                                None => continue,
+                            }
                         }
                         Err(e) => {
                             pool.cancel_connection();
+                            pool.emit_checkout_failed(&e);
                             Poll::Ready(Err(e))
                         }
                     };
@@ -151,21 +221,31 @@ impl Future for GetConn {
                             self.inner = GetConnInner::Done;
 
                             let pool = self.pool_take();
+                            #[cfg(any(feature = "pool-latency", feature = "metrics"))]
+                            pool.inner.metrics.get_conn_done(self.started.elapsed());
                             c.inner.pool = Some(pool);
                             c.inner.reset_upon_returning_to_a_pool =
                                 self.reset_upon_returning_to_a_pool;
-                            return Poll::Ready(Ok(c));
+                            match self.select_db_if_needed(c) {
+                                Some(c) => return Poll::Ready(Ok(c)),
+                                None => continue,
+                            }
                         }
                         Err(_) => {
                             // Idling connection is broken. We'll drop it and try again.
                             self.inner = GetConnInner::New;
 
                             let pool = self.pool_mut();
-                            pool.cancel_connection();
+                            pool.discard_connection();
                             continue;
                         }
                     }
                 }
+                GetConnInner::SelectingDb(ref mut f) => {
+                    let result = ready!(Pin::new(f).poll(cx));
+                    self.inner = GetConnInner::Done;
+                    return Poll::Ready(result);
+                }
             }
         }
     }
@@ -0,0 +1,256 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{
+    convert::TryFrom,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Source of `pool_id` labels of unnamed pools.
+#[cfg(feature = "metrics")]
+static NEXT_POOL_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A snapshot of pool metrics (see [`crate::Pool::metrics`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PoolMetrics {
+    pub(super) created: u64,
+    pub(super) dropped: u64,
+    pub(super) active: usize,
+    pub(super) idle: usize,
+    pub(super) waiters: usize,
+    pub(super) resets: u64,
+    pub(super) get_conn_p50: Option<Duration>,
+    pub(super) get_conn_p99: Option<Duration>,
+}
+
+impl PoolMetrics {
+    /// Total number of connections established by the pool.
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    /// Total number of established connections that were removed from the pool
+    /// (broken, expired, failed to clean up or closed during disconnect).
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Number of connections that are either taken from the pool or being established.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Number of idling connections.
+    pub fn idle(&self) -> usize {
+        self.idle
+    }
+
+    /// Number of tasks waiting for a connection.
+    pub fn waiters(&self) -> usize {
+        self.waiters
+    }
+
+    /// Total number of connections reset upon returning to the pool
+    /// (see [`crate::PoolOpts::reset_connection`]).
+    pub fn resets(&self) -> u64 {
+        self.resets
+    }
+
+    /// Median `get_conn` latency.
+    ///
+    /// Only tracked if the `pool-latency` crate feature is enabled, `None` otherwise
+    /// or if there were no `get_conn` calls yet. The value is an upper bound of a histogram
+    /// bucket, i.e. it may overestimate the actual latency by up to two times.
+    pub fn get_conn_p50(&self) -> Option<Duration> {
+        self.get_conn_p50
+    }
+
+    /// 99th percentile of `get_conn` latency (see [`PoolMetrics::get_conn_p50`]).
+    pub fn get_conn_p99(&self) -> Option<Duration> {
+        self.get_conn_p99
+    }
+}
+
+/// Pool counters.
+///
+/// If the `metrics` crate feature is enabled, then counters are also exported via
+/// the `metrics` crate facade (see [`crate::PoolOpts::with_name`]).
+#[derive(Debug, Default)]
+pub(super) struct Metrics {
+    created: AtomicU64,
+    dropped: AtomicU64,
+    resets: AtomicU64,
+    #[cfg(feature = "pool-latency")]
+    get_conn_latency: LatencyHistogram,
+    /// Labels of exported metrics.
+    #[cfg(feature = "metrics")]
+    labels: Vec<metrics::Label>,
+}
+
+impl Metrics {
+    /// Creates counters of a pool with the given name (see [`crate::PoolOpts::with_name`]).
+    ///
+    /// Metrics of an unnamed pool are labeled with a process-unique `pool_id`,
+    /// so that gauges of different pools don't overwrite each other.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    pub(super) fn new(name: Option<&str>) -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            labels: match name {
+                Some(name) => vec![metrics::Label::new("pool", name.to_owned())],
+                None => vec![metrics::Label::new(
+                    "pool_id",
+                    NEXT_POOL_ID.fetch_add(1, Ordering::Relaxed).to_string(),
+                )],
+            },
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn conn_created(&self) {
+        self.created.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::counter!("mysql_pool_connections_created", self.labels.iter()).increment(1);
+    }
+
+    pub(super) fn conns_dropped(&self, num: usize) {
+        self.dropped.fetch_add(num as u64, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::counter!("mysql_pool_connections_dropped", self.labels.iter())
+            .increment(num as u64);
+    }
+
+    pub(super) fn conn_reset(&self) {
+        self.resets.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::counter!("mysql_pool_connections_reset", self.labels.iter()).increment(1);
+    }
+
+    #[cfg(any(feature = "pool-latency", feature = "metrics"))]
+    pub(super) fn get_conn_done(&self, latency: Duration) {
+        #[cfg(feature = "pool-latency")]
+        self.get_conn_latency.record(latency);
+        #[cfg(feature = "metrics")]
+        metrics::histogram!("mysql_get_conn_wait_seconds", self.labels.iter())
+            .record(latency.as_secs_f64());
+    }
+
+    /// Exports the number of active and idling connections of the given pool state
+    /// (no-op if the `metrics` crate feature is disabled).
+    ///
+    /// Should be called every time the pool state changes.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    pub(super) fn pool_state(&self, exchange: &super::Exchange) {
+        #[cfg(feature = "metrics")]
+        {
+            let idle = exchange.available.len();
+            metrics::gauge!("mysql_pool_active", self.labels.iter())
+                .set(exchange.exist.saturating_sub(idle) as f64);
+            metrics::gauge!("mysql_pool_idle", self.labels.iter()).set(idle as f64);
+        }
+    }
+
+    /// Fills counters of the given snapshot.
+    pub(super) fn fill(&self, snapshot: &mut PoolMetrics) {
+        snapshot.created = self.created.load(Ordering::Relaxed);
+        snapshot.dropped = self.dropped.load(Ordering::Relaxed);
+        snapshot.resets = self.resets.load(Ordering::Relaxed);
+        #[cfg(feature = "pool-latency")]
+        {
+            snapshot.get_conn_p50 = self.get_conn_latency.quantile(0.5);
+            snapshot.get_conn_p99 = self.get_conn_latency.quantile(0.99);
+        }
+    }
+}
+
+/// Lock-free histogram with power-of-two microsecond buckets.
+///
+/// Bucket `0` holds zero durations, bucket `i` holds durations in `[2^(i-1), 2^i)`µs,
+/// the last bucket also holds everything above.
+#[cfg_attr(not(feature = "pool-latency"), allow(dead_code))]
+#[derive(Debug, Default)]
+struct LatencyHistogram {
+    buckets: [AtomicU64; 32],
+}
+
+#[cfg_attr(not(feature = "pool-latency"), allow(dead_code))]
+impl LatencyHistogram {
+    fn record(&self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let index = (64 - micros.leading_zeros() as usize).min(self.buckets.len() - 1);
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns an upper bound of the bucket that contains the given quantile.
+    fn quantile(&self, q: f64) -> Option<Duration> {
+        let counts = self
+            .buckets
+            .iter()
+            .map(|x| x.load(Ordering::Relaxed))
+            .collect::<Vec<_>>();
+        let total = counts.iter().sum::<u64>();
+        if total == 0 {
+            return None;
+        }
+
+        let rank = ((total as f64 * q).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, count) in counts.into_iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Duration::from_micros(1 << i));
+            }
+        }
+
+        unreachable!("rank never exceeds the total")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::LatencyHistogram;
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn should_label_unnamed_pools_distinctly() {
+        use super::Metrics;
+
+        let named = Metrics::new(Some("primary"));
+        assert_eq!(named.labels.len(), 1);
+        assert_eq!(named.labels[0].key(), "pool");
+        assert_eq!(named.labels[0].value(), "primary");
+
+        let a = Metrics::new(None);
+        let b = Metrics::new(None);
+        assert_eq!(a.labels[0].key(), "pool_id");
+        assert_eq!(b.labels[0].key(), "pool_id");
+        assert_ne!(a.labels, b.labels);
+    }
+
+    #[test]
+    fn should_compute_quantiles() {
+        let histogram = LatencyHistogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+
+        for _ in 0..98 {
+            histogram.record(Duration::from_micros(100));
+        }
+        histogram.record(Duration::from_millis(10));
+        histogram.record(Duration::from_secs(u64::MAX));
+
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_micros(128)));
+        assert_eq!(histogram.quantile(0.99), Some(Duration::from_micros(16384)));
+        assert_eq!(
+            histogram.quantile(1.0),
+            Some(Duration::from_micros(1 << 31))
+        );
+    }
+}
@@ -6,16 +6,18 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use futures_util::FutureExt;
+use futures_core::future::BoxFuture;
+use futures_util::{task::AtomicWaker, FutureExt};
 use keyed_priority_queue::KeyedPriorityQueue;
 use tokio::sync::mpsc;
 
 use std::{
     borrow::Borrow,
     cmp::Reverse,
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     convert::TryFrom,
     hash::{Hash, Hasher},
+    num::NonZeroUsize,
     str::FromStr,
     sync::{atomic, Arc, Mutex},
     task::{Context, Poll, Waker},
@@ -23,15 +25,30 @@ use std::{
 };
 
 use crate::{
-    conn::{pool::futures::*, Conn},
+    conn::{
+        pool::{futures::*, stmt_metadata::StmtMetadataCache},
+        Conn,
+    },
     error::*,
-    opts::{Opts, PoolOpts},
+    opts::{Opts, PoolOpts, CON_COUNT_ERROR_BACKOFF, CON_COUNT_ERROR_RETRIES},
+    prelude::Queryable,
     queryable::transaction::{Transaction, TxOpts},
 };
 
+mod builder;
+pub(crate) mod event;
+mod metrics;
 mod recycler;
+mod replicated;
+pub(crate) mod stmt_metadata;
 // this is a really unfortunate name for a module
 pub mod futures;
+pub use builder::PoolBuilder;
+pub use event::{CloseReason, PoolEvent, PoolEventHandler};
+pub use metrics::PoolMetrics;
+pub use replicated::{ReaderSelection, ReplicatedPool};
+pub use session_profile::SessionProfile;
+mod session_profile;
 mod ttl_check_inerval;
 
 /// Connection that is idling in the pool.
@@ -100,6 +117,28 @@ impl Exchange {
             }
         }
     }
+
+    /// Takes the most recently returned idle connection, preferring the one,
+    /// that uses the given default database (see [`Pool::get_conn_for_db`]).
+    fn take_available(&mut self, db_name: Option<&str>) -> Option<IdlingConn> {
+        let position = self
+            .available
+            .iter()
+            .rposition(|idling_conn| idling_conn.conn.opts().db_name() == db_name);
+        match position {
+            Some(position) => self.available.remove(position),
+            None => self.available.pop_back(),
+        }
+    }
+
+    /// Takes the most recently returned idle connection, that uses the given default database.
+    fn take_available_exact(&mut self, db_name: Option<&str>) -> Option<IdlingConn> {
+        let position = self
+            .available
+            .iter()
+            .rposition(|idling_conn| idling_conn.conn.opts().db_name() == db_name)?;
+        self.available.remove(position)
+    }
 }
 
 #[derive(Default, Debug)]
@@ -179,12 +218,56 @@ impl Hash for QueuedWaker {
     }
 }
 
+/// A snapshot of the pool state (see [`Pool::status`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PoolStatus {
+    idle: usize,
+    active: usize,
+    max: usize,
+    waiters: usize,
+}
+
+impl PoolStatus {
+    /// Number of idling connections.
+    pub fn idle(&self) -> usize {
+        self.idle
+    }
+
+    /// Number of connections that are either taken from the pool or being established.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Maximum number of connections (see [`crate::PoolConstraints::max`]).
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Number of tasks waiting for a connection.
+    pub fn waiters(&self) -> usize {
+        self.waiters
+    }
+}
+
 /// Connection pool data.
 #[derive(Debug)]
 pub struct Inner {
     close: atomic::AtomicBool,
     closed: atomic::AtomicBool,
     exchange: Mutex<Exchange>,
+    stmt_metadata: Option<StmtMetadataCache>,
+    session_profiles: Mutex<HashMap<String, SessionProfile>>,
+    metrics: metrics::Metrics,
+    /// Wakes the TTL check interval, so that it exits as soon as the pool is closing.
+    ttl_check_waker: AtomicWaker,
+}
+
+impl Inner {
+    /// Asks the pool to close (it's idempotent).
+    fn start_closing(&self) {
+        self.close.store(true, atomic::Ordering::SeqCst);
+        self.ttl_check_waker.wake();
+    }
 }
 
 /// Asynchronous pool of MySql connections.
@@ -215,6 +298,10 @@ impl Pool {
     {
         let opts = Opts::try_from(opts).unwrap();
         let pool_opts = opts.pool_opts().clone();
+        let stmt_metadata = NonZeroUsize::new(opts.stmt_cache_size())
+            .filter(|_| pool_opts.shared_stmt_metadata())
+            .map(StmtMetadataCache::new);
+        let metrics = metrics::Metrics::new(pool_opts.name());
         let (tx, rx) = mpsc::unbounded_channel();
         Pool {
             opts,
@@ -227,11 +314,25 @@ impl Pool {
                     exist: 0,
                     recycler: Some((rx, pool_opts)),
                 }),
+                stmt_metadata,
+                session_profiles: Mutex::default(),
+                metrics,
+                ttl_check_waker: AtomicWaker::new(),
             }),
             drop: tx,
         }
     }
 
+    /// Returns statement metadata cache shared between connections of this pool, if enabled.
+    pub(crate) fn stmt_metadata(&self) -> Option<&StmtMetadataCache> {
+        self.inner.stmt_metadata.as_ref()
+    }
+
+    /// Returns a [`PoolBuilder`] to fluently configure a new pool.
+    pub fn builder() -> PoolBuilder {
+        PoolBuilder::default()
+    }
+
     /// Creates a new pool of connections.
     pub fn from_url<T: AsRef<str>>(url: T) -> Result<Pool> {
         let opts = Opts::from_str(url.as_ref())?;
@@ -239,21 +340,287 @@ impl Pool {
     }
 
     /// Async function that resolves to `Conn`.
+    ///
+    /// The connection uses the default database of the pool (see [`Opts::db_name`]).
+    /// An idle connection, that uses this database, is preferred. Otherwise (e.g. if all idle
+    /// connections were switched to other databases via [`Pool::get_conn_for_db`] or
+    /// [`Conn::select_db`]) the default database is selected back using [`Conn::select_db`].
+    ///
+    /// Note that a database can't be deselected, so if the pool has no default database,
+    /// then a connection, that was switched to some database, keeps it (an idle connection
+    /// without a default database is still preferred).
     pub fn get_conn(&self) -> GetConn {
         let reset_connection = self.opts.pool_opts().reset_connection();
         GetConn::new(self, reset_connection)
     }
 
+    /// Async function that resolves to `Conn`, which uses the given default database.
+    ///
+    /// An idle connection, that already uses this database, is preferred (e.g. one returned
+    /// to the pool after a previous call to this function), otherwise the database
+    /// of the connection is changed using [`Conn::select_db`]. So in a setup, where
+    /// each tenant uses its own schema, connections keep an affinity to schemas,
+    /// that saves a round trip per checkout.
+    ///
+    /// The connection keeps the selected database after returning to the pool,
+    /// so that it's preferred by subsequent calls for the same database
+    /// ([`Pool::get_conn`] selects the default database of the pool back).
+    ///
+    /// Returns a server error (`ER_BAD_DB_ERROR`) if the database does not exist.
+    ///
+    /// ```rust
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Pool};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let pool = Pool::new(get_opts());
+    ///
+    /// let mut conn = pool.get_conn_for_db("information_schema").await?;
+    /// let db: Option<String> = conn.query_first("SELECT DATABASE()").await?;
+    /// assert_eq!(db.as_deref(), Some("information_schema"));
+    /// # drop(conn); pool.disconnect().await }
+    /// ```
+    pub async fn get_conn_for_db(&self, db_name: &str) -> Result<Conn> {
+        let reset_connection = self.opts.pool_opts().reset_connection();
+        GetConn::new(self, reset_connection)
+            .with_db_name(db_name.into())
+            .await
+    }
+
+    /// Registers a named [`SessionProfile`] (replaces a profile with the same name, if any).
+    ///
+    /// Profiles are shared between clones of this pool.
+    pub fn register_session_profile<T: Into<String>>(&self, name: T, profile: SessionProfile) {
+        self.inner
+            .session_profiles
+            .lock()
+            .unwrap()
+            .insert(name.into(), profile);
+    }
+
+    /// Async function that resolves to `Conn` with the given session profile applied
+    /// (see [`Pool::register_session_profile`]).
+    ///
+    /// Returns [`DriverError::UnknownSessionProfile`] if there is no such profile.
+    ///
+    /// # Revert cost
+    ///
+    /// Profile settings are reverted when the connection returns to the pool by resetting the
+    /// connection, regardless of [`PoolOpts::reset_connection`]. This means an additional
+    /// round trip (`COM_RESET_CONNECTION`, or `COM_CHANGE_USER` on servers that don't support it),
+    /// loss of the statement cache and re-execution of [`Opts::setup`] commands.
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, IsolationLevel, Pool, SessionProfile};
+    /// # use mysql_async::prelude::*;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let pool = Pool::new(get_opts());
+    /// pool.register_session_profile(
+    ///     "reporting",
+    ///     SessionProfile::new()
+    ///         .with_isolation_level(IsolationLevel::ReadCommitted)
+    ///         .with_variable("wait_timeout", 3600),
+    /// );
+    ///
+    /// let mut conn = pool.get_conn_with_profile("reporting").await?;
+    /// let timeout: Option<u32> = conn.query_first("SELECT @@SESSION.wait_timeout").await?;
+    /// assert_eq!(timeout, Some(3600));
+    /// # drop(conn);
+    /// # pool.disconnect().await
+    /// # }
+    /// ```
+    pub async fn get_conn_with_profile(&self, name: &str) -> Result<Conn> {
+        let profile = self
+            .inner
+            .session_profiles
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| DriverError::UnknownSessionProfile { name: name.into() })?;
+
+        let mut conn = self.get_conn().await?;
+        conn.inner.reset_upon_returning_to_a_pool = true;
+        profile.apply(&mut conn).await?;
+        Ok(conn)
+    }
+
+    /// Returns an idle connection, if one is available right now.
+    ///
+    /// Unlike [`Pool::get_conn`] this function never awaits – it won't establish a new
+    /// connection and it won't join the queue of pending `GetConn`s. It also yields to pending
+    /// `GetConn`s, i.e. it'll return `None` if there are tasks waiting for a connection.
+    ///
+    /// The idle connection is given out as is, so (unlike [`Pool::get_conn`]) it's only checked
+    /// for liveness on the socket level, it isn't validated (see
+    /// [`PoolOpts::with_validation_query`]) and only a connection, that uses the default database
+    /// of the pool, is given out.
+    ///
+    /// Returns `None` if the pool is disconnected.
+    pub fn try_get_conn(&self) -> Option<Conn> {
+        #[cfg(any(feature = "pool-latency", feature = "metrics"))]
+        let started = Instant::now();
+
+        loop {
+            let IdlingConn { mut conn, since } = {
+                let mut exchange = self.inner.exchange.lock().unwrap();
+
+                if self.inner.close.load(atomic::Ordering::Acquire)
+                    || exchange.waiting.peek_id().is_some()
+                {
+                    return None;
+                }
+
+                let idling_conn = exchange.take_available_exact(self.opts.db_name())?;
+                self.inner.metrics.pool_state(&exchange);
+                idling_conn
+            };
+
+            if conn.expired() {
+                self.send_to_recycler(conn);
+                continue;
+            }
+
+            // liveness check resolves immediately, unless the socket is not ready for writing
+            let check = conn
+                .stream_mut()
+                .map(|stream| stream.check().now_or_never());
+
+            match check {
+                Ok(Some(Ok(()))) => {
+                    #[cfg(any(feature = "pool-latency", feature = "metrics"))]
+                    self.inner.metrics.get_conn_done(started.elapsed());
+                    conn.inner.pool = Some(self.clone());
+                    conn.inner.reset_upon_returning_to_a_pool =
+                        self.opts.pool_opts().reset_connection();
+                    return Some(conn);
+                }
+                Ok(None) => {
+                    // we can't tell right now, so let's leave it to `get_conn`
+                    let mut exchange = self.inner.exchange.lock().unwrap();
+                    exchange.available.push_back(IdlingConn { conn, since });
+                    self.inner.metrics.pool_state(&exchange);
+                    return None;
+                }
+                Ok(Some(Err(_))) | Err(_) => {
+                    // Idling connection is broken. We'll drop it and try again.
+                    self.opts
+                        .pool_opts()
+                        .emit_event(|| PoolEvent::ConnectionClosed {
+                            id: conn.id(),
+                            reason: CloseReason::Broken,
+                        });
+                    drop(conn);
+                    self.discard_connection();
+                }
+            }
+        }
+    }
+
+    /// Returns the pool name, if any (see [`PoolOpts::with_name`]).
+    pub fn name(&self) -> Option<&str> {
+        self.opts.pool_opts().name()
+    }
+
+    /// Returns a snapshot of the pool state.
+    ///
+    /// This neither checks out a connection nor joins the queue of pending `GetConn`s,
+    /// so it's suitable for monitoring (see also [`Pool::is_ready`]).
+    pub fn status(&self) -> PoolStatus {
+        let exchange = self.inner.exchange.lock().unwrap();
+        PoolStatus {
+            idle: exchange.available.len(),
+            active: exchange.exist - exchange.available.len(),
+            max: self.opts.pool_opts().constraints().max(),
+            waiters: exchange.waiting.queue.len(),
+        }
+    }
+
+    /// Returns a snapshot of the pool metrics.
+    ///
+    /// Unlike [`Pool::status`] this also includes cumulative counters and,
+    /// if the `pool-latency` crate feature is enabled, `get_conn` latency.
+    pub fn metrics(&self) -> PoolMetrics {
+        let exchange = self.inner.exchange.lock().unwrap();
+        let mut metrics = PoolMetrics {
+            created: 0,
+            dropped: 0,
+            active: exchange.exist - exchange.available.len(),
+            idle: exchange.available.len(),
+            waiters: exchange.waiting.queue.len(),
+            resets: 0,
+            get_conn_p50: None,
+            get_conn_p99: None,
+        };
+        self.inner.metrics.fill(&mut metrics);
+        metrics
+    }
+
+    /// Returns `true` if the pool is able to serve a connection right now, i.e. there are no
+    /// pending `GetConn`s and there is either an idling connection or a room for a new one.
+    ///
+    /// Just like [`Pool::status`] this won't check out a connection, so it's suitable for
+    /// readiness probes. Note that it doesn't check the server availability (idling
+    /// connections are not pinged, and new connections may fail to establish).
+    ///
+    /// Returns `false` if the pool is disconnected.
+    pub fn is_ready(&self) -> bool {
+        if self.inner.close.load(atomic::Ordering::Acquire) {
+            return false;
+        }
+
+        let exchange = self.inner.exchange.lock().unwrap();
+        exchange.waiting.queue.is_empty()
+            && (!exchange.available.is_empty()
+                || exchange.exist < self.opts.pool_opts().constraints().max())
+    }
+
     /// Starts a new transaction.
     pub async fn start_transaction(&self, options: TxOpts) -> Result<Transaction<'static>> {
         let conn = self.get_conn().await?;
         Transaction::new(conn, options).await
     }
 
+    /// Takes a connection from the pool and runs the given closure with it.
+    ///
+    /// The connection is returned to the pool as soon as the future, returned by the closure,
+    /// completes (successfully or not) or gets dropped, so it can't be held longer than
+    /// the scope. Whether it's reset upon returning depends on [`PoolOpts::reset_connection`].
+    /// If the closure panics, then the connection is discarded and its slot in the pool is freed.
+    /// Result of the closure is returned as is and an error of taking a connection is converted
+    /// into `E`.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use futures_util::FutureExt;
+    /// use mysql_async::{prelude::*, Pool};
+    ///
+    /// let pool = Pool::new(get_opts());
+    /// let value: Option<u8> = pool
+    ///     .scoped(|conn| async move { conn.query_first("SELECT 42").await }.boxed())
+    ///     .await?;
+    /// assert_eq!(value, Some(42));
+    /// # pool.disconnect().await }
+    /// ```
+    pub async fn scoped<F, T, E>(&self, f: F) -> std::result::Result<T, E>
+    where
+        F: for<'a> FnOnce(&'a mut Conn) -> BoxFuture<'a, std::result::Result<T, E>>,
+        E: From<Error>,
+    {
+        let mut conn = self.get_conn().await?;
+        f(&mut conn).await
+    }
+
     /// Async function that disconnects this pool from the server and resolves to `()`.
     ///
     /// **Note:** This Future won't resolve until all active connections, taken from it,
     /// are dropped or disonnected. Also all pending and new `GetConn`'s will resolve to error.
+    ///
+    /// It's safe to disconnect a pool more than once (e.g. using different clones of it),
+    /// every call will resolve once the pool is closed. A pool, that is dropped without
+    /// disconnecting, closes its idle connections and background tasks on its own.
     pub fn disconnect(self) -> DisconnectPool {
         DisconnectPool::new(self)
     }
@@ -289,17 +656,36 @@ impl Pool {
     pub(super) fn cancel_connection(&self) {
         let mut exchange = self.inner.exchange.lock().unwrap();
         exchange.exist -= 1;
+        self.inner.metrics.pool_state(&exchange);
         // we just enabled the creation of a new connection!
         if let Some(w) = exchange.waiting.pop() {
             w.wake();
         }
     }
 
+    /// Same as [`Pool::cancel_connection`] but for an established connection.
+    pub(super) fn discard_connection(&self) {
+        self.inner.metrics.conns_dropped(1);
+        self.cancel_connection();
+    }
+
+    /// Notifies the event handler about a failed checkout.
+    pub(super) fn emit_checkout_failed(&self, error: &Error) {
+        self.opts
+            .pool_opts()
+            .emit_event(|| PoolEvent::CheckoutFailed {
+                error: error.to_string(),
+            });
+    }
+
     /// Poll the pool for an available connection.
+    ///
+    /// An idle connection, that uses the given default database, is preferred.
     fn poll_new_conn(
         &mut self,
         cx: &mut Context<'_>,
         queue_id: QueueId,
+        db_name: Option<&str>,
     ) -> Poll<Result<GetConnInner>> {
         let mut exchange = self.inner.exchange.lock().unwrap();
 
@@ -325,15 +711,10 @@ impl Pool {
             return Poll::Pending;
         }
 
-        while let Some(IdlingConn { mut conn, .. }) = exchange.available.pop_back() {
+        while let Some(IdlingConn { conn, .. }) = exchange.take_available(db_name) {
             if !conn.expired() {
-                return Poll::Ready(Ok(GetConnInner::Checking(
-                    async move {
-                        conn.stream_mut()?.check().await?;
-                        Ok(conn)
-                    }
-                    .boxed(),
-                )));
+                self.inner.metrics.pool_state(&exchange);
+                return Poll::Ready(Ok(GetConnInner::Checking(self.check_idling_conn(conn))));
             } else {
                 self.send_to_recycler(conn);
             }
@@ -344,9 +725,10 @@ impl Pool {
         if exchange.exist < self.opts.pool_opts().constraints().max() {
             // we are allowed to make a new connection, so we will!
             exchange.exist += 1;
+            self.inner.metrics.pool_state(&exchange);
 
             return Poll::Ready(Ok(GetConnInner::Connecting(
-                Conn::new(self.opts.clone()).boxed(),
+                Self::new_conn(self.opts.clone()).boxed(),
             )));
         }
 
@@ -355,6 +737,70 @@ impl Pool {
         Poll::Pending
     }
 
+    /// Checks, that the given idling connection is alive, and validates it
+    /// (see [`PoolOpts::with_validation_query`]).
+    fn check_idling_conn(&self, mut conn: Conn) -> BoxFuture<'static, Result<Conn>> {
+        let validation_query = self.opts.pool_opts().validation_query().map(String::from);
+        let event_handler = self.opts.pool_opts().event_handler();
+        async move {
+            let check = async {
+                conn.stream_mut()?.check().await?;
+                if let Some(query) = validation_query {
+                    conn.query_drop(query).await?;
+                }
+                Ok(())
+            };
+            match check.await {
+                Ok(()) => Ok(conn),
+                Err(err) => {
+                    if let Some(handler) = event_handler {
+                        handler.handle(PoolEvent::ConnectionClosed {
+                            id: conn.id(),
+                            reason: CloseReason::Broken,
+                        });
+                    }
+                    Err(err)
+                }
+            }
+        }
+        .boxed()
+    }
+
+    /// Establishes a new connection retrying on `ER_CON_COUNT_ERROR`
+    /// if [`PoolOpts::retry_on_con_count_error`] is enabled.
+    ///
+    /// Statements listed in [`PoolOpts::prepared_statement_warmup`] are prepared
+    /// on the new connection.
+    async fn new_conn(opts: Opts) -> Result<Conn> {
+        let mut retries = if opts.pool_opts().retry_on_con_count_error() {
+            CON_COUNT_ERROR_RETRIES
+        } else {
+            0
+        };
+        let mut backoff = CON_COUNT_ERROR_BACKOFF;
+
+        let mut attempt = 0;
+        let mut conn = loop {
+            match Conn::new(opts.clone()).await {
+                Err(err) if retries > 0 && err.is_con_count_error() => {
+                    retries -= 1;
+                    attempt += 1;
+                    opts.pool_opts().emit_event(|| PoolEvent::ConnectRetry {
+                        attempt,
+                        backoff,
+                        error: err.to_string(),
+                    });
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => break result?,
+            }
+        };
+
+        conn.warm_up_stmt_cache().await?;
+        Ok(conn)
+    }
+
     fn unqueue(&self, queue_id: QueueId) {
         let mut exchange = self.inner.exchange.lock().unwrap();
         exchange.waiting.remove(queue_id);
@@ -368,7 +814,7 @@ impl Drop for Conn {
         if std::thread::panicking() {
             // Try to decrease the number of existing connections.
             if let Some(pool) = self.inner.pool.take() {
-                pool.cancel_connection();
+                pool.discard_connection();
             }
 
             return;
@@ -385,7 +831,7 @@ impl Drop for Conn {
 #[cfg(test)]
 mod test {
     use futures_util::{
-        future::{join_all, select, select_all, try_join_all, Either},
+        future::{join_all, select, select_all, try_join_all, BoxFuture, Either},
         poll, try_join, FutureExt,
     };
     use tokio::time::{sleep, timeout};
@@ -405,7 +851,7 @@ mod test {
         opts::PoolOpts,
         prelude::*,
         test_misc::get_opts,
-        PoolConstraints, Row, TxOpts, Value,
+        Conn, PoolConstraints, Row, TxOpts, Value,
     };
 
     macro_rules! conn_ex_field {
@@ -426,6 +872,759 @@ mod test {
         Pool::new(opts)
     }
 
+    #[test]
+    fn should_build_pool_with_fluent_builder() {
+        let pool = Pool::builder()
+            .ip_or_hostname("example.com")
+            .tcp_port(3307)
+            .db_name(Some("foo"))
+            .init(vec!["SET NAMES utf8mb4"])
+            .stmt_cache_size(8)
+            .constraints(PoolConstraints::new(2, 4).unwrap())
+            .inactive_connection_ttl(Duration::from_secs(42))
+            .reset_connection(false)
+            .shared_stmt_metadata(true)
+            .prepared_statement_warmup(vec!["SELECT 1"])
+            .name(Some("primary"))
+            .build();
+
+        assert_eq!(pool.name(), Some("primary"));
+        assert_eq!(pool.opts.ip_or_hostname(), "example.com");
+        assert_eq!(pool.opts.tcp_port(), 3307);
+        assert_eq!(pool.opts.db_name(), Some("foo"));
+        assert_eq!(pool.opts.init(), ["SET NAMES utf8mb4"]);
+        assert_eq!(pool.opts.stmt_cache_size(), 8);
+        let pool_opts = pool.opts.pool_opts();
+        assert_eq!(pool_opts.constraints(), PoolConstraints::new(2, 4).unwrap());
+        assert_eq!(pool_opts.inactive_connection_ttl(), Duration::from_secs(42));
+        assert!(!pool_opts.reset_connection());
+        assert!(pool_opts.shared_stmt_metadata());
+        assert_eq!(pool_opts.prepared_statement_warmup(), ["SELECT 1"]);
+        assert!(pool.stmt_metadata().is_some());
+
+        // pool options should survive `from_opts`
+        let builder = super::PoolBuilder::from_opts(pool.opts.clone()).tcp_port(3308);
+        let pool = builder.build();
+        assert_eq!(pool.opts.tcp_port(), 3308);
+        assert_eq!(
+            pool.opts.pool_opts().constraints(),
+            PoolConstraints::new(2, 4).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn should_try_get_idle_conn_without_waiting() -> super::Result<()> {
+        let pool = pool_with_one_connection();
+
+        // nothing is idling yet
+        assert!(pool.try_get_conn().is_none());
+        assert_eq!(ex_field!(pool, exist), 0);
+
+        let conn = pool.get_conn().await?;
+        let id = conn.id();
+        assert!(pool.try_get_conn().is_none());
+
+        // wait for the recycler
+        drop(conn);
+        while ex_field!(pool, available).is_empty() {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let mut conn = pool.try_get_conn().unwrap();
+        assert_eq!(conn.id(), id);
+        assert_eq!(ex_field!(pool, exist), 1);
+        conn.ping().await?;
+
+        // pending `GetConn` has priority
+        let mut get_conn = pool.get_conn();
+        assert!(poll!(&mut get_conn).is_pending());
+        drop(conn);
+        assert!(pool.try_get_conn().is_none());
+        assert_eq!(get_conn.await?.id(), id);
+
+        pool.clone().disconnect().await?;
+        assert!(pool.try_get_conn().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_keep_selected_db_of_pooled_connection() -> super::Result<()> {
+        let pool_opts = PoolOpts::new().with_constraints(PoolConstraints::new(1, 1).unwrap());
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+
+        let mut conn = pool.get_conn().await?;
+        let id = conn.id();
+        conn.select_db("information_schema").await?;
+        drop(conn);
+
+        let mut conn = pool.get_conn().await?;
+        assert_eq!(conn.id(), id);
+        assert_eq!(conn.opts().db_name(), Some("information_schema"));
+        let db: Option<String> = conn.query_first("SELECT DATABASE()").await?;
+        assert_eq!(db.as_deref(), conn.opts().db_name());
+
+        // the pool itself isn't affected
+        let opts = crate::Opts::from(get_opts());
+        assert_eq!(pool.opts.db_name(), opts.db_name());
+
+        drop(conn);
+        pool.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_prefer_connections_using_requested_db() -> super::Result<()> {
+        let pool_opts = PoolOpts::new().with_constraints(PoolConstraints::new(0, 2).unwrap());
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+
+        let conn_a = pool.get_conn_for_db("information_schema").await?;
+        let conn_b = pool.get_conn_for_db("mysql").await?;
+        let (id_a, id_b) = (conn_a.id(), conn_b.id());
+        drop(conn_a);
+        drop(conn_b);
+        while pool.status().idle() < 2 {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        // the most recently returned connection uses `mysql`, but it's skipped
+        for _ in 0..3 {
+            let mut conn = pool.get_conn_for_db("information_schema").await?;
+            assert_eq!(conn.id(), id_a);
+            let db: Option<String> = conn.query_first("SELECT DATABASE()").await?;
+            assert_eq!(db.as_deref(), Some("information_schema"));
+            drop(conn);
+            while pool.status().idle() < 2 {
+                sleep(Duration::from_millis(10)).await;
+            }
+
+            let conn = pool.get_conn_for_db("mysql").await?;
+            assert_eq!(conn.id(), id_b);
+            drop(conn);
+            while pool.status().idle() < 2 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }
+
+        // falls back to any connection
+        let mut conn = pool.get_conn_for_db("performance_schema").await?;
+        assert!(conn.id() == id_a || conn.id() == id_b);
+        let db: Option<String> = conn.query_first("SELECT DATABASE()").await?;
+        assert_eq!(db.as_deref(), Some("performance_schema"));
+        drop(conn);
+
+        let err = pool
+            .get_conn_for_db("mysql_async_nonexistent_db")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::Server(ref e) if e.code == 1049));
+
+        pool.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_select_default_db_back() -> super::Result<()> {
+        let pool_opts = PoolOpts::new().with_constraints(PoolConstraints::new(0, 1).unwrap());
+        let pool = Pool::new(get_opts().db_name(Some("mysql")).pool_opts(pool_opts));
+
+        let conn = pool.get_conn_for_db("information_schema").await?;
+        let id = conn.id();
+        drop(conn);
+        while pool.status().idle() < 1 {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        let mut conn = pool.get_conn().await?;
+        assert_eq!(conn.id(), id);
+        assert_eq!(conn.opts().db_name(), Some("mysql"));
+        let db: Option<String> = conn.query_first("SELECT DATABASE()").await?;
+        assert_eq!(db.as_deref(), Some("mysql"));
+        conn.select_db("information_schema").await?;
+        drop(conn);
+        while pool.status().idle() < 1 {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        // `try_get_conn` only gives out connections, that use the default database
+        assert!(pool.try_get_conn().is_none());
+        let conn = pool.get_conn().await?;
+        assert_eq!(conn.id(), id);
+        drop(conn);
+        while pool.status().idle() < 1 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        let mut conn = pool.try_get_conn().unwrap();
+        assert_eq!(conn.id(), id);
+        let db: Option<String> = conn.query_first("SELECT DATABASE()").await?;
+        assert_eq!(db.as_deref(), Some("mysql"));
+        drop(conn);
+
+        pool.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_return_scoped_connection() -> super::Result<()> {
+        let pool = pool_with_one_connection();
+
+        let id = pool
+            .scoped(|conn| async move { Ok::<_, crate::Error>(conn.id()) }.boxed())
+            .await?;
+
+        // the result of the closure is propagated
+        let result = pool
+            .scoped(|conn| {
+                async move {
+                    assert_eq!(conn.id(), id);
+                    conn.query_drop("SELECT * FROM unknown_table").await
+                }
+                .boxed()
+            })
+            .await;
+        assert!(matches!(result, Err(crate::Error::Server(_))));
+
+        // the connection is discarded on panic
+        let panicked =
+            std::panic::AssertUnwindSafe(pool.scoped(|_| -> BoxFuture<'_, crate::Result<()>> {
+                async move { panic!("in scope") }.boxed()
+            }))
+            .catch_unwind()
+            .await;
+        assert!(panicked.is_err());
+        assert_eq!(pool.status().active(), 0);
+
+        let conn = pool.get_conn().await?;
+        assert_ne!(conn.id(), id);
+        drop(conn);
+
+        pool.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_emit_pool_events() -> super::Result<()> {
+        use std::sync::Mutex;
+
+        use crate::{CloseReason, PoolEvent, PoolEventHandler};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let events = events.clone();
+            move |event: PoolEvent| events.lock().unwrap().push(event)
+        };
+        let pool_opts = PoolOpts::new()
+            .with_constraints(PoolConstraints::new(1, 1).unwrap())
+            .with_event_handler(Some(Arc::new(handler) as Arc<dyn PoolEventHandler>));
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+        let take_events = || std::mem::take(&mut *events.lock().unwrap());
+
+        let conn = pool.get_conn().await?;
+        let first_id = conn.id();
+        drop(conn);
+        while pool.status().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(matches!(
+            &take_events()[..],
+            [PoolEvent::ConnectionCreated { id }] if *id == first_id
+        ));
+
+        // kill the idle connection, so that it's replaced at checkout
+        let mut master = Conn::new(get_opts()).await?;
+        master.kill_connection(first_id).await?;
+        sleep(Duration::from_millis(500)).await;
+
+        let conn = pool.get_conn().await?;
+        let second_id = conn.id();
+        assert!(matches!(
+            &take_events()[..],
+            [
+                PoolEvent::ConnectionClosed { id, reason: CloseReason::Broken },
+                PoolEvent::ConnectionCreated { id: new_id },
+            ] if *id == first_id && *new_id == second_id
+        ));
+
+        // idle connections are closed once the pool is closing
+        drop(conn);
+        pool.clone().disconnect().await?;
+        assert!(matches!(
+            &take_events()[..],
+            [PoolEvent::ConnectionClosed { id, reason: CloseReason::PoolClosed }]
+                if *id == second_id
+        ));
+
+        let result = pool.get_conn().await;
+        assert!(result.is_err());
+        assert!(matches!(
+            &take_events()[..],
+            [PoolEvent::CheckoutFailed { .. }]
+        ));
+
+        master.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_status_and_readiness() -> super::Result<()> {
+        let pool = pool_with_one_connection();
+
+        assert!(pool.is_ready());
+        let status = pool.status();
+        assert_eq!(
+            (
+                status.idle(),
+                status.active(),
+                status.max(),
+                status.waiters()
+            ),
+            (0, 0, 1, 0)
+        );
+
+        // saturate the pool
+        let conn = pool.get_conn().await?;
+        assert!(!pool.is_ready());
+        let status = pool.status();
+        assert_eq!(
+            (status.idle(), status.active(), status.waiters()),
+            (0, 1, 0)
+        );
+
+        let mut get_conn = pool.get_conn();
+        assert!(poll!(&mut get_conn).is_pending());
+        assert_eq!(pool.status().waiters(), 1);
+
+        // the connection goes to the pending `GetConn`
+        drop(conn);
+        let conn = get_conn.await?;
+        assert!(!pool.is_ready());
+        assert_eq!(pool.status().waiters(), 0);
+
+        // wait for the recycler
+        drop(conn);
+        while pool.status().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(pool.is_ready());
+        let status = pool.status();
+        assert_eq!(
+            (status.idle(), status.active(), status.waiters()),
+            (1, 0, 0)
+        );
+
+        pool.clone().disconnect().await?;
+        assert!(!pool.is_ready());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_split_reads_and_writes() -> super::Result<()> {
+        use crate::{DriverError, Error, OptsBuilder, ReaderSelection, ReplicatedPool};
+
+        // nothing listens on port 1
+        let broken = || Pool::new(OptsBuilder::from_opts(get_opts()).tcp_port(1));
+
+        let writer = Pool::new(get_opts());
+        let reader = Pool::new(get_opts());
+        let pool = ReplicatedPool::new(writer.clone(), vec![broken(), reader.clone()]);
+        assert_eq!(pool.reader_selection(), ReaderSelection::RoundRobin);
+
+        // the broken reader is skipped
+        for _ in 0..2 {
+            let conn = pool.get_reader_conn().await?;
+            drop(conn);
+        }
+        assert!(reader.metrics().created() > 0);
+        assert_eq!(writer.metrics().created(), 0);
+
+        drop(pool.get_writer_conn().await?);
+        assert_eq!(writer.metrics().created(), 1);
+        pool.disconnect().await?;
+
+        // no healthy readers
+        let writer = Pool::new(get_opts());
+        let pool = ReplicatedPool::new(writer.clone(), vec![broken()])
+            .with_reader_selection(ReaderSelection::Random);
+        drop(pool.get_reader_conn().await?);
+        assert_eq!(writer.metrics().created(), 1);
+
+        let pool = pool.with_writer_fallback(false);
+        assert!(matches!(pool.get_reader_conn().await, Err(Error::Io(_))));
+        pool.disconnect().await?;
+
+        let pool = ReplicatedPool::new(Pool::new(get_opts()), vec![]).with_writer_fallback(false);
+        assert!(matches!(
+            pool.get_reader_conn().await,
+            Err(Error::Driver(DriverError::NoReaders))
+        ));
+        pool.disconnect().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_validate_idling_connections() -> super::Result<()> {
+        let validated = |query: &str| {
+            let pool_opts = PoolOpts::new()
+                .with_constraints(PoolConstraints::new(1, 1).unwrap())
+                .with_reset_connection(false)
+                .with_validation_query(Some(query));
+            Pool::new(get_opts().pool_opts(pool_opts))
+        };
+
+        // the validation query is executed on checkout of an idling connection
+        let pool = validated("SET @validations = COALESCE(@validations, 0) + 1");
+        for _ in 0..3 {
+            drop(pool.get_conn().await?);
+            while pool.status().idle() == 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        }
+        let mut conn = pool.get_conn().await?;
+        let validations: Option<u32> = conn.query_first("SELECT @validations").await?;
+        // the first connection is new
+        assert_eq!(validations, Some(3));
+        drop(conn);
+        while pool.status().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        // `try_get_conn` gives out the connection as is
+        let mut conn = pool.try_get_conn().unwrap();
+        let validations: Option<u32> = conn.query_first("SELECT @validations").await?;
+        assert_eq!(validations, Some(3));
+        drop(conn);
+        pool.disconnect().await?;
+
+        // a connection that fails to validate is replaced
+        let pool = validated("SELECT * FROM definitely_missing_table");
+        let id = pool.get_conn().await?.id();
+        while pool.status().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        let conn = pool.get_conn().await?;
+        assert_ne!(conn.id(), id);
+        assert_eq!(pool.metrics().dropped(), 1);
+        drop(conn);
+        pool.disconnect().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_warm_up_stmt_cache_of_pooled_connections() -> super::Result<()> {
+        const STATEMENTS: [&str; 2] = ["SELECT ?", "SELECT ?, ?"];
+
+        let pool_opts = PoolOpts::new()
+            .with_constraints(PoolConstraints::new(1, 1).unwrap())
+            .with_prepared_statement_warmup(STATEMENTS.to_vec());
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+
+        // a new connection
+        let conn = pool.get_conn().await?;
+        assert_eq!(conn.stmt_cache_ref().len(), 2);
+        let id = conn.id();
+        drop(conn);
+
+        // the same connection after reset
+        while pool.status().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        let mut conn = pool.get_conn().await?;
+        assert_eq!(conn.id(), id);
+        assert_eq!(conn.stmt_cache_ref().len(), 2);
+        let row: Option<(u8, u8)> = conn.exec_first(STATEMENTS[1], (1, 2)).await?;
+        assert_eq!(row, Some((1, 2)));
+        assert_eq!(conn.stmt_cache_ref().len(), 2);
+        drop(conn);
+
+        pool.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_metrics() -> super::Result<()> {
+        let pool = pool_with_one_connection();
+        let metrics = pool.metrics();
+        assert_eq!(
+            (metrics.created(), metrics.dropped(), metrics.resets()),
+            (0, 0, 0)
+        );
+        assert_eq!(metrics.get_conn_p50(), None);
+
+        let conn = pool.get_conn().await?;
+        let metrics = pool.metrics();
+        assert_eq!(
+            (metrics.created(), metrics.active(), metrics.idle()),
+            (1, 1, 0)
+        );
+
+        // wait for the recycler
+        drop(conn);
+        while pool.metrics().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        // reused connection isn't counted as created
+        drop(pool.get_conn().await?);
+        while pool.metrics().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        let metrics = pool.metrics();
+        assert_eq!(
+            (metrics.created(), metrics.active(), metrics.idle()),
+            (1, 0, 1)
+        );
+        assert_eq!(metrics.resets(), 2);
+        if cfg!(feature = "pool-latency") {
+            assert!(metrics.get_conn_p50() <= metrics.get_conn_p99());
+            assert!(metrics.get_conn_p99().is_some());
+        } else {
+            assert_eq!(metrics.get_conn_p99(), None);
+        }
+
+        pool.clone().disconnect().await?;
+        assert_eq!(pool.metrics().dropped(), 1);
+        Ok(())
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn should_export_metrics() -> super::Result<()> {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+        use std::collections::HashMap;
+
+        static SNAPSHOTTER: OnceLock<Snapshotter> = OnceLock::new();
+        let snapshotter = SNAPSHOTTER.get_or_init(|| {
+            let recorder = DebuggingRecorder::new();
+            let snapshotter = recorder.snapshotter();
+            recorder.install().unwrap();
+            snapshotter
+        });
+
+        // returns metrics of the pool named `should_export_metrics`
+        let snapshot = || {
+            snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .filter(|(key, ..)| {
+                    key.key()
+                        .labels()
+                        .any(|l| l.key() == "pool" && l.value() == "should_export_metrics")
+                })
+                .map(|(key, _, _, value)| (key.key().name().to_owned(), value))
+                .collect::<HashMap<_, _>>()
+        };
+        let gauge = |x: f64| DebugValue::Gauge(x.into());
+
+        let pool = Pool::new(
+            get_opts().pool_opts(
+                PoolOpts::default()
+                    .with_constraints(PoolConstraints::new(0, 2).unwrap())
+                    .with_name(Some("should_export_metrics")),
+            ),
+        );
+        assert_eq!(pool.name(), Some("should_export_metrics"));
+
+        let conn1 = pool.get_conn().await?;
+        let conn2 = pool.get_conn().await?;
+        let metrics = snapshot();
+        assert_eq!(metrics["mysql_pool_active"], gauge(2.0));
+        assert_eq!(metrics["mysql_pool_idle"], gauge(0.0));
+        assert_eq!(
+            metrics["mysql_pool_connections_created"],
+            DebugValue::Counter(2)
+        );
+        match &metrics["mysql_get_conn_wait_seconds"] {
+            DebugValue::Histogram(values) => assert_eq!(values.len(), 2),
+            other => panic!("unexpected value {:?}", other),
+        }
+
+        // wait for the recycler
+        drop(conn1);
+        while pool.metrics().idle() == 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+        let metrics = snapshot();
+        assert_eq!(metrics["mysql_pool_active"], gauge(1.0));
+        assert_eq!(metrics["mysql_pool_idle"], gauge(1.0));
+        assert_eq!(
+            metrics["mysql_pool_connections_reset"],
+            DebugValue::Counter(1)
+        );
+
+        drop(conn2);
+        pool.disconnect().await?;
+        let metrics = snapshot();
+        assert_eq!(
+            metrics["mysql_pool_connections_dropped"],
+            DebugValue::Counter(2)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_apply_session_profiles() -> super::Result<()> {
+        use crate::{DriverError, Error, IsolationLevel, SessionProfile};
+
+        const QUERY: &str = "SELECT @@SESSION.sql_mode, @@SESSION.wait_timeout";
+
+        let pool_opts = PoolOpts::new()
+            .with_constraints(PoolConstraints::new(1, 1).unwrap())
+            .with_reset_connection(false);
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+        pool.register_session_profile(
+            "oltp",
+            SessionProfile::new()
+                .with_isolation_level(IsolationLevel::ReadCommitted)
+                .with_variable("wait_timeout", 100),
+        );
+        pool.register_session_profile(
+            "reporting",
+            SessionProfile::new()
+                .with_variable("sql_mode", "ANSI_QUOTES")
+                .with_variable("wait_timeout", 3600),
+        );
+
+        let mut conn = pool.get_conn().await?;
+        let id = conn.id();
+        let (default_mode, default_timeout): (String, u32) =
+            conn.query_first(QUERY).await?.unwrap();
+        drop(conn);
+
+        let mut conn = pool.get_conn_with_profile("oltp").await?;
+        assert_eq!(conn.id(), id);
+        let settings: Option<(String, u32)> = conn.query_first(QUERY).await?;
+        assert_eq!(settings, Some((default_mode.clone(), 100)));
+        // `tx_isolation` or `transaction_isolation`, depending on the server version
+        let isolation: Option<(String, String)> = conn
+            .query_first("SHOW SESSION VARIABLES LIKE '%_isolation'")
+            .await?;
+        assert_eq!(isolation.unwrap().1, "READ-COMMITTED");
+        drop(conn);
+
+        let mut conn = pool.get_conn_with_profile("reporting").await?;
+        assert_eq!(conn.id(), id);
+        let settings: Option<(String, u32)> = conn.query_first(QUERY).await?;
+        assert_eq!(settings, Some(("ANSI_QUOTES".into(), 3600)));
+        drop(conn);
+
+        // settings are reverted upon returning to the pool
+        let mut conn = pool.get_conn().await?;
+        assert_eq!(conn.id(), id);
+        let settings: Option<(String, u32)> = conn.query_first(QUERY).await?;
+        assert_eq!(settings, Some((default_mode, default_timeout)));
+        drop(conn);
+
+        let err = pool.get_conn_with_profile("unknown").await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Driver(DriverError::UnknownSessionProfile { ref name }) if name == "unknown"
+        ));
+
+        pool.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_restore_autocommit_on_return() -> super::Result<()> {
+        let pool_opts = PoolOpts::new()
+            .with_constraints(PoolConstraints::new(1, 1).unwrap())
+            .with_reset_connection(false);
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+
+        let mut conn = pool.get_conn().await?;
+        let id = conn.id();
+        assert!(conn.autocommit());
+        conn.query_drop("CREATE TEMPORARY TABLE tmp (id INT)")
+            .await?;
+
+        conn.set_autocommit(false).await?;
+        assert!(!conn.autocommit());
+        conn.query_drop("INSERT INTO tmp VALUES (1)").await?;
+        drop(conn);
+
+        let mut conn = pool.get_conn().await?;
+        assert_eq!(conn.id(), id);
+        assert!(conn.autocommit());
+        let count: Option<u64> = conn.query_first("SELECT COUNT(*) FROM tmp").await?;
+        assert_eq!(count, Some(0));
+
+        // committed changes survive
+        conn.set_autocommit(false).await?;
+        conn.query_drop("INSERT INTO tmp VALUES (1)").await?;
+        conn.query_drop("COMMIT").await?;
+        drop(conn);
+
+        let mut conn = pool.get_conn().await?;
+        assert!(conn.autocommit());
+        let count: Option<u64> = conn.query_first("SELECT COUNT(*) FROM tmp").await?;
+        assert_eq!(count, Some(1));
+        drop(conn);
+
+        pool.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_retry_on_con_count_error() -> super::Result<()> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+        use crate::{DriverError, Error, OptsBuilder, CON_COUNT_ERROR_RETRIES};
+
+        // server that refuses every connection with `ER_CON_COUNT_ERROR`
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        tokio::spawn({
+            let accepted = accepted.clone();
+            async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    accepted.fetch_add(1, Ordering::SeqCst);
+                    let mut payload = vec![0xFF, 0x10, 0x04];
+                    payload.extend_from_slice(b"Too many connections");
+                    let mut packet = (payload.len() as u32).to_le_bytes().to_vec();
+                    packet[3] = 0;
+                    packet.extend(payload);
+                    let _ = stream.write_all(&packet).await;
+                }
+            }
+        });
+
+        let opts = OptsBuilder::default()
+            .ip_or_hostname("127.0.0.1")
+            .tcp_port(port)
+            .prefer_socket(false);
+
+        let pool = Pool::new(opts.clone());
+        let err = pool.get_conn().await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                Error::Driver(DriverError::ServerConnectionLimit { ref error }) if error.code == 1040
+            ),
+            "{:?}",
+            err
+        );
+        assert!(err.is_con_count_error());
+        assert_eq!(accepted.swap(0, Ordering::SeqCst), 1);
+        assert_eq!(ex_field!(pool, exist), 0);
+        pool.disconnect().await?;
+
+        let pool =
+            Pool::new(opts.pool_opts(PoolOpts::default().with_retry_on_con_count_error(true)));
+        let err = pool.get_conn().await.unwrap_err();
+        assert!(err.is_con_count_error());
+        assert_eq!(
+            accepted.load(Ordering::SeqCst),
+            1 + CON_COUNT_ERROR_RETRIES as usize
+        );
+        assert_eq!(ex_field!(pool, exist), 0);
+        pool.disconnect().await?;
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_opt_out_of_connection_reset() -> super::Result<()> {
         let pool_opts = PoolOpts::new().with_constraints(PoolConstraints::new(1, 1).unwrap());
@@ -475,6 +1674,53 @@ mod test {
         pool.disconnect().await
     }
 
+    #[tokio::test]
+    async fn should_share_stmt_metadata() -> super::Result<()> {
+        const QUERY: &str = "SELECT ? AS foo, ? AS bar";
+
+        let pool_opts = PoolOpts::new()
+            .with_constraints(PoolConstraints::new(2, 2).unwrap())
+            .with_shared_stmt_metadata(true);
+        let pool = Pool::new(get_opts().pool_opts(pool_opts.clone()));
+
+        let mut conn1 = pool.get_conn().await?;
+        let mut conn2 = pool.get_conn().await?;
+        assert_ne!(conn1.id(), conn2.id());
+
+        let stmt1 = conn1.prep(QUERY).await?;
+        let stmt2 = conn2.prep(QUERY).await?;
+        assert_ne!(stmt1.connection_id(), stmt2.connection_id());
+        assert_eq!(stmt1.columns(), stmt2.columns());
+        assert_eq!(stmt1.params(), stmt2.params());
+        assert_eq!(pool.stmt_metadata().unwrap().len(), 1);
+
+        // metadata is shared
+        let (metadata1, metadata2) = (stmt1.inner.metadata(), stmt2.inner.metadata());
+        assert!(Arc::ptr_eq(
+            metadata1.columns.as_ref().unwrap(),
+            metadata2.columns.as_ref().unwrap()
+        ));
+        assert!(Arc::ptr_eq(
+            metadata1.params.as_ref().unwrap(),
+            metadata2.params.as_ref().unwrap()
+        ));
+
+        // statements are still usable
+        let row: Option<(u8, String)> = conn2.exec_first(&stmt2, (1, "two")).await?;
+        assert_eq!(row, Some((1, "two".into())));
+        drop(conn1);
+        drop(conn2);
+        pool.disconnect().await?;
+
+        // disabled by default
+        let pool = Pool::new(get_opts());
+        let mut conn = pool.get_conn().await?;
+        conn.prep(QUERY).await?;
+        assert!(pool.stmt_metadata().is_none());
+        drop(conn);
+        pool.disconnect().await
+    }
+
     #[test]
     fn should_not_hang() -> super::Result<()> {
         pub struct Database {
@@ -642,6 +1888,43 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_move_owned_transaction_into_a_task() -> super::Result<()> {
+        let pool = pool_with_one_connection();
+
+        "CREATE TABLE IF NOT EXISTS mysql.tmp_owned_tx(id int)"
+            .ignore(&pool)
+            .await?;
+        "DELETE FROM mysql.tmp_owned_tx".ignore(&pool).await?;
+
+        let conn = pool.get_conn().await?;
+        let mut tx = conn.start_transaction_owned(TxOpts::default()).await?;
+        tokio::spawn(async move {
+            tx.exec_batch(
+                "INSERT INTO mysql.tmp_owned_tx (id) VALUES (?)",
+                vec![(1_u8,), (2_u8,)],
+            )
+            .await?;
+            tx.commit().await
+        })
+        .await
+        .unwrap()?;
+
+        // the connection must be back in the pool
+        let row_opt = pool
+            .get_conn()
+            .await?
+            .query_first("SELECT COUNT(*) FROM mysql.tmp_owned_tx")
+            .await?;
+        assert_eq!(row_opt, Some((2u8,)));
+        pool.get_conn()
+            .await?
+            .query_drop("DROP TABLE mysql.tmp_owned_tx")
+            .await?;
+        pool.disconnect().await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_check_inactive_connection_ttl() -> super::Result<()> {
         const POOL_MIN: usize = 5;
@@ -805,6 +2088,106 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_disconnect_pool_more_than_once() -> super::Result<()> {
+        let pool = Pool::new(get_opts());
+        pool.get_conn().await?;
+
+        pool.clone().disconnect().await?;
+        pool.clone().disconnect().await?;
+        assert!(pool.get_conn().await.is_err());
+
+        // concurrent disconnects, that wait for an active connection
+        let pool = Pool::new(get_opts());
+        let conn = pool.get_conn().await?;
+        let drop_conn = async move {
+            sleep(Duration::from_millis(100)).await;
+            drop(conn);
+            Ok::<_, crate::Error>(())
+        };
+        try_join!(pool.clone().disconnect(), pool.disconnect(), drop_conn)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_stop_background_tasks_of_dropped_pool() -> super::Result<()> {
+        // ttl check interval is long enough to outlive the test
+        let pool_opts = PoolOpts::default()
+            .with_inactive_connection_ttl(Duration::from_secs(60))
+            .with_ttl_check_interval(Duration::from_secs(60));
+        let pool = Pool::new(get_opts().pool_opts(pool_opts));
+        pool.get_conn().await?;
+        let inner = Arc::downgrade(&pool.inner);
+
+        // never disconnected
+        drop(pool);
+
+        // the recycler and the ttl check interval hold the pool until they exit
+        let released = async {
+            while inner.upgrade().is_some() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        };
+        timeout(Duration::from_secs(5), released)
+            .await
+            .expect("background tasks are leaked");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_close_idle_connections_of_dropped_pools() -> super::Result<()> {
+        let pool_opts = PoolOpts::default()
+            .with_constraints(PoolConstraints::new(2, 2).unwrap())
+            .with_inactive_connection_ttl(Duration::from_secs(60))
+            .with_ttl_check_interval(Duration::from_secs(60));
+
+        let mut ids = Vec::new();
+        let mut inners = Vec::new();
+        for _ in 0..10 {
+            let pool = Pool::new(get_opts().pool_opts(pool_opts.clone()));
+            let (a, b) = try_join!(pool.get_conn(), pool.get_conn())?;
+            ids.extend([a.id(), b.id()]);
+            inners.push(Arc::downgrade(&pool.inner));
+            // connections are idling in the pool
+            drop((a, b));
+            drop(pool);
+        }
+
+        // every pool is released by its background tasks
+        let released = async {
+            while inners.iter().any(|inner| inner.upgrade().is_some()) {
+                sleep(Duration::from_millis(10)).await;
+            }
+        };
+        timeout(Duration::from_secs(10), released)
+            .await
+            .expect("background tasks are leaked");
+
+        // and every connection is closed
+        let mut conn = Conn::new(get_opts()).await?;
+        let query = format!(
+            "SELECT COUNT(*) FROM information_schema.processlist WHERE id IN ({})",
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let closed = async {
+            while conn.query_first::<usize, _>(&query).await? != Some(0) {
+                sleep(Duration::from_millis(10)).await;
+            }
+            Ok::<_, crate::Error>(())
+        };
+        timeout(Duration::from_secs(10), closed)
+            .await
+            .expect("connections are leaked")?;
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn droptest() -> super::Result<()> {
         let pool = Pool::new(get_opts());
@@ -1178,6 +2561,43 @@ mod test {
             runtime.block_on(pool.disconnect()).unwrap();
         }
 
+        #[bench]
+        fn prepare_on_many_connections(bencher: &mut test::Bencher) {
+            prepare_on_many_connections_impl(bencher, false);
+        }
+
+        #[bench]
+        fn prepare_on_many_connections_with_shared_stmt_metadata(bencher: &mut test::Bencher) {
+            prepare_on_many_connections_impl(bencher, true);
+        }
+
+        fn prepare_on_many_connections_impl(bencher: &mut test::Bencher, shared: bool) {
+            const QUERY: &str = "SELECT ?, ?, ?, ?, ?, ?, ?, ?";
+            const NUM_CONNS: usize = 10;
+
+            let mut runtime = Runtime::new().unwrap();
+            let pool_opts = PoolOpts::default()
+                .with_constraints(PoolConstraints::new(NUM_CONNS, NUM_CONNS).unwrap())
+                .with_shared_stmt_metadata(shared);
+            let pool = Pool::new(get_opts().pool_opts(pool_opts));
+
+            bencher.iter(|| {
+                let fut = async {
+                    let mut conns = Vec::with_capacity(NUM_CONNS);
+                    for _ in 0..NUM_CONNS {
+                        let mut conn = pool.get_conn().await?;
+                        let stmt = conn.prep(QUERY).await?;
+                        conn.close(stmt).await?;
+                        conns.push(conn);
+                    }
+                    crate::Result::Ok(())
+                };
+                runtime.block_on(fut).unwrap();
+            });
+
+            runtime.block_on(pool.disconnect()).unwrap();
+        }
+
         #[bench]
         fn new_conn_on_pool_soft_boundary(bencher: &mut test::Bencher) {
             let mut runtime = Runtime::new().unwrap();
@@ -17,7 +17,7 @@ use std::{
     task::{Context, Poll},
 };
 
-use super::{IdlingConn, Inner};
+use super::{CloseReason, IdlingConn, Inner, PoolEvent};
 use crate::{queryable::transaction::TxStatus, BoxFuture, Conn, PoolOpts};
 use tokio::sync::mpsc::UnboundedReceiver;
 
@@ -54,6 +54,37 @@ impl Recycler {
             eof: false,
         }
     }
+
+    /// Notifies the event handler about the connection, that is going to be closed.
+    fn emit_closed(&self, conn: &Conn, reason: CloseReason) {
+        self.pool_opts.emit_event(|| PoolEvent::ConnectionClosed {
+            id: conn.id(),
+            reason,
+        });
+    }
+
+    /// Notifies the event handler if the given cleanup of a connection fails,
+    /// since the connection is discarded in this case.
+    fn emit_closed_on_error(
+        &self,
+        id: u32,
+        fut: BoxFuture<'static, Conn>,
+    ) -> BoxFuture<'static, Conn> {
+        match self.pool_opts.event_handler() {
+            Some(handler) => async move {
+                let result = fut.await;
+                if result.is_err() {
+                    handler.handle(PoolEvent::ConnectionClosed {
+                        id,
+                        reason: CloseReason::Broken,
+                    });
+                }
+                result
+            }
+            .boxed(),
+            None => fut,
+        }
+    }
 }
 
 impl Future for Recycler {
@@ -67,9 +98,16 @@ impl Future for Recycler {
                 let mut exchange = $self.inner.exchange.lock().unwrap();
                 if $pool_is_closed || exchange.available.len() >= $self.pool_opts.active_bound() {
                     drop(exchange);
+                    let reason = if $pool_is_closed {
+                        CloseReason::PoolClosed
+                    } else {
+                        CloseReason::Excess
+                    };
+                    $self.emit_closed(&$conn, reason);
                     $self.discard.push($conn.close_conn().boxed());
                 } else {
                     exchange.available.push_back($conn.into());
+                    $self.inner.metrics.pool_state(&exchange);
                     if let Some(w) = exchange.waiting.pop() {
                         w.wake();
                     }
@@ -81,13 +119,27 @@ impl Future for Recycler {
             ($self:ident, $conn:ident) => {
                 if $conn.inner.stream.is_none() || $conn.inner.disconnected {
                     // drop unestablished connection
+                    $self.emit_closed(&$conn, CloseReason::Broken);
                     $self.discard.push(futures_util::future::ok(()).boxed());
-                } else if $conn.inner.tx_status != TxStatus::None || $conn.has_pending_result() {
-                    $self.cleaning.push($conn.cleanup_for_pool().boxed());
+                } else if $conn.inner.tx_status != TxStatus::None
+                    || $conn.has_pending_result()
+                    || $conn.autocommit_is_dirty()
+                {
+                    let id = $conn.id();
+                    let fut = $self.emit_closed_on_error(id, $conn.cleanup_for_pool().boxed());
+                    $self.cleaning.push(fut);
                 } else if $conn.expired() || close {
+                    let reason = if close {
+                        CloseReason::PoolClosed
+                    } else {
+                        CloseReason::Expired
+                    };
+                    $self.emit_closed(&$conn, reason);
                     $self.discard.push($conn.close_conn().boxed());
                 } else if $conn.inner.reset_upon_returning_to_a_pool {
-                    $self.reset.push($conn.reset_for_pool().boxed());
+                    let id = $conn.id();
+                    let fut = $self.emit_closed_on_error(id, $conn.reset_for_pool().boxed());
+                    $self.reset.push(fut);
                 } else {
                     conn_return!($self, $conn, false);
                 }
@@ -109,7 +161,7 @@ impl Future for Recycler {
                 }
                 Poll::Ready(None) => {
                     // no more connections are coming -- time to exit!
-                    self.inner.close.store(true, Ordering::Release);
+                    self.inner.start_closing();
                     self.eof = true;
                     close = true;
                 }
@@ -152,7 +204,10 @@ impl Future for Recycler {
         loop {
             match Pin::new(&mut self.reset).poll_next(cx) {
                 Poll::Pending | Poll::Ready(None) => break,
-                Poll::Ready(Some(Ok(conn))) => conn_return!(self, conn, close),
+                Poll::Ready(Some(Ok(conn))) => {
+                    self.inner.metrics.conn_reset();
+                    conn_return!(self, conn, close)
+                }
                 Poll::Ready(Some(Err(e))) => {
                     // an error during reset.
                     // replace with a new connection
@@ -185,8 +240,10 @@ impl Future for Recycler {
 
         if self.discarded != 0 {
             // we need to open up slots for new connctions to be established!
+            self.inner.metrics.conns_dropped(self.discarded);
             let mut exchange = self.inner.exchange.lock().unwrap();
             exchange.exist -= self.discarded;
+            self.inner.metrics.pool_state(&exchange);
             for _ in 0..self.discarded {
                 if let Some(w) = exchange.waiting.pop() {
                     w.wake();
@@ -247,7 +304,7 @@ impl Drop for Recycler {
             // user did not wait for outstanding connections to finish!
             // this is not good -- we won't be able to shut down our connections cleanly
             // all we can do is try to ensure a clean shutdown
-            self.inner.close.store(true, Ordering::SeqCst);
+            self.inner.start_closing();
         }
     }
 }
@@ -0,0 +1,157 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use crate::{Conn, DriverError, Pool, Result};
+
+/// Defines how [`ReplicatedPool`] selects a reader.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReaderSelection {
+    /// Readers are used one after another (the default).
+    #[default]
+    RoundRobin,
+    /// A random reader is used.
+    Random,
+}
+
+/// Read/write splitting wrapper over a writer [`Pool`] (primary) and a set of reader pools
+/// (replicas).
+///
+/// Just like [`Pool`] this is a shared reference, so it's cheap to clone.
+///
+/// Reader failover: [`ReplicatedPool::get_reader_conn`] starts with a reader chosen according
+/// to the [`ReaderSelection`] and skips readers that fail to give out a connection, trying
+/// the next one. If every reader fails (or there are no readers), then it falls back to
+/// the writer, unless this is disabled via [`ReplicatedPool::with_writer_fallback`].
+///
+/// Note that there is no replication lag awareness – the application is responsible for
+/// reading its own writes from the writer, if necessary.
+///
+/// ```rust
+/// # use mysql_async::{test_misc::get_opts, Pool, ReaderSelection, ReplicatedPool};
+/// # use mysql_async::prelude::*;
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// let pool = ReplicatedPool::new(Pool::new(get_opts()), vec![Pool::new(get_opts())])
+///     .with_reader_selection(ReaderSelection::Random);
+///
+/// let mut writer = pool.get_writer_conn().await?;
+/// writer.query_drop("DO 1").await?;
+///
+/// let mut reader = pool.get_reader_conn().await?;
+/// let one: Option<u8> = reader.query_first("SELECT 1").await?;
+/// assert_eq!(one, Some(1));
+///
+/// # drop(writer);
+/// # drop(reader);
+/// pool.disconnect().await?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplicatedPool {
+    writer: Pool,
+    readers: Arc<[Pool]>,
+    selection: ReaderSelection,
+    writer_fallback: bool,
+    next_reader: Arc<AtomicUsize>,
+}
+
+impl ReplicatedPool {
+    /// Creates a new replicated pool with round-robin reader selection and writer fallback.
+    pub fn new(writer: Pool, readers: Vec<Pool>) -> Self {
+        Self {
+            writer,
+            readers: readers.into(),
+            selection: ReaderSelection::default(),
+            writer_fallback: true,
+            next_reader: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Defines the reader selection policy (defaults to [`ReaderSelection::RoundRobin`]).
+    pub fn with_reader_selection(mut self, selection: ReaderSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Defines whether [`ReplicatedPool::get_reader_conn`] falls back to the writer if
+    /// no reader is able to give out a connection (defaults to `true`).
+    pub fn with_writer_fallback(mut self, writer_fallback: bool) -> Self {
+        self.writer_fallback = writer_fallback;
+        self
+    }
+
+    /// Returns the writer pool.
+    pub fn writer(&self) -> &Pool {
+        &self.writer
+    }
+
+    /// Returns reader pools.
+    pub fn readers(&self) -> &[Pool] {
+        &self.readers
+    }
+
+    /// Returns the reader selection policy.
+    pub fn reader_selection(&self) -> ReaderSelection {
+        self.selection
+    }
+
+    /// Returns `true` if readers fall back to the writer.
+    pub fn writer_fallback(&self) -> bool {
+        self.writer_fallback
+    }
+
+    /// Returns a connection to the writer.
+    pub async fn get_writer_conn(&self) -> Result<Conn> {
+        self.writer.get_conn().await
+    }
+
+    /// Returns a connection to a reader (see [`ReplicatedPool`] for the failover behavior).
+    ///
+    /// If every reader fails and the writer fallback is disabled, then the error of the last
+    /// reader is returned ([`DriverError::NoReaders`] if there are no readers).
+    pub async fn get_reader_conn(&self) -> Result<Conn> {
+        let num_readers = self.readers.len();
+        let first = match self.selection {
+            _ if num_readers == 0 => 0,
+            ReaderSelection::RoundRobin => self.next_reader.fetch_add(1, Ordering::Relaxed),
+            ReaderSelection::Random => rand::random::<usize>(),
+        };
+
+        let mut last_error = None;
+        for i in 0..num_readers {
+            let reader = &self.readers[first.wrapping_add(i) % num_readers];
+            match reader.get_conn().await {
+                Ok(conn) => return Ok(conn),
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        if self.writer_fallback {
+            self.writer.get_conn().await
+        } else {
+            Err(last_error.unwrap_or_else(|| DriverError::NoReaders.into()))
+        }
+    }
+
+    /// Disconnects the writer and all the readers (see [`Pool::disconnect`]).
+    ///
+    /// Every pool is disconnected, even if some of them fail. The first error is returned.
+    pub async fn disconnect(self) -> Result<()> {
+        let mut result = self.writer.disconnect().await;
+        for reader in self.readers.iter() {
+            let reader_result = reader.clone().disconnect().await;
+            result = result.and(reader_result);
+        }
+        result
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use crate::{
+    prelude::Queryable, queryable::transaction::IsolationLevel, Conn, Params, Result, Value,
+};
+
+/// A named set of session settings, that could be applied to a pooled connection on checkout
+/// (see [`crate::Pool::register_session_profile`] and [`crate::Pool::get_conn_with_profile`]).
+///
+/// ```
+/// # use mysql_async::*;
+/// let reporting = SessionProfile::new()
+///     .with_isolation_level(IsolationLevel::ReadCommitted)
+///     .with_variable("max_execution_time", 60_000)
+///     .with_variable("sql_mode", "ANSI");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SessionProfile {
+    isolation_level: Option<IsolationLevel>,
+    variables: Vec<(String, Value)>,
+}
+
+impl SessionProfile {
+    /// Creates an empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines the session transaction isolation level
+    /// (`SET SESSION TRANSACTION ISOLATION LEVEL ..`).
+    pub fn with_isolation_level(mut self, isolation_level: IsolationLevel) -> Self {
+        self.isolation_level = Some(isolation_level);
+        self
+    }
+
+    /// Defines a value of a session system variable (`SET SESSION name = value`),
+    /// e.g. `sql_mode`, `wait_timeout` or `max_execution_time`.
+    pub fn with_variable<T: Into<String>, V: Into<Value>>(mut self, name: T, value: V) -> Self {
+        self.variables.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns the session transaction isolation level of this profile.
+    pub fn isolation_level(&self) -> Option<IsolationLevel> {
+        self.isolation_level
+    }
+
+    /// Returns session system variables of this profile.
+    pub fn variables(&self) -> &[(String, Value)] {
+        &self.variables
+    }
+
+    /// Applies this profile to the given connection.
+    pub(crate) async fn apply(&self, conn: &mut Conn) -> Result<()> {
+        if let Some(isolation_level) = self.isolation_level {
+            conn.query_drop(format!(
+                "SET SESSION TRANSACTION ISOLATION LEVEL {}",
+                isolation_level
+            ))
+            .await?;
+        }
+
+        if !self.variables.is_empty() {
+            let assignments = self
+                .variables
+                .iter()
+                .map(|(name, _)| format!("SESSION {} = ?", crate::quote_identifier(name)))
+                .collect::<Vec<_>>();
+            let params = self
+                .variables
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect::<Vec<_>>();
+            conn.exec_drop(
+                format!("SET {}", assignments.join(", ")),
+                Params::Positional(params),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
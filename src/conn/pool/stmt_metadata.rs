@@ -0,0 +1,81 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use lru::LruCache;
+use mysql_common::packets::Column;
+
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+/// Params and columns metadata of a prepared statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct StmtMetadata {
+    pub(crate) params: Option<Arc<[Column]>>,
+    pub(crate) columns: Option<Arc<[Column]>>,
+}
+
+impl StmtMetadata {
+    fn num_params(&self) -> usize {
+        self.params.as_ref().map(|x| x.len()).unwrap_or_default()
+    }
+
+    fn num_columns(&self) -> usize {
+        self.columns.as_ref().map(|x| x.len()).unwrap_or_default()
+    }
+}
+
+/// Current database and statement text.
+type Key = (Option<String>, Arc<[u8]>);
+
+/// Statement metadata cache shared between connections of a pool
+/// (see [`crate::PoolOpts::with_shared_stmt_metadata`]).
+///
+/// Metadata is keyed by the current database and the statement text.
+#[derive(Debug)]
+pub(crate) struct StmtMetadataCache {
+    cache: Mutex<LruCache<Key, StmtMetadata>>,
+}
+
+impl StmtMetadataCache {
+    pub(crate) fn new(cap: NonZeroUsize) -> Self {
+        Self {
+            cache: Mutex::new(LruCache::new(cap)),
+        }
+    }
+
+    /// Returns cached metadata, if it matches the given number of params and columns.
+    pub(crate) fn get(
+        &self,
+        db_name: Option<&str>,
+        query: &Arc<[u8]>,
+        num_params: u16,
+        num_columns: u16,
+    ) -> Option<StmtMetadata> {
+        let mut cache = self.cache.lock().unwrap();
+        let metadata = cache.get(&(db_name.map(Into::into), query.clone()))?;
+        if metadata.num_params() == usize::from(num_params)
+            && metadata.num_columns() == usize::from(num_columns)
+        {
+            Some(metadata.clone())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn put(&self, db_name: Option<&str>, query: Arc<[u8]>, metadata: StmtMetadata) {
+        let mut cache = self.cache.lock().unwrap();
+        cache.put((db_name.map(Into::into), query), metadata);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+}
@@ -16,7 +16,7 @@ use std::{
     sync::{atomic::Ordering, Arc},
 };
 
-use super::Inner;
+use super::{CloseReason, Inner, PoolEvent};
 use crate::PoolOpts;
 use futures_core::task::{Context, Poll};
 use std::pin::Pin;
@@ -70,15 +70,26 @@ impl TtlCheckInterval {
                 }
             }
             exchange.available = kept_available;
+            self.inner.metrics.pool_state(&exchange);
             to_be_dropped
         };
 
         for idling_conn in to_be_dropped {
             assert!(idling_conn.conn.inner.pool.is_none());
+            self.pool_opts.emit_event(|| PoolEvent::ConnectionClosed {
+                id: idling_conn.conn.id(),
+                reason: if idling_conn.conn.expired() {
+                    CloseReason::Expired
+                } else {
+                    CloseReason::Inactive
+                },
+            });
             let inner = self.inner.clone();
             tokio::spawn(idling_conn.conn.disconnect().then(move |_| {
+                inner.metrics.conns_dropped(1);
                 let mut exchange = inner.exchange.lock().unwrap();
                 exchange.exist -= 1;
+                inner.metrics.pool_state(&exchange);
                 ok::<_, ()>(())
             }));
         }
@@ -90,14 +101,14 @@ impl Future for TtlCheckInterval {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         loop {
-            let _ = futures_core::ready!(self.as_mut().project().interval.poll_tick(cx));
-            let close = self.inner.close.load(Ordering::Acquire);
-
-            if !close {
-                self.check_ttl();
-            } else {
+            // the pool wakes us up once it's closing, so that we don't outlive it
+            self.inner.ttl_check_waker.register(cx.waker());
+            if self.inner.close.load(Ordering::Acquire) {
                 return Poll::Ready(());
             }
+
+            let _ = futures_core::ready!(self.as_mut().project().interval.poll_tick(cx));
+            self.check_ttl();
         }
     }
 }
@@ -29,7 +29,7 @@ impl Routine<()> for ChangeUser {
             .with_auth_plugin_data(
                 conn.inner
                     .auth_plugin
-                    .gen_data(conn.opts().pass(), &conn.inner.nonce)
+                    .gen_data(conn.auth_pass(), &conn.inner.nonce)
                     .as_deref(),
             )
             .with_more_data(Some(
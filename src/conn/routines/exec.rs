@@ -2,11 +2,17 @@ use std::mem;
 
 use futures_core::future::BoxFuture;
 use futures_util::FutureExt;
-use mysql_common::{packets::ComStmtExecuteRequestBuilder, params::Params};
+use mysql_common::{
+    constants::{ColumnType, CursorType},
+    packets::{Column, ComStmtExecuteRequest, ComStmtExecuteRequestBuilder},
+    params::Params,
+    proto::MySerialize,
+    value::Value,
+};
 #[cfg(feature = "tracing")]
 use tracing::{field, info_span, Level, Span};
 
-use crate::{BinaryProtocol, Conn, DriverError, Statement};
+use crate::{buffer_pool::PooledBuf, BinaryProtocol, Conn, DriverError, Statement};
 
 use super::Routine;
 
@@ -15,14 +21,141 @@ use super::Routine;
 pub struct ExecRoutine<'a> {
     stmt: &'a Statement,
     params: Params,
+    param_types: Option<&'a [ColumnType]>,
+    read_only_cursor: bool,
+    long_data: bool,
 }
 
 impl<'a> ExecRoutine<'a> {
     pub fn new(stmt: &'a Statement, params: Params) -> Self {
-        Self { stmt, params }
+        Self {
+            stmt,
+            params,
+            param_types: None,
+            read_only_cursor: false,
+            long_data: false,
+        }
+    }
+
+    /// Overrides parameter types sent to the server (see [`crate::Conn::exec_typed`]).
+    pub fn with_param_types(mut self, param_types: &'a [ColumnType]) -> Self {
+        self.param_types = Some(param_types);
+        self
+    }
+
+    /// Asks the server to open a read-only cursor (see [`crate::Conn::exec_cursor`]).
+    pub fn with_read_only_cursor(mut self) -> Self {
+        self.read_only_cursor = true;
+        self
+    }
+
+    /// Sends every `Value::Bytes` parameter as long data regardless of the request size
+    /// (see [`crate::Conn::exec_long_data`]).
+    pub fn with_long_data(mut self) -> Self {
+        self.long_data = true;
+        self
+    }
+
+    /// Reads the result of the sent request timing the statement
+    /// (see [`crate::Opts::slow_query_threshold`]).
+    async fn read_result_set(&self, conn: &mut Conn) -> crate::Result<()> {
+        conn.start_slow_query_timer(|| self.stmt.inner.raw_query.clone());
+        let result = conn.read_result_set::<BinaryProtocol>(true).await;
+        if result.is_err() {
+            conn.cancel_slow_query_timer();
+        }
+        result
+    }
+
+    /// Serializes the request applying overrides that aren't supported by the request builder.
+    fn serialize_request(&self, mut buf: PooledBuf, body: &ComStmtExecuteRequest<'_>) -> PooledBuf {
+        body.serialize(buf.as_mut());
+
+        if let Some(param_types) = self.param_types {
+            // type bytes follow the header, the null bitmap
+            // and the new-params-bound flag
+            let offset = ComStmtExecuteRequestBuilder::NULL_BITMAP_OFFSET + body.bitmap().len() + 1;
+            for (i, column_type) in param_types.iter().enumerate() {
+                buf.as_mut()[offset + i * 2] = *column_type as u8;
+            }
+        }
+
+        if self.read_only_cursor {
+            // flags byte follows the command byte and the statement id
+            buf.as_mut()[5] = CursorType::CURSOR_TYPE_READ_ONLY.bits();
+        }
+
+        if self.long_data && !body.as_long_data() && !body.params().is_empty() {
+            // values follow the type bytes, but bytes are sent as long data
+            let offset = ComStmtExecuteRequestBuilder::NULL_BITMAP_OFFSET
+                + body.bitmap().len()
+                + 1
+                + body.params().len() * 2;
+            buf.as_mut().truncate(offset);
+            for param in body.params() {
+                if !matches!(param, Value::NULL | Value::Bytes(_)) {
+                    param.serialize(buf.as_mut());
+                }
+            }
+        }
+
+        buf
+    }
+}
+
+/// Returns `true` if the binary encoding of the `value` matches the given `column_type`.
+fn is_compatible_param_type(value: &Value, column_type: ColumnType) -> bool {
+    use ColumnType::*;
+
+    match value {
+        Value::NULL => true,
+        Value::Bytes(_) => matches!(
+            column_type,
+            MYSQL_TYPE_VARCHAR
+                | MYSQL_TYPE_VAR_STRING
+                | MYSQL_TYPE_STRING
+                | MYSQL_TYPE_DECIMAL
+                | MYSQL_TYPE_NEWDECIMAL
+                | MYSQL_TYPE_ENUM
+                | MYSQL_TYPE_SET
+                | MYSQL_TYPE_TINY_BLOB
+                | MYSQL_TYPE_MEDIUM_BLOB
+                | MYSQL_TYPE_LONG_BLOB
+                | MYSQL_TYPE_BLOB
+                | MYSQL_TYPE_BIT
+                | MYSQL_TYPE_JSON
+                | MYSQL_TYPE_GEOMETRY
+        ),
+        Value::Int(_) | Value::UInt(_) => column_type == MYSQL_TYPE_LONGLONG,
+        Value::Float(_) => column_type == MYSQL_TYPE_FLOAT,
+        Value::Double(_) => column_type == MYSQL_TYPE_DOUBLE,
+        Value::Date(..) => matches!(
+            column_type,
+            MYSQL_TYPE_DATE | MYSQL_TYPE_DATETIME | MYSQL_TYPE_TIMESTAMP
+        ),
+        Value::Time(..) => column_type == MYSQL_TYPE_TIME,
     }
 }
 
+/// Checks lengths of string parameters against lengths reported in parameter metadata
+/// (see [`crate::Opts::validate_param_lengths`]).
+fn check_param_lengths(values: &[Value], params: &[Column]) -> Result<(), DriverError> {
+    for (index, (value, param)) in values.iter().zip(params).enumerate() {
+        let max_length = param.column_length() as usize;
+        if let Value::Bytes(bytes) = value {
+            if max_length > 0 && bytes.len() > max_length {
+                return Err(DriverError::ParamTooLong {
+                    index,
+                    length: bytes.len(),
+                    max_length,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
 impl Routine<()> for ExecRoutine<'_> {
     fn call<'a>(&'a mut self, conn: &'a mut Conn) -> BoxFuture<'a, crate::Result<()>> {
         #[cfg(feature = "tracing")]
@@ -59,15 +192,44 @@ impl Routine<()> for ExecRoutine<'_> {
                             })?
                         }
 
+                        if let Some(param_types) = self.param_types {
+                            if param_types.len() != params.len() {
+                                Err(DriverError::ParamTypesMismatch {
+                                    required: self.stmt.num_params(),
+                                    supplied: param_types.len() as u16,
+                                })?
+                            }
+
+                            for (index, (value, column_type)) in
+                                params.iter().zip(param_types).enumerate()
+                            {
+                                if !is_compatible_param_type(value, *column_type) {
+                                    Err(DriverError::IncompatibleParamType {
+                                        index,
+                                        column_type: *column_type,
+                                    })?
+                                }
+                            }
+                        }
+
+                        if conn.opts().validate_param_lengths() {
+                            check_param_lengths(params, self.stmt.params())?;
+                        }
+
                         let (body, as_long_data) =
                             ComStmtExecuteRequestBuilder::new(self.stmt.id()).build(params);
 
-                        if as_long_data {
+                        if as_long_data || self.long_data {
                             conn.send_long_data(self.stmt.id(), params.iter()).await?;
                         }
 
-                        conn.write_command(&body).await?;
-                        conn.read_result_set::<BinaryProtocol>(true).await?;
+                        if self.param_types.is_some() || self.read_only_cursor || self.long_data {
+                            let buf = self.serialize_request(conn.buffer_pool().get(), &body);
+                            conn.write_command_raw(buf).await?;
+                        } else {
+                            conn.write_command(&body).await?;
+                        }
+                        self.read_result_set(conn).await?;
                         break;
                     }
                     Params::Named(_) => {
@@ -91,10 +253,24 @@ impl Routine<()> for ExecRoutine<'_> {
                             return Err(error);
                         }
 
+                        if let Some(param_types) = self.param_types.filter(|x| !x.is_empty()) {
+                            let error = DriverError::ParamTypesMismatch {
+                                required: 0,
+                                supplied: param_types.len() as u16,
+                            }
+                            .into();
+                            return Err(error);
+                        }
+
                         let (body, _) =
                             ComStmtExecuteRequestBuilder::new(self.stmt.id()).build(&[]);
-                        conn.write_command(&body).await?;
-                        conn.read_result_set::<BinaryProtocol>(true).await?;
+                        if self.read_only_cursor {
+                            let buf = self.serialize_request(conn.buffer_pool().get(), &body);
+                            conn.write_command_raw(buf).await?;
+                        } else {
+                            conn.write_command(&body).await?;
+                        }
+                        self.read_result_set(conn).await?;
                         break;
                     }
                 }
@@ -108,3 +284,78 @@ impl Routine<()> for ExecRoutine<'_> {
         fut.boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mysql_common::{constants::ColumnType, packets::Column, value::Value};
+
+    use super::check_param_lengths;
+    use crate::{prelude::*, test_misc::get_opts, Conn, DriverError, Error, OptsBuilder};
+
+    #[test]
+    fn should_check_param_lengths() {
+        let params = [
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_column_length(8),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING),
+            Column::new(ColumnType::MYSQL_TYPE_LONGLONG).with_column_length(1),
+        ];
+
+        let values = [
+            Value::from("12345678"),
+            Value::from("unknown length"),
+            Value::Int(42),
+        ];
+        assert!(check_param_lengths(&values, &params).is_ok());
+
+        let values = [Value::from("123456789"), Value::NULL, Value::Int(42)];
+        match check_param_lengths(&values, &params) {
+            Err(DriverError::ParamTooLong {
+                index: 0,
+                length: 9,
+                max_length: 8,
+            }) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_validate_param_lengths_reported_by_server() -> crate::Result<()> {
+        let opts = OptsBuilder::from_opts(get_opts()).validate_param_lengths(true);
+        let mut conn = Conn::new(opts).await?;
+
+        // parameter types are derived from the context starting from MySQL 8.0.22
+        if conn.inner.is_mariadb || conn.server_version() < (8, 0, 22) {
+            return conn.disconnect().await;
+        }
+
+        conn.query_drop(
+            "CREATE TEMPORARY TABLE tmp_param_lengths (val VARCHAR(4) CHARACTER SET latin1)",
+        )
+        .await?;
+        let stmt = conn
+            .prep("INSERT INTO tmp_param_lengths (val) VALUES (?)")
+            .await?;
+
+        let max_length = stmt.params()[0].column_length() as usize;
+        assert!(max_length >= 4);
+
+        conn.exec_drop(&stmt, ("a".repeat(4),)).await?;
+        match conn.exec_drop(&stmt, ("a".repeat(max_length + 1),)).await {
+            Err(Error::Driver(DriverError::ParamTooLong {
+                index: 0,
+                length,
+                max_length: reported,
+            })) => {
+                assert_eq!(length, max_length + 1);
+                assert_eq!(reported, max_length);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // the oversized value never reached the server
+        let vals: Vec<String> = conn.query("SELECT val FROM tmp_param_lengths").await?;
+        assert_eq!(vals, vec!["aaaa".to_owned()]);
+
+        conn.disconnect().await
+    }
+}
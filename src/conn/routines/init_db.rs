@@ -0,0 +1,43 @@
+use futures_core::future::BoxFuture;
+use futures_util::FutureExt;
+use mysql_common::constants::Command;
+#[cfg(feature = "tracing")]
+use tracing::debug_span;
+
+use crate::Conn;
+
+use super::Routine;
+
+/// A routine that performs `COM_INIT_DB`.
+#[derive(Debug, Copy, Clone)]
+pub struct InitDbRoutine<'a> {
+    db_name: &'a [u8],
+}
+
+impl<'a> InitDbRoutine<'a> {
+    pub fn new(db_name: &'a [u8]) -> Self {
+        Self { db_name }
+    }
+}
+
+impl Routine<()> for InitDbRoutine<'_> {
+    fn call<'a>(&'a mut self, conn: &'a mut Conn) -> BoxFuture<'a, crate::Result<()>> {
+        #[cfg(feature = "tracing")]
+        let span = debug_span!(
+            "mysql_async::init_db",
+            mysql_async.connection.id = conn.id()
+        );
+
+        let fut = async move {
+            conn.write_command_data(Command::COM_INIT_DB, self.db_name)
+                .await?;
+            conn.read_packet().await?;
+            Ok(())
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = instrument_result!(fut, span);
+
+        fut.boxed()
+    }
+}
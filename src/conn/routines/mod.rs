@@ -2,15 +2,23 @@ use futures_core::future::BoxFuture;
 
 use crate::Conn;
 
-pub use self::{change_user::*, exec::*, next_set::*, ping::*, prepare::*, query::*, reset::*};
+pub use self::{
+    change_user::*, exec::*, init_db::*, next_set::*, ping::*, prepare::*, query::*,
+    raw_command::*, reset::*, statistics::*, stmt_fetch::*, stmt_reset::*,
+};
 
 mod change_user;
 mod exec;
+mod init_db;
 mod next_set;
 mod ping;
 mod prepare;
 mod query;
+mod raw_command;
 mod reset;
+mod statistics;
+mod stmt_fetch;
+mod stmt_reset;
 
 mod helpers;
 
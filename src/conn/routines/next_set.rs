@@ -31,7 +31,11 @@ where
         );
         conn.sync_seq_id();
         let fut = async move {
-            conn.read_result_set::<P>(false).await?;
+            if let Err(err) = conn.read_result_set::<P>(false).await {
+                // the query failed, so it won't be reported (see `Opts::slow_query_threshold`)
+                conn.cancel_slow_query_timer();
+                return Err(err);
+            }
             Ok(())
         };
 
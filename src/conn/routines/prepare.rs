@@ -52,14 +52,43 @@ impl Routine<Arc<StmtInner>> for PrepareRoutine {
             #[cfg(feature = "tracing")]
             Span::current().record("mysql_async.statement.id", inner_stmt.id());
 
-            if inner_stmt.num_params() > 0 {
-                let params = conn.read_column_defs(inner_stmt.num_params()).await?;
-                inner_stmt = inner_stmt.with_params(params);
-            }
+            let shared_metadata = conn.pool().and_then(|pool| pool.stmt_metadata());
+            let db_name = conn.opts().db_name().map(String::from);
+            let cached_metadata = shared_metadata.and_then(|cache| {
+                cache.get(
+                    db_name.as_deref(),
+                    &self.query,
+                    inner_stmt.num_params(),
+                    inner_stmt.num_columns(),
+                )
+            });
+
+            if let Some(metadata) = cached_metadata {
+                if inner_stmt.num_params() > 0 {
+                    conn.skip_column_defs(inner_stmt.num_params()).await?;
+                }
+                if inner_stmt.num_columns() > 0 {
+                    conn.skip_column_defs(inner_stmt.num_columns()).await?;
+                }
+                inner_stmt = inner_stmt.with_metadata(metadata);
+            } else {
+                if inner_stmt.num_params() > 0 {
+                    let params = conn.read_column_defs(inner_stmt.num_params()).await?;
+                    inner_stmt = inner_stmt.with_params(params);
+                }
+
+                if inner_stmt.num_columns() > 0 {
+                    let columns = conn.read_column_defs(inner_stmt.num_columns()).await?;
+                    inner_stmt = inner_stmt.with_columns(columns);
+                }
 
-            if inner_stmt.num_columns() > 0 {
-                let columns = conn.read_column_defs(inner_stmt.num_columns()).await?;
-                inner_stmt = inner_stmt.with_columns(columns);
+                if let Some(cache) = conn.pool().and_then(|pool| pool.stmt_metadata()) {
+                    cache.put(
+                        db_name.as_deref(),
+                        self.query.clone(),
+                        inner_stmt.metadata(),
+                    );
+                }
             }
 
             Ok(Arc::new(inner_stmt))
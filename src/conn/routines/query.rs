@@ -49,7 +49,11 @@ impl<L: TracingLevel> Routine<()> for QueryRoutine<'_, L> {
         let fut = async move {
             conn.write_command_data(Command::COM_QUERY, self.data)
                 .await?;
-            conn.read_result_set::<TextProtocol>(true).await?;
+            conn.start_slow_query_timer(|| self.data.into());
+            if let Err(err) = conn.read_result_set::<TextProtocol>(true).await {
+                conn.cancel_slow_query_timer();
+                return Err(err);
+            }
             Ok(())
         };
 
@@ -0,0 +1,57 @@
+use bytes::Bytes;
+use futures_core::future::BoxFuture;
+use futures_util::FutureExt;
+#[cfg(feature = "tracing")]
+use tracing::debug_span;
+
+use crate::{queryable::is_result_set_terminator, Conn};
+
+use super::Routine;
+
+/// A routine that sends an arbitrary command and collects raw response packets.
+#[derive(Debug, Copy, Clone)]
+pub struct RawCommandRoutine<'a> {
+    cmd: u8,
+    payload: &'a [u8],
+}
+
+impl<'a> RawCommandRoutine<'a> {
+    pub fn new(cmd: u8, payload: &'a [u8]) -> Self {
+        Self { cmd, payload }
+    }
+}
+
+impl Routine<Vec<Bytes>> for RawCommandRoutine<'_> {
+    fn call<'a>(&'a mut self, conn: &'a mut Conn) -> BoxFuture<'a, crate::Result<Vec<Bytes>>> {
+        #[cfg(feature = "tracing")]
+        let span = debug_span!(
+            "mysql_async::raw_command",
+            mysql_async.connection.id = conn.id(),
+            mysql_async.command = self.cmd
+        );
+
+        let fut = async move {
+            let mut body = conn.buffer_pool().get();
+            body.as_mut().push(self.cmd);
+            body.as_mut().extend_from_slice(self.payload);
+            conn.write_command_raw(body).await?;
+
+            let mut packets = Vec::new();
+            loop {
+                // ERR packet is returned as an error by `read_packet`
+                let packet = conn.read_packet().await?;
+                let is_last = (packets.is_empty() && packet.first() == Some(&0x00))
+                    || is_result_set_terminator(conn.capabilities(), &packet);
+                packets.push(Bytes::copy_from_slice(&packet));
+                if is_last {
+                    break Ok(packets);
+                }
+            }
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = instrument_result!(fut, span);
+
+        fut.boxed()
+    }
+}
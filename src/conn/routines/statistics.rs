@@ -0,0 +1,38 @@
+use futures_core::future::BoxFuture;
+use futures_util::FutureExt;
+use mysql_common::constants::Command;
+#[cfg(feature = "tracing")]
+use tracing::debug_span;
+
+use crate::Conn;
+
+use super::Routine;
+
+/// A routine that executes `COM_STATISTICS`.
+#[derive(Debug, Copy, Clone)]
+pub struct StatisticsRoutine;
+
+impl Routine<String> for StatisticsRoutine {
+    fn call<'a>(&'a mut self, conn: &'a mut Conn) -> BoxFuture<'a, crate::Result<String>> {
+        #[cfg(feature = "tracing")]
+        let span = debug_span!(
+            "mysql_async::statistics",
+            mysql_async.connection.id = conn.id()
+        );
+
+        let fut = async move {
+            conn.write_command_data(Command::COM_STATISTICS, &[])
+                .await?;
+            // the response is a bare string (neither an OK packet nor a result set),
+            // but the server may send an ERR packet instead, that is returned as an error
+            // by `read_packet` (the statistics string never starts with `0xFF`)
+            let packet = conn.read_packet().await?;
+            Ok(String::from_utf8_lossy(&packet).into_owned())
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = instrument_result!(fut, span);
+
+        fut.boxed()
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use futures_core::future::BoxFuture;
+use futures_util::FutureExt;
+use mysql_common::{constants::Command, packets::Column};
+#[cfg(feature = "tracing")]
+use tracing::debug_span;
+
+use crate::{queryable::query_result::ResultSetMeta, Conn};
+
+use super::Routine;
+
+/// A routine that performs `COM_STMT_FETCH`.
+///
+/// Fetched rows are left to be consumed as a binary result set with the given columns.
+#[derive(Debug, Clone)]
+pub struct StmtFetchRoutine {
+    stmt_id: u32,
+    num_rows: u32,
+    columns: Arc<[Column]>,
+}
+
+impl StmtFetchRoutine {
+    pub fn new(stmt_id: u32, num_rows: u32, columns: Arc<[Column]>) -> Self {
+        Self {
+            stmt_id,
+            num_rows,
+            columns,
+        }
+    }
+}
+
+impl Routine<()> for StmtFetchRoutine {
+    fn call<'a>(&'a mut self, conn: &'a mut Conn) -> BoxFuture<'a, crate::Result<()>> {
+        #[cfg(feature = "tracing")]
+        let span = debug_span!(
+            "mysql_async::stmt_fetch",
+            mysql_async.connection.id = conn.id(),
+            mysql_async.statement.id = self.stmt_id
+        );
+
+        let fut = async move {
+            let mut body = [0_u8; 8];
+            body[..4].copy_from_slice(&self.stmt_id.to_le_bytes());
+            body[4..].copy_from_slice(&self.num_rows.to_le_bytes());
+            conn.write_command_data(Command::COM_STMT_FETCH, body)
+                .await?;
+            // the server responds with rows (without column definitions)
+            conn.set_pending_result(Some(ResultSetMeta::Binary(self.columns.clone())))?;
+            Ok(())
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = instrument_result!(fut, span);
+
+        fut.boxed()
+    }
+}
@@ -0,0 +1,44 @@
+use futures_core::future::BoxFuture;
+use futures_util::FutureExt;
+use mysql_common::constants::Command;
+#[cfg(feature = "tracing")]
+use tracing::debug_span;
+
+use crate::Conn;
+
+use super::Routine;
+
+/// A routine that performs `COM_STMT_RESET` (it also closes an open cursor, if any).
+#[derive(Debug, Copy, Clone)]
+pub struct StmtResetRoutine {
+    stmt_id: u32,
+}
+
+impl StmtResetRoutine {
+    pub fn new(stmt_id: u32) -> Self {
+        Self { stmt_id }
+    }
+}
+
+impl Routine<()> for StmtResetRoutine {
+    fn call<'a>(&'a mut self, conn: &'a mut Conn) -> BoxFuture<'a, crate::Result<()>> {
+        #[cfg(feature = "tracing")]
+        let span = debug_span!(
+            "mysql_async::stmt_reset",
+            mysql_async.connection.id = conn.id(),
+            mysql_async.statement.id = self.stmt_id
+        );
+
+        let fut = async move {
+            conn.write_command_data(Command::COM_STMT_RESET, self.stmt_id.to_le_bytes())
+                .await?;
+            conn.read_packet().await?;
+            Ok(())
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = instrument_result!(fut, span);
+
+        fut.boxed()
+    }
+}
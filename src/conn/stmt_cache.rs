@@ -6,17 +6,21 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
+use futures_util::FutureExt;
 use lru::LruCache;
 use twox_hash::XxHash;
 
 use std::{
-    borrow::Borrow,
+    borrow::{Borrow, Cow},
     collections::HashMap,
     hash::{BuildHasherDefault, Hash},
     sync::Arc,
 };
 
-use crate::queryable::stmt::StmtInner;
+use crate::{
+    conn::routines::PrepareRoutine,
+    queryable::stmt::{Statement, StmtInner},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct QueryString(pub Arc<[u8]>);
@@ -140,4 +144,53 @@ impl super::Conn {
             .by_query(raw_query)
             .map(|entry| entry.stmt.clone())
     }
+
+    /// Prepares and pins the given statement, unless it is already pinned
+    /// (see [`crate::Conn::prepare_pinned`]).
+    ///
+    /// Pinned statements bypass the statement cache, so they are never evicted.
+    pub(crate) async fn pin_stmt(&mut self, raw_query: &[u8]) -> crate::Result<Arc<StmtInner>> {
+        if let Some(stmt) = self.inner.pinned_stmts.get(raw_query) {
+            return Ok(stmt.clone());
+        }
+        let stmt = self
+            .routine(PrepareRoutine::new(Cow::Borrowed(raw_query)))
+            .await?;
+        self.inner
+            .pinned_stmts
+            .insert(QueryString(stmt.raw_query.clone()), stmt.clone());
+        Ok(stmt)
+    }
+
+    /// Unpins the given statement. Returns the current version of the pinned statement.
+    pub(crate) fn unpin_stmt(&mut self, raw_query: &[u8]) -> Option<Arc<StmtInner>> {
+        self.inner.pinned_stmts.remove(raw_query)
+    }
+
+    /// Replaces the statement of a pinned handle with its current version.
+    pub(crate) fn resolve_pinned_stmt(&self, mut stmt: Statement) -> Statement {
+        if stmt.pinned {
+            if let Some(inner) = self.inner.pinned_stmts.get(&*stmt.inner.raw_query) {
+                stmt.inner = inner.clone();
+            }
+        }
+        stmt
+    }
+
+    /// Re-prepares pinned statements after server-side statements are gone
+    /// (i.e. after a reset, a `COM_CHANGE_USER` or a reconnect).
+    pub(crate) fn reprepare_pinned_stmts(&mut self) -> crate::BoxFuture<'_, ()> {
+        // NOTE: we need to box this since it may recurse via `Conn::reconnect_if_needed`
+        async move {
+            let queries = self.inner.pinned_stmts.keys().cloned().collect::<Vec<_>>();
+            for query in queries {
+                let stmt = self
+                    .routine(PrepareRoutine::new(Cow::Borrowed(&*query.0)))
+                    .await?;
+                self.inner.pinned_stmts.insert(query, stmt);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
 }
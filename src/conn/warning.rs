@@ -0,0 +1,136 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::row::convert::FromRowError;
+
+use crate::{
+    prelude::{FromRow, Queryable},
+    Conn, Result, Row,
+};
+
+/// Level of a [`Warning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WarningLevel {
+    /// `Note` – e.g. `DROP TABLE IF EXISTS` of a nonexistent table.
+    Note,
+    /// `Warning` – e.g. division by zero.
+    Warning,
+    /// `Error` – the condition of a failed statement.
+    Error,
+}
+
+impl WarningLevel {
+    fn parse(level: &str) -> Option<Self> {
+        match level {
+            "Note" => Some(WarningLevel::Note),
+            "Warning" => Some(WarningLevel::Warning),
+            "Error" => Some(WarningLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// A row of the `SHOW WARNINGS` output (see [`Conn::warnings`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Warning {
+    level: WarningLevel,
+    code: u16,
+    message: String,
+}
+
+impl Warning {
+    /// Level of this warning.
+    pub fn level(&self) -> WarningLevel {
+        self.level
+    }
+
+    /// Error code, e.g. `1365` for `ER_DIVISION_BY_ZERO`.
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// Human-readable message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl FromRow for Warning {
+    fn from_row_opt(row: Row) -> std::result::Result<Self, FromRowError> {
+        let level = row
+            .get_opt::<String, _>(0)
+            .and_then(|x| x.ok())
+            .and_then(|x| WarningLevel::parse(&x));
+        let code = row.get_opt::<u16, _>(1).and_then(|x| x.ok());
+        let message = row.get_opt::<String, _>(2).and_then(|x| x.ok());
+
+        match (level, code, message) {
+            (Some(level), Some(code), Some(message)) if row.len() == 3 => Ok(Warning {
+                level,
+                code,
+                message,
+            }),
+            _ => Err(FromRowError(row)),
+        }
+    }
+}
+
+impl Conn {
+    /// Executes `SHOW WARNINGS` and returns warnings of the last statement, that generated
+    /// messages (see [`crate::QueryResult::warnings`] for the number of warnings).
+    ///
+    /// Note that `SHOW WARNINGS` shows at most `max_error_count` messages.
+    ///
+    /// ```rust
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn, WarningLevel};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// conn.query_drop("DROP TABLE IF EXISTS mysql_async_nonexistent_table").await?;
+    ///
+    /// let warnings = conn.warnings().await?;
+    /// assert_eq!(warnings[0].level(), WarningLevel::Note);
+    /// assert_eq!(warnings[0].code(), 1051);
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn warnings(&mut self) -> Result<Vec<Warning>> {
+        self.query("SHOW WARNINGS").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{prelude::*, test_misc::get_opts, Conn, WarningLevel};
+
+    #[tokio::test]
+    async fn should_show_warnings() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+
+        conn.query_drop("DO 1").await?;
+        assert!(conn.warnings().await?.is_empty());
+
+        conn.query_drop("SELECT 1 / 0").await?;
+        assert_eq!(conn.get_warnings(), 1);
+        let warnings = conn.warnings().await?;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].level(), WarningLevel::Warning);
+        assert_eq!(warnings[0].code(), 1365);
+        assert_eq!(warnings[0].message(), "Division by 0");
+
+        conn.query_drop("DROP TABLE IF EXISTS mysql_async_nonexistent_table")
+            .await?;
+        let warnings = conn.warnings().await?;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].level(), WarningLevel::Note);
+        assert_eq!(warnings[0].code(), 1051);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
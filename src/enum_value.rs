@@ -0,0 +1,327 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::value::convert::{FromValue, FromValueError};
+
+use std::{collections::HashSet, convert::TryFrom, hash::Hash};
+
+use crate::Value;
+
+/// Rust enum, that corresponds to members of an `ENUM` or `SET` column.
+///
+/// MySql gives out `ENUM` and `SET` values as strings in both protocols, so implement
+/// this trait (mapping each variant to the member name) to read and bind the enum using
+/// the [`EnumValue`] and [`SetValue`] wrappers.
+///
+/// ```rust
+/// use mysql_async::prelude::MySqlEnum;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// enum Size {
+///     Small,
+///     Large,
+/// }
+///
+/// impl MySqlEnum for Size {
+///     fn from_mysql_str(value: &str) -> Option<Self> {
+///         match value {
+///             "small" => Some(Size::Small),
+///             "large" => Some(Size::Large),
+///             _ => None,
+///         }
+///     }
+///
+///     fn as_mysql_str(&self) -> &str {
+///         match self {
+///             Size::Small => "small",
+///             Size::Large => "large",
+///         }
+///     }
+/// }
+/// ```
+pub trait MySqlEnum: Sized {
+    /// Returns the variant for the given member name (`None` if there is no such variant).
+    fn from_mysql_str(value: &str) -> Option<Self>;
+
+    /// Returns the member name of this variant.
+    fn as_mysql_str(&self) -> &str;
+}
+
+/// Value of an `ENUM` column as a [`MySqlEnum`].
+///
+/// Conversion fails with a [`FromValueError`] (that holds the original value) if the value
+/// doesn't correspond to any variant, e.g. if it's an empty string, that MySql stores
+/// for an invalid value in a non-strict mode.
+///
+/// ```rust
+/// # use mysql_async::test_misc::get_opts;
+/// # use mysql_async::prelude::MySqlEnum;
+/// # #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # enum Size { Small, Large }
+/// # impl MySqlEnum for Size {
+/// #     fn from_mysql_str(value: &str) -> Option<Self> {
+/// #         match value { "small" => Some(Size::Small), "large" => Some(Size::Large), _ => None }
+/// #     }
+/// #     fn as_mysql_str(&self) -> &str {
+/// #         match self { Size::Small => "small", Size::Large => "large" }
+/// #     }
+/// # }
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// use mysql_async::{prelude::*, Conn, EnumValue};
+///
+/// let mut conn = Conn::new(get_opts()).await?;
+/// conn.query_drop("CREATE TEMPORARY TABLE shirts (size ENUM('small', 'large'))").await?;
+/// conn.exec_drop("INSERT INTO shirts VALUES (?)", (EnumValue(Size::Large),)).await?;
+///
+/// let size: Option<EnumValue<Size>> = conn.query_first("SELECT size FROM shirts").await?;
+/// assert_eq!(size.map(EnumValue::into_inner), Some(Size::Large));
+/// # conn.disconnect().await }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EnumValue<T>(pub T);
+
+impl<T> EnumValue<T> {
+    /// Returns the wrapped enum.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: MySqlEnum> From<EnumValue<T>> for Value {
+    fn from(value: EnumValue<T>) -> Self {
+        Value::Bytes(value.0.as_mysql_str().as_bytes().to_vec())
+    }
+}
+
+impl<T: MySqlEnum> TryFrom<Value> for EnumValue<T> {
+    type Error = FromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(ref bytes) => std::str::from_utf8(bytes)
+                .ok()
+                .and_then(T::from_mysql_str)
+                .map(EnumValue)
+                .ok_or(FromValueError(value)),
+            _ => Err(FromValueError(value)),
+        }
+    }
+}
+
+impl<T: MySqlEnum> FromValue for EnumValue<T> {
+    type Intermediate = EnumValue<T>;
+}
+
+/// Value of a `SET` column as a set of [`MySqlEnum`]s.
+///
+/// MySql represents a `SET` value as a comma-separated list of members (an empty string
+/// is an empty set). Conversion fails with a [`FromValueError`] if any of the members doesn't
+/// correspond to a variant. Members are bound in an unspecified order, that doesn't matter
+/// to MySql.
+///
+/// ```rust
+/// # use mysql_async::test_misc::get_opts;
+/// # use mysql_async::prelude::MySqlEnum;
+/// # #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # enum Size { Small, Large }
+/// # impl MySqlEnum for Size {
+/// #     fn from_mysql_str(value: &str) -> Option<Self> {
+/// #         match value { "small" => Some(Size::Small), "large" => Some(Size::Large), _ => None }
+/// #     }
+/// #     fn as_mysql_str(&self) -> &str {
+/// #         match self { Size::Small => "small", Size::Large => "large" }
+/// #     }
+/// # }
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// use mysql_async::{prelude::*, Conn, SetValue};
+/// use std::collections::HashSet;
+///
+/// let mut conn = Conn::new(get_opts()).await?;
+///
+/// let sizes: Option<SetValue<Size>> = conn.query_first("SELECT 'large,small'").await?;
+/// let expected: HashSet<_> = [Size::Small, Size::Large].iter().copied().collect();
+/// assert_eq!(sizes.map(SetValue::into_inner), Some(expected));
+/// # conn.disconnect().await }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetValue<T: Eq + Hash>(pub HashSet<T>);
+
+impl<T: Eq + Hash> SetValue<T> {
+    /// Returns the wrapped set.
+    pub fn into_inner(self) -> HashSet<T> {
+        self.0
+    }
+}
+
+impl<T: Eq + Hash> From<HashSet<T>> for SetValue<T> {
+    fn from(value: HashSet<T>) -> Self {
+        SetValue(value)
+    }
+}
+
+impl<T: MySqlEnum + Eq + Hash> From<SetValue<T>> for Value {
+    fn from(value: SetValue<T>) -> Self {
+        let members = value
+            .0
+            .iter()
+            .map(MySqlEnum::as_mysql_str)
+            .collect::<Vec<_>>();
+        Value::Bytes(members.join(",").into_bytes())
+    }
+}
+
+impl<T: MySqlEnum + Eq + Hash> TryFrom<Value> for SetValue<T> {
+    type Error = FromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let members = match value {
+            Value::Bytes(ref bytes) => std::str::from_utf8(bytes).ok().and_then(|x| {
+                x.split(',')
+                    .filter(|x| !x.is_empty())
+                    .map(T::from_mysql_str)
+                    .collect::<Option<HashSet<_>>>()
+            }),
+            _ => None,
+        };
+        members.map(SetValue).ok_or(FromValueError(value))
+    }
+}
+
+impl<T: MySqlEnum + Eq + Hash> FromValue for SetValue<T> {
+    type Intermediate = SetValue<T>;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        from_value, prelude::*, test_misc::get_opts, Conn, EnumValue, FromValueError, Row,
+        SetValue, Value,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    impl MySqlEnum for Color {
+        fn from_mysql_str(value: &str) -> Option<Self> {
+            match value {
+                "red" => Some(Color::Red),
+                "green" => Some(Color::Green),
+                "blue" => Some(Color::Blue),
+                _ => None,
+            }
+        }
+
+        fn as_mysql_str(&self) -> &str {
+            match self {
+                Color::Red => "red",
+                Color::Green => "green",
+                Color::Blue => "blue",
+            }
+        }
+    }
+
+    fn set(colors: &[Color]) -> SetValue<Color> {
+        SetValue(colors.iter().copied().collect())
+    }
+
+    #[test]
+    fn should_convert_enum_and_set_values() {
+        assert_eq!(
+            from_value::<EnumValue<Color>>(Value::from("green")),
+            EnumValue(Color::Green)
+        );
+        assert_eq!(
+            Value::from(EnumValue(Color::Blue)),
+            Value::Bytes(b"blue".to_vec())
+        );
+
+        assert_eq!(from_value::<SetValue<Color>>(Value::from("")), set(&[]));
+        assert_eq!(
+            from_value::<SetValue<Color>>(Value::from("red,blue")),
+            set(&[Color::Red, Color::Blue])
+        );
+        assert_eq!(Value::from(set(&[])), Value::Bytes(vec![]));
+        let value = Value::from(set(&[Color::Red, Color::Green]));
+        assert_eq!(
+            from_value::<SetValue<Color>>(value),
+            set(&[Color::Red, Color::Green])
+        );
+
+        for invalid in [
+            Value::from(""),
+            Value::from("Red"),
+            Value::from("red,blue"),
+            Value::Bytes(vec![0xff]),
+            Value::Int(1),
+            Value::NULL,
+        ] {
+            assert_eq!(
+                EnumValue::<Color>::get_intermediate(invalid.clone()),
+                Err(FromValueError(invalid))
+            );
+        }
+
+        for invalid in [
+            Value::from("red,yellow"),
+            Value::Bytes(vec![0xff]),
+            Value::NULL,
+        ] {
+            assert_eq!(
+                SetValue::<Color>::get_intermediate(invalid.clone()),
+                Err(FromValueError(invalid))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn should_roundtrip_enum_and_set_values() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop(
+            "CREATE TEMPORARY TABLE tmp_enum (
+                id INT,
+                color ENUM('red', 'green', 'blue'),
+                colors SET('red', 'green', 'blue')
+            )",
+        )
+        .await?;
+
+        let values = vec![
+            (1, EnumValue(Color::Red), set(&[])),
+            (2, EnumValue(Color::Green), set(&[Color::Green])),
+            (3, EnumValue(Color::Blue), set(&[Color::Red, Color::Blue])),
+        ];
+        conn.exec_batch("INSERT INTO tmp_enum VALUES (?, ?, ?)", values.clone())
+            .await?;
+
+        let query = "SELECT id, color, colors FROM tmp_enum ORDER BY id";
+        let binary: Vec<(u32, EnumValue<Color>, SetValue<Color>)> = conn.exec(query, ()).await?;
+        assert_eq!(binary, values);
+        let text: Vec<(u32, EnumValue<Color>, SetValue<Color>)> = conn.query(query).await?;
+        assert_eq!(text, values);
+
+        // values, that don't correspond to a variant
+        let row: Row = conn
+            .query_first("SELECT 'yellow', 'red,yellow'")
+            .await?
+            .unwrap();
+        assert!(matches!(
+            row.get_opt::<EnumValue<Color>, _>(0),
+            Some(Err(_))
+        ));
+        assert!(matches!(row.get_opt::<SetValue<Color>, _>(1), Some(Err(_))));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
@@ -11,12 +11,43 @@ pub use url::ParseError;
 mod tls;
 
 use mysql_common::{
-    named_params::MixedParamsError, params::MissingNamedParameterError,
+    constants::ColumnType, named_params::MixedParamsError, params::MissingNamedParameterError,
     proto::codec::error::PacketCodecError, row::Row, value::Value,
 };
 use thiserror::Error;
 
-use std::{io, result};
+use std::{io, result, time::Duration};
+
+/// `ER_DBACCESS_DENIED_ERROR` server error code ("Access denied for user to database").
+const ER_DBACCESS_DENIED_ERROR: u16 = 1044;
+/// `ER_TABLEACCESS_DENIED_ERROR` server error code ("Command denied to user for table").
+const ER_TABLEACCESS_DENIED_ERROR: u16 = 1142;
+/// `ER_SPECIFIC_ACCESS_DENIED_ERROR` server error code ("Access denied; you need
+/// the privilege for this operation").
+const ER_SPECIFIC_ACCESS_DENIED_ERROR: u16 = 1227;
+/// `ER_CON_COUNT_ERROR` server error code ("Too many connections").
+const ER_CON_COUNT_ERROR: u16 = 1040;
+/// `ER_DUP_KEY` server error code ("Can't write; duplicate key in table").
+const ER_DUP_KEY: u16 = 1022;
+/// `ER_DUP_ENTRY` server error code ("Duplicate entry for key").
+const ER_DUP_ENTRY: u16 = 1062;
+/// `ER_DUP_ENTRY_WITH_KEY_NAME` server error code ("Duplicate entry for key").
+const ER_DUP_ENTRY_WITH_KEY_NAME: u16 = 1586;
+/// `ER_NO_SUCH_THREAD` server error code ("Unknown thread id").
+const ER_NO_SUCH_THREAD: u16 = 1094;
+/// `ER_QUERY_INTERRUPTED` server error code ("Query execution was interrupted").
+const ER_QUERY_INTERRUPTED: u16 = 1317;
+/// `ER_SERVER_SHUTDOWN` server error code ("Server shutdown in progress").
+const ER_SERVER_SHUTDOWN: u16 = 1053;
+/// `ER_LOCK_WAIT_TIMEOUT` server error code ("Lock wait timeout exceeded").
+const ER_LOCK_WAIT_TIMEOUT: u16 = 1205;
+/// `ER_LOCK_DEADLOCK` server error code ("Deadlock found when trying to get lock").
+const ER_LOCK_DEADLOCK: u16 = 1213;
+/// `ER_CONNECTION_KILLED` server error code of MariaDb ("Connection was killed").
+const ER_CONNECTION_KILLED: u16 = 1927;
+/// `ER_CLIENT_INTERACTION_TIMEOUT` server error code of MySql 8.0.24+ ("The client was
+/// disconnected by the server because of inactivity").
+const ER_CLIENT_INTERACTION_TIMEOUT: u16 = 4031;
 
 /// Result type alias for this library.
 pub type Result<T> = result::Result<T, Error>;
@@ -48,6 +79,72 @@ impl Error {
             Error::Server(_) => false,
         }
     }
+
+    /// Returns `true` if the server refused a connection because of too many connections,
+    /// i.e. this is [`DriverError::ServerConnectionLimit`] or a server error
+    /// (see [`ServerError::is_con_count_error`]).
+    pub fn is_con_count_error(&self) -> bool {
+        match self {
+            Error::Driver(DriverError::ServerConnectionLimit { .. }) => true,
+            Error::Server(err) => err.is_con_count_error(),
+            Error::Driver(_) | Error::Io(_) | Error::Other(_) | Error::Url(_) => false,
+        }
+    }
+
+    /// Returns `true` if this is a deadlock server error (see [`ServerError::is_deadlock`]).
+    pub fn is_deadlock(&self) -> bool {
+        matches!(self, Error::Server(err) if err.is_deadlock())
+    }
+
+    /// Returns `true` if this is a lock wait timeout server error
+    /// (see [`ServerError::is_lock_wait_timeout`]).
+    pub fn is_lock_wait_timeout(&self) -> bool {
+        matches!(self, Error::Server(err) if err.is_lock_wait_timeout())
+    }
+
+    /// Returns `true` if this is a duplicate key server error
+    /// (see [`ServerError::is_duplicate_key`]).
+    pub fn is_duplicate_key(&self) -> bool {
+        matches!(self, Error::Server(err) if err.is_duplicate_key())
+    }
+
+    /// Returns `true` if this is an unknown thread id server error
+    /// (see [`ServerError::is_no_such_thread`]).
+    pub fn is_no_such_thread(&self) -> bool {
+        matches!(self, Error::Server(err) if err.is_no_such_thread())
+    }
+
+    /// Returns `true` if this is a query interrupted server error
+    /// (see [`ServerError::is_query_interrupted`]).
+    pub fn is_query_interrupted(&self) -> bool {
+        matches!(self, Error::Server(err) if err.is_query_interrupted())
+    }
+
+    /// Returns `true` if this is an access denied server error
+    /// (see [`ServerError::is_access_denied`]).
+    pub fn is_access_denied(&self) -> bool {
+        matches!(self, Error::Server(err) if err.is_access_denied())
+    }
+
+    /// Returns `true` if the error means that the connection to the server is lost,
+    /// i.e. this is an IO error, [`DriverError::ConnectionClosed`] or a server error
+    /// reported right before the server closes the connection
+    /// (see [`ServerError::is_connection_lost`]).
+    pub fn is_connection_lost(&self) -> bool {
+        match self {
+            Error::Io(_) | Error::Driver(DriverError::ConnectionClosed) => true,
+            Error::Server(err) => err.is_connection_lost(),
+            Error::Driver(_) | Error::Other(_) | Error::Url(_) => false,
+        }
+    }
+
+    /// Returns SQLSTATE of a server error (`None` for other errors).
+    pub fn sqlstate(&self) -> Option<&str> {
+        match self {
+            Error::Server(err) => Some(err.sqlstate()),
+            _ => None,
+        }
+    }
 }
 
 /// This type enumerates IO errors.
@@ -69,6 +166,81 @@ pub struct ServerError {
     pub state: String,
 }
 
+impl ServerError {
+    /// Returns `true` if the server refused a connection because of too many connections
+    /// (`ER_CON_COUNT_ERROR`).
+    ///
+    /// This error is usually transient, see [`crate::PoolOpts::with_retry_on_con_count_error`].
+    /// Note, that [`crate::Conn::new`] reports it as [`DriverError::ServerConnectionLimit`].
+    pub fn is_con_count_error(&self) -> bool {
+        self.code == ER_CON_COUNT_ERROR
+    }
+
+    /// Returns `true` if the transaction was rolled back because of a deadlock
+    /// (`ER_LOCK_DEADLOCK`, SQLSTATE `40001`).
+    ///
+    /// The whole transaction should be retried.
+    pub fn is_deadlock(&self) -> bool {
+        self.code == ER_LOCK_DEADLOCK
+    }
+
+    /// Returns `true` if a lock wait timeout was exceeded (`ER_LOCK_WAIT_TIMEOUT`).
+    ///
+    /// Note that only the statement is rolled back by default
+    /// (see `innodb_rollback_on_timeout`), the transaction is still active.
+    pub fn is_lock_wait_timeout(&self) -> bool {
+        self.code == ER_LOCK_WAIT_TIMEOUT
+    }
+
+    /// Returns `true` if a unique or primary key constraint is violated
+    /// (`ER_DUP_ENTRY`, `ER_DUP_ENTRY_WITH_KEY_NAME` or `ER_DUP_KEY`).
+    pub fn is_duplicate_key(&self) -> bool {
+        matches!(
+            self.code,
+            ER_DUP_ENTRY | ER_DUP_ENTRY_WITH_KEY_NAME | ER_DUP_KEY
+        )
+    }
+
+    /// Returns `true` if a connection with the given id doesn't exist (`ER_NO_SUCH_THREAD`),
+    /// e.g. the target of [`crate::Conn::kill_connection`] is already closed.
+    pub fn is_no_such_thread(&self) -> bool {
+        self.code == ER_NO_SUCH_THREAD
+    }
+
+    /// Returns `true` if the statement was killed (`ER_QUERY_INTERRUPTED`),
+    /// e.g. by [`crate::Conn::kill_query`].
+    pub fn is_query_interrupted(&self) -> bool {
+        self.code == ER_QUERY_INTERRUPTED
+    }
+
+    /// Returns `true` if the user lacks a privilege, that is required for the statement
+    /// (`ER_SPECIFIC_ACCESS_DENIED_ERROR`, `ER_TABLEACCESS_DENIED_ERROR`
+    /// or `ER_DBACCESS_DENIED_ERROR`), e.g. `RELOAD` for [`crate::Conn::flush`].
+    pub fn is_access_denied(&self) -> bool {
+        matches!(
+            self.code,
+            ER_SPECIFIC_ACCESS_DENIED_ERROR
+                | ER_TABLEACCESS_DENIED_ERROR
+                | ER_DBACCESS_DENIED_ERROR
+        )
+    }
+
+    /// Returns `true` if the server is about to close the connection
+    /// (`ER_SERVER_SHUTDOWN`, `ER_CLIENT_INTERACTION_TIMEOUT` or MariaDb's
+    /// `ER_CONNECTION_KILLED`) or the SQLSTATE is of the connection exception class (`08`).
+    pub fn is_connection_lost(&self) -> bool {
+        matches!(
+            self.code,
+            ER_SERVER_SHUTDOWN | ER_CLIENT_INTERACTION_TIMEOUT | ER_CONNECTION_KILLED
+        ) || self.state.starts_with("08")
+    }
+
+    /// Returns SQLSTATE of this error, e.g. `23000` for integrity constraint violations.
+    pub fn sqlstate(&self) -> &str {
+        &self.state
+    }
+}
+
 /// This type enumerates connection URL errors.
 #[derive(Debug, Error, Clone, Eq, PartialEq)]
 pub enum UrlError {
@@ -103,6 +275,12 @@ pub enum DriverError {
     #[error("Connection to the server is closed.")]
     ConnectionClosed,
 
+    #[error(
+        "Server refused the connection because of too many connections: {}",
+        error
+    )]
+    ServerConnectionLimit { error: ServerError },
+
     #[error("Error converting from mysql value.")]
     FromValue { value: Value },
 
@@ -137,18 +315,62 @@ pub enum DriverError {
     )]
     StmtParamsMismatch { required: u16, supplied: u16 },
 
+    #[error(
+        "Statement takes {} parameters but {} parameter types were supplied.",
+        required,
+        supplied
+    )]
+    ParamTypesMismatch { required: u16, supplied: u16 },
+
+    #[error(
+        "Parameter {} value is not compatible with the `{:?}' type hint.",
+        index,
+        column_type
+    )]
+    IncompatibleParamType {
+        index: usize,
+        column_type: ColumnType,
+    },
+
+    #[error(
+        "Parameter {} value is {} bytes long, but at most {} bytes are allowed.",
+        index,
+        length,
+        max_length
+    )]
+    ParamTooLong {
+        index: usize,
+        length: usize,
+        max_length: usize,
+    },
+
     #[error("Unexpected packet.")]
     UnexpectedPacket { payload: Vec<u8> },
 
     #[error("Unknown authentication plugin `{}'.", name)]
     UnknownAuthPlugin { name: String },
 
+    #[error("Authentication plugin `{}' is not supported by the driver.", name)]
+    UnsupportedAuthPlugin { name: String },
+
+    #[error(
+        "Authentication plugin requires a secure transport (TLS or socket connection) \
+         or the server public key, but none is available."
+    )]
+    AuthPluginRequiresSecureTransport,
+
     #[error("Packet too large.")]
     PacketTooLarge,
 
     #[error("Bad compressed packet header.")]
     BadCompressedPacketHeader,
 
+    #[error("Unknown session profile `{}'.", name)]
+    UnknownSessionProfile { name: String },
+
+    #[error("There are no readers in the replicated pool.")]
+    NoReaders,
+
     #[error("Named pipe connections temporary disabled (see tokio-rs/tokio#3118)")]
     NamedPipesDisabled,
 
@@ -166,6 +388,9 @@ pub enum DriverError {
 
     #[error("mysql_clear_password must be enabled on the client side")]
     CleartextPluginDisabled,
+
+    #[error("Statement didn't complete within {:?} and was killed.", timeout)]
+    StatementTimeout { timeout: Duration },
 }
 
 #[derive(Debug, Error)]
@@ -319,3 +544,58 @@ impl From<PacketCodecError> for Error {
         Error::Io(err.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DriverError, Error, ServerError};
+
+    fn server_error(code: u16, state: &str) -> Error {
+        Error::Server(ServerError {
+            code,
+            message: String::new(),
+            state: state.into(),
+        })
+    }
+
+    #[test]
+    fn should_classify_server_errors() {
+        let deadlock = server_error(1213, "40001");
+        assert!(deadlock.is_deadlock());
+        assert!(!deadlock.is_lock_wait_timeout());
+        assert_eq!(deadlock.sqlstate(), Some("40001"));
+
+        assert!(server_error(1205, "HY000").is_lock_wait_timeout());
+
+        for code in [1022, 1062, 1586] {
+            let err = server_error(code, "23000");
+            assert!(err.is_duplicate_key());
+            assert!(!err.is_connection_lost());
+        }
+
+        assert!(server_error(1053, "08S01").is_connection_lost());
+        assert!(server_error(4031, "HY000").is_connection_lost());
+        assert!(server_error(1927, "70100").is_connection_lost());
+        assert!(server_error(1158, "08S01").is_connection_lost());
+        assert!(!server_error(1146, "42S02").is_connection_lost());
+
+        assert!(server_error(1317, "70100").is_query_interrupted());
+        assert!(!server_error(1094, "HY000").is_query_interrupted());
+
+        for code in [1044, 1142, 1227] {
+            assert!(server_error(code, "42000").is_access_denied());
+        }
+        assert!(!server_error(1045, "28000").is_access_denied());
+    }
+
+    #[test]
+    fn should_classify_other_errors() {
+        let closed = Error::Driver(DriverError::ConnectionClosed);
+        assert!(closed.is_connection_lost());
+        assert!(!closed.is_deadlock());
+        assert_eq!(closed.sqlstate(), None);
+
+        let io = Error::from(std::io::Error::from(std::io::ErrorKind::BrokenPipe));
+        assert!(io.is_connection_lost());
+        assert!(!io.is_duplicate_key());
+    }
+}
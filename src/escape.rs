@@ -0,0 +1,312 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! String escaping and identifier quoting (see [`crate::Conn::escape_string`]).
+
+use std::fmt;
+
+use crate::Value;
+
+/// Returns the length of a multibyte character at the beginning of `bytes`,
+/// if `bytes` starts with a valid multibyte character of the given charset.
+///
+/// Only charsets that allow ASCII bytes (particularly the backslash) as trailing bytes
+/// are taken into account, because for other charsets bytewise escaping is safe.
+fn mb_char_len(charset: &str, bytes: &[u8]) -> Option<usize> {
+    let lead = *bytes.first()?;
+    let trail = *bytes.get(1)?;
+
+    let (is_lead, is_trail) = match &*charset.to_ascii_lowercase() {
+        "big5" => (
+            matches!(lead, 0xA1..=0xF9),
+            matches!(trail, 0x40..=0x7E | 0xA1..=0xFE),
+        ),
+        "cp932" | "sjis" => (
+            matches!(lead, 0x81..=0x9F | 0xE0..=0xFC),
+            matches!(trail, 0x40..=0x7E | 0x80..=0xFC),
+        ),
+        "gbk" => (
+            matches!(lead, 0x81..=0xFE),
+            matches!(trail, 0x40..=0x7E | 0x80..=0xFE),
+        ),
+        "gb18030" => {
+            if matches!(
+                bytes,
+                [0x81..=0xFE, 0x30..=0x39, 0x81..=0xFE, 0x30..=0x39, ..]
+            ) {
+                return Some(4);
+            }
+            (
+                matches!(lead, 0x81..=0xFE),
+                matches!(trail, 0x40..=0x7E | 0x80..=0xFE),
+            )
+        }
+        _ => return None,
+    };
+
+    if is_lead && is_trail {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Escapes `input` for a single-quoted SQL string literal
+/// the same way `mysql_real_escape_string` does.
+///
+/// * `charset` – connection character set;
+/// * `no_backslash_escapes` – whether `NO_BACKSLASH_ESCAPES` SQL mode is enabled.
+pub(crate) fn escape_with(input: &str, charset: &str, no_backslash_escapes: bool) -> String {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len() + bytes.len() / 8);
+
+    let mut i = 0;
+    while i < bytes.len() {
+        // multibyte character is copied as is
+        if let Some(len) = mb_char_len(charset, &bytes[i..]) {
+            output.extend_from_slice(&bytes[i..i + len]);
+            i += len;
+            continue;
+        }
+
+        let byte = bytes[i];
+        if no_backslash_escapes {
+            if byte == b'\'' {
+                output.push(b'\'');
+            }
+            output.push(byte);
+        } else {
+            match byte {
+                0 => output.extend_from_slice(b"\\0"),
+                b'\n' => output.extend_from_slice(b"\\n"),
+                b'\r' => output.extend_from_slice(b"\\r"),
+                b'\\' => output.extend_from_slice(b"\\\\"),
+                b'\'' => output.extend_from_slice(b"\\'"),
+                b'"' => output.extend_from_slice(b"\\\""),
+                0x1A => output.extend_from_slice(b"\\Z"),
+                _ => output.push(byte),
+            }
+        }
+        i += 1;
+    }
+
+    // escapes are only inserted before ASCII bytes, i.e. at char boundaries
+    String::from_utf8(output).expect("escaping should preserve UTF-8")
+}
+
+/// Returns a single-quoted SQL string literal for the given string.
+///
+/// **Prefer parameters (see [`crate::Params`]) whenever possible.**
+///
+/// Escaping depends on the state of the connection, the literal is meant for:
+///
+/// * `charset` – connection character set (e.g. `utf8mb4`), multibyte sequences
+///   of which are never broken apart;
+/// * `no_backslash_escapes` – whether `NO_BACKSLASH_ESCAPES` SQL mode is enabled.
+///
+/// Use [`crate::Conn::quote_string`] to take the actual state of a connection into account.
+///
+/// ```
+/// # use mysql_async::quote_string;
+/// assert_eq!(quote_string(r"it's \ ok", "utf8mb4", false), r"'it\'s \\ ok'");
+/// assert_eq!(quote_string(r"it's \ ok", "utf8mb4", true), r"'it''s \ ok'");
+/// ```
+pub fn quote_string(input: &str, charset: &str, no_backslash_escapes: bool) -> String {
+    format!("'{}'", escape_with(input, charset, no_backslash_escapes))
+}
+
+/// Returns a backtick-quoted SQL identifier (e.g. a database, table or column name).
+///
+/// Backticks within the identifier are doubled. Backtick-quoted identifiers are not
+/// affected by SQL mode, so the result is valid regardless of `ANSI_QUOTES`.
+///
+/// ```
+/// assert_eq!(mysql_async::quote_identifier("my`table"), "`my``table`");
+/// ```
+pub fn quote_identifier(name: &str) -> String {
+    let mut output = String::with_capacity(name.len() + 2);
+    output.push('`');
+    for c in name.chars() {
+        if c == '`' {
+            output.push('`');
+        }
+        output.push(c);
+    }
+    output.push('`');
+    output
+}
+
+/// String parameter with an explicit collation, e.g. for a case-sensitive comparison
+/// on a case-insensitive column.
+///
+/// Statement parameters carry values only, so a collation can't be bound along with
+/// a parameter – it must be a part of the query text. This wrapper renders (via `Display`)
+/// a placeholder followed by a `COLLATE` clause with a quoted collation name:
+///
+/// ```text
+/// ? COLLATE `utf8mb4_bin`
+/// ```
+///
+/// and converts into the parameter value (see `From<Collated> for Value`), so the value
+/// itself is never a part of the query text and no escaping is involved.
+///
+/// Note that the collation must be compatible with the connection character set,
+/// since parameters are sent using this character set.
+///
+/// ```rust
+/// # use mysql_async::{prelude::*, test_misc::get_opts, Collated, Conn};
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// let mut conn = Conn::new(get_opts()).await?;
+///
+/// let value = Collated::new("FOO", "utf8mb4_bin");
+/// let query = format!("SELECT 'foo' = {}", value);
+/// assert_eq!(conn.exec_first::<bool, _, _>(query, (value,)).await?, Some(false));
+/// # conn.disconnect().await }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Collated<'a> {
+    value: &'a str,
+    collation: &'a str,
+}
+
+impl<'a> Collated<'a> {
+    /// Creates a string parameter with the given collation (e.g. `utf8mb4_bin`).
+    pub fn new(value: &'a str, collation: &'a str) -> Self {
+        Self { value, collation }
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &'a str {
+        self.value
+    }
+
+    /// Returns the collation name.
+    pub fn collation(&self) -> &'a str {
+        self.collation
+    }
+}
+
+impl fmt::Display for Collated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "? COLLATE {}", quote_identifier(self.collation))
+    }
+}
+
+impl From<Collated<'_>> for Value {
+    fn from(collated: Collated<'_>) -> Self {
+        Value::from(collated.value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{escape_with, quote_identifier, quote_string, Collated};
+    use crate::{prelude::*, test_misc::get_opts, Conn, Value};
+
+    #[test]
+    fn should_escape_string() {
+        assert_eq!(
+            escape_with("it's a \"test\"\\\n\r\0\x1A", "utf8mb4", false),
+            "it\\'s a \\\"test\\\"\\\\\\n\\r\\0\\Z"
+        );
+        assert_eq!(escape_with("ÿ'\\ ĳ", "utf8mb4", false), "ÿ\\'\\\\ ĳ");
+    }
+
+    #[test]
+    fn should_escape_string_if_no_backslash_escapes() {
+        assert_eq!(
+            escape_with("it's a \"test\"\\\n", "utf8mb4", true),
+            "it''s a \"test\"\\\n"
+        );
+    }
+
+    #[test]
+    fn should_not_break_multibyte_characters() {
+        // "縗" is `0xE7 0xB8 0x97` in UTF-8, and `0x97 0x5C` is a valid GBK character,
+        // so escaping the backslash would allow the quote to terminate the literal.
+        assert_eq!(escape_with("縗\\'", "gbk", false), "縗\\\\'");
+        assert_eq!(escape_with("縗\\'", "GBK", true), "縗\\''");
+        assert_eq!(escape_with("縗\\'", "sjis", false), "縗\\\\'");
+        assert_eq!(escape_with("縗\\'", "gb18030", false), "縗\\\\'");
+        assert_eq!(escape_with("縗\\'", "big5", false), "縗\\\\\\'");
+        assert_eq!(escape_with("縗\\'", "utf8mb4", false), "縗\\\\\\'");
+    }
+
+    #[test]
+    fn should_quote_string_literal() {
+        assert_eq!(quote_string("", "utf8mb4", false), "''");
+        assert_eq!(quote_string("foo", "utf8mb4", false), "'foo'");
+        assert_eq!(
+            quote_string("'; DROP TABLE t; --", "utf8mb4", false),
+            "'\\'; DROP TABLE t; --'"
+        );
+        assert_eq!(
+            quote_string("'; DROP TABLE t; --", "utf8mb4", true),
+            "'''; DROP TABLE t; --'"
+        );
+        assert_eq!(quote_string("\\'", "utf8mb4", false), "'\\\\\\''");
+        assert_eq!(quote_string("縗\\'", "gbk", false), "'縗\\\\''");
+    }
+
+    #[test]
+    fn should_quote_identifier() {
+        assert_eq!(quote_identifier("foo"), "`foo`");
+        assert_eq!(quote_identifier(""), "``");
+        assert_eq!(quote_identifier("a`b``c"), "`a``b````c`");
+        assert_eq!(quote_identifier("a'b\"c\\"), "`a'b\"c\\`");
+        assert_eq!(quote_identifier("ĳ"), "`ĳ`");
+    }
+
+    #[test]
+    fn should_render_collated_param() {
+        let collated = Collated::new("it's", "utf8mb4_bin");
+        assert_eq!(collated.to_string(), "? COLLATE `utf8mb4_bin`");
+        assert_eq!(Value::from(collated), Value::Bytes(b"it's".to_vec()));
+
+        assert_eq!(
+            Collated::new("foo", "x` OR 1 -- _bin").to_string(),
+            "? COLLATE `x`` OR 1 -- _bin`"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_compare_collated_strings() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop(
+            "CREATE TEMPORARY TABLE tmp_collated (name VARCHAR(16)) \
+            CHARACTER SET utf8mb4 COLLATE utf8mb4_general_ci",
+        )
+        .await?;
+        conn.query_drop("INSERT INTO tmp_collated VALUES ('foo'), ('FOO')")
+            .await?;
+
+        let query = |value: Collated<'_>| {
+            format!(
+                "SELECT name FROM tmp_collated WHERE name = {} ORDER BY name",
+                value
+            )
+        };
+        let value = Collated::new("FOO", "utf8mb4_general_ci");
+        let names: Vec<String> = conn.exec(query(value), (value,)).await?;
+        assert_eq!(names.len(), 2);
+        let value = Collated::new("FOO", "utf8mb4_bin");
+        let names: Vec<String> = conn.exec(query(value), (value,)).await?;
+        assert_eq!(names, vec!["FOO"]);
+
+        // the value never gets into the query text
+        conn.query_drop("SET SESSION sql_mode = 'NO_BACKSLASH_ESCAPES'")
+            .await?;
+        let value = Collated::new("\\' OR 1 = 1 -- ", "utf8mb4_bin");
+        let names: Vec<String> = conn.exec(query(value), (value,)).await?;
+        assert!(names.is_empty());
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
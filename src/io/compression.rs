@@ -0,0 +1,494 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Streaming codec for the compressed protocol.
+
+use bytes::{Buf, BufMut, BytesMut};
+use flate2::{write::ZlibEncoder, Decompress, FlushDecompress};
+use mysql_common::{
+    constants::MAX_PAYLOAD_LEN,
+    proto::codec::{
+        error::PacketCodecError, packet_to_chunks, ChunkDecoder, ChunkInfo, Compression,
+    },
+};
+
+use std::{
+    cmp::min,
+    io::{self, Write},
+};
+
+/// Maximum number of bytes decompressed at once.
+///
+/// A compressed packet may carry up to 16MiB of plain data, so it is decompressed
+/// in steps of this size as plain packets are consumed.
+pub(crate) const DECOMPRESS_STEP: usize = 64 * 1024;
+
+/// State of a compressed packet being decoded.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum CompState {
+    /// Waiting for a compressed packet header.
+    Idle,
+    /// Reading a compressed packet, that wasn't actually compressed.
+    Uncompressed { remaining: usize },
+    /// Decompressing a compressed packet.
+    Compressed {
+        remaining_in: usize,
+        remaining_out: usize,
+    },
+}
+
+/// Codec for the compressed protocol, that decompresses incoming data on demand.
+///
+/// Unlike the codec of `mysql_common`, that decompresses every compressed packet as a whole,
+/// this one keeps at most [`DECOMPRESS_STEP`] bytes of decompressed data (plus a plain packet
+/// being decoded) in memory, so large compressed result sets don't spike memory usage.
+#[derive(Debug)]
+pub(crate) struct CompPacketCodec {
+    /// Compression level for outgoing packets.
+    level: Compression,
+    /// Outgoing chunks shorter than this are sent uncompressed.
+    threshold: usize,
+    /// Compressed packet sequence id.
+    comp_seq_id: u8,
+    /// Plain packet sequence id.
+    seq_id: u8,
+    /// Decompressed data, that is not yet decoded into plain packets.
+    in_buf: BytesMut,
+    /// Buffer for plain outgoing data.
+    out_buf: BytesMut,
+    /// Scratch buffer for the decompressor output.
+    scratch: Vec<u8>,
+    state: CompState,
+    inflate: Decompress,
+    chunk_decoder: ChunkDecoder,
+}
+
+impl CompPacketCodec {
+    pub(crate) fn new(level: Compression, threshold: usize) -> Self {
+        Self {
+            level,
+            threshold,
+            comp_seq_id: 0,
+            seq_id: 0,
+            in_buf: BytesMut::new(),
+            out_buf: BytesMut::new(),
+            scratch: Vec::with_capacity(DECOMPRESS_STEP),
+            state: CompState::Idle,
+            inflate: Decompress::new(true),
+            chunk_decoder: ChunkDecoder::default(),
+        }
+    }
+
+    pub(crate) fn set_level(&mut self, level: Compression, threshold: usize) {
+        self.level = level;
+        self.threshold = threshold;
+    }
+
+    /// Returns compression level for outgoing packets.
+    pub(crate) fn level(&self) -> Compression {
+        self.level
+    }
+
+    /// Sets sequence ids to `0`.
+    pub(crate) fn reset_seq_id(&mut self) {
+        self.comp_seq_id = 0;
+        self.seq_id = 0;
+    }
+
+    /// Overwrites plain sequence id with compressed sequence id
+    /// if on compressed packet boundary.
+    pub(crate) fn sync_seq_id(&mut self) {
+        if self.state == CompState::Idle && self.in_buf.is_empty() {
+            self.seq_id = self.comp_seq_id;
+        }
+    }
+
+    /// Will try to decode a plain packet from `src` into `dst`.
+    ///
+    /// Returns `true` if `dst` contains a full packet.
+    pub(crate) fn decode<T>(
+        &mut self,
+        src: &mut BytesMut,
+        dst: &mut T,
+        max_allowed_packet: usize,
+    ) -> Result<bool, PacketCodecError>
+    where
+        T: AsRef<[u8]>,
+        T: BufMut,
+    {
+        loop {
+            if !self.in_buf.is_empty() && self.decode_plain(dst, max_allowed_packet)? {
+                return Ok(true);
+            }
+
+            if !self.feed(src, max_allowed_packet)? {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Decodes a plain packet from decompressed data.
+    fn decode_plain<T>(
+        &mut self,
+        dst: &mut T,
+        max_allowed_packet: usize,
+    ) -> Result<bool, PacketCodecError>
+    where
+        T: AsRef<[u8]>,
+        T: BufMut,
+    {
+        while let Some(chunk_info) =
+            self.chunk_decoder
+                .decode(&mut self.in_buf, dst, max_allowed_packet)?
+        {
+            let (seq_id, is_last) = match chunk_info {
+                ChunkInfo::Middle(seq_id) => (seq_id, false),
+                ChunkInfo::Last(seq_id) => (seq_id, true),
+            };
+
+            if self.seq_id != seq_id {
+                // the server could sync the sequence id of the plain packet
+                // with the id of the last compressed packet
+                if seq_id == self.comp_seq_id.wrapping_sub(1) {
+                    self.seq_id = seq_id;
+                } else {
+                    return Err(PacketCodecError::PacketsOutOfSync);
+                }
+            }
+
+            self.seq_id = self.seq_id.wrapping_add(1);
+
+            if is_last {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Moves data from `src` to the decompressed buffer.
+    ///
+    /// Returns `false` if `src` doesn't have enough data to make progress.
+    fn feed(
+        &mut self,
+        src: &mut BytesMut,
+        max_allowed_packet: usize,
+    ) -> Result<bool, PacketCodecError> {
+        match self.state {
+            CompState::Idle => {
+                if src.len() < 7 {
+                    return Ok(false);
+                }
+
+                let compressed_len = (&src[..3]).get_uint_le(3) as usize;
+                let seq_id = src[3];
+                let uncompressed_len = (&src[4..7]).get_uint_le(3) as usize;
+
+                if compressed_len.max(uncompressed_len) > max_allowed_packet {
+                    return Err(PacketCodecError::PacketTooLarge);
+                }
+                if compressed_len == 0 && uncompressed_len > 0 {
+                    return Err(PacketCodecError::BadCompressedPacketHeader);
+                }
+                if self.comp_seq_id != seq_id {
+                    return Err(PacketCodecError::PacketsOutOfSync);
+                }
+
+                self.comp_seq_id = self.comp_seq_id.wrapping_add(1);
+                src.advance(7);
+
+                if uncompressed_len > 0 {
+                    self.inflate.reset(true);
+                    self.state = CompState::Compressed {
+                        remaining_in: compressed_len,
+                        remaining_out: uncompressed_len,
+                    };
+                } else if compressed_len > 0 {
+                    self.state = CompState::Uncompressed {
+                        remaining: compressed_len,
+                    };
+                }
+
+                Ok(true)
+            }
+            CompState::Uncompressed { remaining } => {
+                let count = min(remaining, src.len());
+                if count == 0 {
+                    return Ok(false);
+                }
+
+                self.in_buf.extend_from_slice(&src[..count]);
+                src.advance(count);
+
+                self.state = match remaining - count {
+                    0 => CompState::Idle,
+                    remaining => CompState::Uncompressed { remaining },
+                };
+
+                Ok(true)
+            }
+            CompState::Compressed {
+                remaining_in,
+                remaining_out,
+            } => {
+                let input = &src[..min(remaining_in, src.len())];
+                let total_in = self.inflate.total_in();
+                let total_out = self.inflate.total_out();
+
+                self.scratch.clear();
+                self.inflate
+                    .decompress_vec(input, &mut self.scratch, FlushDecompress::None)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+                let consumed = (self.inflate.total_in() - total_in) as usize;
+                let produced = (self.inflate.total_out() - total_out) as usize;
+
+                let remaining_out = remaining_out
+                    .checked_sub(produced)
+                    .ok_or(PacketCodecError::BadCompressedPacketHeader)?;
+                let remaining_in = remaining_in - consumed;
+
+                src.advance(consumed);
+                self.in_buf.extend_from_slice(&self.scratch);
+
+                self.state = if remaining_in == 0 {
+                    if remaining_out > 0 {
+                        return Err(PacketCodecError::BadCompressedPacketHeader);
+                    }
+                    CompState::Idle
+                } else {
+                    CompState::Compressed {
+                        remaining_in,
+                        remaining_out,
+                    }
+                };
+
+                Ok(consumed > 0 || produced > 0)
+            }
+        }
+    }
+
+    /// Will encode the given packet into `dst`.
+    pub(crate) fn encode<T: Buf>(
+        &mut self,
+        packet: &mut T,
+        dst: &mut BytesMut,
+        max_allowed_packet: usize,
+    ) -> Result<(), PacketCodecError> {
+        if packet.remaining() > max_allowed_packet {
+            return Err(PacketCodecError::PacketTooLarge);
+        }
+
+        self.seq_id = packet_to_chunks(self.seq_id, packet, &mut self.out_buf);
+        self.comp_seq_id = compress(
+            self.comp_seq_id,
+            self.level,
+            self.threshold,
+            max_allowed_packet,
+            &mut self.out_buf,
+            dst,
+        )?;
+
+        // sync packet number if using compression (see net_serv.cc)
+        self.seq_id = self.comp_seq_id;
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn buffered(&self) -> usize {
+        self.in_buf.len()
+    }
+}
+
+/// Will compress all data from `src` to `dst`.
+///
+/// Same as `mysql_common::proto::codec::compress`, but chunks shorter than `threshold`
+/// are sent uncompressed. Returns the next compressed packet sequence id.
+fn compress(
+    mut seq_id: u8,
+    level: Compression,
+    threshold: usize,
+    max_allowed_packet: usize,
+    src: &mut BytesMut,
+    dst: &mut BytesMut,
+) -> Result<u8, PacketCodecError> {
+    if src.is_empty() {
+        return Ok(0);
+    }
+
+    for chunk in src.chunks(min(MAX_PAYLOAD_LEN, max_allowed_packet)) {
+        dst.reserve(7 + chunk.len());
+
+        if level != Compression::none() && chunk.len() >= threshold {
+            // header is written once the compressed length is known
+            let header_pos = dst.len();
+            dst.put_bytes(0, 7);
+
+            let mut encoder = ZlibEncoder::new(dst.writer(), level);
+            encoder.write_all(chunk)?;
+            let dst_ref = encoder.finish()?.into_inner();
+
+            let compressed_len = dst_ref.len() - header_pos - 7;
+            let mut header = &mut dst_ref[header_pos..header_pos + 7];
+            header.put_uint_le(compressed_len as u64, 3);
+            header.put_u8(seq_id);
+            header.put_uint_le(chunk.len() as u64, 3);
+        } else {
+            dst.put_uint_le(chunk.len() as u64, 3);
+            dst.put_u8(seq_id);
+            dst.put_uint_le(0, 3);
+            dst.put_slice(chunk);
+        }
+
+        seq_id = seq_id.wrapping_add(1);
+    }
+
+    src.clear();
+
+    Ok(seq_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use mysql_common::{
+        constants::DEFAULT_MAX_ALLOWED_PACKET,
+        proto::codec::{Compression, PacketCodec},
+    };
+
+    use super::{CompPacketCodec, DECOMPRESS_STEP};
+    use crate::DEFAULT_COMPRESSION_THRESHOLD;
+
+    fn rows() -> impl Iterator<Item = Vec<u8>> {
+        (0..20_000_u32).map(|i| format!("row number {:08} of a large result set", i).into_bytes())
+    }
+
+    #[test]
+    fn should_decode_large_compressed_packet_in_steps() {
+        // put every row into a single compressed packet
+        let mut plain = BytesMut::new();
+        let mut seq_id = 0;
+        for row in rows() {
+            seq_id =
+                mysql_common::proto::codec::packet_to_chunks(seq_id, &mut &row[..], &mut plain);
+        }
+        assert!(plain.len() > 8 * DECOMPRESS_STEP);
+        let mut src = BytesMut::new();
+        mysql_common::proto::codec::compress(
+            0,
+            Compression::default(),
+            DEFAULT_MAX_ALLOWED_PACKET,
+            &mut plain,
+            &mut src,
+        )
+        .unwrap();
+
+        let mut codec = CompPacketCodec::new(Compression::default(), DEFAULT_COMPRESSION_THRESHOLD);
+        for row in rows() {
+            let mut dst = Vec::new();
+            assert!(codec
+                .decode(&mut src, &mut dst, DEFAULT_MAX_ALLOWED_PACKET)
+                .unwrap());
+            assert_eq!(dst, row);
+            assert!(codec.buffered() < 2 * DECOMPRESS_STEP);
+        }
+
+        let mut dst = Vec::new();
+        assert!(!codec
+            .decode(&mut src, &mut dst, DEFAULT_MAX_ALLOWED_PACKET)
+            .unwrap());
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn should_be_compatible_with_mysql_common_codec() {
+        for level in [Compression::none(), Compression::fast()] {
+            let mut encoder = PacketCodec::default();
+            encoder.compress(level);
+            let mut decoder = CompPacketCodec::new(level, DEFAULT_COMPRESSION_THRESHOLD);
+
+            let mut src = BytesMut::new();
+            for row in rows().take(100) {
+                encoder.encode(&mut &row[..], &mut src).unwrap();
+            }
+            encoder.encode(&mut &[][..], &mut src).unwrap();
+
+            // feed the data byte by byte
+            let mut input = BytesMut::new();
+            let mut decoded = Vec::new();
+            let mut dst = Vec::new();
+            for byte in src.iter() {
+                input.extend_from_slice(&[*byte]);
+                while decoder
+                    .decode(&mut input, &mut dst, DEFAULT_MAX_ALLOWED_PACKET)
+                    .unwrap()
+                {
+                    decoded.push(std::mem::take(&mut dst));
+                }
+            }
+            let mut expected = rows().take(100).collect::<Vec<_>>();
+            expected.push(Vec::new());
+            assert_eq!(decoded, expected);
+
+            // and the other way around
+            let mut encoder = CompPacketCodec::new(level, DEFAULT_COMPRESSION_THRESHOLD);
+            let mut decoder = PacketCodec::default();
+            decoder.compress(level);
+            let mut src = BytesMut::new();
+            for row in rows().take(100) {
+                encoder.reset_seq_id();
+                decoder.reset_seq_id();
+                encoder
+                    .encode(&mut &row[..], &mut src, DEFAULT_MAX_ALLOWED_PACKET)
+                    .unwrap();
+                let mut dst = Vec::new();
+                assert!(decoder.decode(&mut src, &mut dst).unwrap());
+                assert_eq!(dst, row);
+            }
+        }
+    }
+
+    #[test]
+    fn should_send_short_packets_uncompressed() {
+        let short = vec![b'a'; 20];
+        let long = vec![b'a'; 200];
+
+        for (threshold, packet, compressed) in [
+            (DEFAULT_COMPRESSION_THRESHOLD, &short, false),
+            (DEFAULT_COMPRESSION_THRESHOLD, &long, true),
+            (0, &short, true),
+            (1000, &long, false),
+        ] {
+            let mut encoder = CompPacketCodec::new(Compression::fast(), threshold);
+            let mut src = BytesMut::new();
+            encoder
+                .encode(&mut &packet[..], &mut src, DEFAULT_MAX_ALLOWED_PACKET)
+                .unwrap();
+
+            // uncompressed length is zero if the packet isn't compressed
+            let uncompressed_len = src[4..7]
+                .iter()
+                .rev()
+                .fold(0, |acc, x| acc << 8 | *x as usize);
+            assert_eq!(
+                uncompressed_len != 0,
+                compressed,
+                "{} {}",
+                threshold,
+                packet.len()
+            );
+
+            let mut decoder = PacketCodec::default();
+            decoder.compress(Compression::fast());
+            let mut dst = Vec::new();
+            assert!(decoder.decode(&mut src, &mut dst).unwrap());
+            assert_eq!(&dst, packet);
+        }
+    }
+}
@@ -13,15 +13,15 @@ use futures_core::{ready, stream};
 use mysql_common::proto::codec::PacketCodec as PacketCodecInner;
 use pin_project::pin_project;
 use socket2::{Socket as Socket2Socket, TcpKeepalive};
-#[cfg(unix)]
+#[cfg(any(unix, windows))]
 use tokio::io::AsyncWriteExt;
 use tokio::{
     io::{AsyncRead, AsyncWrite, ErrorKind::Interrupted, ReadBuf},
-    net::TcpStream,
+    net::{lookup_host, TcpStream},
 };
 use tokio_util::codec::{Decoder, Encoder, Framed, FramedParts};
 
-#[cfg(unix)]
+#[cfg(any(unix, windows))]
 use std::path::Path;
 use std::{
     fmt,
@@ -31,19 +31,22 @@ use std::{
         ErrorKind::{BrokenPipe, NotConnected, Other},
     },
     mem::replace,
-    ops::{Deref, DerefMut},
+    net::SocketAddr,
+    path::PathBuf,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
 
+use self::compression::CompPacketCodec;
 use crate::{
-    buffer_pool::PooledBuf,
+    buffer_pool::{BufferPool, PooledBuf},
     error::IoError,
     opts::{HostPortOrUrl, SslOpts, DEFAULT_PORT},
 };
 
-#[cfg(unix)]
+#[cfg(any(unix, windows))]
 use crate::io::socket::Socket;
 
 mod tls;
@@ -59,36 +62,72 @@ macro_rules! with_interrupted {
     };
 }
 
+mod compression;
 mod read_packet;
 mod socket;
 mod write_packet;
 
 #[derive(Debug)]
 pub struct PacketCodec {
+    /// Codec for the plain protocol.
     inner: PacketCodecInner,
+    /// Codec for the compressed protocol (if compression is enabled).
+    comp: Option<CompPacketCodec>,
     decode_buf: PooledBuf,
+    buffer_pool: Arc<BufferPool>,
 }
 
-impl Default for PacketCodec {
-    fn default() -> Self {
-        Self {
-            inner: Default::default(),
-            decode_buf: crate::BUFFER_POOL.get(),
+impl PacketCodec {
+    /// Sets sequence id to `0`.
+    pub fn reset_seq_id(&mut self) {
+        match self.comp.as_mut() {
+            Some(comp) => comp.reset_seq_id(),
+            None => self.inner.reset_seq_id(),
+        }
+    }
+
+    /// Overwrites plain sequence id with compressed sequence id.
+    pub fn sync_seq_id(&mut self) {
+        if let Some(comp) = self.comp.as_mut() {
+            comp.sync_seq_id();
+        }
+    }
+
+    /// Turns compression on.
+    ///
+    /// Packets shorter than `threshold` bytes will be sent uncompressed.
+    pub fn compress(&mut self, level: crate::Compression, threshold: usize) {
+        match self.comp.as_mut() {
+            Some(comp) => comp.set_level(level, threshold),
+            None => self.comp = Some(CompPacketCodec::new(level, threshold)),
         }
     }
-}
 
-impl Deref for PacketCodec {
-    type Target = PacketCodecInner;
+    /// Returns compression level, if compression is on.
+    pub fn compression(&self) -> Option<crate::Compression> {
+        self.comp.as_ref().map(CompPacketCodec::level)
+    }
+
+    /// Sets maximum size of a packet for this codec.
+    pub fn set_max_allowed_packet(&mut self, max_allowed_packet: usize) {
+        self.inner.max_allowed_packet = max_allowed_packet;
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+    /// Returns maximum size of a packet for this codec.
+    pub fn max_allowed_packet(&self) -> usize {
+        self.inner.max_allowed_packet
     }
 }
 
-impl DerefMut for PacketCodec {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+impl PacketCodec {
+    /// Creates a codec, that takes buffers from the given pool.
+    pub fn new(buffer_pool: Arc<BufferPool>) -> Self {
+        Self {
+            inner: Default::default(),
+            comp: None,
+            decode_buf: buffer_pool.get(),
+            buffer_pool,
+        }
     }
 }
 
@@ -97,8 +136,14 @@ impl Decoder for PacketCodec {
     type Error = IoError;
 
     fn decode(&mut self, src: &mut BytesMut) -> std::result::Result<Option<Self::Item>, IoError> {
-        if self.inner.decode(src, self.decode_buf.as_mut())? {
-            let new_buf = crate::BUFFER_POOL.get();
+        let decoded = match self.comp.as_mut() {
+            Some(comp) => {
+                comp.decode(src, self.decode_buf.as_mut(), self.inner.max_allowed_packet)?
+            }
+            None => self.inner.decode(src, self.decode_buf.as_mut())?,
+        };
+        if decoded {
+            let new_buf = self.buffer_pool.get();
             Ok(Some(replace(&mut self.decode_buf, new_buf)))
         } else {
             Ok(None)
@@ -110,7 +155,12 @@ impl Encoder<PooledBuf> for PacketCodec {
     type Error = IoError;
 
     fn encode(&mut self, item: PooledBuf, dst: &mut BytesMut) -> std::result::Result<(), IoError> {
-        Ok(self.inner.encode(&mut item.as_ref(), dst)?)
+        match self.comp.as_mut() {
+            Some(comp) => {
+                Ok(comp.encode(&mut item.as_ref(), dst, self.inner.max_allowed_packet)?)
+            }
+            None => Ok(self.inner.encode(&mut item.as_ref(), dst)?),
+        }
     }
 }
 
@@ -122,10 +172,38 @@ pub(crate) enum Endpoint {
     Secure(#[pin] tokio_native_tls::TlsStream<TcpStream>),
     #[cfg(feature = "rustls-tls")]
     Secure(#[pin] tokio_rustls::client::TlsStream<tokio::net::TcpStream>),
-    #[cfg(unix)]
+    #[cfg(any(unix, windows))]
     Socket(#[pin] Socket),
 }
 
+/// Address of either end of a connection (see [`crate::Conn::peer_addr`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EndpointAddr {
+    /// TCP connection address.
+    Tcp(SocketAddr),
+    /// Path of a unix socket (or of a named pipe on Windows).
+    Socket(PathBuf),
+}
+
+impl EndpointAddr {
+    /// Returns the address of a TCP connection.
+    pub fn as_tcp(&self) -> Option<SocketAddr> {
+        match self {
+            EndpointAddr::Tcp(addr) => Some(*addr),
+            EndpointAddr::Socket(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for EndpointAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EndpointAddr::Tcp(addr) => addr.fmt(f),
+            EndpointAddr::Socket(path) => path.display().fmt(f),
+        }
+    }
+}
+
 /// This future will check that TcpStream is live.
 ///
 /// This check is similar to a one, implemented by GitHub team for the go-sql-driver/mysql.
@@ -153,7 +231,7 @@ impl Future for CheckTcpStream<'_> {
 }
 
 impl Endpoint {
-    #[cfg(unix)]
+    #[cfg(any(unix, windows))]
     fn is_socket(&self) -> bool {
         matches!(self, Self::Socket(_))
     }
@@ -177,7 +255,7 @@ impl Endpoint {
                 CheckTcpStream(stream).await?;
                 Ok(())
             }
-            #[cfg(unix)]
+            #[cfg(any(unix, windows))]
             Endpoint::Socket(socket) => {
                 let _ = socket.write(&[]).await?;
                 Ok(())
@@ -203,6 +281,41 @@ impl Endpoint {
         )
     }
 
+    /// Returns the underlying TCP stream (`None` for a socket).
+    fn tcp_stream(&self) -> Option<&TcpStream> {
+        match self {
+            Endpoint::Plain(stream) => stream.as_ref(),
+            #[cfg(feature = "native-tls-tls")]
+            Endpoint::Secure(stream) => Some(stream.get_ref().get_ref().get_ref()),
+            #[cfg(feature = "rustls-tls")]
+            Endpoint::Secure(stream) => Some(stream.get_ref().0),
+            #[cfg(any(unix, windows))]
+            Endpoint::Socket(_) => None,
+        }
+    }
+
+    /// Returns the address of the remote end of this endpoint.
+    pub fn peer_addr(&self) -> io::Result<EndpointAddr> {
+        #[cfg(any(unix, windows))]
+        if let Endpoint::Socket(socket) = self {
+            return Ok(EndpointAddr::Socket(socket.path().to_owned()));
+        }
+        match self.tcp_stream() {
+            Some(stream) => stream.peer_addr().map(EndpointAddr::Tcp),
+            None => Err(io::Error::new(NotConnected, "not connected")),
+        }
+    }
+
+    /// Returns the address of the local end of this endpoint.
+    ///
+    /// Client end of a socket is unnamed, so its address is the path of the socket.
+    pub fn local_addr(&self) -> io::Result<EndpointAddr> {
+        match self.tcp_stream() {
+            Some(stream) => stream.local_addr().map(EndpointAddr::Tcp),
+            None => self.peer_addr(),
+        }
+    }
+
     pub fn set_tcp_nodelay(&self, val: bool) -> io::Result<()> {
         match *self {
             Endpoint::Plain(Some(ref stream)) => stream.set_nodelay(val)?,
@@ -216,7 +329,7 @@ impl Endpoint {
                 let stream = stream.get_ref().0;
                 stream.set_nodelay(val)?;
             }
-            #[cfg(unix)]
+            #[cfg(any(unix, windows))]
             Endpoint::Socket(_) => (/* inapplicable */),
         }
         Ok(())
@@ -229,7 +342,7 @@ impl From<TcpStream> for Endpoint {
     }
 }
 
-#[cfg(unix)]
+#[cfg(any(unix, windows))]
 impl From<Socket> for Endpoint {
     fn from(socket: Socket) -> Self {
         Endpoint::Socket(socket)
@@ -262,7 +375,7 @@ impl AsyncRead for Endpoint {
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_read(cx, buf),
             #[cfg(feature = "rustls-tls")]
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_read(cx, buf),
-            #[cfg(unix)]
+            #[cfg(any(unix, windows))]
             EndpointProj::Socket(ref mut stream) => stream.as_mut().poll_read(cx, buf),
         })
     }
@@ -283,7 +396,7 @@ impl AsyncWrite for Endpoint {
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_write(cx, buf),
             #[cfg(feature = "rustls-tls")]
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_write(cx, buf),
-            #[cfg(unix)]
+            #[cfg(any(unix, windows))]
             EndpointProj::Socket(ref mut stream) => stream.as_mut().poll_write(cx, buf),
         })
     }
@@ -301,7 +414,7 @@ impl AsyncWrite for Endpoint {
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_flush(cx),
             #[cfg(feature = "rustls-tls")]
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_flush(cx),
-            #[cfg(unix)]
+            #[cfg(any(unix, windows))]
             EndpointProj::Socket(ref mut stream) => stream.as_mut().poll_flush(cx),
         })
     }
@@ -319,7 +432,7 @@ impl AsyncWrite for Endpoint {
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_shutdown(cx),
             #[cfg(feature = "rustls-tls")]
             EndpointProj::Secure(ref mut stream) => stream.as_mut().poll_shutdown(cx),
-            #[cfg(unix)]
+            #[cfg(any(unix, windows))]
             EndpointProj::Socket(ref mut stream) => stream.as_mut().poll_shutdown(cx),
         })
     }
@@ -342,29 +455,33 @@ impl fmt::Debug for Stream {
 }
 
 impl Stream {
-    #[cfg(unix)]
-    fn new<T: Into<Endpoint>>(endpoint: T) -> Self {
+    #[cfg(any(unix, windows))]
+    fn new<T: Into<Endpoint>>(endpoint: T, buffer_pool: Arc<BufferPool>) -> Self {
         let endpoint = endpoint.into();
 
         Self {
             closed: false,
-            codec: Box::new(Framed::new(endpoint, PacketCodec::default())).into(),
+            codec: Box::new(Framed::new(endpoint, PacketCodec::new(buffer_pool))).into(),
+        }
+    }
+
+    /// Resolves the given address into a list of socket addresses.
+    pub(crate) async fn resolve(addr: &HostPortOrUrl) -> io::Result<Vec<SocketAddr>> {
+        match addr {
+            HostPortOrUrl::HostPort(host, port) => {
+                Ok(lookup_host((host.as_str(), *port)).await?.collect())
+            }
+            HostPortOrUrl::Url(url) => url.socket_addrs(|| Some(DEFAULT_PORT)),
         }
     }
 
+    /// Connects to the first reachable address among `addrs` (see [`Stream::resolve`]).
     pub(crate) async fn connect_tcp(
-        addr: &HostPortOrUrl,
+        addrs: &[SocketAddr],
         keepalive: Option<Duration>,
+        buffer_pool: Arc<BufferPool>,
     ) -> io::Result<Stream> {
-        let tcp_stream = match addr {
-            HostPortOrUrl::HostPort(host, port) => {
-                TcpStream::connect((host.as_str(), *port)).await?
-            }
-            HostPortOrUrl::Url(url) => {
-                let addrs = url.socket_addrs(|| Some(DEFAULT_PORT))?;
-                TcpStream::connect(&*addrs).await?
-            }
-        };
+        let tcp_stream = TcpStream::connect(addrs).await?;
 
         if let Some(duration) = keepalive {
             #[cfg(unix)]
@@ -385,19 +502,38 @@ impl Stream {
 
         Ok(Stream {
             closed: false,
-            codec: Box::new(Framed::new(tcp_stream.into(), PacketCodec::default())).into(),
+            codec: Box::new(Framed::new(
+                tcp_stream.into(),
+                PacketCodec::new(buffer_pool),
+            ))
+            .into(),
         })
     }
 
-    #[cfg(unix)]
-    pub(crate) async fn connect_socket<P: AsRef<Path>>(path: P) -> io::Result<Stream> {
-        Ok(Stream::new(Socket::new(path).await?))
+    #[cfg(any(unix, windows))]
+    pub(crate) async fn connect_socket<P: AsRef<Path>>(
+        path: P,
+        buffer_pool: Arc<BufferPool>,
+    ) -> io::Result<Stream> {
+        Ok(Stream::new(Socket::new(path).await?, buffer_pool))
     }
 
     pub(crate) fn set_tcp_nodelay(&self, val: bool) -> io::Result<()> {
         self.codec.as_ref().unwrap().get_ref().set_tcp_nodelay(val)
     }
 
+    pub(crate) fn peer_addr(&self) -> Option<EndpointAddr> {
+        self.codec
+            .as_ref()
+            .and_then(|codec| codec.get_ref().peer_addr().ok())
+    }
+
+    pub(crate) fn local_addr(&self) -> Option<EndpointAddr> {
+        self.codec
+            .as_ref()
+            .and_then(|codec| codec.get_ref().local_addr().ok())
+    }
+
     pub(crate) async fn make_secure(
         &mut self,
         domain: String,
@@ -416,7 +552,7 @@ impl Stream {
         self.codec.as_ref().unwrap().get_ref().is_secure()
     }
 
-    #[cfg(unix)]
+    #[cfg(any(unix, windows))]
     pub(crate) fn is_socket(&self) -> bool {
         self.codec.as_ref().unwrap().get_ref().is_socket()
     }
@@ -435,16 +571,28 @@ impl Stream {
 
     pub(crate) fn set_max_allowed_packet(&mut self, max_allowed_packet: usize) {
         if let Some(codec) = self.codec.as_mut() {
-            codec.codec_mut().max_allowed_packet = max_allowed_packet;
+            codec.codec_mut().set_max_allowed_packet(max_allowed_packet);
         }
     }
 
-    pub(crate) fn compress(&mut self, level: crate::Compression) {
+    pub(crate) fn max_allowed_packet(&self) -> Option<usize> {
+        self.codec
+            .as_ref()
+            .map(|codec| codec.codec().max_allowed_packet())
+    }
+
+    pub(crate) fn compress(&mut self, level: crate::Compression, threshold: usize) {
         if let Some(codec) = self.codec.as_mut() {
-            codec.codec_mut().compress(level);
+            codec.codec_mut().compress(level, threshold);
         }
     }
 
+    pub(crate) fn compression(&self) -> Option<crate::Compression> {
+        self.codec
+            .as_ref()
+            .and_then(|codec| codec.codec().compression())
+    }
+
     /// Checks, that connection is alive.
     pub(crate) async fn check(&mut self) -> std::result::Result<(), IoError> {
         if let Some(codec) = self.codec.as_mut() {
@@ -6,14 +6,14 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-#![cfg(unix)]
+#![cfg(any(unix, windows))]
 
 use pin_project::pin_project;
 use tokio::io::{Error, ErrorKind::Interrupted, ReadBuf};
 
 use std::{
     io,
-    path::Path,
+    path::{Path, PathBuf},
     pin::Pin,
     task::{Context, Poll},
 };
@@ -26,6 +26,11 @@ pub(crate) struct Socket {
     #[pin]
     #[cfg(unix)]
     inner: tokio::net::UnixStream,
+    #[pin]
+    #[cfg(windows)]
+    inner: tokio::net::windows::named_pipe::NamedPipeClient,
+    /// Path, this socket is connected to.
+    path: PathBuf,
 }
 
 impl Socket {
@@ -33,9 +38,60 @@ impl Socket {
     #[cfg(unix)]
     pub async fn new<P: AsRef<Path>>(path: P) -> Result<Socket, io::Error> {
         Ok(Socket {
-            inner: tokio::net::UnixStream::connect(path).await?,
+            inner: tokio::net::UnixStream::connect(path.as_ref()).await?,
+            path: path.as_ref().to_owned(),
         })
     }
+
+    /// Connects a new named pipe.
+    ///
+    /// `path` is either a full pipe path (e.g. `\\.\pipe\MySQL`) or a bare pipe name
+    /// (e.g. `MySQL`), in which case it is looked up on the local machine.
+    #[cfg(windows)]
+    pub async fn new<P: AsRef<Path>>(path: P) -> Result<Socket, io::Error> {
+        use std::{ffi::OsString, time::Duration};
+        use tokio::net::windows::named_pipe::ClientOptions;
+
+        // ERROR_PIPE_BUSY: all pipe instances are busy, so wait and retry
+        // (see `ClientOptions::open` docs)
+        const ERROR_PIPE_BUSY: i32 = 231;
+        const BUSY_RETRIES: usize = 20;
+        const BUSY_DELAY: Duration = Duration::from_millis(50);
+
+        let path = path.as_ref().as_os_str();
+        let path = if path.to_string_lossy().starts_with(r"\\") {
+            path.to_owned()
+        } else {
+            let mut full = OsString::from(r"\\.\pipe\");
+            full.push(path);
+            full
+        };
+
+        let mut retries = 0;
+        loop {
+            match ClientOptions::new().open(&path) {
+                Ok(inner) => {
+                    return Ok(Socket {
+                        inner,
+                        path: PathBuf::from(path),
+                    })
+                }
+                Err(err) if err.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                    if retries == BUSY_RETRIES {
+                        return Err(err);
+                    }
+                    retries += 1;
+                }
+                Err(err) => return Err(err),
+            }
+            tokio::time::sleep(BUSY_DELAY).await;
+        }
+    }
+
+    /// Returns the path, this socket is connected to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 }
 
 impl AsyncRead for Socket {
@@ -3,7 +3,7 @@
 use std::{convert::TryInto, sync::Arc};
 
 use rustls::{
-    client::{ServerCertVerifier, WebPkiVerifier},
+    client::{Resumption, ServerCertVerifier, WebPkiVerifier},
     Certificate, ClientConfig, OwnedTrustAnchor, RootCertStore,
 };
 
@@ -61,6 +61,12 @@ impl Endpoint {
             config_builder.with_no_client_auth()
         };
 
+        config.resumption = if ssl_opts.session_resumption() {
+            Resumption::store(ssl_opts.session_cache().store())
+        } else {
+            Resumption::disabled()
+        };
+
         let server_name = domain
             .as_str()
             .try_into()
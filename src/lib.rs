@@ -50,7 +50,7 @@
 //!     -   `flate2/zlib"
 //!     -   `mysql_common/bigdecimal03`
 //!     -   `mysql_common/rust_decimal`
-//!     -   `mysql_common/time03`
+//!     -   `time`
 //!     -   `mysql_common/uuid`
 //!     -   `mysql_common/frunk`
 //!     -   `binlog`
@@ -94,8 +94,30 @@
 //!     mysql_async = { version = "*", features = ["tracing"] }
 //!     ```
 //!
+//! *   `pool-latency` – enables tracking of `get_conn` latency
+//!     (see `PoolMetrics::get_conn_p50`).
+//!
+//!     **Example:**
+//!
+//!     ```toml
+//!     [dependencies]
+//!     mysql_async = { version = "*", features = ["pool-latency"] }
+//!     ```
+//!
+//! *   `metrics` – enables export of pool metrics via the `metrics` crate facade
+//!     (see `PoolOpts::with_name`).
+//!
+//!     **Example:**
+//!
+//!     ```toml
+//!     [dependencies]
+//!     mysql_async = { version = "*", features = ["metrics"] }
+//!     ```
+//!
 //! *   `derive` – enables `mysql_commom/derive` feature
 //!
+//! *   `time` – enables `mysql_common/time` feature (conversions for the `time` crate types)
+//!
 //! *   `binlog` - enables binlog-related functionality. Enables:
 //!
 //!     -   `mysql_common/binlog"
@@ -240,6 +262,38 @@
 //!     This function is useful to probe conversion in cases, where source database schema
 //!     is unknown.
 //!
+//! ## Date and time values
+//!
+//! MySql stores up to six fractional digits (i.e. microseconds) of `TIME`, `DATETIME`
+//! and `TIMESTAMP` values, but only if the column declares them (e.g. `DATETIME(6)`) –
+//! otherwise the server rounds the value to the declared precision. Within that limit
+//! conversions are lossless in both protocols:
+//!
+//! *   the binary protocol transfers the microseconds part as is;
+//! *   the text protocol transfers a string with up to six fractional digits, that is parsed
+//!     back by `FromValue` implementations.
+//!
+//! Sub-microsecond part of a Rust value (e.g. nanoseconds of `time::PrimitiveDateTime`)
+//! is truncated on conversion into a `Value`.
+//!
+//! `std::time::SystemTime` can't implement `FromValue` directly, so use the [`UtcDateTime`]
+//! wrapper, that binds and retrieves it as a UTC `DATETIME`.
+//!
+//! ## Bit values
+//!
+//! `BIT(n)` values are given out as big-endian bytes, use the [`BitValue`] wrapper to convert
+//! them to (and from) an `u64`.
+//!
+//! ## Time values
+//!
+//! `TIME` values may be negative, so they don't always convert to `std::time::Duration`.
+//! Use the [`SignedDuration`] wrapper to convert them losslessly.
+//!
+//! ## `ENUM` and `SET` values
+//!
+//! `ENUM` and `SET` values are given out as strings. Implement [`prelude::MySqlEnum`]
+//! for a Rust enum to read and bind it via the [`EnumValue`] and [`SetValue`] wrappers.
+//!
 //! # MySql query protocols
 //!
 //! ## Text protocol
@@ -262,7 +316,21 @@
 //! is expected, i.e. you can't execute something like `SELECT ... WHERE id IN ?`
 //! with a vector as a parameter. You'll need to build a query that looks like
 //! `SELECT ... WHERE id IN (?, ?, ...)` and to pass each vector element as
-//! a parameter.
+//! a parameter. [`QueryWithParams::bind_in`] does this for a named parameter, e.g.
+//! `"SELECT ... WHERE id IN (:ids)".with(()).bind_in("ids", &ids)?`.
+//!
+//! **Note:** `Vec<u8>` and `&[u8]` parameters are always bound as a single BLOB value
+//! (i.e. `Value::Bytes`), be it an element of a tuple, a `Vec<Value>` or a `params!` value.
+//! But `Vec<T>` converts to positional parameters itself, so if `Vec<u8>` is passed
+//! as a whole set of parameters, then each byte will become a separate parameter.
+//! Use a single-element tuple to bind it as a BLOB:
+//!
+//! ```rust
+//! # use mysql_async::{Params, Value};
+//! let blob = vec![0_u8, 1, 2];
+//! assert_eq!(Params::from((blob.clone(),)), Params::Positional(vec![Value::Bytes(blob.clone())]));
+//! assert_eq!(Params::from(blob), Params::Positional(vec![0_u8.into(), 1_u8.into(), 2_u8.into()])); // three parameters!
+//! ```
 //!
 //! # Named parameters
 //!
@@ -392,6 +460,29 @@
 //!
 //! [1]: https://dev.mysql.com/doc/refman/8.0/en/load-data-local-security.html
 //!
+//! # Buffer pool
+//!
+//! The driver keeps a global pool of I/O buffers that are reused across connections
+//! to avoid an allocation per packet. It is configured using the following environment
+//! variables, that are read once, at the first use of the pool:
+//!
+//! * `MYSQL_ASYNC_BUFFER_POOL_CAP` – maximum number of pooled buffers (defaults to `128`).
+//!   Set it to `0` to disable pooling – every buffer will be allocated on use and freed
+//!   as soon as it is dropped;
+//! * `MYSQL_ASYNC_BUFFER_SIZE_CAP` – buffers larger than this will be shrunk before
+//!   returning to the pool (defaults to `4MiB`);
+//! * `MYSQL_ASYNC_BUFFER_INIT_CAP` – initial capacity of a newly allocated buffer
+//!   (defaults to `0`).
+//!
+//! Disabling the pool trades an allocation (and reallocations while the buffer grows)
+//! per packet for a lower resident memory footprint, since up to
+//! `MYSQL_ASYNC_BUFFER_POOL_CAP * MYSQL_ASYNC_BUFFER_SIZE_CAP` bytes may otherwise stay
+//! allocated. It also makes allocation patterns easier to inspect with memory profilers.
+//!
+//! Pooling may also be disabled per connection using [`OptsBuilder::buffer_strategy`]
+//! with [`BufferStrategy::Unpooled`] – such a connection won't touch (nor initialize)
+//! the global pool, which is useful for environments, where a global static is undesirable.
+//!
 //! # Testing
 //!
 //! Tests uses followin environment variables:
@@ -432,6 +523,7 @@ pub use mysql_common::{constants as consts, params};
 
 use std::sync::Arc;
 
+mod bit_value;
 mod buffer_pool;
 
 #[macro_use]
@@ -441,13 +533,19 @@ mod tracing_utils;
 mod macros;
 mod conn;
 mod connection_like;
+mod enum_value;
 /// Errors used in this crate
 mod error;
+mod escape;
 mod io;
 mod local_infile_handler;
 mod opts;
 mod query;
 mod queryable;
+mod row_ext;
+mod signed_duration;
+mod system_time;
+mod value_ext;
 
 type BoxFuture<'a, T> = futures_core::future::BoxFuture<'a, Result<T>>;
 
@@ -456,13 +554,40 @@ static BUFFER_POOL: once_cell::sync::Lazy<Arc<crate::buffer_pool::BufferPool>> =
 
 #[cfg(feature = "binlog")]
 #[doc(inline)]
-pub use self::conn::binlog_stream::{request::BinlogStreamRequest, BinlogStream};
+pub use self::conn::binlog_stream::{
+    request::BinlogStreamRequest, BinlogStream, BinlogStreamEvent, ReconnectPolicy,
+    ResilientBinlogStream, RowChange,
+};
 
 #[doc(inline)]
 pub use self::conn::Conn;
 
 #[doc(inline)]
-pub use self::conn::pool::Pool;
+pub use self::conn::alter_progress::AlterProgress;
+
+#[doc(inline)]
+pub use self::conn::connect_stats::ConnectStats;
+
+#[doc(inline)]
+pub use self::conn::flavor::ServerFlavor;
+
+#[doc(inline)]
+pub use self::conn::flush::FlushOption;
+
+#[doc(inline)]
+pub use self::conn::long_data::LongData;
+
+#[doc(inline)]
+pub use self::conn::warning::{Warning, WarningLevel};
+
+#[doc(inline)]
+pub use self::io::EndpointAddr;
+
+#[doc(inline)]
+pub use self::conn::pool::{
+    CloseReason, Pool, PoolBuilder, PoolEvent, PoolEventHandler, PoolMetrics, PoolStatus,
+    ReaderSelection, ReplicatedPool, SessionProfile,
+};
 
 #[doc(inline)]
 pub use self::error::{
@@ -470,7 +595,7 @@ pub use self::error::{
 };
 
 #[doc(inline)]
-pub use self::query::QueryWithParams;
+pub use self::query::{BatchFailure, BatchOpts, BatchProgress, BatchReport, QueryWithParams};
 
 #[doc(inline)]
 pub use self::queryable::transaction::IsolationLevel;
@@ -481,9 +606,10 @@ pub use self::opts::ClientIdentity;
 
 #[doc(inline)]
 pub use self::opts::{
-    ChangeUserOpts, Opts, OptsBuilder, PoolConstraints, PoolOpts, SslOpts,
-    DEFAULT_INACTIVE_CONNECTION_TTL, DEFAULT_POOL_CONSTRAINTS, DEFAULT_STMT_CACHE_SIZE,
-    DEFAULT_TTL_CHECK_INTERVAL,
+    BufferStrategy, ChangeUserOpts, CredentialProvider, Opts, OptsBuilder, PoolConstraints,
+    PoolOpts, SlowQueryHandler, SslOpts, CON_COUNT_ERROR_BACKOFF, CON_COUNT_ERROR_RETRIES,
+    DEFAULT_COMPRESSION_THRESHOLD, DEFAULT_INACTIVE_CONNECTION_TTL, DEFAULT_POOL_CONSTRAINTS,
+    DEFAULT_ROW_PREFETCH, DEFAULT_STMT_CACHE_SIZE, DEFAULT_TTL_CHECK_INTERVAL,
 };
 
 #[doc(inline)]
@@ -529,7 +655,15 @@ pub use mysql_common::value::convert::{from_value, from_value_opt, FromValueErro
 pub use mysql_common::value::json::{Deserialized, Serialized};
 
 #[doc(inline)]
-pub use self::queryable::query_result::{result_set_stream::ResultSetStream, QueryResult};
+pub use self::queryable::query_result::{
+    result_set_stream::ResultSetStream, Columns, CsvOpts, CsvQuoting, QueryResult,
+};
+
+#[doc(inline)]
+pub use self::escape::{quote_identifier, quote_string, Collated};
+
+#[doc(inline)]
+pub use self::queryable::cursor::Cursor;
 
 #[doc(inline)]
 pub use self::queryable::transaction::{Transaction, TxOpts};
@@ -540,6 +674,18 @@ pub use self::queryable::{BinaryProtocol, TextProtocol};
 #[doc(inline)]
 pub use self::queryable::stmt::Statement;
 
+#[doc(inline)]
+pub use self::system_time::UtcDateTime;
+
+#[doc(inline)]
+pub use self::bit_value::BitValue;
+
+#[doc(inline)]
+pub use self::signed_duration::SignedDuration;
+
+#[doc(inline)]
+pub use self::enum_value::{EnumValue, SetValue};
+
 /// Futures used in this crate
 pub mod futures {
     pub use crate::conn::pool::futures::{DisconnectPool, GetConn};
@@ -547,6 +693,8 @@ pub mod futures {
 
 /// Traits used in this crate
 pub mod prelude {
+    #[doc(inline)]
+    pub use crate::enum_value::MySqlEnum;
     #[doc(inline)]
     pub use crate::local_infile_handler::GlobalHandler;
     #[doc(inline)]
@@ -554,7 +702,15 @@ pub mod prelude {
     #[doc(inline)]
     pub use crate::query::{BatchQuery, Query, WithParams};
     #[doc(inline)]
+    pub use crate::queryable::query_result::FromResultSets;
+    #[doc(inline)]
     pub use crate::queryable::Queryable;
+    #[cfg(feature = "binlog")]
+    #[doc(inline)]
+    pub use crate::row_ext::BinlogRowExt;
+    #[doc(inline)]
+    pub use crate::row_ext::RowExt;
+    pub use crate::value_ext::ValueExt;
     #[doc(inline)]
     pub use mysql_common::prelude::FromRow;
     #[doc(inline)]
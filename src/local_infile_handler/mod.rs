@@ -7,14 +7,19 @@
 // modified, or distributed except according to those terms.
 
 use bytes::Bytes;
-use futures_core::stream::BoxStream;
+use futures_core::{
+    ready,
+    stream::{BoxStream, Stream},
+};
 
 use std::{
     fmt,
+    pin::Pin,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc,
     },
+    task::{Context, Poll},
 };
 
 use crate::error::LocalInfileError;
@@ -88,3 +93,85 @@ impl fmt::Debug for GlobalHandlerObject {
         f.debug_tuple("GlobalHandlerObject").field(&"..").finish()
     }
 }
+
+/// Wraps infile data and reports the cumulative number of bytes sent to the server
+/// (see [`crate::Conn::set_infile_handler_with_progress`]).
+///
+/// A chunk is considered sent once the next chunk is requested, because the driver
+/// writes a chunk before polling for the next one.
+pub(crate) struct InfileProgress<F> {
+    inner: InfileData,
+    sent: u64,
+    pending: u64,
+    callback: F,
+}
+
+impl<F> InfileProgress<F> {
+    pub(crate) fn new(inner: InfileData, callback: F) -> Self {
+        Self {
+            inner,
+            sent: 0,
+            pending: 0,
+            callback,
+        }
+    }
+}
+
+impl<F> Stream for InfileProgress<F>
+where
+    F: FnMut(u64) + Unpin,
+{
+    type Item = std::io::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if this.pending > 0 {
+            this.sent += std::mem::take(&mut this.pending);
+            (this.callback)(this.sent);
+        }
+
+        let item = ready!(this.inner.as_mut().poll_next(cx));
+        if let Some(Ok(ref bytes)) = item {
+            this.pending = bytes.len() as u64;
+        }
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures_util::{stream, StreamExt};
+
+    use std::sync::{Arc, Mutex};
+
+    use super::InfileProgress;
+
+    #[tokio::test]
+    async fn should_report_infile_progress() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let data = stream::iter(vec![
+            Ok(Bytes::from_static(b"foo")),
+            Ok(Bytes::new()),
+            Ok(Bytes::from_static(b"barbaz")),
+            Err(std::io::Error::other("broken")),
+        ])
+        .boxed();
+
+        let mut progress = InfileProgress::new(data, {
+            let reported = reported.clone();
+            move |sent| reported.lock().unwrap().push(sent)
+        });
+
+        assert_eq!(&progress.next().await.unwrap().unwrap()[..], b"foo");
+        assert!(reported.lock().unwrap().is_empty());
+        assert!(progress.next().await.unwrap().unwrap().is_empty());
+        assert_eq!(&progress.next().await.unwrap().unwrap()[..], b"barbaz");
+        // errors are passed through
+        assert!(progress.next().await.unwrap().is_err());
+        assert!(progress.next().await.is_none());
+
+        assert_eq!(*reported.lock().unwrap(), vec![3, 9]);
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{fmt, sync::Arc};
+
+use crate::BoxFuture;
+
+/// Supplies a password at connection establishment (see [`crate::OptsBuilder::credential_provider`]).
+///
+/// The purpose of the provider is to support authentication using short-lived tokens
+/// (e.g. AWS IAM or Azure AD database authentication), which are usually sent
+/// via `mysql_clear_password` authentication plugin, so you'll also need to enable
+/// [`crate::Opts::enable_cleartext_plugin`] and TLS.
+///
+/// The driver calls [`CredentialProvider::fetch_password`] every time it authenticates,
+/// i.e. for every new connection (including reconnects) and for every `COM_CHANGE_USER`
+/// (see [`crate::Conn::change_user`]). The fetched password is dropped right after
+/// the authentication, so it is never reused.
+///
+/// It is implemented for every `Fn() -> BoxFuture<'static, String>`:
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use futures_util::FutureExt;
+/// # use mysql_async::{CredentialProvider, OptsBuilder};
+/// let provider = || async { Ok::<_, mysql_async::Error>(String::from("token")) }.boxed();
+/// let opts = OptsBuilder::default()
+///     .user(Some("iam_user"))
+///     .credential_provider(Some(Arc::new(provider) as Arc<dyn CredentialProvider>))
+///     .enable_cleartext_plugin(true);
+/// ```
+pub trait CredentialProvider: Send + Sync + 'static {
+    /// Returns the current password.
+    fn fetch_password(&self) -> BoxFuture<'static, String>;
+}
+
+impl<T> CredentialProvider for T
+where
+    T: Fn() -> BoxFuture<'static, String>,
+    T: Send + Sync + 'static,
+{
+    fn fetch_password(&self) -> BoxFuture<'static, String> {
+        (self)()
+    }
+}
+
+/// Credential provider, that is comparable by identity.
+#[derive(Clone)]
+pub(crate) struct CredentialProviderObject(Arc<dyn CredentialProvider>);
+
+impl CredentialProviderObject {
+    pub(crate) fn new(provider: Arc<dyn CredentialProvider>) -> Self {
+        Self(provider)
+    }
+
+    pub(crate) fn clone_inner(&self) -> Arc<dyn CredentialProvider> {
+        self.0.clone()
+    }
+}
+
+impl PartialEq for CredentialProviderObject {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const () == Arc::as_ptr(&other.0) as *const ()
+    }
+}
+
+impl Eq for CredentialProviderObject {}
+
+impl fmt::Debug for CredentialProviderObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CredentialProviderObject")
+            .field(&"..")
+            .finish()
+    }
+}
@@ -1,8 +1,8 @@
 #![cfg(feature = "native-tls")]
 
-use std::{borrow::Cow, path::Path};
+use std::{borrow::Cow, fmt, path::Path};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct ClientIdentity {
     pkcs12_path: Cow<'static, Path>,
     password: Option<Cow<'static, str>>,
@@ -39,3 +39,12 @@ impl ClientIdentity {
         self.password.as_ref().map(AsRef::as_ref)
     }
 }
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientIdentity")
+            .field("pkcs12_path", &self.pkcs12_path)
+            .field("password", &self.password.as_ref().map(|_| "..."))
+            .finish()
+    }
+}
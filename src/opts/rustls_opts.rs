@@ -1,9 +1,15 @@
 #![cfg(feature = "rustls-tls")]
 
-use rustls::{Certificate, PrivateKey};
+use rustls::{
+    client::{ClientSessionMemoryCache, ClientSessionStore},
+    Certificate, PrivateKey,
+};
 use rustls_pemfile::{certs, rsa_private_keys};
 
-use std::{borrow::Cow, path::Path};
+use std::{borrow::Cow, fmt, hash, path::Path, sync::Arc};
+
+/// Maximum number of TLS sessions stored in a [`TlsSessionCache`].
+const TLS_SESSION_CACHE_SIZE: usize = 256;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ClientIdentity {
@@ -85,3 +91,49 @@ impl ClientIdentity {
         ))
     }
 }
+
+/// Bounded in-memory TLS session cache.
+///
+/// Clones share the same underlying cache, so that every connection created
+/// from the same `Opts` (e.g. every connection of a `Pool`) may resume a TLS session
+/// instead of performing a full handshake.
+#[derive(Clone)]
+pub(crate) struct TlsSessionCache(Arc<ClientSessionMemoryCache>);
+
+impl TlsSessionCache {
+    pub(crate) fn store(&self) -> Arc<dyn ClientSessionStore> {
+        self.0.clone()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_shared_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Default for TlsSessionCache {
+    fn default() -> Self {
+        Self(Arc::new(ClientSessionMemoryCache::new(
+            TLS_SESSION_CACHE_SIZE,
+        )))
+    }
+}
+
+impl fmt::Debug for TlsSessionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TlsSessionCache").finish()
+    }
+}
+
+// The cache is a runtime state rather than an option, so it doesn't affect equality.
+impl PartialEq for TlsSessionCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for TlsSessionCache {}
+
+impl hash::Hash for TlsSessionCache {
+    fn hash<H: hash::Hasher>(&self, _state: &mut H) {}
+}
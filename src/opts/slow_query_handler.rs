@@ -0,0 +1,75 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Receives queries, that took longer than [`crate::Opts::slow_query_threshold`]
+/// (see [`crate::OptsBuilder::slow_query_handler`]).
+///
+/// The handler is called by the connection right after the last packet of the query result
+/// is read, so it must not block – e.g. log the query or send it to a channel.
+///
+/// It is implemented for every `Fn(&str, Duration)`:
+///
+/// ```rust
+/// # use std::{sync::Arc, time::Duration};
+/// # use mysql_async::{OptsBuilder, SlowQueryHandler};
+/// let handler = |query: &str, elapsed: Duration| {
+///     eprintln!("slow query ({:?}): {}", elapsed, query);
+/// };
+/// let opts = OptsBuilder::default()
+///     .slow_query_threshold(Some(Duration::from_secs(1)))
+///     .slow_query_handler(Some(Arc::new(handler) as Arc<dyn SlowQueryHandler>));
+/// ```
+pub trait SlowQueryHandler: Send + Sync + 'static {
+    /// Handles a slow query.
+    ///
+    /// `query` is the text of a query or of a prepared statement (with `?` placeholders),
+    /// `elapsed` is the time from sending the query to reading the last packet of its result.
+    fn handle(&self, query: &str, elapsed: Duration);
+}
+
+impl<T> SlowQueryHandler for T
+where
+    T: Fn(&str, Duration),
+    T: Send + Sync + 'static,
+{
+    fn handle(&self, query: &str, elapsed: Duration) {
+        (self)(query, elapsed)
+    }
+}
+
+/// Slow query handler, that is comparable by identity.
+#[derive(Clone)]
+pub(crate) struct SlowQueryHandlerObject(Arc<dyn SlowQueryHandler>);
+
+impl SlowQueryHandlerObject {
+    pub(crate) fn new(handler: Arc<dyn SlowQueryHandler>) -> Self {
+        Self(handler)
+    }
+
+    pub(crate) fn clone_inner(&self) -> Arc<dyn SlowQueryHandler> {
+        self.0.clone()
+    }
+}
+
+impl PartialEq for SlowQueryHandlerObject {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const () == Arc::as_ptr(&other.0) as *const ()
+    }
+}
+
+impl Eq for SlowQueryHandlerObject {}
+
+impl fmt::Debug for SlowQueryHandlerObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SlowQueryHandlerObject")
+            .field(&"..")
+            .finish()
+    }
+}
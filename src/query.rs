@@ -6,16 +6,18 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap, fmt, sync::Arc};
 
 use futures_util::FutureExt;
+use mysql_common::named_params::ParsedNamedParams;
 
 use crate::{
     connection_like::ToConnectionResult,
     from_row,
     prelude::{FromRow, StatementLike, ToConnection},
     tracing_utils::LevelInfo,
-    BinaryProtocol, BoxFuture, Params, QueryResult, ResultSetStream, TextProtocol,
+    BinaryProtocol, BoxFuture, DriverError, Error, FromRowError, Params, QueryResult,
+    ResultSetStream, ServerError, TextProtocol, Value,
 };
 
 /// Types that can be treated as a MySQL query.
@@ -136,6 +138,21 @@ pub trait Query: Send + Sized {
         async move { self.run(conn).await?.collect_and_drop::<T>().await }.boxed()
     }
 
+    /// This methods corresponds to [`Queryable::query_opt`][query_opt].
+    ///
+    /// [query_opt]: crate::prelude::Queryable::query_opt
+    fn fetch_opt<'a, 't: 'a, T, C>(
+        self,
+        conn: C,
+    ) -> BoxFuture<'a, Vec<std::result::Result<T, FromRowError>>>
+    where
+        Self: 'a,
+        C: ToConnection<'a, 't> + 'a,
+        T: FromRow + Send + 'static,
+    {
+        async move { self.run(conn).await?.try_collect_and_drop::<T>().await }.boxed()
+    }
+
     /// This methods corresponds to [`Queryable::query_fold`][query_fold].
     ///
     /// [query_fold]: crate::prelude::Queryable::query_fold
@@ -170,6 +187,26 @@ pub trait Query: Send + Sized {
         .boxed()
     }
 
+    /// This methods corresponds to [`Queryable::query_map_opt`][query_map_opt].
+    ///
+    /// [query_map_opt]: crate::prelude::Queryable::query_map_opt
+    fn map_opt<'a, 't: 'a, T, U, F, C>(self, conn: C, mut map: F) -> BoxFuture<'a, Vec<U>>
+    where
+        Self: 'a,
+        C: ToConnection<'a, 't> + 'a,
+        F: FnMut(std::result::Result<T, FromRowError>) -> U + Send + 'a,
+        T: FromRow + Send + 'static,
+        U: Send + 'a,
+    {
+        async move {
+            self.run(conn)
+                .await?
+                .map_and_drop(|row| map(T::from_row_opt(row)))
+                .await
+        }
+        .boxed()
+    }
+
     /// Returns a stream over the first result set.
     ///
     /// This method corresponds to [`QueryResult::stream_and_drop`][stream_and_drop].
@@ -251,6 +288,268 @@ impl<T: StatementLike> WithParams for T {
     }
 }
 
+impl<Q, P> QueryWithParams<Q, P>
+where
+    Q: AsQuery,
+    P: Into<Params> + Clone,
+{
+    /// Renders the query with placeholders replaced by SQL literals of the bound values.
+    ///
+    /// **This is a diagnostic aid for logging only – never execute the result.**
+    /// Literals are escaped assuming the default SQL mode, so the output may differ from
+    /// what the server would've seen.
+    ///
+    /// Both `?` and named (`:name`) placeholders are replaced (placeholders within string
+    /// literals and comments are left as is). A placeholder without a corresponding value
+    /// is left as is as well, so the output of a mismatched statement is still useful.
+    ///
+    /// ```
+    /// # use mysql_async::{params, prelude::*};
+    /// let query = "SELECT * FROM users WHERE name = ? AND age > ?".with(("it's", 42));
+    /// assert_eq!(
+    ///     query.to_debug_sql(),
+    ///     "SELECT * FROM users WHERE name = 'it\\'s' AND age > 42",
+    /// );
+    ///
+    /// let query = "SELECT :a, :b, :a".with(params! { "a" => 1, "b" => "?" });
+    /// assert_eq!(query.to_debug_sql(), "SELECT 1, '?', 1");
+    /// ```
+    pub fn to_debug_sql(&self) -> String {
+        let query = self.query.as_query();
+        let params = self.params.clone().into();
+
+        let mut output = Vec::with_capacity(query.len());
+        let parsed = ParsedNamedParams::parse(&query);
+        if let Ok(ref parsed) = parsed {
+            if !parsed.params().is_empty() {
+                let values = match params {
+                    Params::Named(values)
+                        if parsed.params().iter().all(|x| values.contains_key(&**x)) =>
+                    {
+                        values
+                    }
+                    _ => return String::from_utf8_lossy(parsed.query()).into_owned(),
+                };
+                for_each_named_param(&query, parsed, |chunk, name| {
+                    match name.and_then(|name| values.get(name)) {
+                        Some(value) => output.extend_from_slice(value.as_sql(false).as_bytes()),
+                        None => output.extend_from_slice(chunk),
+                    }
+                });
+                return String::from_utf8_lossy(&output).into_owned();
+            }
+        }
+
+        let mut values = match params {
+            Params::Positional(values) => values.into_iter(),
+            Params::Empty | Params::Named(_) => Vec::new().into_iter(),
+        };
+
+        for_each_placeholder(&query, |chunk, is_placeholder| {
+            let value = if is_placeholder { values.next() } else { None };
+            match value {
+                Some(value) => output.extend_from_slice(value.as_sql(false).as_bytes()),
+                None => output.extend_from_slice(chunk),
+            }
+        });
+
+        String::from_utf8_lossy(&output).into_owned()
+    }
+}
+
+impl<Q, P> QueryWithParams<Q, P>
+where
+    Q: AsQuery,
+    P: Into<Params>,
+{
+    /// Binds the given values to the named parameter `name`, that is used as an `IN` list,
+    /// e.g. `WHERE id IN (:ids)`.
+    ///
+    /// Each occurrence of the `:name` placeholder is expanded into `:name_0, :name_1, ...`
+    /// (so these parameter names are reserved) and every value is bound to the corresponding
+    /// parameter. For an empty list the placeholder is replaced by `NULL`, so `IN (NULL)`
+    /// matches nothing. Note that `NOT IN (NULL)` matches nothing as well.
+    ///
+    /// Parameters of this query must be named (or empty). It may be called repeatedly
+    /// to bind multiple lists.
+    ///
+    /// # Errors
+    ///
+    /// * [`DriverError::MixedParams`] if parameters of this query are positional
+    ///   or the query contains both named and positional placeholders;
+    /// * [`DriverError::MissingNamedParam`] if the query doesn't contain the `:name` placeholder.
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, Conn};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{params, prelude::*};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let ids = vec![1, 3];
+    ///
+    /// let query = "SELECT n FROM (SELECT 1 AS n UNION ALL SELECT 2 UNION ALL SELECT 3) t \
+    ///     WHERE n IN (:ids) AND n > :min"
+    ///     .with(params! { "min" => 0 })
+    ///     .bind_in("ids", &ids)?;
+    /// assert_eq!(
+    ///     query.query,
+    ///     b"SELECT n FROM (SELECT 1 AS n UNION ALL SELECT 2 UNION ALL SELECT 3) t \
+    ///     WHERE n IN (:ids_0, :ids_1) AND n > :min",
+    /// );
+    ///
+    /// let found: Vec<u8> = query.fetch(&mut conn).await?;
+    /// assert_eq!(found, vec![1, 3]);
+    /// # conn.disconnect().await }
+    /// ```
+    pub fn bind_in<I, T>(
+        self,
+        name: &str,
+        values: I,
+    ) -> crate::Result<QueryWithParams<Vec<u8>, Params>>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Value>,
+    {
+        let query = self.query.as_query();
+        let mut params = match self.params.into() {
+            Params::Empty => HashMap::new(),
+            Params::Named(params) => params,
+            Params::Positional(_) => return Err(DriverError::MixedParams.into()),
+        };
+
+        let parsed = ParsedNamedParams::parse(&query)?;
+        if !parsed.params().iter().any(|x| &**x == name.as_bytes()) {
+            return Err(DriverError::MissingNamedParam {
+                name: name.as_bytes().to_vec(),
+            }
+            .into());
+        }
+
+        let mut len = 0;
+        for (i, value) in values.into_iter().enumerate() {
+            params.insert(format!("{}_{}", name, i).into_bytes(), value.into());
+            len += 1;
+        }
+        let expansion = if len == 0 {
+            b"NULL".to_vec()
+        } else {
+            (0..len)
+                .map(|i| format!(":{}_{}", name, i))
+                .collect::<Vec<_>>()
+                .join(", ")
+                .into_bytes()
+        };
+
+        let mut output = Vec::with_capacity(query.len() + expansion.len());
+        for_each_named_param(&query, &parsed, |chunk, param| match param {
+            Some(param) if param == name.as_bytes() => output.extend_from_slice(&expansion),
+            _ => output.extend_from_slice(chunk),
+        });
+
+        Ok(QueryWithParams {
+            query: output,
+            params: named_params(params),
+        })
+    }
+}
+
+/// Converts the given named parameters into `Params` (empty map is `Params::Empty`).
+fn named_params(params: HashMap<Vec<u8>, Value>) -> Params {
+    if params.is_empty() {
+        Params::Empty
+    } else {
+        Params::Named(params)
+    }
+}
+
+/// Splits the given query into chunks, where each named parameter (`:name`) is a separate chunk.
+///
+/// `fun` is called for every chunk with the parameter name, if the chunk is a parameter.
+/// Parameters are located by comparing the query with its `parsed` form, so they are
+/// exactly the ones, that [`ParsedNamedParams`] has found.
+fn for_each_named_param<F>(query: &[u8], parsed: &ParsedNamedParams<'_>, mut fun: F)
+where
+    F: FnMut(&[u8], Option<&[u8]>),
+{
+    let parsed_query = parsed.query();
+    let mut names = parsed.params().iter();
+    let mut start = 0;
+    let mut i = 0;
+    let mut j = 0;
+    while i < query.len() {
+        // the parsed query only differs from the original one in `:name` replaced by `?`
+        if query[i] == b':' && parsed_query.get(j) == Some(&b'?') {
+            if let Some(name) = names.next() {
+                fun(&query[start..i], None);
+                fun(&query[i..i + 1 + name.len()], Some(name));
+                i += 1 + name.len();
+                start = i;
+                j += 1;
+                continue;
+            }
+        }
+        i += 1;
+        j += 1;
+    }
+    fun(&query[start..], None);
+}
+
+/// Splits the given query into chunks, where each `?` placeholder is a separate chunk.
+///
+/// `fun` is called for every chunk with a flag, that is `true` for a placeholder.
+/// String literals, quoted identifiers and comments are never split (except for
+/// `/*! ... */` and `/*+ ... */` comments, that are executed by the server).
+fn for_each_placeholder<F>(query: &[u8], mut fun: F)
+where
+    F: FnMut(&[u8], bool),
+{
+    let mut start = 0;
+    let mut i = 0;
+    while i < query.len() {
+        match query[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < query.len() && query[i] != quote {
+                    if query[i] == b'\\' && quote != b'`' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'#' => {
+                while i < query.len() && query[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'-' if query[i..].starts_with(b"--")
+                && !matches!(query.get(i + 2), Some(x) if !x.is_ascii_whitespace()) =>
+            {
+                while i < query.len() && query[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if query[i..].starts_with(b"/*")
+                && !matches!(query.get(i + 2), Some(b'!' | b'+')) =>
+            {
+                i += 2;
+                while i < query.len() && !query[i..].starts_with(b"*/") {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'?' => {
+                fun(&query[start..i], false);
+                fun(&query[i..i + 1], true);
+                start = i + 1;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    fun(&query[start.min(query.len())..], false);
+}
+
 impl<Q, P> Query for QueryWithParams<Q, P>
 where
     Q: StatementLike,
@@ -341,10 +640,380 @@ where
     }
 }
 
+impl<Q, I, P> QueryWithParams<Q, I>
+where
+    Q: StatementLike,
+    I: IntoIterator<Item = P> + Send,
+    I::IntoIter: Send,
+    P: Into<Params> + Send,
+{
+    /// Same as [`BatchQuery::batch`] but reports progress and optionally tolerates
+    /// server errors (see [`BatchOpts`]).
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::*;
+    /// use mysql_async::prelude::*;
+    ///
+    /// let pool = Pool::new(get_opts());
+    ///
+    /// let mut opts = BatchOpts::new(2);
+    /// opts.with_continue_on_error(true);
+    ///
+    /// // the subquery returns more than one row for `1`, the rest is still executed
+    /// let report = "DO IF(? = 1, (SELECT 1 UNION SELECT 2), 0)"
+    ///     .with((0..5).map(|x| (x,)))
+    ///     .batch_chunked(&pool, opts)
+    ///     .await?;
+    ///
+    /// assert_eq!(report.executed(), 5);
+    /// assert_eq!(report.failures().len(), 1);
+    /// assert_eq!(report.failures()[0].index(), 1);
+    /// # Ok(()) }
+    /// ```
+    pub fn batch_chunked<'a, 't: 'a, C>(
+        self,
+        conn: C,
+        opts: BatchOpts,
+    ) -> BoxFuture<'a, BatchReport>
+    where
+        Self: 'a,
+        C: ToConnection<'a, 't> + 'a,
+    {
+        async move {
+            let mut conn = match conn.to_connection() {
+                ToConnectionResult::Immediate(conn) => conn,
+                ToConnectionResult::Mediate(fut) => fut.await?,
+            };
+
+            let statement = conn.get_statement(self.query).await?;
+
+            let mut report = BatchReport::default();
+            for params in self.params {
+                match conn.execute_statement(&statement, params).await {
+                    Ok(()) => (),
+                    Err(Error::Server(error)) if opts.continue_on_error => {
+                        report.failures.push(BatchFailure {
+                            index: report.executed,
+                            error,
+                        });
+                    }
+                    Err(err) => return Err(err),
+                }
+                report.executed += 1;
+
+                if report.executed % opts.chunk_size == 0 {
+                    if let Some(ref progress) = opts.progress {
+                        progress(&report.progress());
+                    }
+                }
+            }
+
+            if report.executed % opts.chunk_size != 0 {
+                if let Some(ref progress) = opts.progress {
+                    progress(&report.progress());
+                }
+            }
+
+            Ok(report)
+        }
+        .boxed()
+    }
+}
+
+/// Options for [`QueryWithParams::batch_chunked`].
+///
+/// Example:
+///
+/// ```
+/// # use mysql_async::*;
+/// # fn main() -> Result<()> {
+/// let mut opts = BatchOpts::new(1000);
+/// opts.with_continue_on_error(true)
+///     .with_progress(|progress| println!("{} rows done", progress.executed()));
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct BatchOpts {
+    chunk_size: usize,
+    continue_on_error: bool,
+    progress: Option<ProgressFn>,
+}
+
+type ProgressFn = Arc<dyn Fn(&BatchProgress) + Send + Sync>;
+
+impl BatchOpts {
+    /// Creates options with the given chunk size (see [`BatchOpts::chunk_size`]).
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: std::cmp::max(chunk_size, 1),
+            continue_on_error: false,
+            progress: None,
+        }
+    }
+
+    /// See [`BatchOpts::chunk_size`].
+    pub fn with_chunk_size(&mut self, value: usize) -> &mut Self {
+        self.chunk_size = std::cmp::max(value, 1);
+        self
+    }
+
+    /// See [`BatchOpts::continue_on_error`].
+    pub fn with_continue_on_error(&mut self, value: bool) -> &mut Self {
+        self.continue_on_error = value;
+        self
+    }
+
+    /// Sets a callback, that will be called after each chunk of executions.
+    pub fn with_progress<F>(&mut self, callback: F) -> &mut Self
+    where
+        F: Fn(&BatchProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Number of executions between two progress reports. Defaults to `1`.
+    ///
+    /// Note that statements are still executed one at a time, chunking only defines
+    /// the granularity of progress reporting.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// If `true`, then server errors won't abort the batch, but will be collected
+    /// into the resulting [`BatchReport`] instead. Defaults to `false`.
+    ///
+    /// Other errors (e.g. IO errors) always abort the batch, because the connection
+    /// is unusable after them.
+    pub fn continue_on_error(&self) -> bool {
+        self.continue_on_error
+    }
+}
+
+impl Default for BatchOpts {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl fmt::Debug for BatchOpts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchOpts")
+            .field("chunk_size", &self.chunk_size)
+            .field("continue_on_error", &self.continue_on_error)
+            .field("progress", &self.progress.as_ref().map(|_| "..."))
+            .finish()
+    }
+}
+
+/// Progress of a [`QueryWithParams::batch_chunked`] call, reported after each chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchProgress {
+    executed: usize,
+    failed: usize,
+}
+
+impl BatchProgress {
+    /// Number of executions performed so far (including failed ones).
+    pub fn executed(&self) -> usize {
+        self.executed
+    }
+
+    /// Number of failed executions so far.
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+}
+
+/// A failed execution within [`QueryWithParams::batch_chunked`].
+#[derive(Debug, Clone)]
+pub struct BatchFailure {
+    index: usize,
+    error: ServerError,
+}
+
+impl BatchFailure {
+    /// Zero-based position of the failed parameters within the params iterator.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The error returned by the server.
+    pub fn error(&self) -> &ServerError {
+        &self.error
+    }
+}
+
+/// Result of a [`QueryWithParams::batch_chunked`] call.
+#[derive(Debug, Clone, Default)]
+pub struct BatchReport {
+    executed: usize,
+    failures: Vec<BatchFailure>,
+}
+
+impl BatchReport {
+    /// Total number of executions (including failed ones).
+    pub fn executed(&self) -> usize {
+        self.executed
+    }
+
+    /// Collected failures (see [`BatchOpts::continue_on_error`]).
+    pub fn failures(&self) -> &[BatchFailure] {
+        &self.failures
+    }
+
+    fn progress(&self) -> BatchProgress {
+        BatchProgress {
+            executed: self.executed,
+            failed: self.failures.len(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{prelude::*, test_misc::get_opts, *};
 
+    #[test]
+    fn should_render_debug_sql() {
+        assert_eq!("SELECT 1".with(()).to_debug_sql(), "SELECT 1");
+        assert_eq!(
+            "SELECT ?, ?, ?"
+                .with((Value::NULL, 1.5_f64, b"a\\b".to_vec()))
+                .to_debug_sql(),
+            "SELECT NULL, 1.5, 'a\\\\b'"
+        );
+        // placeholders within literals and comments are skipped
+        assert_eq!(
+            "SELECT '?', `?`, \"\\\"?\", ? /* ? */ -- ?\n, ? # ?"
+                .with((1, 2))
+                .to_debug_sql(),
+            "SELECT '?', `?`, \"\\\"?\", 1 /* ? */ -- ?\n, 2 # ?"
+        );
+        // missing values
+        assert_eq!("SELECT ?, ?".with((1,)).to_debug_sql(), "SELECT 1, ?");
+        assert_eq!(
+            "SELECT :foo, :bar, :foo"
+                .with(params! { "foo" => "x", "bar" => 2 })
+                .to_debug_sql(),
+            "SELECT 'x', 2, 'x'"
+        );
+        assert_eq!(
+            "SELECT :foo, :bar"
+                .with(params! { "foo" => 1 })
+                .to_debug_sql(),
+            "SELECT ?, ?"
+        );
+    }
+
+    #[test]
+    fn should_expand_in_lists() {
+        let query = "SELECT * FROM t WHERE a IN (:ids) AND b = :b AND c IN (:ids) AND d = ':ids'"
+            .with(params! { "b" => 2 })
+            .bind_in("ids", vec![1_u8, 3])
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&query.query),
+            "SELECT * FROM t WHERE a IN (:ids_0, :ids_1) AND b = :b \
+            AND c IN (:ids_0, :ids_1) AND d = ':ids'"
+        );
+        assert_eq!(
+            query.params,
+            params! { "b" => 2, "ids_0" => 1_u8, "ids_1" => 3_u8 }
+        );
+        assert_eq!(
+            query.to_debug_sql(),
+            "SELECT * FROM t WHERE a IN (1, 3) AND b = 2 AND c IN (1, 3) AND d = ':ids'"
+        );
+
+        // multiple lists and an empty list
+        let query = "SELECT :a, :b"
+            .with(())
+            .bind_in("a", Vec::<u8>::new())
+            .unwrap()
+            .bind_in("b", ["x", "y"])
+            .unwrap();
+        assert_eq!(query.query, b"SELECT NULL, :b_0, :b_1");
+        assert_eq!(query.to_debug_sql(), "SELECT NULL, 'x', 'y'");
+
+        let query = "SELECT :a".with(()).bind_in("a", Vec::<u8>::new()).unwrap();
+        assert_eq!(query.params, Params::Empty);
+
+        // placeholders are located the same way named parameters are parsed
+        let query = "SELECT /*+ SET_VAR(sort_buffer_size = 1) */ `:b`, :b, /*! :a */ :b"
+            .with(())
+            .bind_in("b", vec![1])
+            .unwrap()
+            .bind_in("a", vec![2])
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&query.query),
+            "SELECT /*+ SET_VAR(sort_buffer_size = 1) */ `:b_0`, :b_0, /*! :a_0 */ :b_0"
+        );
+        assert_eq!(
+            query.to_debug_sql(),
+            "SELECT /*+ SET_VAR(sort_buffer_size = 1) */ `1`, 1, /*! 2 */ 1"
+        );
+
+        // unknown name
+        let err = "SELECT :a"
+            .with(params! { "a" => 1 })
+            .bind_in("b", vec![1])
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Driver(DriverError::MissingNamedParam { ref name }) if name == b"b"),
+            "{}",
+            err
+        );
+
+        // positional parameters
+        let err = "SELECT ?".with((1,)).bind_in("a", vec![1]).unwrap_err();
+        assert!(
+            matches!(err, Error::Driver(DriverError::MixedParams)),
+            "{}",
+            err
+        );
+        let err = "SELECT ?, :a".with(()).bind_in("a", vec![1]).unwrap_err();
+        assert!(
+            matches!(err, Error::Driver(DriverError::MixedParams)),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn should_bind_in_lists() -> Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_bind_in (id INT, name TEXT)")
+            .await?;
+        conn.exec_batch(
+            "INSERT INTO tmp_bind_in VALUES (?, ?)",
+            (1..=5).map(|id| (id, format!("name{}", id))),
+        )
+        .await?;
+
+        let ids = vec![2, 4, 42];
+        let names: Vec<String> = "SELECT name FROM tmp_bind_in WHERE id IN (:ids) ORDER BY id"
+            .with(())
+            .bind_in("ids", &ids)?
+            .fetch(&mut conn)
+            .await?;
+        assert_eq!(names, vec!["name2", "name4"]);
+
+        let names: Vec<String> = "SELECT name FROM tmp_bind_in WHERE id IN (:ids)"
+            .with(())
+            .bind_in("ids", Vec::<u32>::new())?
+            .fetch(&mut conn)
+            .await?;
+        assert!(names.is_empty());
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_run_text_query() -> Result<()> {
         let query_static = "SELECT 1, 2 UNION ALL SELECT 3, 4; SELECT 5, 6;";
@@ -498,4 +1167,57 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn should_batch_chunked() -> Result<()> {
+        use std::sync::{Arc, Mutex};
+
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp (id INT PRIMARY KEY)")
+            .await?;
+
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let mut opts = BatchOpts::new(2);
+        opts.with_continue_on_error(true).with_progress({
+            let reported = reported.clone();
+            move |progress| reported.lock().unwrap().push(*progress)
+        });
+
+        // 1 and 3 are duplicates
+        let report = "INSERT INTO tmp (id) VALUES (?)"
+            .with(vec![(1,), (1,), (2,), (3,), (3,)])
+            .batch_chunked(&mut conn, opts.clone())
+            .await?;
+        assert_eq!(report.executed(), 5);
+        let failed = report
+            .failures()
+            .iter()
+            .map(|x| (x.index(), x.error().code))
+            .collect::<Vec<_>>();
+        assert_eq!(failed, vec![(1, 1062), (4, 1062)]);
+
+        let reported = reported
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|x| (x.executed(), x.failed()))
+            .collect::<Vec<_>>();
+        assert_eq!(reported, vec![(2, 1), (4, 1), (5, 2)]);
+
+        let count: Option<u64> = conn.query_first("SELECT COUNT(*) FROM tmp").await?;
+        assert_eq!(count, Some(3));
+
+        // aborts at the first error by default
+        let err = "INSERT INTO tmp (id) VALUES (?)"
+            .with(vec![(4,), (1,), (5,)])
+            .batch_chunked(&mut conn, BatchOpts::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Server(ref e) if e.code == 1062));
+        let count: Option<u64> = conn.query_first("SELECT COUNT(*) FROM tmp").await?;
+        assert_eq!(count, Some(4));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,325 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use futures_core::{future::BoxFuture, FusedStream, Stream};
+use futures_util::FutureExt;
+use mysql_common::packets::Column;
+
+use std::{
+    any::type_name,
+    collections::VecDeque,
+    convert::TryFrom,
+    fmt,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use crate::{
+    conn::routines::StmtFetchRoutine,
+    consts::StatusFlags,
+    error::*,
+    prelude::{FromRow, StatementLike},
+    BinaryProtocol, Conn, Params, QueryResult, Row, Statement,
+};
+
+/// Rows fetched by a single `COM_STMT_FETCH` and whether the cursor is still open.
+type Fetched = Result<(Vec<Row>, bool)>;
+
+enum CursorState<'a> {
+    Idle(&'a mut Conn),
+    Fetching(BoxFuture<'a, (Fetched, &'a mut Conn)>),
+}
+
+impl fmt::Debug for CursorState<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Idle(conn) => f.debug_tuple("Idle").field(conn).finish(),
+            Self::Fetching(_) => f
+                .debug_tuple("Fetching")
+                .field(&type_name::<BoxFuture<'_, (Fetched, &mut Conn)>>())
+                .finish(),
+        }
+    }
+}
+
+/// Server-side cursor over the result set of a statement
+/// (see [`Conn::exec_cursor`]).
+///
+/// Rows are fetched from the server in batches of `fetch_size` rows on demand, either by
+/// polling this [`Stream`] (that yields rows one by one) or by calling [`Cursor::next_batch`].
+///
+/// # Conversion
+///
+/// Rows are converted into `T` using [`FromRow`] implementation. If the row type is unknown
+/// please use the [`Row`] type for `T` to make this conversion infallible.
+///
+/// # Drop
+///
+/// The server keeps the cursor open until the last row is fetched, so a cursor dropped
+/// before being exhausted (even in the middle of a fetch) will be closed
+/// (using `COM_STMT_RESET`) before the next command on the connection.
+#[derive(Debug)]
+pub struct Cursor<'a, T> {
+    state: Option<CursorState<'a>>,
+    statement: Statement,
+    columns: Arc<[Column]>,
+    fetch_size: u32,
+    /// Rows fetched from the server, but not yet consumed.
+    buffer: VecDeque<Row>,
+    /// `true` if the server-side cursor is open.
+    is_open: bool,
+    __from_row_type: PhantomData<T>,
+}
+
+impl Conn {
+    /// Executes the given statement with given params using a server-side read-only cursor
+    /// (`CURSOR_TYPE_READ_ONLY`).
+    ///
+    /// Unlike [`Queryable::exec_iter`][exec_iter], where the server sends the whole result set at once,
+    /// rows are fetched from the server in batches of `fetch_size` rows (using
+    /// `COM_STMT_FETCH`) when requested by the consumer of the returned [`Cursor`], so memory
+    /// usage is bounded regardless of the result set size. Note that the server materializes
+    /// the result set (in a temporary table), so it's not a replacement for streaming
+    /// in general.
+    ///
+    /// It'll prepare `stmt`, if necessary. The statement must stay prepared while the cursor
+    /// is in use, i.e. it must not be closed or evicted from the statement cache
+    /// (see [`stmt_cache_size`][stmt_cache_size]), otherwise fetching will fail.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use futures_util::TryStreamExt;
+    /// use mysql_async::{prelude::*, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    ///
+    /// let query = "SELECT seq FROM (SELECT 1 AS seq UNION ALL SELECT 2 UNION ALL SELECT 3) t";
+    /// let mut cursor = conn.exec_cursor::<u8, _, _>(query, (), 2).await?;
+    /// assert_eq!(cursor.next_batch().await?, Some(vec![1, 2]));
+    /// assert_eq!(cursor.try_collect::<Vec<_>>().await?, vec![3]);
+    /// # conn.disconnect().await }
+    /// ```
+    ///
+    /// [exec_iter]: crate::prelude::Queryable::exec_iter
+    /// [stmt_cache_size]: crate::Opts::stmt_cache_size
+    pub async fn exec_cursor<T, Q, P>(
+        &mut self,
+        stmt: Q,
+        params: P,
+        fetch_size: usize,
+    ) -> Result<Cursor<'_, T>>
+    where
+        T: FromRow + Send + 'static,
+        Q: StatementLike,
+        P: Into<Params>,
+    {
+        let statement = self.get_statement(stmt).await?;
+        Cursor::open(self, statement, params.into(), fetch_size).await
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// Executes the given statement asking the server to open a read-only cursor.
+    pub(crate) async fn open(
+        conn: &'a mut Conn,
+        statement: Statement,
+        params: Params,
+        fetch_size: usize,
+    ) -> Result<Cursor<'a, T>> {
+        conn.routine(
+            crate::conn::routines::ExecRoutine::new(&statement, params).with_read_only_cursor(),
+        )
+        .await?;
+
+        let mut result = QueryResult::<BinaryProtocol>::new(&mut *conn);
+        let columns = result.columns().unwrap_or_else(|| Vec::new().into());
+        // the server won't open a cursor if the statement produced no result set,
+        // or it may decide to send rows right away (e.g. for `CALL`)
+        let rows = result.collect::<Row>().await?;
+        drop(result);
+
+        let is_open = is_cursor_open(conn.status());
+        if !is_open {
+            conn.drop_result().await?;
+        }
+
+        Ok(Cursor {
+            state: Some(CursorState::Idle(conn)),
+            statement,
+            columns,
+            fetch_size: u32::try_from(fetch_size).unwrap_or(u32::MAX).max(1),
+            buffer: rows.into(),
+            is_open,
+            __from_row_type: PhantomData,
+        })
+    }
+
+    /// Returns columns of the result set.
+    pub fn columns_ref(&self) -> &[Column] {
+        &self.columns[..]
+    }
+
+    /// Returns columns of the result set.
+    pub fn columns(&self) -> Arc<[Column]> {
+        self.columns.clone()
+    }
+
+    /// Returns the number of rows fetched in one batch.
+    pub fn fetch_size(&self) -> usize {
+        self.fetch_size as usize
+    }
+
+    /// Returns the number of rows fetched from the server, but not yet consumed.
+    pub fn buffered_rows(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if the server-side cursor is still open,
+    /// i.e. more rows may be fetched from the server.
+    pub fn is_open(&self) -> bool {
+        self.is_open
+    }
+
+    /// Polls the next batch of rows (buffered rows go first).
+    fn poll_batch(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Vec<Row>>>> {
+        if !self.buffer.is_empty() {
+            return Poll::Ready(Some(Ok(self.buffer.drain(..).collect())));
+        }
+
+        loop {
+            match self.state.take() {
+                Some(CursorState::Idle(conn)) => {
+                    if !self.is_open {
+                        self.state = Some(CursorState::Idle(conn));
+                        return Poll::Ready(None);
+                    }
+
+                    let routine =
+                        StmtFetchRoutine::new(self.statement.id(), self.fetch_size, self.columns());
+                    let mut guard = FetchGuard {
+                        conn: Some(conn),
+                        statement_id: self.statement.id(),
+                    };
+                    let fut = async move {
+                        let fetched = fetch(guard.conn.as_deref_mut().unwrap(), routine).await;
+                        (fetched, guard.conn.take().unwrap())
+                    };
+                    self.state = Some(CursorState::Fetching(fut.boxed()));
+                }
+                Some(CursorState::Fetching(mut fut)) => match fut.poll_unpin(cx) {
+                    Poll::Ready((fetched, conn)) => {
+                        self.state = Some(CursorState::Idle(conn));
+                        match fetched {
+                            Ok((rows, is_open)) => {
+                                self.is_open = is_open;
+                                if !rows.is_empty() {
+                                    return Poll::Ready(Some(Ok(rows)));
+                                }
+                            }
+                            Err(err) => return Poll::Ready(Some(Err(err))),
+                        }
+                    }
+                    Poll::Pending => {
+                        self.state = Some(CursorState::Fetching(fut));
+                        return Poll::Pending;
+                    }
+                },
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<'a, T> Cursor<'a, T>
+where
+    T: FromRow + Send + 'static,
+{
+    /// Returns the next batch of rows (up to `fetch_size` rows fetched from the server).
+    ///
+    /// Returns `None` if the cursor is exhausted.
+    ///
+    /// # Panic
+    ///
+    /// It'll panic if any row isn't convertible to `T` (see [`FromRow`] docs).
+    pub async fn next_batch(&mut self) -> Result<Option<Vec<T>>> {
+        futures_util::future::poll_fn(|cx| self.poll_batch(cx))
+            .await
+            .transpose()
+            .map(|rows| rows.map(|rows| rows.into_iter().map(crate::from_row).collect()))
+    }
+}
+
+/// Closes the cursor if a fetch is dropped before it's completed.
+struct FetchGuard<'a> {
+    conn: Option<&'a mut Conn>,
+    statement_id: u32,
+}
+
+impl Drop for FetchGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            conn.set_cursor_to_close(self.statement_id);
+        }
+    }
+}
+
+/// Fetches the next batch of rows and returns `true` if the cursor is still open.
+async fn fetch(conn: &mut Conn, routine: StmtFetchRoutine) -> Fetched {
+    conn.routine(routine).await?;
+    let rows = QueryResult::<BinaryProtocol>::new(&mut *conn)
+        .collect::<Row>()
+        .await?;
+    Ok((rows, is_cursor_open(conn.status())))
+}
+
+fn is_cursor_open(status: StatusFlags) -> bool {
+    status.contains(StatusFlags::SERVER_STATUS_CURSOR_EXISTS)
+        && !status.contains(StatusFlags::SERVER_STATUS_LAST_ROW_SENT)
+}
+
+impl<T> Stream for Cursor<'_, T>
+where
+    T: FromRow + Unpin + Send + 'static,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.buffer.is_empty() {
+            match futures_core::ready!(this.poll_batch(cx)) {
+                Some(Ok(rows)) => this.buffer = rows.into(),
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => return Poll::Ready(None),
+            }
+        }
+        Poll::Ready(this.buffer.pop_front().map(|row| Ok(crate::from_row(row))))
+    }
+}
+
+impl<T> FusedStream for Cursor<'_, T>
+where
+    T: FromRow + Unpin + Send + 'static,
+{
+    fn is_terminated(&self) -> bool {
+        self.buffer.is_empty() && !self.is_open
+    }
+}
+
+impl<T> Drop for Cursor<'_, T> {
+    fn drop(&mut self) {
+        if self.is_open {
+            if let Some(CursorState::Idle(conn)) = self.state.as_mut() {
+                conn.set_cursor_to_close(self.statement.id());
+            }
+        }
+    }
+}
@@ -18,22 +18,23 @@ use mysql_common::{
 use std::{fmt, sync::Arc};
 
 use self::{
-    query_result::QueryResult,
+    query_result::{FromResultSets, QueryResult},
     stmt::Statement,
     transaction::{Transaction, TxStatus},
 };
 
 use crate::{
-    conn::routines::{PingRoutine, QueryRoutine},
-    consts::CapabilityFlags,
+    conn::routines::{ExecRoutine, PingRoutine, QueryRoutine, StmtResetRoutine},
+    consts::{CapabilityFlags, ColumnType},
     error::*,
     prelude::{FromRow, StatementLike},
     query::AsQuery,
     queryable::query_result::ResultSetMeta,
     tracing_utils::{LevelInfo, LevelTrace, TracingLevel},
-    BoxFuture, Column, Conn, Params, ResultSetStream, Row,
+    BoxFuture, Column, Conn, FromRowError, Params, ResultSetStream, Row,
 };
 
+pub mod cursor;
 pub mod query_result;
 pub mod stmt;
 pub mod transaction;
@@ -43,11 +44,31 @@ pub trait Protocol: fmt::Debug + Send + Sync + 'static {
     fn result_set_meta(columns: Arc<[Column]>) -> ResultSetMeta;
     fn read_result_set_row(packet: &[u8], columns: Arc<[Column]>) -> Result<Row>;
     fn is_last_result_set_packet(capabilities: CapabilityFlags, packet: &[u8]) -> bool {
-        if capabilities.contains(CapabilityFlags::CLIENT_DEPRECATE_EOF) {
-            packet[0] == 0xFE && packet.len() < MAX_PAYLOAD_LEN
-        } else {
-            packet[0] == 0xFE && packet.len() < 8
-        }
+        is_result_set_terminator(capabilities, packet)
+    }
+}
+
+/// Returns `true` if the given packet terminates a result set.
+///
+/// Both the terminator and a row packet may start with `0xFE` (a row packet starts with `0xFE`
+/// if its first value is longer than 2^24 bytes), so they are distinguished by length:
+///
+/// * if `CLIENT_DEPRECATE_EOF` is negotiated, then the terminator is an OK packet with `0xFE`
+///   header, that is always shorter than `MAX_PAYLOAD_LEN` unlike the row packet;
+/// * otherwise the terminator is an EOF packet, that is always shorter than 8 bytes
+///   unlike the row packet (the length prefix alone takes 9 bytes).
+///
+/// Note that the terminating OK packet never starts with `0x00`, so it couldn't be confused
+/// with a row packet, that starts with an empty string or with a binary row header.
+pub(crate) fn is_result_set_terminator(capabilities: CapabilityFlags, packet: &[u8]) -> bool {
+    if packet.first() != Some(&0xFE) {
+        return false;
+    }
+
+    if capabilities.contains(CapabilityFlags::CLIENT_DEPRECATE_EOF) {
+        packet.len() < MAX_PAYLOAD_LEN
+    } else {
+        packet.len() < 8
     }
 }
 
@@ -88,20 +109,73 @@ impl Protocol for BinaryProtocol {
 impl Conn {
     /// The purpose of this function is to rollback a transaction or to drop query result in cases,
     /// where `Transaction` was dropped without an explicit call to `commit` or `rollback`,
-    /// or where `QueryResult` was dropped without being consumed.
+    /// or where `QueryResult` was dropped without being consumed. It also closes a cursor
+    /// of a `Cursor` that was dropped without being exhausted.
     ///
     /// The difference betwee this function and [`Conn::cleanup`] is that this function
     /// won't rollback existing transaction. Another difference, is that this function
     /// won't ignore non-fatal errors.
     pub(crate) async fn clean_dirty(&mut self) -> Result<()> {
         self.drop_result().await?;
+        if let Some(stmt_id) = self.take_cursor_to_close() {
+            match self.routine(StmtResetRoutine::new(stmt_id)).await {
+                // the statement might be closed already
+                Ok(()) | Err(Error::Server(_)) => (),
+                Err(err) => return Err(err),
+            }
+        }
         if self.get_tx_status() == TxStatus::RequiresRollback {
             self.set_tx_status(TxStatus::None);
-            self.exec_drop("ROLLBACK", ()).await?;
+            if let Err(err) = self.exec_drop("ROLLBACK", ()).await {
+                self.untrack_prepared_statements();
+                return Err(err);
+            }
+            self.close_tracked_statements().await?;
         }
         Ok(())
     }
 
+    /// Executes the given statement with given params and explicit parameter types.
+    ///
+    /// Works the same way as [`Queryable::exec_iter`], but sends the given types
+    /// instead of types inferred from param values. Use it to avoid server-side coercion
+    /// of ambiguous values, e.g. to bind a `NULL` as a `DECIMAL`.
+    ///
+    /// `param_types` must contain a type for every positional parameter (named parameters
+    /// are matched in the order of their appearance in the statement). Each type must be
+    /// compatible with the binary encoding of the corresponding value:
+    ///
+    /// * `NULL` is compatible with any type;
+    /// * `Value::Bytes` is compatible with string, blob, decimal, enum, set, bit,
+    ///   json and geometry types;
+    /// * `Value::Int` and `Value::UInt` are compatible with `MYSQL_TYPE_LONGLONG`;
+    /// * `Value::Float` is compatible with `MYSQL_TYPE_FLOAT`;
+    /// * `Value::Double` is compatible with `MYSQL_TYPE_DOUBLE`;
+    /// * `Value::Date` is compatible with date, datetime and timestamp types;
+    /// * `Value::Time` is compatible with `MYSQL_TYPE_TIME`.
+    ///
+    /// Otherwise [`DriverError::IncompatibleParamType`][1] is returned
+    /// ([`DriverError::ParamTypesMismatch`][2] if the number of types is wrong).
+    ///
+    /// [1]: crate::DriverError::IncompatibleParamType
+    /// [2]: crate::DriverError::ParamTypesMismatch
+    pub async fn exec_typed<Q, P>(
+        &mut self,
+        stmt: Q,
+        params: P,
+        param_types: &[ColumnType],
+    ) -> Result<QueryResult<'_, 'static, BinaryProtocol>>
+    where
+        Q: StatementLike,
+        P: Into<Params>,
+    {
+        let params = params.into();
+        let statement = self.get_statement(stmt).await?;
+        self.routine(ExecRoutine::new(&statement, params).with_param_types(param_types))
+            .await?;
+        Ok(QueryResult::new(self))
+    }
+
     /// Low level function that performs a text query.
     pub(crate) async fn raw_query<'a, Q, L: TracingLevel>(&'a mut self, query: Q) -> Result<()>
     where
@@ -150,16 +224,26 @@ pub trait Queryable: Send {
     ///
     /// Note, that `Statement` will exist only in the context of this queryable.
     ///
+    /// The statement is put into the statement cache and a cached statement is returned as is,
+    /// i.e. the server is only asked to prepare the query if it isn't in the cache yet. So this
+    /// is the way to control when a query is prepared – the returned handle is the one `exec*`
+    /// methods will use for the same query. Use [`Queryable::close`] to evict it.
+    ///
     /// Also note, that this call may close the least recently used statement
     /// if statement cache is at its capacity (see. [`stmt_cache_size`][stmt_cache_size]).
+    /// Use [`Conn::prepare_pinned`][prepare_pinned] for a statement, that is never evicted.
     ///
     /// [stmt_cache_size]: crate::Opts::stmt_cache_size
+    /// [prepare_pinned]: crate::Conn::prepare_pinned
     fn prep<'a, Q>(&'a mut self, query: Q) -> BoxFuture<'a, Statement>
     where
         Q: AsQuery + 'a;
 
     /// Closes the given statement.
     ///
+    /// The statement is evicted from the statement cache, so the next [`Queryable::prep`]
+    /// (or `exec*`) of the same query will prepare it again.
+    ///
     /// Usually there is no need to explicitly close statements
     /// (see. [`stmt_cache_size`][stmt_cache_size]).
     ///
@@ -193,6 +277,27 @@ pub trait Queryable: Send {
         async move { self.query_iter(query).await?.collect_and_drop::<T>().await }.boxed()
     }
 
+    /// Performs the given query and collects the first result set.
+    ///
+    /// It works the same way as [`Queryable::query`] but won't panic if a row isn't
+    /// convertible to `T` – a conversion error is returned for such a row instead.
+    fn query_opt<'a, T, Q>(
+        &'a mut self,
+        query: Q,
+    ) -> BoxFuture<'a, Vec<std::result::Result<T, FromRowError>>>
+    where
+        Q: AsQuery + 'a,
+        T: FromRow + Send + 'static,
+    {
+        async move {
+            self.query_iter(query)
+                .await?
+                .try_collect_and_drop::<T>()
+                .await
+        }
+        .boxed()
+    }
+
     /// Performs the given query and returns the first row of the first result set.
     ///
     /// ## Conversion
@@ -218,6 +323,53 @@ pub trait Queryable: Send {
         .boxed()
     }
 
+    /// Performs the given query and collects at most `limit` rows of the first result set.
+    ///
+    /// Useful when the query can't be amended with a `LIMIT` clause. Note, that the protocol
+    /// doesn't allow to abort a result set, so the rest of it is still read from the server
+    /// (but not parsed nor collected) before the connection is ready for the next query.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{prelude::*, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let rows: Vec<u8> = conn
+    ///     .query_limited("SELECT 1 UNION ALL SELECT 2 UNION ALL SELECT 3", 2)
+    ///     .await?;
+    /// assert_eq!(rows, vec![1, 2]);
+    /// # conn.disconnect().await }
+    /// ```
+    ///
+    /// ## Conversion
+    ///
+    /// This stream will convert each row into `T` using [`FromRow`] implementation.
+    /// If the row type is unknown please use the [`Row`] type for `T`
+    /// to make this conversion infallible.
+    fn query_limited<'a, T, Q>(&'a mut self, query: Q, limit: usize) -> BoxFuture<'a, Vec<T>>
+    where
+        Q: AsQuery + 'a,
+        T: FromRow + Send + 'static,
+    {
+        async move {
+            let mut result = self.query_iter(query).await?;
+            let mut output = Vec::new();
+            if !result.is_empty() {
+                while output.len() < limit {
+                    match result.next().await? {
+                        Some(row) => output.push(crate::from_row(row)),
+                        None => break,
+                    }
+                }
+            }
+            result.drop_result().await?;
+            Ok(output)
+        }
+        .boxed()
+    }
+
     /// Performs the given query and maps each row of the first result set.
     ///
     /// ## Conversion
@@ -242,6 +394,26 @@ pub trait Queryable: Send {
         .boxed()
     }
 
+    /// Performs the given query and maps each row of the first result set.
+    ///
+    /// It works the same way as [`Queryable::query_map`] but won't panic if a row isn't
+    /// convertible to `T` – `f` receives a conversion error for such a row instead.
+    fn query_map_opt<'a, T, F, Q, U>(&'a mut self, query: Q, mut f: F) -> BoxFuture<'a, Vec<U>>
+    where
+        Q: AsQuery + 'a,
+        T: FromRow + Send + 'static,
+        F: FnMut(std::result::Result<T, FromRowError>) -> U + Send + 'a,
+        U: Send,
+    {
+        async move {
+            self.query_iter(query)
+                .await?
+                .map_and_drop(|row| f(T::from_row_opt(row)))
+                .await
+        }
+        .boxed()
+    }
+
     /// Performs the given query and folds the first result set to a single value.
     ///
     /// ## Conversion
@@ -307,9 +479,33 @@ pub trait Queryable: Send {
         .boxed()
     }
 
+    /// Executes the given statement and collects the first result set.
+    ///
+    /// It works the same way as [`Queryable::exec`] but won't panic if a row isn't
+    /// convertible to `T` – a conversion error is returned for such a row instead.
+    fn exec_opt<'a: 'b, 'b, T, S, P>(
+        &'a mut self,
+        stmt: S,
+        params: P,
+    ) -> BoxFuture<'b, Vec<std::result::Result<T, FromRowError>>>
+    where
+        S: StatementLike + 'b,
+        P: Into<Params> + Send + 'b,
+        T: FromRow + Send + 'static,
+    {
+        async move {
+            self.exec_iter(stmt, params)
+                .await?
+                .try_collect_and_drop::<T>()
+                .await
+        }
+        .boxed()
+    }
+
     /// Executes the given statement and returns the first row of the first result set.
     ///
-    /// It'll prepare `stmt`, if necessary.
+    /// It'll prepare `stmt`, if necessary. This is the binary protocol counterpart
+    /// of [`Queryable::query_first`].
     ///
     /// ## Conversion
     ///
@@ -337,7 +533,33 @@ pub trait Queryable: Send {
 
     /// Executes the given stmt and maps each row of the first result set.
     ///
-    /// It'll prepare `stmt`, if necessary.
+    /// It'll prepare `stmt`, if necessary. This is the binary protocol counterpart
+    /// of [`Queryable::query_map`].
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{prelude::*, Conn};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Payment {
+    ///     customer_id: u32,
+    ///     amount: u32,
+    /// }
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let payments = conn
+    ///     .exec_map(
+    ///         "SELECT ?, ? UNION ALL SELECT ?, ?",
+    ///         (1, 2, 3, 4),
+    ///         |(customer_id, amount)| Payment { customer_id, amount },
+    ///     )
+    ///     .await?;
+    ///
+    /// assert_eq!(payments[1], Payment { customer_id: 3, amount: 4 });
+    /// # conn.disconnect().await }
+    /// ```
     ///
     /// ## Conversion
     ///
@@ -367,9 +589,36 @@ pub trait Queryable: Send {
         .boxed()
     }
 
-    /// Executes the given stmt and folds the first result set to a signel value.
+    /// Executes the given stmt and maps each row of the first result set.
     ///
-    /// It'll prepare `stmt`, if necessary.
+    /// It works the same way as [`Queryable::exec_map`] but won't panic if a row isn't
+    /// convertible to `T` – `f` receives a conversion error for such a row instead.
+    fn exec_map_opt<'a: 'b, 'b, T, S, P, U, F>(
+        &'a mut self,
+        stmt: S,
+        params: P,
+        mut f: F,
+    ) -> BoxFuture<'b, Vec<U>>
+    where
+        S: StatementLike + 'b,
+        P: Into<Params> + Send + 'b,
+        T: FromRow + Send + 'static,
+        F: FnMut(std::result::Result<T, FromRowError>) -> U + Send + 'a,
+        U: Send + 'a,
+    {
+        async move {
+            self.exec_iter(stmt, params)
+                .await?
+                .map_and_drop(|row| f(T::from_row_opt(row)))
+                .await
+        }
+        .boxed()
+    }
+
+    /// Executes the given stmt and folds the first result set to a single value.
+    ///
+    /// It'll prepare `stmt`, if necessary. This is the binary protocol counterpart
+    /// of [`Queryable::query_fold`].
     ///
     /// ## Conversion
     ///
@@ -408,10 +657,86 @@ pub trait Queryable: Send {
         async move { self.exec_iter(stmt, params).await?.drop_result().await }.boxed()
     }
 
+    /// Executes the given statement and collects a number of consecutive result sets,
+    /// possibly of different row types (e.g. result sets of a stored procedure).
+    ///
+    /// It'll prepare `stmt`, if necessary. Everything after the collected result sets,
+    /// including the trailing OK packet emitted by `CALL`, is dropped.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::*;
+    /// use mysql_async::prelude::*;
+    /// let mut conn = Conn::new(get_opts()).await?;
+    ///
+    /// conn.query_drop(r"DROP PROCEDURE IF EXISTS two_sets").await?;
+    /// conn.query_drop(r"CREATE PROCEDURE two_sets(x INT)
+    ///     BEGIN
+    ///         SELECT x, 'foo';
+    ///         SELECT 'bar';
+    ///     END").await?;
+    ///
+    /// let (first, second) = conn
+    ///     .exec_call::<(Vec<(u8, String)>, Vec<String>), _, _>("CALL two_sets(?)", (42,))
+    ///     .await?;
+    /// assert_eq!(first, vec![(42, "foo".into())]);
+    /// assert_eq!(second, vec![String::from("bar")]);
+    ///
+    /// conn.query_drop(r"DROP PROCEDURE two_sets").await?;
+    /// # conn.disconnect().await }
+    /// ```
+    ///
+    /// ## Conversion
+    ///
+    /// See [`FromResultSets`][from_result_sets].
+    ///
+    /// [from_result_sets]: crate::prelude::FromResultSets
+    fn exec_call<'a: 'b, 'b, R, S, P>(&'a mut self, stmt: S, params: P) -> BoxFuture<'b, R>
+    where
+        S: StatementLike + 'b,
+        P: Into<Params> + Send + 'b,
+        R: FromResultSets,
+    {
+        async move {
+            self.exec_iter(stmt, params)
+                .await?
+                .collect_sets_and_drop::<R>()
+                .await
+        }
+        .boxed()
+    }
+
     /// Returns a stream over the first result set.
     ///
     /// Please see [`QueryResult::stream_and_drop`][stream_and_drop].
     ///
+    /// The stream borrows the connection, so rows can be consumed in a simple loop without
+    /// collecting them. It's safe to break out of the loop early – remaining rows
+    /// (and result sets) are drained before the next command on this connection.
+    ///
+    /// ```rust
+    /// # use futures_util::StreamExt;
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    ///
+    /// let mut stream = conn
+    ///     .query_stream::<u8, _>("SELECT 1 UNION ALL SELECT 2 UNION ALL SELECT 3")
+    ///     .await?;
+    /// while let Some(row) = stream.next().await {
+    ///     if row? == 2 {
+    ///         break;
+    ///     }
+    /// }
+    /// drop(stream);
+    ///
+    /// assert_eq!(conn.query_first::<u8, _>("SELECT 42").await?, Some(42));
+    /// # conn.disconnect().await }
+    /// ```
+    ///
     /// [stream_and_drop]: crate::QueryResult::stream_and_drop
     fn query_stream<'a, T, Q>(
         &'a mut self,
@@ -436,6 +761,8 @@ pub trait Queryable: Send {
     ///
     /// Please see [`QueryResult::stream_and_drop`][stream_and_drop].
     ///
+    /// Same as [`Queryable::query_stream`], it's safe to stop consuming the stream early.
+    ///
     /// [stream_and_drop]: crate::QueryResult::stream_and_drop
     fn exec_stream<'a: 's, 's, T, Q, P>(
         &'a mut self,
@@ -491,6 +818,10 @@ impl Queryable for Conn {
 
     fn close(&mut self, stmt: Statement) -> BoxFuture<'_, ()> {
         async move {
+            let stmt = self.resolve_pinned_stmt(stmt);
+            if stmt.pinned {
+                self.unpin_stmt(&stmt.inner.raw_query);
+            }
             self.stmt_cache_mut().remove(stmt.id());
             self.close_statement(stmt.id()).await
         }
@@ -590,6 +921,123 @@ mod tests {
     use super::Queryable;
     use crate::{error::Result, prelude::*, test_misc::get_opts, Conn};
 
+    #[test]
+    fn should_distinguish_result_set_terminator_from_row() {
+        use super::is_result_set_terminator;
+        use crate::consts::CapabilityFlags;
+        use mysql_common::constants::MAX_PAYLOAD_LEN;
+
+        let deprecate_eof = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_TRANSACTIONS
+            | CapabilityFlags::CLIENT_DEPRECATE_EOF;
+        let old_eof = CapabilityFlags::CLIENT_PROTOCOL_41 | CapabilityFlags::CLIENT_TRANSACTIONS;
+
+        // OK packet terminator: header, affected rows, last insert id, status, warnings
+        let ok_terminator = [0xFE, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00];
+        // EOF packet terminator: header, warnings, status
+        let eof_terminator = [0xFE, 0x00, 0x00, 0x02, 0x00];
+        // text row with an empty string followed by `1`
+        let empty_string_row = [0x00, 0x01, b'1'];
+        // binary row header followed by a null bitmap
+        let binary_row = [0x00, 0x00];
+        // text row, which first value is longer than 2^24 bytes
+        let mut huge_row = vec![0xFE; MAX_PAYLOAD_LEN];
+        huge_row[1..9].copy_from_slice(&(MAX_PAYLOAD_LEN as u64).to_le_bytes());
+
+        assert!(is_result_set_terminator(deprecate_eof, &ok_terminator));
+        assert!(is_result_set_terminator(deprecate_eof, &eof_terminator));
+        assert!(!is_result_set_terminator(deprecate_eof, &empty_string_row));
+        assert!(!is_result_set_terminator(deprecate_eof, &binary_row));
+        assert!(!is_result_set_terminator(deprecate_eof, &huge_row));
+        assert!(!is_result_set_terminator(deprecate_eof, &[]));
+
+        assert!(is_result_set_terminator(old_eof, &eof_terminator));
+        assert!(!is_result_set_terminator(old_eof, &empty_string_row));
+        assert!(!is_result_set_terminator(old_eof, &binary_row));
+        assert!(!is_result_set_terminator(old_eof, &huge_row[..9]));
+        assert!(!is_result_set_terminator(old_eof, &[]));
+    }
+
+    #[tokio::test]
+    async fn should_negotiate_deprecate_eof_and_count_rows() -> Result<()> {
+        use crate::consts::CapabilityFlags;
+
+        let mut conn = Conn::new(get_opts()).await?;
+        assert!(conn
+            .capabilities()
+            .contains(CapabilityFlags::CLIENT_DEPRECATE_EOF));
+
+        // rows that start with 0x00
+        let rows: Vec<(String, u8)> = conn
+            .query("SELECT '', 1 UNION ALL SELECT '', 2 UNION ALL SELECT '', 3")
+            .await?;
+        assert_eq!(rows.len(), 3);
+        let rows: Vec<(String, u8)> = conn
+            .exec("SELECT '', ? UNION ALL SELECT '', ?", (1, 2))
+            .await?;
+        assert_eq!(rows.len(), 2);
+
+        // the terminator must not leave the connection in a dirty state
+        assert_eq!(conn.query_first::<u8, _>("SELECT 42").await?, Some(42));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_exec_with_param_type_hints() -> Result<()> {
+        use crate::{consts::ColumnType, DriverError, Error, Value};
+
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_typed (id INT, amount DECIMAL(10, 2))")
+            .await?;
+
+        let types = [
+            ColumnType::MYSQL_TYPE_LONGLONG,
+            ColumnType::MYSQL_TYPE_NEWDECIMAL,
+        ];
+        conn.exec_typed(
+            "INSERT INTO tmp_typed VALUES (?, ?)",
+            (1, Value::NULL),
+            &types,
+        )
+        .await?
+        .drop_result()
+        .await?;
+        conn.exec_typed("INSERT INTO tmp_typed VALUES (?, ?)", (2, "1.5"), &types)
+            .await?
+            .drop_result()
+            .await?;
+
+        let rows: Vec<(u8, Option<String>)> = conn
+            .query("SELECT id, CAST(amount AS CHAR) FROM tmp_typed ORDER BY id")
+            .await?;
+        assert_eq!(rows, vec![(1, None), (2, Some("1.50".into()))]);
+
+        let err = conn
+            .exec_typed("SELECT ?", (1.5_f64,), &[ColumnType::MYSQL_TYPE_NEWDECIMAL])
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Driver(DriverError::IncompatibleParamType {
+                index: 0,
+                column_type: ColumnType::MYSQL_TYPE_NEWDECIMAL,
+            })
+        ));
+
+        let err = conn.exec_typed("SELECT ?", (1,), &types).await.unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Driver(DriverError::ParamTypesMismatch {
+                required: 1,
+                supplied: 2,
+            })
+        ));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn should_prep() -> Result<()> {
         const NAMED: &str = "SELECT :foo, :bar, :foo";
@@ -625,4 +1073,224 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn should_bind_byte_vector_as_single_value() {
+        use crate::{params, Params, Value};
+
+        let blob = vec![0_u8, 0xFF, b','];
+
+        let named = params! { "blob" => blob.clone(), "opt" => Some(blob.clone()) };
+        let expected = Params::Named(
+            vec![
+                (b"blob".to_vec(), Value::Bytes(blob.clone())),
+                (b"opt".to_vec(), Value::Bytes(blob.clone())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(named, expected);
+
+        assert_eq!(
+            Params::from((blob.clone(), &blob[..], None::<Vec<u8>>)),
+            Params::Positional(vec![
+                Value::Bytes(blob.clone()),
+                Value::Bytes(blob.clone()),
+                Value::NULL
+            ])
+        );
+        assert_eq!(
+            Params::from(vec![Value::from(blob.clone())]),
+            Params::Positional(vec![Value::Bytes(blob.clone())])
+        );
+
+        // `Vec<T>` is a list of positional parameters
+        assert_eq!(
+            Params::from(blob),
+            Params::Positional(vec![
+                Value::from(0_u8),
+                Value::from(0xFF_u8),
+                Value::from(b',')
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_roundtrip_byte_vector_as_blob() -> Result<()> {
+        use crate::params;
+
+        let blob = vec![0_u8, 0xFF, b',', b'\'', 0x5C];
+
+        let mut conn = Conn::new(get_opts()).await?;
+        let positional: Option<(Vec<u8>, u64)> = conn
+            .exec_first("SELECT ?, LENGTH(?)", (blob.clone(), blob.clone()))
+            .await?;
+        assert_eq!(positional, Some((blob.clone(), blob.len() as u64)));
+
+        let named: Option<(Vec<u8>, u64)> = conn
+            .exec_first(
+                "SELECT :blob, LENGTH(:blob)",
+                params! { "blob" => blob.clone() },
+            )
+            .await?;
+        assert_eq!(named, Some((blob.clone(), blob.len() as u64)));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_exec_map_and_fold() -> Result<()> {
+        #[derive(Debug, PartialEq)]
+        struct Item {
+            id: u32,
+            name: String,
+        }
+
+        const QUERY: &str = "SELECT id, name FROM (
+            SELECT 1 AS id, 'foo' AS name UNION ALL SELECT 2, 'bar' UNION ALL SELECT 3, 'baz'
+        ) t WHERE id >= ? ORDER BY id";
+
+        let mut conn = Conn::new(get_opts()).await?;
+
+        let items = conn
+            .exec_map(QUERY, (2,), |(id, name)| Item { id, name })
+            .await?;
+        assert_eq!(
+            items,
+            vec![
+                Item {
+                    id: 2,
+                    name: "bar".into()
+                },
+                Item {
+                    id: 3,
+                    name: "baz".into()
+                },
+            ]
+        );
+
+        // same as the text protocol counterpart
+        let text = conn
+            .query_map(QUERY.replace('?', "2"), |(id, name)| Item { id, name })
+            .await?;
+        assert_eq!(items, text);
+
+        let sum = conn
+            .exec_fold(QUERY, (1,), 0, |acc, (id, _): (u32, String)| acc + id)
+            .await?;
+        assert_eq!(sum, 6);
+
+        let first: Option<(u32, String)> = conn.exec_first(QUERY, (3,)).await?;
+        assert_eq!(first, Some((3, "baz".into())));
+        let none: Option<(u32, String)> = conn.exec_first(QUERY, (4,)).await?;
+        assert_eq!(none, None);
+
+        // the connection is usable afterwards
+        assert_eq!(conn.exec_first("SELECT ?", (42,)).await?, Some(42_u8));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_conversion_errors_per_row() -> Result<()> {
+        const QUERY: &str = "SELECT '1' UNION ALL SELECT 'foo' UNION ALL SELECT '3'";
+
+        let mut conn = Conn::new(get_opts()).await?;
+
+        let rows = conn.query_opt::<u8, _>(QUERY).await?;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].as_ref().ok(), Some(&1));
+        assert!(rows[1].is_err());
+        assert_eq!(rows[2].as_ref().ok(), Some(&3));
+
+        let rows = conn.exec_opt::<u8, _, _>(QUERY, ()).await?;
+        assert!(rows[1].is_err());
+        assert_eq!(rows.iter().filter(|x| x.is_ok()).count(), 2);
+
+        let map = |x: std::result::Result<u8, crate::FromRowError>| x.ok();
+        assert_eq!(
+            conn.query_map_opt(QUERY, map).await?,
+            vec![Some(1), None, Some(3)]
+        );
+        assert_eq!(
+            conn.exec_map_opt(QUERY, (), map).await?,
+            vec![Some(1), None, Some(3)]
+        );
+        assert_eq!(
+            QUERY.map_opt(&mut conn, map).await?,
+            vec![Some(1), None, Some(3)]
+        );
+        assert!(QUERY.fetch_opt::<u8, _>(&mut conn).await?[1].is_err());
+
+        // the connection is usable afterwards
+        assert_eq!(conn.query_first("SELECT 42").await?, Some(42_u8));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_exec_cursor() -> Result<()> {
+        use futures_util::TryStreamExt;
+
+        const QUERY: &str = "SELECT seq, CONCAT('row', seq) FROM (
+            SELECT 1 AS seq UNION ALL SELECT 2 UNION ALL SELECT 3
+            UNION ALL SELECT 4 UNION ALL SELECT 5) t ORDER BY seq";
+
+        let mut conn = Conn::new(get_opts()).await?;
+        let expected: Vec<(u8, String)> = conn.exec(QUERY, ()).await?;
+
+        // rows are streamed one by one
+        let cursor = conn.exec_cursor::<(u8, String), _, _>(QUERY, (), 2).await?;
+        assert_eq!(cursor.fetch_size(), 2);
+        assert_eq!(cursor.columns_ref().len(), 2);
+        assert_eq!(cursor.try_collect::<Vec<_>>().await?, expected);
+
+        // or in batches
+        let mut cursor = conn.exec_cursor::<(u8, String), _, _>(QUERY, (), 2).await?;
+        let mut batches = Vec::new();
+        while let Some(batch) = cursor.next_batch().await? {
+            batches.push(batch.len());
+        }
+        assert_eq!(batches, vec![2, 2, 1]);
+        assert!(!cursor.is_open());
+        drop(cursor);
+
+        // statement without a result set
+        let mut cursor = conn.exec_cursor::<u8, _, _>("DO 1", (), 2).await?;
+        assert!(!cursor.is_open());
+        assert_eq!(cursor.next_batch().await?, None);
+        drop(cursor);
+
+        // non-exhausted cursor is closed before the next command
+        let mut cursor = conn.exec_cursor::<(u8, String), _, _>(QUERY, (), 1).await?;
+        assert_eq!(cursor.next_batch().await?, Some(vec![expected[0].clone()]));
+        assert!(cursor.is_open());
+        drop(cursor);
+        assert_eq!(conn.query_first("SELECT 42").await?, Some(42_u8));
+
+        // as well as a cursor dropped in the middle of a fetch
+        let mut cursor = conn.exec_cursor::<(u8, String), _, _>(QUERY, (), 1).await?;
+        assert_eq!(cursor.next_batch().await?, Some(vec![expected[0].clone()]));
+        {
+            let mut next_batch = Box::pin(cursor.next_batch());
+            assert!(futures_util::poll!(&mut next_batch).is_pending());
+        }
+        drop(cursor);
+        assert_eq!(conn.query_first("SELECT 42").await?, Some(42_u8));
+
+        let rows: Vec<(u8, String)> = conn.exec_cursor(QUERY, (), 10).await?.try_collect().await?;
+        assert_eq!(rows, expected);
+
+        // within a transaction
+        let mut tx = conn.start_transaction(Default::default()).await?;
+        let rows: Vec<(u8, String)> = tx.exec_cursor(QUERY, (), 3).await?.try_collect().await?;
+        assert_eq!(rows, expected);
+        tx.rollback().await?;
+
+        conn.disconnect().await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,263 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::{
+    row::ColumnIndex,
+    value::convert::{from_value, from_value_opt, FromValue, FromValueError},
+};
+
+use std::{mem::take, sync::Arc};
+
+use crate::{error::*, prelude::Protocol, Column, QueryResult, Value};
+
+/// Result set in a column-oriented layout (see [`QueryResult::collect_columns`]).
+///
+/// Values of every column are stored contiguously, so that a column could be converted
+/// to a vector of `T` at once using [`Columns::get`] or [`Columns::take`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns {
+    columns: Arc<[Column]>,
+    values: Vec<Vec<Value>>,
+    num_rows: usize,
+}
+
+impl Columns {
+    fn new(columns: Arc<[Column]>) -> Self {
+        Self {
+            values: vec![Vec::new(); columns.len()],
+            columns,
+            num_rows: 0,
+        }
+    }
+
+    /// Returns columns metadata of the result set.
+    pub fn columns_ref(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Returns the number of columns.
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns the number of rows.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Returns `true` if the result set contains no rows.
+    pub fn is_empty(&self) -> bool {
+        self.num_rows == 0
+    }
+
+    /// Returns raw values of a column with the given index or name.
+    ///
+    /// Returns `None` if there is no such column or if the column was taken.
+    pub fn values<I: ColumnIndex>(&self, index: I) -> Option<&[Value]> {
+        self.column_values(&index).map(|values| &values[..])
+    }
+
+    /// Will copy values of a column with the given index or name and convert them to `T`.
+    ///
+    /// Returns `None` if there is no such column or if the column was taken.
+    ///
+    /// # Panic
+    ///
+    /// It'll panic if any value isn't convertible to `T` (see [`Columns::get_opt`]).
+    pub fn get<T, I>(&self, index: I) -> Option<Vec<T>>
+    where
+        T: FromValue,
+        I: ColumnIndex,
+    {
+        self.column_values(&index)
+            .map(|values| values.iter().cloned().map(from_value).collect())
+    }
+
+    /// Will copy values of a column with the given index or name and attempt to convert
+    /// them to `T`. Unlike [`Columns::get`] it allows to handle conversion errors.
+    pub fn get_opt<T, I>(&self, index: I) -> Option<std::result::Result<Vec<T>, FromValueError>>
+    where
+        T: FromValue,
+        I: ColumnIndex,
+    {
+        self.column_values(&index)
+            .map(|values| values.iter().cloned().map(from_value_opt).collect())
+    }
+
+    /// Will take values of a column with the given index or name and convert them to `T`.
+    ///
+    /// Returns `None` if there is no such column or if the column was taken.
+    ///
+    /// # Panic
+    ///
+    /// It'll panic if any value isn't convertible to `T` (see [`Columns::take_opt`]).
+    pub fn take<T, I>(&mut self, index: I) -> Option<Vec<T>>
+    where
+        T: FromValue,
+        I: ColumnIndex,
+    {
+        self.take_column_values(&index)
+            .map(|values| values.into_iter().map(from_value).collect())
+    }
+
+    /// Will take values of a column with the given index or name and attempt to convert
+    /// them to `T`. Unlike [`Columns::take`] it allows to handle conversion errors.
+    pub fn take_opt<T, I>(
+        &mut self,
+        index: I,
+    ) -> Option<std::result::Result<Vec<T>, FromValueError>>
+    where
+        T: FromValue,
+        I: ColumnIndex,
+    {
+        self.take_column_values(&index)
+            .map(|values| values.into_iter().map(from_value_opt).collect())
+    }
+
+    /// Returns raw values of every column (taken columns are empty).
+    pub fn unwrap(self) -> Vec<Vec<Value>> {
+        self.values
+    }
+
+    fn column_values<I: ColumnIndex>(&self, index: &I) -> Option<&Vec<Value>> {
+        let values = &self.values[index.idx(&self.columns)?];
+        // taken column is empty (unless the result set is empty)
+        if values.len() == self.num_rows {
+            Some(values)
+        } else {
+            None
+        }
+    }
+
+    fn take_column_values<I: ColumnIndex>(&mut self, index: &I) -> Option<Vec<Value>> {
+        let idx = index.idx(&self.columns)?;
+        if self.values[idx].len() == self.num_rows {
+            Some(take(&mut self.values[idx]))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, 't: 'a, P> QueryResult<'a, 't, P>
+where
+    P: Protocol,
+{
+    /// Collects the current result set of this query result in a column-oriented layout.
+    ///
+    /// It will stop on the nearest result set boundary (see `QueryResult::collect` docs).
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{prelude::*, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let mut columns = conn
+    ///     .query_iter("SELECT 1 AS id, 'foo' AS name UNION ALL SELECT 2, 'bar'")
+    ///     .await?
+    ///     .collect_columns()
+    ///     .await?;
+    ///
+    /// assert_eq!(columns.num_rows(), 2);
+    /// assert_eq!(columns.take::<u32, _>("id"), Some(vec![1, 2]));
+    /// assert_eq!(columns.take::<String, _>(1), Some(vec!["foo".into(), "bar".into()]));
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn collect_columns(&mut self) -> Result<Columns> {
+        let mut output = Columns::new(self.columns().unwrap_or_else(|| Vec::new().into()));
+
+        while let Some(row) = self.next().await? {
+            if output.num_rows == 0 {
+                // columns of the result set that is actually being read
+                output = Columns::new(row.columns());
+            }
+            for (column, value) in output.values.iter_mut().zip(row.unwrap()) {
+                column.push(value);
+            }
+            output.num_rows += 1;
+        }
+
+        Ok(output)
+    }
+
+    /// Collects the current result set of this query result in a column-oriented layout
+    /// and drops everything else.
+    pub async fn collect_columns_and_drop(mut self) -> Result<Columns> {
+        let output = self.collect_columns().await?;
+        self.drop_result().await?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{prelude::*, test_misc::get_opts, Conn, Value};
+
+    #[tokio::test]
+    async fn should_collect_columns() -> crate::Result<()> {
+        const QUERY: &str = "SELECT 1 AS id, 'foo' AS name, 1.5 AS score
+            UNION ALL SELECT 2, NULL, 2.5
+            UNION ALL SELECT 3, 'baz', NULL";
+
+        let mut conn = Conn::new(get_opts()).await?;
+
+        let rows: Vec<(u32, Option<String>, Option<f64>)> = conn.query(QUERY).await?;
+
+        for binary in [false, true] {
+            let mut columns = if binary {
+                conn.exec_iter(QUERY, ())
+                    .await?
+                    .collect_columns_and_drop()
+                    .await?
+            } else {
+                conn.query_iter(QUERY)
+                    .await?
+                    .collect_columns_and_drop()
+                    .await?
+            };
+
+            assert_eq!(columns.num_columns(), 3);
+            assert_eq!(columns.num_rows(), 3);
+            assert_eq!(columns.columns_ref()[2].name_str(), "score");
+            assert_eq!(columns.values("name").unwrap()[1], Value::NULL);
+
+            let ids = columns.get::<u32, _>(0).unwrap();
+            let names = columns.take::<Option<String>, _>("name").unwrap();
+            let scores = columns.take_opt::<Option<f64>, _>(2).unwrap().unwrap();
+            assert_eq!(
+                ids.into_iter()
+                    .zip(names)
+                    .zip(scores)
+                    .map(|((id, name), score)| (id, name, score))
+                    .collect::<Vec<_>>(),
+                rows
+            );
+
+            // taken column is no longer available
+            assert!(columns.get::<Option<String>, _>(1).is_none());
+            assert!(columns.take::<Option<f64>, _>("score").is_none());
+            assert!(columns.get_opt::<u32, _>("unknown").is_none());
+            assert!(columns.get_opt::<Vec<u8>, _>(0).unwrap().is_ok());
+        }
+
+        // empty result set still has columns
+        let columns = conn
+            .query_iter("SELECT 1 AS id FROM DUAL WHERE FALSE")
+            .await?
+            .collect_columns_and_drop()
+            .await?;
+        assert!(columns.is_empty());
+        assert_eq!(columns.num_columns(), 1);
+        assert_eq!(columns.get::<u32, _>("id"), Some(vec![]));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,430 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::io::ParseBuf;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use std::{borrow::Cow, io};
+
+use crate::{conn::PendingResult, error::*, QueryResult, TextProtocol, Value};
+
+/// Rows are written to the underlying writer in chunks of at least this size.
+const CSV_CHUNK_SIZE: usize = 64 * 1024;
+
+/// NULL cell marker in a text protocol row.
+const NULL_CELL: u8 = 0xFB;
+
+/// Defines when a CSV field is enclosed in quotes (see [`CsvOpts::with_quoting`]).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum CsvQuoting {
+    /// Quote fields that contain a delimiter, a quote or a line break, and fields that are
+    /// equal to the NULL representation (so that an empty string differs from a NULL
+    /// represented as an empty field).
+    Necessary,
+    /// Quote every non-NULL field.
+    Always,
+    /// Never quote fields. Output may not be parseable if data contains special characters.
+    Never,
+}
+
+/// Options of a CSV export (see [`QueryResult::write_csv`]).
+///
+/// Defaults follow RFC 4180 except for the line terminator (`\n`):
+///
+/// ```
+/// # use mysql_async::{CsvOpts, CsvQuoting};
+/// let csv_opts = CsvOpts::default()
+///     .with_delimiter(b';')
+///     .with_quoting(CsvQuoting::Always)
+///     .with_null(&b"\\N"[..]);
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CsvOpts {
+    delimiter: u8,
+    quote: u8,
+    quoting: CsvQuoting,
+    null: Cow<'static, [u8]>,
+    line_terminator: Cow<'static, [u8]>,
+    header: bool,
+}
+
+impl CsvOpts {
+    /// Defines the field delimiter (defaults to `,`).
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Defines the quote character (defaults to `"`).
+    ///
+    /// Quote characters within quoted fields are escaped by doubling.
+    pub fn with_quoting_char(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Defines when fields are quoted (defaults to [`CsvQuoting::Necessary`]).
+    pub fn with_quoting(mut self, quoting: CsvQuoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Defines the representation of a NULL value (defaults to an empty field).
+    ///
+    /// NULL representation is never quoted.
+    pub fn with_null<T: Into<Cow<'static, [u8]>>>(mut self, null: T) -> Self {
+        self.null = null.into();
+        self
+    }
+
+    /// Defines the line terminator (defaults to `\n`).
+    pub fn with_line_terminator<T: Into<Cow<'static, [u8]>>>(mut self, line_terminator: T) -> Self {
+        self.line_terminator = line_terminator.into();
+        self
+    }
+
+    /// Defines whether to write a header line with column names (defaults to `true`).
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Returns the field delimiter.
+    pub fn delimiter(&self) -> u8 {
+        self.delimiter
+    }
+
+    /// Returns the quote character.
+    pub fn quoting_char(&self) -> u8 {
+        self.quote
+    }
+
+    /// Returns the quoting mode.
+    pub fn quoting(&self) -> CsvQuoting {
+        self.quoting
+    }
+
+    /// Returns the representation of a NULL value.
+    pub fn null(&self) -> &[u8] {
+        &self.null
+    }
+
+    /// Returns the line terminator.
+    pub fn line_terminator(&self) -> &[u8] {
+        &self.line_terminator
+    }
+
+    /// Returns `true` if a header line is written.
+    pub fn header(&self) -> bool {
+        self.header
+    }
+
+    fn write_field(&self, output: &mut Vec<u8>, value: Option<&[u8]>) {
+        let value = match value {
+            Some(value) => value,
+            None => {
+                output.extend_from_slice(&self.null);
+                return;
+            }
+        };
+
+        let quote = match self.quoting {
+            CsvQuoting::Always => true,
+            CsvQuoting::Never => false,
+            CsvQuoting::Necessary => {
+                value == &*self.null
+                    || value.iter().any(|x| {
+                        *x == self.delimiter || *x == self.quote || *x == b'\n' || *x == b'\r'
+                    })
+            }
+        };
+
+        if quote {
+            output.push(self.quote);
+            for chunk in value.split_inclusive(|x| *x == self.quote) {
+                output.extend_from_slice(chunk);
+                if chunk.last() == Some(&self.quote) {
+                    output.push(self.quote);
+                }
+            }
+            output.push(self.quote);
+        } else {
+            output.extend_from_slice(value);
+        }
+    }
+
+    fn write_line<'v, I>(&self, output: &mut Vec<u8>, fields: I)
+    where
+        I: IntoIterator<Item = Option<&'v [u8]>>,
+    {
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                output.push(self.delimiter);
+            }
+            self.write_field(output, field);
+        }
+        output.extend_from_slice(&self.line_terminator);
+    }
+
+    /// Writes a line for the given text protocol row packet using raw cell bytes.
+    fn write_text_row(
+        &self,
+        output: &mut Vec<u8>,
+        packet: &[u8],
+        num_columns: usize,
+    ) -> Result<()> {
+        let mut buf = ParseBuf(packet);
+        let mut fields = Vec::with_capacity(num_columns);
+
+        for _ in 0..num_columns {
+            if buf.0.first() == Some(&NULL_CELL) {
+                buf.skip(1);
+                fields.push(None);
+            } else {
+                let value = buf.checked_eat_lenenc_str().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid text protocol row")
+                })?;
+                fields.push(Some(value));
+            }
+        }
+
+        self.write_line(output, fields);
+        Ok(())
+    }
+}
+
+impl Default for CsvOpts {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            quoting: CsvQuoting::Necessary,
+            null: Cow::Borrowed(b""),
+            line_terminator: Cow::Borrowed(b"\n"),
+            header: true,
+        }
+    }
+}
+
+impl<'a, 't: 'a> QueryResult<'a, 't, TextProtocol> {
+    /// Writes the current result set of this query result into the `writer` as CSV.
+    ///
+    /// Cells are written as they were sent by the server (i.e. without conversion
+    /// into a [`crate::Row`]), so this is the fastest way to export a result set.
+    /// It will stop on the nearest result set boundary (see `QueryResult::collect` docs).
+    ///
+    /// Returns the number of written rows (excluding the header).
+    ///
+    /// Note that this is only available for the text protocol, because binary protocol
+    /// cells are not textual.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{prelude::*, Conn, CsvOpts};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let mut output = Vec::new();
+    ///
+    /// let mut result = conn.query_iter("SELECT 'foo, bar', NULL UNION ALL SELECT 'baz', 1").await?;
+    /// let rows = result.write_csv(&mut output, &CsvOpts::default().with_header(false)).await?;
+    ///
+    /// assert_eq!(rows, 2);
+    /// assert_eq!(output, b"\"foo, bar\",\nbaz,1\n");
+    /// # drop(result);
+    /// # conn.disconnect().await }
+    /// ```
+    pub async fn write_csv<W>(&mut self, writer: &mut W, opts: &CsvOpts) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut output = Vec::new();
+        let mut header_written = false;
+        let mut rows = 0;
+
+        loop {
+            if let Some(None) = self.peeked {
+                // the current result set is exhausted by `peek_row`
+                self.peeked = None;
+                break;
+            }
+
+            let columns = match self.conn.use_pending_result()?.cloned() {
+                Some(PendingResult::Pending(meta)) => meta.columns().clone(),
+                Some(PendingResult::Taken(meta)) => {
+                    self.skip_taken(meta).await?;
+                    continue;
+                }
+                None => break,
+            };
+
+            if opts.header && !header_written && !columns.is_empty() {
+                opts.write_line(
+                    &mut output,
+                    columns.iter().map(|column| Some(column.name_ref())),
+                );
+                header_written = true;
+            }
+
+            if let Some(Some(row)) = self.peeked.take() {
+                opts.write_line(
+                    &mut output,
+                    (0..row.len()).map(|i| match row.as_ref(i) {
+                        Some(Value::Bytes(bytes)) => Some(&bytes[..]),
+                        _ => None,
+                    }),
+                );
+                rows += 1;
+                continue;
+            }
+
+            match self.next_row_packet(&columns).await? {
+                Some(packet) => {
+                    opts.write_text_row(&mut output, &packet, columns.len())?;
+                    rows += 1;
+                }
+                None => {
+                    self.next_set().await?;
+                    break;
+                }
+            }
+
+            if output.len() >= CSV_CHUNK_SIZE {
+                writer.write_all(&output).await?;
+                output.clear();
+            }
+        }
+
+        writer.write_all(&output).await?;
+        writer.flush().await?;
+
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{prelude::*, test_misc::get_opts, Conn, CsvOpts, CsvQuoting};
+
+    /// Minimal RFC 4180 parser that maps empty unquoted fields to `None`.
+    fn parse_csv(input: &[u8]) -> Vec<Vec<Option<Vec<u8>>>> {
+        let mut lines = Vec::new();
+        let mut line = Vec::new();
+        let mut input = input.iter().copied().peekable();
+
+        while input.peek().is_some() {
+            let field = if input.peek() == Some(&b'"') {
+                input.next();
+                let mut field = Vec::new();
+                while let Some(x) = input.next() {
+                    match x {
+                        b'"' if input.peek() == Some(&b'"') => {
+                            input.next();
+                            field.push(b'"');
+                        }
+                        b'"' => break,
+                        x => field.push(x),
+                    }
+                }
+                Some(field)
+            } else {
+                let mut field = Vec::new();
+                while let Some(x) = input.next_if(|x| *x != b',' && *x != b'\n') {
+                    field.push(x);
+                }
+                Some(field).filter(|field| !field.is_empty())
+            };
+            line.push(field);
+            if input.next() == Some(b'\n') || input.peek().is_none() {
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+
+        lines
+    }
+
+    #[test]
+    fn should_write_text_row_as_csv() -> crate::Result<()> {
+        // "a,b", NULL, "", "say \"hi\"", "x"
+        let packet = b"\x03a,b\xFB\x00\x08say \"hi\"\x01x";
+
+        let mut output = Vec::new();
+        CsvOpts::default().write_text_row(&mut output, packet, 5)?;
+        assert_eq!(output, b"\"a,b\",,\"\",\"say \"\"hi\"\"\",x\n");
+
+        let mut output = Vec::new();
+        CsvOpts::default()
+            .with_delimiter(b';')
+            .with_quoting(CsvQuoting::Always)
+            .with_null(&b"\\N"[..])
+            .with_line_terminator(&b"\r\n"[..])
+            .write_text_row(&mut output, packet, 5)?;
+        assert_eq!(output, b"\"a,b\";\\N;\"\";\"say \"\"hi\"\"\";\"x\"\r\n");
+
+        let mut output = Vec::new();
+        CsvOpts::default()
+            .with_quoting(CsvQuoting::Never)
+            .write_text_row(&mut output, packet, 5)?;
+        assert_eq!(output, b"a,b,,,say \"hi\",x\n");
+
+        CsvOpts::default()
+            .write_text_row(&mut Vec::new(), b"\x05abc", 1)
+            .unwrap_err();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_export_result_set_as_csv() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_csv (id INT, txt TEXT, blb BLOB)")
+            .await?;
+
+        let rows: Vec<(u32, Option<String>, Option<Vec<u8>>)> = vec![
+            (1, Some("plain".into()), Some(b"\x00\x01\xFF".to_vec())),
+            (2, Some("comma, \"quote\"\r\nnewline".into()), None),
+            (3, Some("".into()), Some(Vec::new())),
+            (4, None, Some(b"\"".to_vec())),
+        ];
+        conn.exec_batch("INSERT INTO tmp_csv VALUES (?, ?, ?)", rows.clone())
+            .await?;
+
+        let mut output = Vec::new();
+        let mut result = conn
+            .query_iter("SELECT * FROM tmp_csv ORDER BY id; SELECT 1")
+            .await?;
+        let count = result.write_csv(&mut output, &CsvOpts::default()).await?;
+        assert_eq!(count, 4);
+        assert_eq!(result.collect::<u8>().await?, vec![1]);
+        drop(result);
+
+        let mut lines = parse_csv(&output).into_iter();
+        assert_eq!(
+            lines.next().unwrap(),
+            vec![
+                Some(b"id".to_vec()),
+                Some(b"txt".to_vec()),
+                Some(b"blb".to_vec())
+            ]
+        );
+        let exported = lines
+            .map(|line| {
+                let mut line = line.into_iter();
+                let id = String::from_utf8(line.next().unwrap().unwrap()).unwrap();
+                let txt = line.next().unwrap().map(|x| String::from_utf8(x).unwrap());
+                let blb = line.next().unwrap();
+                (id.parse().unwrap(), txt, blb)
+            })
+            .collect::<Vec<(u32, _, _)>>();
+        assert_eq!(exported, rows);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
@@ -6,18 +6,32 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use mysql_common::row::convert::FromRowError;
+use mysql_common::{
+    io::ParseBuf,
+    packets::NullBitmap,
+    row::{convert::FromRowError, new_row_raw},
+    value::{BinValue, ServerSide, TextValue, ValueDeserializer},
+};
+
+use std::{borrow::Cow, fmt, io, marker::PhantomData, result::Result as StdResult, sync::Arc};
 
-use std::{borrow::Cow, fmt, marker::PhantomData, result::Result as StdResult, sync::Arc};
+use futures_util::FutureExt;
 
 use crate::{
+    buffer_pool::PooledBuf,
     conn::{routines::NextSetRoutine, PendingResult},
     connection_like::Connection,
+    consts::{ColumnFlags, ColumnType, StatusFlags},
     error::*,
     prelude::{FromRow, Protocol},
-    Column, Row,
+    BoxFuture, Column, Row, Value,
 };
 
+pub use self::columns::Columns;
+pub use self::csv::{CsvOpts, CsvQuoting};
+
+mod columns;
+mod csv;
 pub mod result_set_stream;
 mod tests;
 
@@ -38,10 +52,96 @@ impl ResultSetMeta {
     }
 }
 
+/// Decodes values of the given row packet into `values` reusing its allocation.
+fn read_row_values(
+    meta: &ResultSetMeta,
+    packet: &[u8],
+    values: &mut Vec<Option<Value>>,
+) -> Result<()> {
+    values.clear();
+    let mut buf = ParseBuf(packet);
+
+    match meta {
+        ResultSetMeta::Text(columns) => {
+            for _ in 0..columns.len() {
+                let value: ValueDeserializer<TextValue> = buf.parse(())?;
+                values.push(Some(value.0));
+            }
+        }
+        ResultSetMeta::Binary(columns) => {
+            // packet header
+            buf.checked_eat_u8()
+                .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+            let bitmap: NullBitmap<ServerSide, Cow<'_, [u8]>> = buf.parse(columns.len())?;
+            for (i, column) in columns.iter().enumerate() {
+                if bitmap.is_null(i) {
+                    values.push(Some(Value::NULL));
+                } else {
+                    let value: ValueDeserializer<BinValue> =
+                        buf.parse((column.column_type(), column.flags()))?;
+                    values.push(Some(value.0));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Result of a query or statement execution.
 ///
 /// Represents an asynchronous query result, that may not be fully consumed.
 ///
+/// # Multiple result sets
+///
+/// A query result may contain multiple result sets (e.g. result of a multi-statement query
+/// or of a `CALL` to a stored procedure). Every call to [`QueryResult::collect`] consumes
+/// exactly one result set, so heterogeneous result sets could be consumed one at a time:
+///
+/// ```rust
+/// # use mysql_async::test_misc::get_opts;
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// use mysql_async::*;
+/// use mysql_async::prelude::*;
+/// let mut conn = Conn::new(get_opts()).await?;
+///
+/// let mut result = conn.query_iter("SELECT 1, 'foo'; SELECT 'bar'").await?;
+/// let first = result.collect::<(u8, String)>().await?;
+/// let second = result.collect::<String>().await?;
+/// assert_eq!(first, vec![(1, "foo".into())]);
+/// assert_eq!(second, vec![String::from("bar")]);
+/// assert!(result.is_empty());
+///
+/// # conn.disconnect().await }
+/// ```
+///
+/// Note that `CALL` always emits a trailing OK packet after the last result set
+/// of a procedure, so the result won't be empty after the last `collect`.
+/// Use [`QueryResult::drop_result`] to consume it or use [`QueryResult::collect_sets_and_drop`]
+/// (see also [`Queryable::exec_call`][exec_call]) that does this automatically:
+///
+/// ```rust
+/// # use mysql_async::test_misc::get_opts;
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// use mysql_async::*;
+/// use mysql_async::prelude::*;
+/// let mut conn = Conn::new(get_opts()).await?;
+///
+/// let (first, second) = conn
+///     .query_iter("SELECT 1, 'foo'; SELECT 'bar'")
+///     .await?
+///     .collect_sets_and_drop::<(Vec<(u8, String)>, Vec<String>)>()
+///     .await?;
+/// assert_eq!(first, vec![(1, "foo".into())]);
+/// assert_eq!(second, vec![String::from("bar")]);
+///
+/// # conn.disconnect().await }
+/// ```
+///
+/// [exec_call]: crate::prelude::Queryable::exec_call
+///
 /// # Note
 ///
 /// Unconsumed query results are dropped implicitly when corresponding connection
@@ -70,6 +170,9 @@ impl ResultSetMeta {
 /// ```
 pub struct QueryResult<'a, 't: 'a, P> {
     conn: Connection<'a, 't>,
+    /// Row read ahead by [`QueryResult::peek_row`] (`Some(None)` means that
+    /// the current result set turned out to be exhausted).
+    peeked: Option<Option<Row>>,
     __phantom: PhantomData<P>,
 }
 
@@ -77,6 +180,7 @@ impl<'a, 't: 'a, P> fmt::Debug for QueryResult<'a, 't, P> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("QueryResult")
             .field("conn", &self.conn)
+            .field("peeked", &self.peeked)
             .field("__phantom", &"PhantomData<P>")
             .finish()
     }
@@ -89,6 +193,7 @@ where
     pub fn new<T: Into<Connection<'a, 't>>>(conn: T) -> Self {
         QueryResult {
             conn: conn.into(),
+            peeked: None,
             __phantom: PhantomData,
         }
     }
@@ -113,18 +218,31 @@ where
     /// by the [`QueryResult::stream`] that was dropped before being fully consumed
     /// (i.e. caller will get `false` even if QueryResult data is reachable only for library internals).
     pub fn is_empty(&self) -> bool {
-        !self.has_rows() && !self.conn.more_results_exists()
+        !matches!(self.peeked, Some(Some(_)))
+            && !self.has_rows()
+            && !self.conn.more_results_exists()
     }
 
     /// Low-level function that reads a result set row.
     ///
     /// Returns `None` if there are no more rows in the current set.
     async fn next_row(&mut self, columns: Arc<[Column]>) -> crate::Result<Option<Row>> {
+        match self.next_row_packet(&columns).await? {
+            Some(packet) => Ok(Some(P::read_result_set_row(&packet, columns)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Low-level function that reads a raw result set row packet.
+    ///
+    /// Returns `None` if there are no more rows in the current set.
+    async fn next_row_packet(&mut self, columns: &[Column]) -> crate::Result<Option<PooledBuf>> {
         let mut row = None;
 
         if columns.is_empty() {
             // Empty, but not yet consumed result set.
             self.conn.set_pending_result(None)?;
+            self.conn.finish_slow_query_timer();
         } else {
             // Not yet consumed non-empty result set.
             let packet = match self.conn.read_packet().await {
@@ -132,6 +250,7 @@ where
                 Err(err) => {
                     // Next row contained an error. No more data will follow.
                     self.conn.set_pending_result(None)?;
+                    self.conn.cancel_slow_query_timer();
                     return Err(err);
                 }
             };
@@ -139,9 +258,10 @@ where
             if P::is_last_result_set_packet(self.conn.capabilities(), &packet) {
                 // `packet` is a result set terminator.
                 self.conn.set_pending_result(None)?;
+                self.conn.finish_slow_query_timer();
             } else {
                 // `packet` is a result set row.
-                row = Some(P::read_result_set_row(&packet, columns)?);
+                row = Some(packet);
             }
         }
 
@@ -173,6 +293,10 @@ where
         &mut self,
         columns: Arc<[Column]>,
     ) -> crate::Result<Option<Row>> {
+        if let Some(row) = self.peeked.take() {
+            return Ok(row);
+        }
+
         if let Some(row) = self.next_row(columns).await? {
             Ok(Some(row))
         } else {
@@ -189,6 +313,10 @@ where
 
     #[doc(hidden)]
     pub async fn next(&mut self) -> Result<Option<Row>> {
+        if let Some(row) = self.peeked.take() {
+            return Ok(row);
+        }
+
         loop {
             match self.conn.use_pending_result()?.cloned() {
                 Some(PendingResult::Pending(meta)) => return self.next_row_or_next_set(meta).await,
@@ -198,6 +326,41 @@ where
         }
     }
 
+    /// Returns the next row of the current result set without consuming it.
+    ///
+    /// The row is read from the server and buffered, so that it's yielded again
+    /// by the subsequent fetch (e.g. by [`QueryResult::collect`] or [`QueryResult::stream`]).
+    /// Exactly one row is buffered, i.e. repeated calls return the same row.
+    ///
+    /// Returns `None` if the current result set is exhausted (or if it never meant to contain
+    /// rows), in which case the subsequent fetch yields nothing as well. Note that
+    /// [`QueryResult::stream`] returns `None` for such a result set.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{prelude::*, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let mut result = conn.query_iter("SELECT 1, 'foo' UNION ALL SELECT 2, 'bar'").await?;
+    ///
+    /// let first = result.peek_row().await?.expect("first row");
+    /// assert_eq!(first.len(), 2);
+    ///
+    /// // the peeked row isn't lost
+    /// let rows = result.collect::<(u8, String)>().await?;
+    /// assert_eq!(rows, vec![(1, "foo".into()), (2, "bar".into())]);
+    /// # drop(result); conn.disconnect().await }
+    /// ```
+    pub async fn peek_row(&mut self) -> Result<Option<&Row>> {
+        if self.peeked.is_none() {
+            let row = self.next().await?;
+            self.peeked = Some(row);
+        }
+        Ok(self.peeked.as_ref().and_then(Option::as_ref))
+    }
+
     /// Last insert id, if any.
     pub fn last_insert_id(&self) -> Option<u64> {
         self.conn.last_insert_id()
@@ -218,6 +381,11 @@ where
         self.conn.get_warnings()
     }
 
+    /// Server status flags as reported by the server (see [`crate::Conn::server_status_flags`]).
+    pub fn server_status(&self) -> StatusFlags {
+        self.conn.server_status_flags()
+    }
+
     /// Collects the current result set of this query result.
     ///
     /// It is parametrized by `R` and internally calls `R::from_row(Row)` on each row.
@@ -243,6 +411,36 @@ where
         .await
     }
 
+    /// Collects a number of consecutive result sets of this query result,
+    /// possibly of different row types (see [`FromResultSets`]).
+    ///
+    /// Remaining result sets won't be consumed.
+    ///
+    /// # Panic
+    ///
+    /// It'll panic if any row isn't convertible to its target type (see [`QueryResult::collect`]).
+    pub async fn collect_sets<R>(&mut self) -> Result<R>
+    where
+        R: FromResultSets,
+    {
+        R::from_result_sets(self).await
+    }
+
+    /// Collects a number of consecutive result sets of this query result and drops
+    /// everything else (including the trailing OK packet emitted by `CALL`).
+    ///
+    /// # Panic
+    ///
+    /// It'll panic if any row isn't convertible to its target type (see [`QueryResult::collect`]).
+    pub async fn collect_sets_and_drop<R>(mut self) -> Result<R>
+    where
+        R: FromResultSets,
+    {
+        let output = self.collect_sets::<R>().await?;
+        self.drop_result().await?;
+        Ok(output)
+    }
+
     /// Collects the current result set of this query result.
     ///
     /// It works the same way as [`QueryResult::collect`] but won't panic if row isn't convertible
@@ -314,6 +512,92 @@ where
         Ok(())
     }
 
+    /// Executes `fun` on a reference to every row of the current result set.
+    ///
+    /// Unlike [`QueryResult::for_each`] and [`QueryResult::map`], it doesn't hand out
+    /// the ownership of a row, so it is meant for high-throughput scanning, where only a couple
+    /// of values are extracted (e.g. via [`Row::as_ref`]) and the row is discarded.
+    ///
+    /// # Buffer reuse
+    ///
+    /// *   row packets are read into buffers of the connection's buffer pool, and a packet buffer
+    ///     is returned to the pool right after the row is decoded, so it is reused by the next row;
+    /// *   every row is decoded into the same [`Row`], i.e. the storage for row values is allocated
+    ///     once per call. Only string and blob values (`Value::Bytes`) are allocated per value;
+    /// *   nothing is accumulated, so the memory usage doesn't grow with the number of rows.
+    ///
+    /// It will stop on the nearest result set boundary (see `QueryResult::collect` docs).
+    ///
+    /// ```rust
+    /// # use mysql_async::{prelude::*, test_misc::get_opts, Conn, Value};
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let mut result = conn
+    ///     .query_iter("SELECT 1, 'foo' UNION ALL SELECT 2, 'bar'")
+    ///     .await?;
+    ///
+    /// let mut total_len = 0;
+    /// result
+    ///     .scan_rows(|row| {
+    ///         if let Some(Value::Bytes(bytes)) = row.as_ref(1) {
+    ///             total_len += bytes.len();
+    ///         }
+    ///     })
+    ///     .await?;
+    /// assert_eq!(total_len, 6);
+    /// # drop(result);
+    /// # conn.disconnect().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn scan_rows<F>(&mut self, mut fun: F) -> Result<()>
+    where
+        F: FnMut(&Row),
+    {
+        match self.peeked.take() {
+            Some(Some(row)) => fun(&row),
+            Some(None) => return Ok(()),
+            None => (),
+        }
+
+        let mut values = Vec::new();
+        loop {
+            let meta = match self.conn.use_pending_result()?.cloned() {
+                Some(PendingResult::Pending(meta)) => meta,
+                Some(PendingResult::Taken(meta)) => {
+                    self.skip_taken(meta).await?;
+                    continue;
+                }
+                None => return Ok(()),
+            };
+
+            match self.next_row_packet(meta.columns()).await? {
+                Some(packet) => {
+                    read_row_values(&meta, &packet, &mut values)?;
+                    drop(packet);
+
+                    let row = new_row_raw(values, meta.columns().clone());
+                    fun(&row);
+                    values = row.unwrap_raw();
+                }
+                None => {
+                    self.next_set().await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Executes `fun` on a reference to every row of the current result set and drops
+    /// everything else (see [`QueryResult::scan_rows`]).
+    pub async fn scan_rows_and_drop<F>(mut self, fun: F) -> Result<()>
+    where
+        F: FnMut(&Row),
+    {
+        self.scan_rows(fun).await?;
+        self.drop_result().await
+    }
+
     /// Maps every row of the current result set to `U` using `fun`.
     ///
     /// It will stop on the nearest result set boundary (see `QueryResult::collect` docs).
@@ -400,4 +684,105 @@ where
             })
             .cloned()
     }
+
+    /// Returns types of columns of this query result.
+    pub fn column_types(&self) -> Vec<ColumnType> {
+        self.columns_ref()
+            .iter()
+            .map(|column| column.column_type())
+            .collect()
+    }
+
+    /// Returns `true` for every column of this query result that may contain `NULL`.
+    pub fn column_nullable_flags(&self) -> Vec<bool> {
+        self.columns_ref()
+            .iter()
+            .map(|column| !column.flags().contains(ColumnFlags::NOT_NULL_FLAG))
+            .collect()
+    }
+
+    /// Returns `true` for every column of this query result that is unsigned.
+    pub fn column_unsigned_flags(&self) -> Vec<bool> {
+        self.columns_ref()
+            .iter()
+            .map(|column| column.flags().contains(ColumnFlags::UNSIGNED_FLAG))
+            .collect()
+    }
+
+    /// Returns `(name, type, nullable)` for every column of this query result.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use mysql_async::{consts::ColumnType, prelude::*, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let result = conn.query_iter("SELECT 1 AS foo, NULL AS bar").await?;
+    ///
+    /// assert_eq!(
+    ///     result.column_descriptions(),
+    ///     vec![
+    ///         ("foo".to_owned(), ColumnType::MYSQL_TYPE_LONGLONG, false),
+    ///         ("bar".to_owned(), ColumnType::MYSQL_TYPE_NULL, true),
+    ///     ]
+    /// );
+    /// # result.drop_result().await?;
+    /// # conn.disconnect().await }
+    /// ```
+    pub fn column_descriptions(&self) -> Vec<(String, ColumnType, bool)> {
+        self.columns_ref()
+            .iter()
+            .map(|column| {
+                (
+                    column.name_str().into_owned(),
+                    column.column_type(),
+                    !column.flags().contains(ColumnFlags::NOT_NULL_FLAG),
+                )
+            })
+            .collect()
+    }
 }
+
+/// Types, that could be collected from a number of consecutive result sets.
+///
+/// It is implemented for tuples of up to twelve `Vec<T>` where `T: FromRow`,
+/// each element corresponds to one result set, so that
+/// `(Vec<A>, Vec<B>)` will collect the first result set into `Vec<A>`
+/// and the second one into `Vec<B>`.
+///
+/// See [`QueryResult::collect_sets`].
+pub trait FromResultSets: Sized + Send + 'static {
+    /// Collects `Self` from the given query result.
+    fn from_result_sets<'r, 'a: 'r, 't: 'a, P: Protocol>(
+        result: &'r mut QueryResult<'a, 't, P>,
+    ) -> BoxFuture<'r, Self>;
+}
+
+macro_rules! impl_from_result_sets {
+    ($($name:ident)+) => {
+        impl<$($name,)+> FromResultSets for ($(Vec<$name>,)+)
+        where
+            $($name: FromRow + Send + 'static,)+
+        {
+            fn from_result_sets<'r, 'a: 'r, 't: 'a, P: Protocol>(
+                result: &'r mut QueryResult<'a, 't, P>,
+            ) -> BoxFuture<'r, Self> {
+                async move { Ok(($(result.collect::<$name>().await?,)+)) }.boxed()
+            }
+        }
+    };
+}
+
+impl_from_result_sets!(A);
+impl_from_result_sets!(A B);
+impl_from_result_sets!(A B C);
+impl_from_result_sets!(A B C D);
+impl_from_result_sets!(A B C D E);
+impl_from_result_sets!(A B C D E F);
+impl_from_result_sets!(A B C D E F G);
+impl_from_result_sets!(A B C D E F G H);
+impl_from_result_sets!(A B C D E F G H I);
+impl_from_result_sets!(A B C D E F G H I J);
+impl_from_result_sets!(A B C D E F G H I J K);
+impl_from_result_sets!(A B C D E F G H I J K L);
@@ -8,6 +8,7 @@
 
 use std::any::type_name;
 use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::task::Poll;
 use std::{fmt, marker::PhantomData};
@@ -46,9 +47,14 @@ impl<'r, 'a: 'r, 't: 'a, P> AsMut<QueryResult<'a, 't, P>> for CowMut<'r, 'a, 't,
     }
 }
 
+/// Rows fetched by a single fetch operation.
+///
+/// `done` is `true` if the result set boundary was reached.
+type Fetched = (VecDeque<Row>, crate::Result<bool>);
+
 enum ResultSetStreamState<'r, 'a: 'r, 't: 'a, P> {
     Idle(CowMut<'r, 'a, 't, P>),
-    NextFut(BoxFuture<'r, (crate::Result<Option<Row>>, CowMut<'r, 'a, 't, P>)>),
+    NextFut(BoxFuture<'r, (Fetched, CowMut<'r, 'a, 't, P>)>),
 }
 
 impl<'r, 'a: 'r, 't: 'a, P> fmt::Debug for ResultSetStreamState<'r, 'a, 't, P> {
@@ -57,9 +63,7 @@ impl<'r, 'a: 'r, 't: 'a, P> fmt::Debug for ResultSetStreamState<'r, 'a, 't, P> {
             Self::Idle(arg0) => f.debug_tuple("Idle").field(arg0).finish(),
             Self::NextFut(_arg0) => f
                 .debug_tuple("NextFut")
-                .field(&type_name::<
-                    BoxFuture<'r, (crate::Result<Option<Row>>, CowMut<'r, 'a, 't, P>)>,
-                >())
+                .field(&type_name::<BoxFuture<'r, (Fetched, CowMut<'r, 'a, 't, P>)>>())
                 .finish(),
         }
     }
@@ -69,8 +73,17 @@ impl<'r, 'a: 'r, 't: 'a, P> fmt::Debug for ResultSetStreamState<'r, 'a, 't, P> {
 /// Rows stream for a single result set.
 pub struct ResultSetStream<'r, 'a: 'r, 't: 'a, T, P> {
     query_result: Option<ResultSetStreamState<'r, 'a, 't, P>>,
+    /// Query result, once the current result set is exhausted
+    /// (see [`ResultSetStream::next_set`]).
+    finished: Option<CowMut<'r, 'a, 't, P>>,
     ok_packet: Option<OkPacket<'static>>,
     columns: Arc<[Column]>,
+    /// Rows fetched ahead of the consumer.
+    buffer: VecDeque<Row>,
+    /// Error that follows buffered rows.
+    error: Option<crate::Error>,
+    /// See [`crate::Opts::row_prefetch`].
+    prefetch: usize,
     __from_row_type: PhantomData<T>,
 }
 
@@ -80,7 +93,7 @@ where
     T: FromRow + Unpin + Send + 'static,
 {
     fn is_terminated(&self) -> bool {
-        self.query_result.is_none()
+        self.query_result.is_none() && self.buffer.is_empty() && self.error.is_none()
     }
 }
 
@@ -138,6 +151,15 @@ impl<'r, 'a: 'r, 't: 'a, T, P> ResultSetStream<'r, 'a, 't, T, P> {
     pub fn ok_packet(&self) -> Option<&OkPacket<'static>> {
         self.ok_packet.as_ref()
     }
+
+    /// Returns the number of rows fetched from the server, but not yet consumed.
+    ///
+    /// It will never exceed [`Opts::row_prefetch`][1] (minus one row yielded to the consumer).
+    ///
+    /// [1]: crate::Opts::row_prefetch
+    pub fn buffered_rows(&self) -> usize {
+        self.buffer.len()
+    }
 }
 
 impl<'r, 'a: 'r, 't: 'a, T, P> Stream for ResultSetStream<'r, 'a, 't, T, P>
@@ -153,24 +175,52 @@ where
     ) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
         loop {
+            if let Some(row) = this.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(crate::from_row(row))));
+            }
+            if let Some(err) = this.error.take() {
+                return Poll::Ready(Some(Err(err)));
+            }
             let columns = this.columns.clone();
+            let prefetch = this.prefetch;
             match this.query_result.take() {
                 Some(ResultSetStreamState::Idle(mut query_result)) => {
                     let fut = Box::pin(async move {
-                        let row = query_result.as_mut().next_row_or_next_set2(columns).await;
-                        (row, query_result)
+                        let mut rows = VecDeque::with_capacity(prefetch);
+                        let done = loop {
+                            match query_result
+                                .as_mut()
+                                .next_row_or_next_set2(columns.clone())
+                                .await
+                            {
+                                Ok(Some(row)) => {
+                                    rows.push_back(row);
+                                    if rows.len() >= prefetch {
+                                        break Ok(false);
+                                    }
+                                }
+                                Ok(None) => break Ok(true),
+                                Err(err) => break Err(err),
+                            }
+                        };
+                        ((rows, done), query_result)
                     });
                     this.query_result = Some(ResultSetStreamState::NextFut(fut));
                 }
                 Some(ResultSetStreamState::NextFut(mut fut)) => match fut.poll_unpin(cx) {
-                    Poll::Ready((row, query_result)) => match row {
-                        Ok(Some(row)) => {
-                            this.query_result = Some(ResultSetStreamState::Idle(query_result));
-                            return Poll::Ready(Some(Ok(crate::from_row(row))));
+                    Poll::Ready(((rows, done), query_result)) => {
+                        this.buffer = rows;
+                        match done {
+                            Ok(false) => {
+                                this.query_result = Some(ResultSetStreamState::Idle(query_result))
+                            }
+                            Ok(true) => this.finished = Some(query_result),
+                            Err(err) => this.error = Some(err),
                         }
-                        Ok(None) => return Poll::Ready(None),
-                        Err(err) => return Poll::Ready(Some(Err(err))),
-                    },
+                        if this.buffer.is_empty() && this.error.is_none() {
+                            return Poll::Ready(None);
+                        }
+                    }
                     Poll::Pending => {
                         this.query_result = Some(ResultSetStreamState::NextFut(fut));
                         return Poll::Pending;
@@ -182,13 +232,114 @@ where
     }
 }
 
+impl<'r, 'a: 'r, 't: 'a, T, P> ResultSetStream<'r, 'a, 't, T, P>
+where
+    P: Protocol + Unpin,
+{
+    /// Returns a stream for the next result set, if any (e.g. for a `CALL` statement
+    /// or a multi-statement query).
+    ///
+    /// Unconsumed rows of the current result set are skipped (any error that remains
+    /// in the current result set is emitted here). Result sets are yielded up to the last one
+    /// containing columns, so the trailing result set without columns (e.g. the OK packet
+    /// emitted by `CALL`) is consumed and `None` is returned.
+    ///
+    /// ```rust
+    /// # use mysql_async::test_misc::get_opts;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// use futures_util::TryStreamExt;
+    /// use mysql_async::{prelude::*, Conn};
+    ///
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let mut result = conn.query_iter("SELECT 1 UNION ALL SELECT 2; SELECT 'foo'").await?;
+    ///
+    /// let mut stream = result.stream::<u8>().await?.expect("first result set");
+    /// assert_eq!(stream.try_next().await?, Some(1));
+    ///
+    /// // the rest of the first result set is skipped
+    /// let stream = stream.next_set::<String>().await?.expect("second result set");
+    /// assert_eq!(stream.try_collect::<Vec<_>>().await?, vec!["foo"]);
+    /// # drop(result); conn.disconnect().await }
+    /// ```
+    pub fn next_set<U>(
+        self,
+    ) -> BoxFuture<'r, crate::Result<Option<ResultSetStream<'r, 'a, 't, U, P>>>>
+    where
+        U: FromRow + Unpin + Send + 'static,
+    {
+        let ResultSetStream {
+            query_result,
+            finished,
+            columns,
+            error,
+            ..
+        } = self;
+
+        async move {
+            if let Some(err) = error {
+                return Err(err);
+            }
+
+            let (mut query_result, done) = match (query_result, finished) {
+                (Some(ResultSetStreamState::Idle(query_result)), _) => (query_result, false),
+                (Some(ResultSetStreamState::NextFut(fut)), _) => {
+                    let ((_, done), query_result) = fut.await;
+                    (query_result, done?)
+                }
+                (None, Some(query_result)) => (query_result, true),
+                // the current result set contained an error, so no more data will follow
+                (None, None) => return Ok(None),
+            };
+
+            if !done {
+                // skip the rest of the current result set
+                while query_result
+                    .as_mut()
+                    .next_row_or_next_set2(columns.clone())
+                    .await?
+                    .is_some()
+                {}
+            }
+
+            match query_result.as_mut().setup_stream().await? {
+                Some((_, columns, _))
+                    if columns.is_empty() && !query_result.as_mut().conn.more_results_exists() =>
+                {
+                    // the trailing OK packet
+                    query_result.as_mut().next_row_or_next_set2(columns).await?;
+                    Ok(None)
+                }
+                Some((ok_packet, columns, prefetch)) => Ok(Some(ResultSetStream {
+                    ok_packet,
+                    columns,
+                    buffer: VecDeque::new(),
+                    error: None,
+                    prefetch,
+                    query_result: Some(ResultSetStreamState::Idle(query_result)),
+                    finished: None,
+                    __from_row_type: PhantomData,
+                })),
+                None => Ok(None),
+            }
+        }
+        .boxed()
+    }
+}
+
 impl<'a, 't: 'a, P> QueryResult<'a, 't, P>
 where
     P: Protocol + Unpin,
 {
     async fn setup_stream(
         &mut self,
-    ) -> crate::Result<Option<(Option<OkPacket<'static>>, Arc<[Column]>)>> {
+    ) -> crate::Result<Option<(Option<OkPacket<'static>>, Arc<[Column]>, usize)>> {
+        if let Some(None) = self.peeked {
+            // the current result set is exhausted by `peek_row`
+            self.peeked = None;
+            return Ok(None);
+        }
+
         match self.conn.use_pending_result()? {
             Some(PendingResult::Taken(meta)) => {
                 let meta = (*meta).clone();
@@ -204,7 +355,7 @@ where
             None => return Ok(None),
         };
 
-        Ok(Some((ok_packet, columns)))
+        Ok(Some((ok_packet, columns, self.conn.opts().row_prefetch())))
     }
 
     /// Returns a [`Stream`] for the current result set.
@@ -220,6 +371,14 @@ where
     /// If the row type is unknown please use the [`Row`] type for `T`
     /// to make this conversion infallible.
     ///
+    /// ## Prefetch
+    ///
+    /// Stream will fetch up to [`Opts::row_prefetch`][row_prefetch] rows from the server
+    /// at once and buffer them until consumed. The default value of `1` means that the next row
+    /// is read from the connection only when requested by the consumer.
+    ///
+    /// [row_prefetch]: crate::Opts::row_prefetch
+    ///
     /// ## Consumption
     ///
     /// The call to [`QueryResult::stream`] entails the consumption of the current result set,
@@ -283,10 +442,14 @@ where
                 .setup_stream()
                 .await?
                 .map(
-                    move |(ok_packet, columns)| ResultSetStream::<'r, 'a, 't, T, P> {
+                    move |(ok_packet, columns, prefetch)| ResultSetStream::<'r, 'a, 't, T, P> {
                         ok_packet,
                         columns,
+                        buffer: VecDeque::new(),
+                        error: None,
+                        prefetch,
                         query_result: Some(ResultSetStreamState::Idle(CowMut::Borrowed(self))),
+                        finished: None,
                         __from_row_type: PhantomData,
                     },
                 ))
@@ -347,12 +510,18 @@ where
             Ok(self
                 .setup_stream()
                 .await?
-                .map(|(ok_packet, columns)| ResultSetStream::<'a, 'a, 't, T, P> {
-                    ok_packet,
-                    columns,
-                    query_result: Some(ResultSetStreamState::Idle(CowMut::Owned(self))),
-                    __from_row_type: PhantomData,
-                }))
+                .map(
+                    |(ok_packet, columns, prefetch)| ResultSetStream::<'a, 'a, 't, T, P> {
+                        ok_packet,
+                        columns,
+                        buffer: VecDeque::new(),
+                        error: None,
+                        prefetch,
+                        query_result: Some(ResultSetStreamState::Idle(CowMut::Owned(self))),
+                        finished: None,
+                        __from_row_type: PhantomData,
+                    },
+                ))
         }
         .boxed()
     }
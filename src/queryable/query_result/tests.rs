@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use futures_util::TryStreamExt;
+use futures_util::{StreamExt, TryStreamExt};
 
 use crate::{from_row, prelude::*, test_misc::get_opts, Conn, Row, TxOpts};
 
@@ -68,6 +68,36 @@ async fn should_stream_binary_result_sets() -> crate::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn should_respect_row_prefetch() -> crate::Result<()> {
+    const QUERY: &str = "SELECT 1 UNION ALL SELECT 2 UNION ALL SELECT 3 UNION ALL SELECT 4";
+
+    let mut conn = Conn::new(get_opts().row_prefetch(1)).await?;
+    let mut result = QUERY.run(&mut conn).await?;
+    let mut stream = result.stream::<u8>().await?.unwrap();
+    assert_eq!(stream.buffered_rows(), 0);
+    assert_eq!(stream.next().await.transpose()?, Some(1));
+    // strict pull semantics: nothing is fetched ahead of the consumer
+    assert_eq!(stream.buffered_rows(), 0);
+    assert_eq!(stream.next().await.transpose()?, Some(2));
+    assert_eq!(stream.buffered_rows(), 0);
+    assert_eq!(vec![3, 4], stream.try_collect::<Vec<_>>().await?);
+    drop(result);
+    conn.disconnect().await?;
+
+    let mut conn = Conn::new(get_opts().row_prefetch(3)).await?;
+    let mut result = QUERY.run(&mut conn).await?;
+    let mut stream = result.stream::<u8>().await?.unwrap();
+    assert_eq!(stream.next().await.transpose()?, Some(1));
+    assert_eq!(stream.buffered_rows(), 2);
+    assert_eq!(vec![2, 3, 4], stream.try_collect::<Vec<_>>().await?);
+    assert!(result.is_empty());
+    drop(result);
+    conn.disconnect().await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn dropped_query_result_should_emit_errors_on_cleanup() -> super::Result<()> {
     use crate::{Error::Server, ServerError};
@@ -148,6 +178,94 @@ async fn should_handle_mutliresult_set() -> super::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn should_collect_heterogeneous_result_sets_of_a_procedure() -> super::Result<()> {
+    let mut conn = Conn::new(get_opts()).await?;
+    conn.query_drop("DROP PROCEDURE IF EXISTS heterogeneous_sets")
+        .await?;
+    conn.query_drop(
+        r"CREATE PROCEDURE heterogeneous_sets(x INT)
+            BEGIN
+                SELECT x, 'foo' UNION ALL SELECT x + 1, 'bar';
+                SELECT 'baz';
+            END",
+    )
+    .await?;
+
+    let mut result = conn.exec_iter("CALL heterogeneous_sets(?)", (1,)).await?;
+    let rows_1 = result.collect::<(u8, String)>().await?;
+    let rows_2 = result.collect::<String>().await?;
+    // trailing OK packet emitted by CALL
+    assert!(!result.is_empty());
+    result.drop_result().await?;
+    assert_eq!(vec![(1, "foo".into()), (2, "bar".into())], rows_1);
+    assert_eq!(vec![String::from("baz")], rows_2);
+
+    let (rows_1, rows_2) = conn
+        .exec_call::<(Vec<(u8, String)>, Vec<String>), _, _>("CALL heterogeneous_sets(?)", (5,))
+        .await?;
+    assert_eq!(vec![(5, "foo".into()), (6, "bar".into())], rows_1);
+    assert_eq!(vec![String::from("baz")], rows_2);
+
+    // connection should be clean
+    assert_eq!(conn.query_first::<u8, _>("SELECT 42").await?, Some(42));
+
+    conn.query_drop("DROP PROCEDURE heterogeneous_sets").await?;
+    conn.disconnect().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_stream_result_sets_of_a_procedure_via_next_set() -> super::Result<()> {
+    let mut conn = Conn::new(get_opts()).await?;
+    conn.query_drop("DROP PROCEDURE IF EXISTS stream_sets")
+        .await?;
+    conn.query_drop(
+        r"CREATE PROCEDURE stream_sets(x INT)
+            BEGIN
+                SELECT x, 'foo' UNION ALL SELECT x + 1, 'bar';
+                SELECT 'baz';
+                SELECT 42;
+            END",
+    )
+    .await?;
+
+    // fully consumed streams
+    let mut result = conn.exec_iter("CALL stream_sets(?)", (1,)).await?;
+    let mut stream = result.stream::<(u8, String)>().await?.unwrap();
+    let rows_1 = stream.by_ref().try_collect::<Vec<_>>().await?;
+    let mut stream = stream.next_set::<String>().await?.unwrap();
+    let rows_2 = stream.by_ref().try_collect::<Vec<_>>().await?;
+    let mut stream = stream.next_set::<u16>().await?.unwrap();
+    let rows_3 = stream.by_ref().try_collect::<Vec<_>>().await?;
+    // the trailing OK packet emitted by CALL is consumed
+    assert!(stream.next_set::<Row>().await?.is_none());
+    assert!(result.is_empty());
+
+    assert_eq!(vec![(1, "foo".into()), (2, "bar".into())], rows_1);
+    assert_eq!(vec!["baz".to_string()], rows_2);
+    assert_eq!(vec![42], rows_3);
+
+    // partially consumed streams
+    let mut result = conn.exec_iter("CALL stream_sets(?)", (1,)).await?;
+    let mut stream = result.stream::<(u8, String)>().await?.unwrap();
+    assert_eq!(Some((1, "foo".into())), stream.try_next().await?);
+    let stream = stream.next_set::<String>().await?.unwrap();
+    let stream = stream.next_set::<u16>().await?.unwrap();
+    assert_eq!(vec![42], stream.try_collect::<Vec<_>>().await?);
+    drop(result);
+
+    // errors are emitted by `next_set`
+    let mut result = conn.query_iter("SELECT 1; SELECT FOO(); SELECT 2").await?;
+    let stream = result.stream::<u8>().await?.unwrap();
+    let err = stream.next_set::<Row>().await.unwrap_err();
+    assert!(err.to_string().contains("FOO"));
+
+    conn.query_drop("DROP PROCEDURE stream_sets").await?;
+    conn.disconnect().await?;
+    Ok(())
+}
+
 #[tokio::test]
 async fn should_map_resultset() -> super::Result<()> {
     let mut conn = Conn::new(get_opts()).await?;
@@ -272,3 +390,222 @@ async fn should_iterate_over_resultset() -> super::Result<()> {
     assert_eq!(acc.load(Ordering::SeqCst), 10);
     Ok(())
 }
+
+#[tokio::test]
+async fn should_expose_column_types_and_flags() -> super::Result<()> {
+    use crate::consts::ColumnType;
+
+    let mut conn = Conn::new(get_opts()).await?;
+    conn.query_drop(
+        "CREATE TEMPORARY TABLE tmp_cols (id INT UNSIGNED NOT NULL, name VARCHAR(16) NULL)",
+    )
+    .await?;
+
+    let result = conn.query_iter("SELECT id, name FROM tmp_cols").await?;
+    assert_eq!(
+        result.column_types(),
+        vec![
+            ColumnType::MYSQL_TYPE_LONG,
+            ColumnType::MYSQL_TYPE_VAR_STRING
+        ]
+    );
+    assert_eq!(result.column_nullable_flags(), vec![false, true]);
+    assert_eq!(result.column_unsigned_flags(), vec![true, false]);
+    assert_eq!(
+        result.column_descriptions(),
+        vec![
+            ("id".to_owned(), ColumnType::MYSQL_TYPE_LONG, false),
+            ("name".to_owned(), ColumnType::MYSQL_TYPE_VAR_STRING, true),
+        ]
+    );
+    result.drop_result().await?;
+
+    // binary protocol exposes the same metadata
+    let result = conn.exec_iter("SELECT id, name FROM tmp_cols", ()).await?;
+    assert_eq!(result.column_nullable_flags(), vec![false, true]);
+    assert_eq!(result.column_unsigned_flags(), vec![true, false]);
+    result.drop_result().await?;
+
+    // no columns for a result without rows
+    let result = conn.query_iter("DO 1").await?;
+    assert!(result.column_descriptions().is_empty());
+    result.drop_result().await?;
+
+    conn.disconnect().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_scan_rows_by_reference() -> super::Result<()> {
+    let mut conn = Conn::new(get_opts()).await?;
+
+    let mut result = conn
+        .query_iter("SELECT 1, 'foo' UNION ALL SELECT 2, 'bar'; SELECT 3")
+        .await?;
+    let mut sum = 0;
+    let mut names = Vec::new();
+    result
+        .scan_rows(|row| {
+            sum += row.get::<u32, _>(0).unwrap();
+            names.push(row.get::<String, _>(1).unwrap());
+        })
+        .await?;
+    assert_eq!(sum, 3);
+    assert_eq!(names, vec!["foo", "bar"]);
+
+    // stops on the result set boundary
+    let mut count = 0;
+    result.scan_rows(|_| count += 1).await?;
+    assert_eq!(count, 1);
+    assert!(result.is_empty());
+
+    let mut count = 0;
+    conn.exec_iter("SELECT ? UNION ALL SELECT ?", (1, 2))
+        .await?
+        .scan_rows_and_drop(|row| {
+            assert!(row.as_ref(0).is_some());
+            count += 1;
+        })
+        .await?;
+    assert_eq!(count, 2);
+
+    conn.disconnect().await?;
+    Ok(())
+}
+
+#[test]
+fn should_decode_row_values_into_reused_buffer() {
+    use std::sync::Arc;
+
+    use super::{read_row_values, ResultSetMeta};
+    use crate::{
+        consts::ColumnType, queryable::Protocol as _, BinaryProtocol, Column, TextProtocol, Value,
+    };
+
+    let columns: Arc<[Column]> = vec![
+        Column::new(ColumnType::MYSQL_TYPE_LONGLONG),
+        Column::new(ColumnType::MYSQL_TYPE_VAR_STRING),
+    ]
+    .into();
+
+    let mut values = vec![Some(Value::Int(42)); 8];
+
+    // text row: `'1', 'foo'`
+    let packet = b"\x011\x03foo";
+    read_row_values(&ResultSetMeta::Text(columns.clone()), packet, &mut values).unwrap();
+    let row = TextProtocol::read_result_set_row(packet, columns.clone()).unwrap();
+    assert_eq!(values, row.unwrap_raw());
+
+    // binary row: `1, NULL`
+    let mut packet = vec![0x00, 0b0000_1000];
+    packet.extend_from_slice(&1_i64.to_le_bytes());
+    read_row_values(
+        &ResultSetMeta::Binary(columns.clone()),
+        &packet,
+        &mut values,
+    )
+    .unwrap();
+    let row = BinaryProtocol::read_result_set_row(&packet, columns).unwrap();
+    assert_eq!(values, row.unwrap_raw());
+    assert_eq!(values, vec![Some(Value::Int(1)), Some(Value::NULL)]);
+}
+
+#[tokio::test]
+async fn should_drain_rows_after_early_break() -> super::Result<()> {
+    const QUERY: &str = "SELECT 1 UNION ALL SELECT 2 UNION ALL SELECT 3 UNION ALL SELECT 4";
+
+    let mut conn = Conn::new(get_opts().row_prefetch(1)).await?;
+
+    let mut stream = conn.query_stream::<u8, _>(QUERY).await?;
+    while let Some(row) = stream.next().await {
+        if row? == 2 {
+            break;
+        }
+    }
+    drop(stream);
+    assert_eq!(conn.query_first::<u8, _>("SELECT 42").await?, Some(42));
+
+    let mut stream = conn
+        .exec_stream::<u8, _, _>("SELECT ? UNION ALL SELECT ?", (1, 2))
+        .await?;
+    assert_eq!(stream.next().await.transpose()?, Some(1));
+    drop(stream);
+    assert_eq!(
+        conn.exec_first::<u8, _, _>("SELECT ?", (42,)).await?,
+        Some(42)
+    );
+
+    conn.disconnect().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_limit_collected_rows() -> super::Result<()> {
+    const DIGITS: &str = "(SELECT 0 AS d UNION ALL SELECT 1 UNION ALL SELECT 2 UNION ALL SELECT 3 \
+        UNION ALL SELECT 4 UNION ALL SELECT 5 UNION ALL SELECT 6 UNION ALL SELECT 7 \
+        UNION ALL SELECT 8 UNION ALL SELECT 9)";
+
+    let mut conn = Conn::new(get_opts()).await?;
+
+    // 1000 rows
+    let query = format!(
+        "SELECT a.d * 100 + b.d * 10 + c.d AS n FROM {0} a, {0} b, {0} c ORDER BY n",
+        DIGITS
+    );
+    let rows: Vec<u32> = conn.query_limited(&*query, 5).await?;
+    assert_eq!(rows, vec![0, 1, 2, 3, 4]);
+    assert_eq!(conn.query_first::<u8, _>("SELECT 42").await?, Some(42));
+
+    let rows: Vec<u32> = conn.query_limited("SELECT 1 UNION ALL SELECT 2", 5).await?;
+    assert_eq!(rows, vec![1, 2]);
+    let rows: Vec<u32> = conn.query_limited("SELECT 1", 0).await?;
+    assert!(rows.is_empty());
+    let rows: Vec<u32> = conn.query_limited("DO 1", 5).await?;
+    assert!(rows.is_empty());
+
+    conn.disconnect().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_peek_row() -> super::Result<()> {
+    let mut conn = Conn::new(get_opts()).await?;
+
+    let mut result = conn
+        .query_iter("SELECT 1 UNION ALL SELECT 2; SELECT 'foo' FROM DUAL WHERE 0; SELECT 3")
+        .await?;
+    let peeked = result.peek_row().await?.cloned().map(from_row::<u8>);
+    assert_eq!(peeked, Some(1));
+    // repeated calls return the same row
+    let peeked = result.peek_row().await?.cloned().map(from_row::<u8>);
+    assert_eq!(peeked, Some(1));
+    assert!(!result.is_empty());
+    assert_eq!(result.collect::<u8>().await?, vec![1, 2]);
+
+    // empty result set doesn't leak into the next one
+    assert!(result.peek_row().await?.is_none());
+    assert!(result.peek_row().await?.is_none());
+    assert!(result.collect::<String>().await?.is_empty());
+
+    // peeked row is yielded by the stream
+    assert!(result.peek_row().await?.is_some());
+    let stream = result.stream::<u8>().await?.unwrap();
+    assert_eq!(stream.try_collect::<Vec<_>>().await?, vec![3]);
+    assert!(result.peek_row().await?.is_none());
+    assert!(result.is_empty());
+    drop(result);
+
+    // peeked row is written as CSV
+    let mut result = conn.query_iter("SELECT 'a,b', NULL").await?;
+    assert!(result.peek_row().await?.is_some());
+    let mut output = Vec::new();
+    let rows = result
+        .write_csv(&mut output, &crate::CsvOpts::default().with_header(false))
+        .await?;
+    assert_eq!(rows, 1);
+    assert_eq!(output, b"\"a,b\",\n");
+    drop(result);
+
+    conn.disconnect().await?;
+    Ok(())
+}
@@ -16,7 +16,10 @@ use mysql_common::{
 use std::{borrow::Cow, sync::Arc};
 
 use crate::{
-    conn::routines::{ExecRoutine, PrepareRoutine},
+    conn::{
+        pool::stmt_metadata::StmtMetadata,
+        routines::{ExecRoutine, PrepareRoutine},
+    },
     consts::CapabilityFlags,
     error::*,
     Column, Params,
@@ -157,11 +160,11 @@ impl StatementLike for Arc<[u8]> {
 }
 
 impl StatementLike for Statement {
-    fn to_statement<'a>(self, _conn: &'a mut crate::Conn) -> ToStatementResult<'static>
+    fn to_statement<'a>(self, conn: &'a mut crate::Conn) -> ToStatementResult<'static>
     where
         Self: 'a,
     {
-        ToStatementResult::Immediate(self.clone())
+        ToStatementResult::Immediate(conn.resolve_pinned_stmt(self))
     }
 }
 
@@ -178,8 +181,8 @@ impl<T: StatementLike + Clone> StatementLike for &'_ T {
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct StmtInner {
     pub(crate) raw_query: Arc<[u8]>,
-    columns: Option<Box<[Column]>>,
-    params: Option<Box<[Column]>>,
+    columns: Option<Arc<[Column]>>,
+    params: Option<Arc<[Column]>>,
     stmt_packet: StmtPacket,
     connection_id: u32,
 }
@@ -205,7 +208,7 @@ impl StmtInner {
         self.params = if params.is_empty() {
             None
         } else {
-            Some(params.into_boxed_slice())
+            Some(params.into())
         };
         self
     }
@@ -214,11 +217,25 @@ impl StmtInner {
         self.columns = if columns.is_empty() {
             None
         } else {
-            Some(columns.into_boxed_slice())
+            Some(columns.into())
         };
         self
     }
 
+    /// Uses the given params and columns metadata (see [`crate::PoolOpts::with_shared_stmt_metadata`]).
+    pub(crate) fn with_metadata(mut self, metadata: StmtMetadata) -> Self {
+        self.params = metadata.params;
+        self.columns = metadata.columns;
+        self
+    }
+
+    pub(crate) fn metadata(&self) -> StmtMetadata {
+        StmtMetadata {
+            params: self.params.clone(),
+            columns: self.columns.clone(),
+        }
+    }
+
     pub(crate) fn columns(&self) -> &[Column] {
         self.columns.as_ref().map(AsRef::as_ref).unwrap_or(&[])
     }
@@ -252,6 +269,8 @@ pub struct Statement {
     pub(crate) inner: Arc<StmtInner>,
     /// An empty vector in case of no named params.
     pub(crate) named_params: Vec<Vec<u8>>,
+    /// Whether the statement is pinned to its connection (see [`crate::Conn::prepare_pinned`]).
+    pub(crate) pinned: bool,
 }
 
 impl Statement {
@@ -259,9 +278,15 @@ impl Statement {
         Self {
             inner,
             named_params,
+            pinned: false,
         }
     }
 
+    pub(crate) fn pinned(mut self) -> Self {
+        self.pinned = true;
+        self
+    }
+
     /// Returned columns.
     pub fn columns(&self) -> &[Column] {
         self.inner.columns()
@@ -291,6 +316,15 @@ impl Statement {
     pub fn num_columns(&self) -> u16 {
         self.inner.num_columns()
     }
+
+    /// Returns `true` if this statement is pinned to its connection
+    /// (see [`crate::Conn::prepare_pinned`]).
+    ///
+    /// Note that [`Statement::id`] of a pinned statement is the id it was prepared with
+    /// and may differ from the id the statement is currently executed with.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
 }
 
 impl crate::Conn {
@@ -320,6 +354,27 @@ impl crate::Conn {
         Ok(defs)
     }
 
+    /// Low-level helper, that skips the given number of column packets terminated by EOF packet.
+    ///
+    /// Requires `num > 0`.
+    pub(crate) async fn skip_column_defs<U>(&mut self, num: U) -> Result<()>
+    where
+        U: Into<usize>,
+    {
+        let num = num.into();
+        debug_assert!(num > 0);
+        self.read_packets(num).await?;
+
+        if !self
+            .capabilities()
+            .contains(CapabilityFlags::CLIENT_DEPRECATE_EOF)
+        {
+            self.read_packet().await?;
+        }
+
+        Ok(())
+    }
+
     /// Helper, that retrieves `Statement` from `StatementLike`.
     pub(crate) async fn get_statement<U>(&mut self, stmt_like: U) -> Result<Statement>
     where
@@ -336,6 +391,7 @@ impl crate::Conn {
     /// `raw_query` is a query with `?` placeholders (if any).
     async fn prepare_statement(&mut self, raw_query: Cow<'_, [u8]>) -> Result<Arc<StmtInner>> {
         let inner_stmt = self.routine(PrepareRoutine::new(raw_query)).await?;
+        self.on_statement_prepared(inner_stmt.id());
 
         if let Some(old_stmt) = self.cache_stmt(&inner_stmt) {
             self.close_statement(old_stmt.id()).await?;
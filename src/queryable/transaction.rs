@@ -8,7 +8,14 @@
 
 use std::{fmt, ops::Deref};
 
-use crate::{connection_like::Connection, error::*, queryable::Queryable, Conn};
+use crate::{
+    connection_like::Connection,
+    consts::ColumnType,
+    error::*,
+    prelude::{AsQuery, FromRow, StatementLike},
+    queryable::{cursor::Cursor, query_result::QueryResult, BinaryProtocol, Queryable},
+    Conn, Params,
+};
 
 /// Transaction status.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -27,6 +34,15 @@ impl Conn {
     pub async fn start_transaction(&mut self, options: TxOpts) -> Result<Transaction<'_>> {
         Transaction::new(self, options).await
     }
+
+    /// Starts a transaction that takes ownership of this connection.
+    ///
+    /// Resulting transaction is `'static`, so it could be moved into a spawned task.
+    /// Connection will be dropped (i.e. returned to its pool, if any) as soon as the transaction
+    /// is committed, rolled back or dropped.
+    pub async fn start_transaction_owned(self, options: TxOpts) -> Result<Transaction<'static>> {
+        Transaction::new(self, options).await
+    }
 }
 
 /// Transaction options.
@@ -46,6 +62,7 @@ pub struct TxOpts {
     consistent_snapshot: bool,
     isolation_level: Option<IsolationLevel>,
     readonly: Option<bool>,
+    close_prepared_on_end: bool,
 }
 
 impl TxOpts {
@@ -78,6 +95,12 @@ impl TxOpts {
         self
     }
 
+    /// See [`TxOpts::close_prepared_on_end`].
+    pub fn with_close_prepared_on_end(&mut self, value: bool) -> &mut Self {
+        self.close_prepared_on_end = value;
+        self
+    }
+
     /// If true, then `START TRANSACTION WITH CONSISTENT SNAPSHOT` will be performed.
     /// Defaults to `false`.
     pub fn consistent_snapshot(&self) -> bool {
@@ -97,6 +120,17 @@ impl TxOpts {
     pub fn readonly(&self) -> Option<bool> {
         self.readonly
     }
+
+    /// If true, then statements prepared within the transaction will be closed
+    /// (and removed from the statement cache) on commit or rollback. Defaults to `false`.
+    ///
+    /// This is useful for transactions that prepare one-off statements,
+    /// that otherwise would pollute the statement cache. Note that this also applies
+    /// to statements explicitly prepared using [`Queryable::prep`], so these statements
+    /// must not be used after the transaction end.
+    pub fn close_prepared_on_end(&self) -> bool {
+        self.close_prepared_on_end
+    }
 }
 
 /// Transaction isolation level.
@@ -138,6 +172,7 @@ impl<'a> Transaction<'a> {
             consistent_snapshot,
             isolation_level,
             readonly,
+            close_prepared_on_end,
         } = options;
 
         let mut conn = conn.into();
@@ -171,15 +206,70 @@ impl<'a> Transaction<'a> {
         };
 
         conn.set_tx_status(TxStatus::InTransaction);
+        if close_prepared_on_end {
+            conn.track_prepared_statements();
+        }
         Ok(Transaction(conn))
     }
 
+    /// Performs the given query and returns the number of affected rows
+    /// (see [`Conn::affected_rows`]).
+    pub async fn query_affected<Q>(&mut self, query: Q) -> Result<u64>
+    where
+        Q: AsQuery,
+    {
+        self.query_drop(query).await?;
+        Ok(self.0.affected_rows())
+    }
+
+    /// Executes the given statement and returns the number of affected rows
+    /// (see [`Conn::affected_rows`]).
+    pub async fn exec_affected<S, P>(&mut self, stmt: S, params: P) -> Result<u64>
+    where
+        S: StatementLike,
+        P: Into<Params> + Send,
+    {
+        self.exec_drop(stmt, params).await?;
+        Ok(self.0.affected_rows())
+    }
+
+    /// Executes the given statement with given params and explicit parameter types
+    /// (see [`Conn::exec_typed`]).
+    pub async fn exec_typed<Q, P>(
+        &mut self,
+        stmt: Q,
+        params: P,
+        param_types: &[ColumnType],
+    ) -> Result<QueryResult<'_, 'static, BinaryProtocol>>
+    where
+        Q: StatementLike,
+        P: Into<Params>,
+    {
+        self.0.exec_typed(stmt, params, param_types).await
+    }
+
+    /// Executes the given statement using a server-side read-only cursor
+    /// (see [`Conn::exec_cursor`]).
+    pub async fn exec_cursor<T, Q, P>(
+        &mut self,
+        stmt: Q,
+        params: P,
+        fetch_size: usize,
+    ) -> Result<Cursor<'_, T>>
+    where
+        T: FromRow + Send + 'static,
+        Q: StatementLike,
+        P: Into<Params>,
+    {
+        self.0.exec_cursor(stmt, params, fetch_size).await
+    }
+
     /// Performs `COMMIT` query.
     pub async fn commit(mut self) -> Result<()> {
         let result = self.0.query_iter("COMMIT").await?;
         result.drop_result().await?;
         self.0.set_tx_status(TxStatus::None);
-        Ok(())
+        self.0.close_tracked_statements().await
     }
 
     /// Performs `ROLLBACK` query.
@@ -187,7 +277,7 @@ impl<'a> Transaction<'a> {
         let result = self.0.query_iter("ROLLBACK").await?;
         result.drop_result().await?;
         self.0.set_tx_status(TxStatus::None);
-        Ok(())
+        self.0.close_tracked_statements().await
     }
 }
 
@@ -0,0 +1,384 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Name-based access to row values (see [`RowExt`]).
+
+#[cfg(feature = "binlog")]
+use mysql_common::binlog::{row::BinlogRow, value::BinlogValue};
+
+use mysql_common::{
+    row::{ColumnIndex, Row},
+    value::{
+        convert::{from_value_opt, FromValue, FromValueError},
+        json::Deserialized,
+        Value,
+    },
+};
+use serde::de::DeserializeOwned;
+
+use std::collections::HashMap;
+
+/// Extends [`Row`] with name-based column access, which (unlike positional [`FromRow`][1])
+/// is robust against column reordering.
+///
+/// Column names are matched case-insensitively (as MySql does), but an exact match
+/// takes precedence. If there are multiple matching columns, then the first one is used.
+///
+/// ```rust
+/// # use mysql_async::{test_misc::get_opts, Conn, Row};
+/// # use mysql_async::prelude::*;
+/// struct User {
+///     id: u32,
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// fn to_user(mut row: Row) -> Option<User> {
+///     Some(User {
+///         id: row.take_by_name("id")?,
+///         name: row.take_by_name("name")?,
+///         // missing column becomes `None`
+///         email: row.take_by_name("email").unwrap_or_default(),
+///     })
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// let mut conn = Conn::new(get_opts()).await?;
+/// let row: Row = conn.query_first("SELECT 'foo' AS NAME, 42 AS Id").await?.unwrap();
+/// let user = to_user(row).unwrap();
+/// assert_eq!((user.id, &*user.name, user.email), (42, "foo", None));
+/// # conn.disconnect().await }
+/// ```
+///
+/// [1]: crate::prelude::FromRow
+pub trait RowExt {
+    /// Returns the index of a column with the given name, if any.
+    fn column_index_by_name(&self, name: &str) -> Option<usize>;
+
+    /// Same as [`Row::take`] but the column is looked up by name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value can't be converted to `T`.
+    fn take_by_name<T: FromValue>(&mut self, name: &str) -> Option<T>;
+
+    /// Same as [`Row::take_opt`] but the column is looked up by name.
+    fn take_by_name_opt<T: FromValue>(&mut self, name: &str) -> Option<Result<T, FromValueError>>;
+
+    /// Parses a `JSON` value of the given column (by position or by name) into `T`.
+    ///
+    /// Queries give out `JSON` values as text, that is parsed the same way as
+    /// [`Deserialized`][1] does. Note that binlog rows, that are converted into a [`Row`],
+    /// hold `JSON` values as text as well (see [`BinlogRowExt::get_json`] for raw binlog rows).
+    ///
+    /// Returns `None` if there is no such column (or if its value is already taken)
+    /// and `Some(Err(_))` (with the original value) if the value can't be parsed into `T`.
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, Conn, Row};
+    /// # use mysql_async::prelude::*;
+    /// # use std::collections::BTreeMap;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let row: Row = conn
+    ///     .query_first(r#"SELECT CAST('{"x": 1, "y": 2}' AS JSON) AS point"#)
+    ///     .await?
+    ///     .unwrap();
+    /// let point: BTreeMap<String, i32> = row.get_json("point").unwrap().unwrap();
+    /// assert_eq!(point.values().copied().collect::<Vec<_>>(), [1, 2]);
+    /// # conn.disconnect().await }
+    /// ```
+    ///
+    /// [1]: crate::Deserialized
+    fn get_json<T, I>(&self, index: I) -> Option<Result<T, FromValueError>>
+    where
+        T: DeserializeOwned,
+        I: ColumnIndex;
+
+    /// Converts the row into a map of values keyed by column name, e.g. to handle
+    /// results of fully dynamic queries.
+    ///
+    /// If there are multiple columns with the same name (e.g. `SELECT a.id, b.id ...`),
+    /// then the first one is used (consistently with [`RowExt::take_by_name`]) – use aliases
+    /// to get all of them. Columns, that are already taken, are skipped. Column names are
+    /// converted lossily if they aren't valid UTF-8.
+    ///
+    /// ```rust
+    /// # use mysql_async::{test_misc::get_opts, Conn, Row, Value};
+    /// # use mysql_async::prelude::*;
+    /// # #[tokio::main]
+    /// # async fn main() -> mysql_async::Result<()> {
+    /// let mut conn = Conn::new(get_opts()).await?;
+    /// let row: Row = conn.exec_first("SELECT 42 AS id, 'foo' AS name", ()).await?.unwrap();
+    /// let map = row.into_map();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map["name"], Value::from("foo"));
+    /// # conn.disconnect().await }
+    /// ```
+    fn into_map(self) -> HashMap<String, Value>;
+}
+
+impl RowExt for Row {
+    fn column_index_by_name(&self, name: &str) -> Option<usize> {
+        let columns = self.columns_ref();
+        columns
+            .iter()
+            .position(|column| column.name_ref() == name.as_bytes())
+            .or_else(|| {
+                columns
+                    .iter()
+                    .position(|column| column.name_ref().eq_ignore_ascii_case(name.as_bytes()))
+            })
+    }
+
+    fn take_by_name<T: FromValue>(&mut self, name: &str) -> Option<T> {
+        let index = self.column_index_by_name(name)?;
+        self.take(index)
+    }
+
+    fn take_by_name_opt<T: FromValue>(&mut self, name: &str) -> Option<Result<T, FromValueError>> {
+        let index = self.column_index_by_name(name)?;
+        self.take_opt(index)
+    }
+
+    fn get_json<T, I>(&self, index: I) -> Option<Result<T, FromValueError>>
+    where
+        T: DeserializeOwned,
+        I: ColumnIndex,
+    {
+        let value = self.as_ref(index.idx(self.columns_ref())?)?.clone();
+        Some(json_from_value(value))
+    }
+
+    fn into_map(self) -> HashMap<String, Value> {
+        let columns = self.columns();
+        let mut map = HashMap::with_capacity(columns.len());
+        for (column, value) in columns.iter().zip(self.unwrap_raw()) {
+            if let Some(value) = value {
+                map.entry(column.name_str().into_owned()).or_insert(value);
+            }
+        }
+        map
+    }
+}
+
+/// Parses a `JSON` text value.
+fn json_from_value<T: DeserializeOwned>(value: Value) -> Result<T, FromValueError> {
+    from_value_opt::<Deserialized<T>>(value).map(|Deserialized(x)| x)
+}
+
+/// Extends [`BinlogRow`] (a row of a binlog rows event) with `JSON` access.
+#[cfg(feature = "binlog")]
+pub trait BinlogRowExt {
+    /// Parses a `JSON` value of the given column (by position or by name) into `T`.
+    ///
+    /// Binlog rows hold `JSON` values in the binary `JSONB` format (see [`BinlogValue::Jsonb`]),
+    /// that is converted to JSON, but a `JSON` text value is parsed as well.
+    /// A partial `JSON` update ([`BinlogValue::JsonDiff`]) can't be parsed.
+    ///
+    /// Returns `None` if there is no such column (or if its value is already taken)
+    /// and `Some(Err(_))` (with the original value) if the value can't be parsed into `T`.
+    fn get_json<T, I>(&self, index: I) -> Option<Result<T, BinlogValue<'static>>>
+    where
+        T: DeserializeOwned,
+        I: ColumnIndex;
+}
+
+#[cfg(feature = "binlog")]
+impl BinlogRowExt for BinlogRow {
+    fn get_json<T, I>(&self, index: I) -> Option<Result<T, BinlogValue<'static>>>
+    where
+        T: DeserializeOwned,
+        I: ColumnIndex,
+    {
+        use std::convert::TryFrom;
+
+        let value = self.as_ref(index.idx(self.columns_ref())?)?;
+        let result = match value {
+            BinlogValue::Value(value) => json_from_value(value.clone()).ok(),
+            BinlogValue::Jsonb(jsonb) => serde_json::Value::try_from(jsonb.clone())
+                .ok()
+                .and_then(|json| serde_json::from_value(json).ok()),
+            BinlogValue::JsonDiff(_) => None,
+        };
+        Some(result.ok_or_else(|| value.clone().into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql_common::{
+        constants::ColumnType,
+        packets::Column,
+        row::{new_row, Row},
+        value::{convert::FromValueError, Value},
+    };
+
+    use std::collections::HashMap;
+
+    use super::RowExt;
+
+    fn row() -> Row {
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"ID"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"name"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"Name"),
+        ];
+        new_row(
+            vec![Value::Int(42), Value::from("foo"), Value::from("bar")],
+            columns.into(),
+        )
+    }
+
+    #[test]
+    fn should_find_columns_by_name() {
+        let row = row();
+        assert_eq!(row.column_index_by_name("ID"), Some(0));
+        assert_eq!(row.column_index_by_name("id"), Some(0));
+        assert_eq!(row.column_index_by_name("Name"), Some(2));
+        assert_eq!(row.column_index_by_name("NAME"), Some(1));
+        assert_eq!(row.column_index_by_name("email"), None);
+    }
+
+    #[test]
+    fn should_take_values_by_name() {
+        let mut row = row();
+        assert_eq!(row.take_by_name::<u32>("id"), Some(42));
+        // already taken
+        assert_eq!(row.take_by_name::<u32>("id"), None);
+        assert_eq!(row.take_by_name::<String>("email"), None);
+        assert!(matches!(row.take_by_name_opt::<u32>("name"), Some(Err(_))));
+        assert_eq!(
+            row.take_by_name_opt::<String>("Name").map(Result::ok),
+            Some(Some("bar".into()))
+        );
+    }
+
+    #[test]
+    fn should_get_json_values() {
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_JSON).with_name(b"text"),
+            Column::new(ColumnType::MYSQL_TYPE_JSON).with_name(b"jsonb"),
+            Column::new(ColumnType::MYSQL_TYPE_JSON).with_name(b"invalid"),
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"int"),
+        ];
+        let row = new_row(
+            vec![
+                Value::from(r#"{"x": 1, "y": [2, 3]}"#),
+                // JSONB string "foo" isn't a JSON text
+                Value::Bytes(vec![0x0c, 0x03, b'f', b'o', b'o']),
+                Value::from("{"),
+                Value::Int(42),
+            ],
+            columns.into(),
+        );
+
+        let text: HashMap<String, serde_json::Value> = row.get_json("text").unwrap().unwrap();
+        assert_eq!(text["x"], serde_json::json!(1));
+        assert_eq!(text["y"], serde_json::json!([2, 3]));
+        assert_eq!(
+            row.get_json::<Vec<u32>, _>(0).map(|x| x.is_err()),
+            Some(true)
+        );
+
+        assert_eq!(
+            row.get_json::<String, _>("jsonb").map(|x| x.is_err()),
+            Some(true)
+        );
+
+        assert_eq!(
+            row.get_json::<serde_json::Value, _>("invalid"),
+            Some(Err(FromValueError(Value::from("{"))))
+        );
+        // `JSON` values are always given out as bytes
+        assert_eq!(
+            row.get_json::<u32, _>("int"),
+            Some(Err(FromValueError(Value::Int(42))))
+        );
+        assert!(row.get_json::<u32, _>("email").is_none());
+    }
+
+    #[cfg(feature = "binlog")]
+    #[test]
+    fn should_get_json_values_of_binlog_rows() {
+        use mysql_common::{
+            binlog::{jsonb, row::BinlogRow, value::BinlogValue},
+            io::ParseBuf,
+        };
+
+        use super::BinlogRowExt;
+
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_JSON).with_name(b"jsonb"),
+            Column::new(ColumnType::MYSQL_TYPE_JSON).with_name(b"text"),
+            Column::new(ColumnType::MYSQL_TYPE_JSON).with_name(b"diff"),
+        ];
+        // JSONB string "foo"
+        let jsonb: jsonb::Value<'_> = ParseBuf(&[0x0c, 0x03, b'f', b'o', b'o']).parse(()).unwrap();
+        let row = BinlogRow::new(
+            vec![
+                Some(BinlogValue::Jsonb(jsonb.into_owned())),
+                Some(BinlogValue::Value(Value::from("[1, 2]"))),
+                Some(BinlogValue::JsonDiff(Vec::new())),
+            ],
+            columns.into(),
+        );
+
+        assert_eq!(
+            row.get_json::<String, _>("jsonb").map(Result::ok),
+            Some(Some("foo".into()))
+        );
+        assert!(matches!(
+            row.get_json::<u32, _>(0),
+            Some(Err(BinlogValue::Jsonb(_)))
+        ));
+        assert_eq!(
+            row.get_json::<Vec<u8>, _>("text").map(Result::ok),
+            Some(Some(vec![1, 2]))
+        );
+        assert!(matches!(
+            row.get_json::<serde_json::Value, _>("diff"),
+            Some(Err(BinlogValue::JsonDiff(_)))
+        ));
+        assert!(row.get_json::<u32, _>("email").is_none());
+    }
+
+    #[test]
+    fn should_convert_row_into_map() {
+        let columns = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"id"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"name"),
+            Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"Name"),
+        ];
+        let duplicates = new_row(
+            vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::from("foo"),
+                Value::NULL,
+            ],
+            columns.into(),
+        );
+        let map = duplicates.into_map();
+        assert_eq!(map.len(), 3);
+        // the first of duplicates is used
+        assert_eq!(map["id"], Value::Int(1));
+        assert_eq!(map["name"], Value::from("foo"));
+        assert_eq!(map["Name"], Value::NULL);
+
+        // taken values are skipped
+        let mut row = row();
+        row.take_by_name::<u32>("id");
+        let map = row.into_map();
+        assert_eq!(map.len(), 2);
+        assert!(!map.contains_key("ID"));
+    }
+}
@@ -0,0 +1,304 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::value::convert::{FromValue, FromValueError};
+
+use std::{convert::TryFrom, fmt, time::Duration};
+
+use crate::Value;
+
+/// Value of a `TIME` column as a signed duration.
+///
+/// `TIME` is an interval rather than a time of day, so it may be negative and may exceed
+/// 24 hours (the column range is `-838:59:59` to `838:59:59`). `std::time::Duration`
+/// is unsigned, so it fails to convert from a negative `TIME` – use this wrapper instead.
+///
+/// The wrapper itself isn't limited to the range of the column, so any `Value::Time`
+/// (and any `[-]H+:MM:SS[.ffffff]` string of the text protocol) round-trips losslessly.
+/// Note that the server clamps out of range values when it stores or casts them to `TIME`.
+/// Sub-microsecond part of the duration is truncated when the value is bound.
+///
+/// ```rust
+/// # use mysql_async::test_misc::get_opts;
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// use mysql_async::{prelude::*, Conn, SignedDuration};
+/// use std::time::Duration;
+///
+/// let mut conn = Conn::new(get_opts()).await?;
+///
+/// let time: Option<SignedDuration> = conn.query_first("SELECT TIME '-26:00:00.5'").await?;
+/// let time = time.unwrap();
+/// assert!(time.is_negative());
+/// assert_eq!(time.abs(), Duration::from_millis(26 * 3600 * 1000 + 500));
+/// assert_eq!(time.to_string(), "-26:00:00.500000");
+/// # conn.disconnect().await }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SignedDuration {
+    negative: bool,
+    abs: Duration,
+}
+
+impl SignedDuration {
+    /// Creates a signed duration with the given sign and absolute value.
+    ///
+    /// Zero duration is never negative.
+    pub fn new(negative: bool, abs: Duration) -> Self {
+        Self {
+            negative: negative && !abs.is_zero(),
+            abs,
+        }
+    }
+
+    /// Creates a positive duration.
+    pub fn positive(abs: Duration) -> Self {
+        Self::new(false, abs)
+    }
+
+    /// Creates a negative duration (zero duration is never negative).
+    pub fn negative(abs: Duration) -> Self {
+        Self::new(true, abs)
+    }
+
+    /// Returns `true` if this duration is negative.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the absolute value of this duration.
+    pub fn abs(&self) -> Duration {
+        self.abs
+    }
+
+    /// Returns this duration as a `Duration`, if it isn't negative.
+    pub fn to_duration(&self) -> Option<Duration> {
+        if self.negative {
+            None
+        } else {
+            Some(self.abs)
+        }
+    }
+
+    /// Returns the total number of whole microseconds in this duration.
+    pub fn as_micros(&self) -> i128 {
+        let micros = self.abs.as_micros() as i128;
+        if self.negative {
+            -micros
+        } else {
+            micros
+        }
+    }
+
+    /// Parses `[-]H+:MM:SS[.f{1,6}]` string of the text protocol.
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let (negative, bytes) = match bytes.split_first() {
+            Some((b'-', rest)) => (true, rest),
+            _ => (false, bytes),
+        };
+
+        let text = std::str::from_utf8(bytes).ok()?;
+        let (hms, fraction) = match text.split_once('.') {
+            Some((hms, fraction)) => (hms, Some(fraction)),
+            None => (text, None),
+        };
+
+        let mut parts = hms.split(':');
+        let (hours, minutes, seconds) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some()
+            || minutes.len() != 2
+            || seconds.len() != 2
+            || ![hours, minutes, seconds]
+                .iter()
+                .all(|x| !x.is_empty() && x.bytes().all(|x| x.is_ascii_digit()))
+        {
+            return None;
+        }
+
+        let (hours, minutes, seconds) = (
+            hours.parse::<u64>().ok()?,
+            minutes.parse::<u64>().ok()?,
+            seconds.parse::<u64>().ok()?,
+        );
+        if minutes > 59 || seconds > 59 {
+            return None;
+        }
+
+        let micros = match fraction {
+            Some(x) if !x.is_empty() && x.len() <= 6 && x.bytes().all(|x| x.is_ascii_digit()) => {
+                x.parse::<u32>().ok()? * 10_u32.pow(6 - x.len() as u32)
+            }
+            Some(_) => return None,
+            None => 0,
+        };
+
+        let secs = hours
+            .checked_mul(3600)?
+            .checked_add(minutes * 60 + seconds)?;
+        Some(Self::new(negative, Duration::new(secs, micros * 1000)))
+    }
+}
+
+impl From<Duration> for SignedDuration {
+    fn from(abs: Duration) -> Self {
+        Self::positive(abs)
+    }
+}
+
+impl From<SignedDuration> for Value {
+    fn from(value: SignedDuration) -> Self {
+        let secs = value.abs.as_secs();
+        Value::Time(
+            value.negative,
+            (secs / 86400) as u32,
+            (secs % 86400 / 3600) as u8,
+            (secs % 3600 / 60) as u8,
+            (secs % 60) as u8,
+            value.abs.subsec_micros(),
+        )
+    }
+}
+
+impl TryFrom<Value> for SignedDuration {
+    type Error = FromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Time(negative, days, hours, minutes, seconds, micros) if micros < 1_000_000 => {
+                let secs = u64::from(days) * 86400
+                    + u64::from(hours) * 3600
+                    + u64::from(minutes) * 60
+                    + u64::from(seconds);
+                Ok(Self::new(negative, Duration::new(secs, micros * 1000)))
+            }
+            Value::Bytes(ref bytes) => Self::parse(bytes).ok_or(FromValueError(value)),
+            _ => Err(FromValueError(value)),
+        }
+    }
+}
+
+impl FromValue for SignedDuration {
+    type Intermediate = SignedDuration;
+}
+
+/// Formats the duration the way MySql formats `TIME(6)`, e.g. `-838:59:59.000000`.
+impl fmt::Display for SignedDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.abs.as_secs();
+        write!(
+            f,
+            "{}{:02}:{:02}:{:02}.{:06}",
+            if self.negative { "-" } else { "" },
+            secs / 3600,
+            secs % 3600 / 60,
+            secs % 60,
+            self.abs.subsec_micros()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{from_value, prelude::*, test_misc::get_opts, Conn, SignedDuration, Value};
+
+    fn hms(hours: u64, minutes: u64, seconds: u64, micros: u32) -> Duration {
+        Duration::new(hours * 3600 + minutes * 60 + seconds, micros * 1000)
+    }
+
+    #[test]
+    fn should_convert_signed_duration() {
+        let max = SignedDuration::positive(hms(838, 59, 59, 0));
+        let min = SignedDuration::negative(hms(838, 59, 59, 0));
+        let huge = SignedDuration::negative(hms(100_000, 0, 1, 999_999));
+
+        assert_eq!(Value::from(min), Value::Time(true, 34, 22, 59, 59, 0));
+        assert_eq!(
+            Value::from(huge),
+            Value::Time(true, 4166, 16, 0, 1, 999_999)
+        );
+        for value in [max, min, huge, SignedDuration::default()] {
+            assert_eq!(from_value::<SignedDuration>(Value::from(value)), value);
+            assert_eq!(
+                from_value::<SignedDuration>(Value::Bytes(value.to_string().into_bytes())),
+                value
+            );
+        }
+
+        assert_eq!(
+            from_value::<SignedDuration>(Value::Bytes(b"-00:00:01.5".to_vec())),
+            SignedDuration::negative(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            from_value::<SignedDuration>(Value::Bytes(b"12:34:56".to_vec())),
+            SignedDuration::positive(hms(12, 34, 56, 0))
+        );
+        // negative zero is zero
+        assert_eq!(
+            from_value::<SignedDuration>(Value::Time(true, 0, 0, 0, 0, 0)),
+            SignedDuration::default()
+        );
+
+        for invalid in [
+            &b"12:34"[..],
+            b"12:34:60",
+            b"12:3:45",
+            b"12:34:56.",
+            b"12:34:56.1234567",
+            b"-12:34:56x",
+            b"+12:34:56",
+        ] {
+            let value = Value::Bytes(invalid.to_vec());
+            assert_eq!(
+                SignedDuration::get_intermediate(value.clone()),
+                Err(crate::FromValueError(value))
+            );
+        }
+
+        // sub-microsecond part is truncated
+        assert_eq!(
+            Value::from(SignedDuration::positive(Duration::new(1, 999_999_999))),
+            Value::Time(false, 0, 0, 0, 1, 999_999)
+        );
+
+        assert_eq!(max.as_micros(), 3_020_399_000_000);
+        assert_eq!(min.as_micros(), -3_020_399_000_000);
+        assert_eq!(min.to_duration(), None);
+        assert_eq!(max.to_duration(), Some(max.abs()));
+    }
+
+    #[tokio::test]
+    async fn should_roundtrip_signed_duration() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts()).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_time (id INT, t TIME(6))")
+            .await?;
+
+        let values = [
+            SignedDuration::negative(hms(838, 59, 59, 0)),
+            SignedDuration::negative(hms(0, 0, 1, 500_000)),
+            SignedDuration::default(),
+            SignedDuration::positive(hms(25, 0, 0, 1)),
+            SignedDuration::positive(hms(838, 59, 59, 0)),
+        ];
+        conn.exec_batch(
+            "INSERT INTO tmp_time VALUES (?, ?)",
+            values.iter().enumerate().map(|(i, x)| (i, *x)),
+        )
+        .await?;
+
+        let query = "SELECT t FROM tmp_time ORDER BY id";
+        let binary: Vec<SignedDuration> = conn.exec(query, ()).await?;
+        assert_eq!(binary, values);
+        let text: Vec<SignedDuration> = conn.query(query).await?;
+        assert_eq!(text, values);
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+use mysql_common::value::convert::{FromValue, FromValueError};
+
+use std::{
+    convert::TryFrom,
+    str::from_utf8,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::Value;
+
+const MICROS_PER_SEC: i128 = 1_000_000;
+const SECS_PER_DAY: i128 = 86_400;
+
+/// [`SystemTime`] that is bound and retrieved as a UTC `DATETIME`/`TIMESTAMP` value.
+///
+/// `SystemTime` carries no timezone, so this wrapper always interprets the MySql value
+/// as UTC. Round-tripping is lossless up to microsecond precision (the maximum precision
+/// supported by MySql), sub-microsecond part is truncated.
+///
+/// # DATETIME vs TIMESTAMP
+///
+/// * `DATETIME` values are stored as is, so this wrapper round-trips through `DATETIME`
+///   regardless of the session `time_zone`;
+/// * `TIMESTAMP` values are converted by the server from the session `time_zone` to UTC
+///   for storage and back for retrieval, so the session `time_zone` must be `'+00:00'`
+///   for values to be interpreted as UTC (see [`Opts::init`][init]).
+///
+/// Also note that the column must be declared with a fractional seconds part
+/// (e.g. `DATETIME(6)`), otherwise the server will round the value to seconds.
+///
+/// ```rust
+/// # use mysql_async::test_misc::get_opts;
+/// # #[tokio::main]
+/// # async fn main() -> mysql_async::Result<()> {
+/// use mysql_async::{prelude::*, Conn, UtcDateTime};
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+///
+/// let mut conn = Conn::new(get_opts()).await?;
+/// let time = UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456);
+///
+/// let output: Option<(String, UtcDateTime)> = conn
+///     .exec_first("SELECT CAST(? AS CHAR), ?", (UtcDateTime(time), UtcDateTime(time)))
+///     .await?;
+///
+/// assert_eq!(output.unwrap(), ("2023-11-14 22:13:20.123456".into(), UtcDateTime(time)));
+/// # conn.disconnect().await }
+/// ```
+///
+/// # Out of range values
+///
+/// Years outside of the `0..=9999` range are not representable by MySql, so conversion
+/// into a [`Value`] saturates such a value to the nearest representable one, i.e. to
+/// `0000-01-01 00:00:00` or to `9999-12-31 23:59:59.999999`.
+///
+/// [init]: crate::Opts::init
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UtcDateTime(pub SystemTime);
+
+impl UtcDateTime {
+    /// Returns the wrapped `SystemTime`.
+    pub fn into_inner(self) -> SystemTime {
+        self.0
+    }
+
+    /// Returns the `(year, month, day, hour, minute, second, micros)` tuple in UTC.
+    fn to_parts(self) -> (i128, u8, u8, u8, u8, u8, u32) {
+        let micros = match self.0.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_micros() as i128,
+            Err(before) => -(before.duration().as_micros() as i128),
+        };
+
+        let secs = micros.div_euclid(MICROS_PER_SEC);
+        let micros = micros.rem_euclid(MICROS_PER_SEC) as u32;
+        let days = secs.div_euclid(SECS_PER_DAY);
+        let secs_of_day = secs.rem_euclid(SECS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        (
+            year,
+            month,
+            day,
+            (secs_of_day / 3600) as u8,
+            (secs_of_day % 3600 / 60) as u8,
+            (secs_of_day % 60) as u8,
+            micros,
+        )
+    }
+
+    /// Creates the value from the given UTC date and time.
+    fn from_parts(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        micros: u32,
+    ) -> Option<Self> {
+        if !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+            || micros >= MICROS_PER_SEC as u32
+        {
+            return None;
+        }
+
+        let secs = days_from_civil(year as i128, month, day) * SECS_PER_DAY
+            + hour as i128 * 3600
+            + minute as i128 * 60
+            + second as i128;
+        let micros = secs * MICROS_PER_SEC + micros as i128;
+
+        let time = if micros >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_micros(u64::try_from(micros).ok()?))
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_micros(u64::try_from(-micros).ok()?))
+        };
+
+        time.map(UtcDateTime)
+    }
+}
+
+impl From<SystemTime> for UtcDateTime {
+    fn from(time: SystemTime) -> Self {
+        UtcDateTime(time)
+    }
+}
+
+impl From<UtcDateTime> for SystemTime {
+    fn from(time: UtcDateTime) -> Self {
+        time.0
+    }
+}
+
+impl From<UtcDateTime> for Value {
+    fn from(time: UtcDateTime) -> Self {
+        match time.to_parts() {
+            (year, ..) if year < 0 => Value::Date(0, 1, 1, 0, 0, 0, 0),
+            (year, ..) if year > 9999 => Value::Date(9999, 12, 31, 23, 59, 59, 999_999),
+            (year, month, day, hour, minute, second, micros) => {
+                Value::Date(year as u16, month, day, hour, minute, second, micros)
+            }
+        }
+    }
+}
+
+impl TryFrom<Value> for UtcDateTime {
+    type Error = FromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let parsed = match value {
+            Value::Date(year, month, day, hour, minute, second, micros) => {
+                UtcDateTime::from_parts(year, month, day, hour, minute, second, micros)
+            }
+            Value::Bytes(ref bytes) => parse_datetime(bytes).and_then(
+                |(year, month, day, hour, minute, second, micros)| {
+                    UtcDateTime::from_parts(year, month, day, hour, minute, second, micros)
+                },
+            ),
+            _ => None,
+        };
+
+        parsed.ok_or(FromValueError(value))
+    }
+}
+
+impl FromValue for UtcDateTime {
+    type Intermediate = UtcDateTime;
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        // 29 for leap years
+        2 => (days_from_civil(year as i128, 3, 1) - days_from_civil(year as i128, 2, 1)) as u8,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days since 1970-01-01 (proleptic Gregorian calendar).
+fn days_from_civil(year: i128, month: u8, day: u8) -> i128 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i128;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i128 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns `(year, month, day)` for the given number of days since 1970-01-01.
+fn civil_from_days(days: i128) -> (i128, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Parses `YYYY-MM-DD[ hh:mm:ss[.ffffff]]`.
+fn parse_datetime(bytes: &[u8]) -> Option<(u16, u8, u8, u8, u8, u8, u32)> {
+    let text = from_utf8(bytes).ok()?;
+    let (date, time) = match text.split_once(' ') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut date = date.splitn(3, '-');
+    let year = date.next()?.parse().ok()?;
+    let month = date.next()?.parse().ok()?;
+    let day = date.next()?.parse().ok()?;
+
+    let (hour, minute, second, micros) = match time {
+        Some(time) => {
+            let (time, fraction) = match time.split_once('.') {
+                Some((time, fraction)) => (time, Some(fraction)),
+                None => (time, None),
+            };
+            let mut time = time.splitn(3, ':');
+            let hour = time.next()?.parse().ok()?;
+            let minute = time.next()?.parse().ok()?;
+            let second = time.next()?.parse().ok()?;
+            let micros = match fraction {
+                Some(fraction)
+                    if !fraction.is_empty()
+                        && fraction.len() <= 6
+                        && fraction.bytes().all(|x| x.is_ascii_digit()) =>
+                {
+                    fraction.parse::<u32>().ok()? * 10_u32.pow(6 - fraction.len() as u32)
+                }
+                Some(_) => return None,
+                None => 0,
+            };
+            (hour, minute, second, micros)
+        }
+        None => (0, 0, 0, 0),
+    };
+
+    Some((year, month, day, hour, minute, second, micros))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::{
+        from_value, prelude::*, test_misc::get_opts, Conn, FromValueError, UtcDateTime, Value,
+    };
+
+    #[test]
+    fn should_convert_to_utc_datetime() {
+        let time = UtcDateTime(UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456));
+        assert_eq!(
+            Value::from(time),
+            Value::Date(2023, 11, 14, 22, 13, 20, 123_456)
+        );
+
+        assert_eq!(
+            Value::from(UtcDateTime(UNIX_EPOCH)),
+            Value::Date(1970, 1, 1, 0, 0, 0, 0)
+        );
+
+        let before_epoch = UtcDateTime(UNIX_EPOCH - Duration::from_micros(1));
+        assert_eq!(
+            Value::from(before_epoch),
+            Value::Date(1969, 12, 31, 23, 59, 59, 999_999)
+        );
+
+        let leap_day = UtcDateTime(UNIX_EPOCH + Duration::from_secs(951_782_400));
+        assert_eq!(Value::from(leap_day), Value::Date(2000, 2, 29, 0, 0, 0, 0));
+
+        // sub-microsecond part is truncated
+        let nanos = UtcDateTime(UNIX_EPOCH + Duration::from_nanos(1_999));
+        assert_eq!(Value::from(nanos), Value::Date(1970, 1, 1, 0, 0, 0, 1));
+
+        // out of range values are saturated
+        let year_10000 = UtcDateTime(UNIX_EPOCH + Duration::from_micros(253_402_300_800_000_000));
+        assert_eq!(
+            Value::from(year_10000),
+            Value::Date(9999, 12, 31, 23, 59, 59, 999_999)
+        );
+        let year_minus_1 = UtcDateTime(UNIX_EPOCH - Duration::from_secs(62_198_755_200));
+        assert_eq!(Value::from(year_minus_1), Value::Date(0, 1, 1, 0, 0, 0, 0));
+    }
+
+    #[test]
+    fn should_roundtrip_utc_datetime() {
+        for micros in [
+            0_i64,
+            1,
+            -1,
+            1_700_000_000_123_456,
+            -30_610_224_000_000_000,
+            253_402_300_799_999_999,
+        ] {
+            let time = if micros >= 0 {
+                UNIX_EPOCH + Duration::from_micros(micros as u64)
+            } else {
+                UNIX_EPOCH - Duration::from_micros(micros.unsigned_abs())
+            };
+            let time = UtcDateTime(time);
+            assert_eq!(from_value::<UtcDateTime>(Value::from(time)), time);
+        }
+    }
+
+    #[test]
+    fn should_parse_text_utc_datetime() {
+        let expected = UtcDateTime(UNIX_EPOCH + Duration::from_micros(1_700_000_000_120_000));
+        assert_eq!(
+            from_value::<UtcDateTime>(Value::Bytes(b"2023-11-14 22:13:20.12".to_vec())),
+            expected
+        );
+        assert_eq!(
+            from_value::<UtcDateTime>(Value::Bytes(b"2023-11-14".to_vec())),
+            UtcDateTime(UNIX_EPOCH + Duration::from_secs(1_699_920_000))
+        );
+
+        for invalid in [
+            &b"2023-02-29 00:00:00"[..],
+            b"2023-11-14 24:00:00",
+            b"2023-11-14 22:13:20.1234567",
+            b"foo",
+        ] {
+            assert_eq!(
+                UtcDateTime::get_intermediate(Value::Bytes(invalid.to_vec())),
+                Err(FromValueError(Value::Bytes(invalid.to_vec())))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn should_roundtrip_utc_datetime_through_datetime_and_timestamp() -> crate::Result<()> {
+        let mut conn = Conn::new(get_opts().init(vec!["SET time_zone = '+00:00'"])).await?;
+        conn.query_drop("CREATE TEMPORARY TABLE tmp_utc (dt DATETIME(6), ts TIMESTAMP(6) NULL)")
+            .await?;
+
+        let time = UtcDateTime(UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456));
+        conn.exec_drop("INSERT INTO tmp_utc VALUES (?, ?)", (time, time))
+            .await?;
+
+        let binary: Option<(UtcDateTime, UtcDateTime)> =
+            conn.exec_first("SELECT dt, ts FROM tmp_utc", ()).await?;
+        assert_eq!(binary, Some((time, time)));
+
+        let text: Option<(UtcDateTime, UtcDateTime)> =
+            conn.query_first("SELECT dt, ts FROM tmp_utc").await?;
+        assert_eq!(text, Some((time, time)));
+
+        // TIMESTAMP is stored in UTC but retrieved in the session time zone
+        conn.query_drop("SET time_zone = '+01:00'").await?;
+        let (dt, ts): (UtcDateTime, UtcDateTime) = conn
+            .query_first("SELECT dt, ts FROM tmp_utc")
+            .await?
+            .unwrap();
+        assert_eq!(dt, time);
+        assert_eq!(ts.0, time.0 + Duration::from_secs(3600));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "time")]
+    #[tokio::test]
+    async fn should_roundtrip_temporal_values_to_the_microsecond() -> crate::Result<()> {
+        use mysql_common::time::{Date, Duration as TimeDuration, Month, PrimitiveDateTime, Time};
+
+        let mut conn = Conn::new(get_opts().init(vec!["SET time_zone = '+00:00'"])).await?;
+        conn.query_drop(
+            "CREATE TEMPORARY TABLE tmp_micros (dt DATETIME(6), ts TIMESTAMP(6) NULL, t TIME(6))",
+        )
+        .await?;
+
+        let date = Date::from_calendar_date(2023, Month::November, 14).unwrap();
+        let datetime =
+            PrimitiveDateTime::new(date, Time::from_hms_micro(22, 13, 20, 123_456).unwrap());
+        let system_time = UtcDateTime(UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456));
+        let duration = TimeDuration::new(-3_723, -456_789_000);
+        conn.exec_drop(
+            "INSERT INTO tmp_micros VALUES (?, ?, ?)",
+            (datetime, system_time, duration),
+        )
+        .await?;
+
+        type Output = (PrimitiveDateTime, UtcDateTime, UtcDateTime, TimeDuration);
+        let query = "SELECT dt, dt, ts, t FROM tmp_micros";
+        let expected = (datetime, system_time, system_time, duration);
+
+        let binary: Option<Output> = conn.exec_first(query, ()).await?;
+        assert_eq!(binary, Some(expected));
+
+        let text: Option<Output> = conn.query_first(query).await?;
+        assert_eq!(text, Some(expected));
+
+        // sub-microsecond part is truncated
+        let nanos =
+            PrimitiveDateTime::new(date, Time::from_hms_nano(22, 13, 20, 123_456_789).unwrap());
+        let truncated: Option<PrimitiveDateTime> = conn
+            .exec_first("SELECT CAST(? AS DATETIME(6))", (nanos,))
+            .await?;
+        assert_eq!(truncated, Some(datetime));
+
+        conn.disconnect().await?;
+        Ok(())
+    }
+}
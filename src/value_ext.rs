@@ -0,0 +1,252 @@
+// Copyright (c) 2023 mysql_async developers.
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Protocol-agnostic comparison of values (see [`ValueExt`]).
+
+use mysql_common::value::Value;
+
+/// Extends [`Value`] with a comparison, that doesn't depend on the protocol the value
+/// was fetched with.
+///
+/// The text protocol gives out every non-`NULL` value as [`Value::Bytes`], while the binary
+/// protocol gives out typed values, so the derived equality says `Value::Int(1)` is not equal
+/// to `Value::Bytes(b"1")`. [`ValueExt::loose_eq`] normalizes text representations to their
+/// typed meaning using the following rules:
+///
+/// *   `NULL` is only equal to `NULL`;
+/// *   two [`Value::Bytes`] are compared byte-wise (there is no type to normalize to);
+/// *   [`Value::Bytes`] and a typed value – the bytes are parsed according to the type
+///     of the other value and the values are compared as typed (see below). Bytes, that
+///     can't be parsed (e.g. non-UTF-8 or `b"foo"` vs an integer), are not equal to
+///     the typed value:
+///     -   integers are parsed from their decimal representation (e.g. `b"-42"`), falling back
+///         to a floating point number (e.g. `b"42.0"` or `b"4.2e1"`),
+///     -   floating point numbers are parsed as `f32` for [`Value::Float`] and as `f64`
+///         for [`Value::Double`] (so `b"0.1"` is equal to `Value::Float(0.1)`),
+///     -   dates are parsed from `YYYY-MM-DD` optionally followed by ` hh:mm:ss` and
+///         a fraction of up to 6 digits,
+///     -   times are parsed from `[-]h:mm:ss` (hours may exceed 24) optionally followed by
+///         a fraction of up to 6 digits;
+/// *   typed values are compared by numeric value across [`Value::Int`], [`Value::UInt`],
+///     [`Value::Float`] and [`Value::Double`] (integers vs floats are compared as `f64`,
+///     [`Value::Float`] vs [`Value::Double`] are compared as `f32`), dates and times are
+///     compared field by field, other combinations are not equal.
+///
+/// ```
+/// # use mysql_async::{prelude::*, Value};
+/// assert!(Value::Int(1).loose_eq(&Value::Bytes(b"1".to_vec())));
+/// assert!(Value::UInt(1).loose_eq(&Value::Double(1.0)));
+/// assert!(Value::Date(2023, 1, 2, 3, 4, 5, 600)
+///     .loose_eq(&Value::Bytes(b"2023-01-02 03:04:05.0006".to_vec())));
+/// assert!(!Value::Bytes(b"1".to_vec()).loose_eq(&Value::Bytes(b"1.0".to_vec())));
+/// assert!(!Value::NULL.loose_eq(&Value::Bytes(b"NULL".to_vec())));
+/// ```
+pub trait ValueExt {
+    /// Compares values normalizing text protocol representations (see [`ValueExt`]).
+    fn loose_eq(&self, other: &Value) -> bool;
+}
+
+impl ValueExt for Value {
+    fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::NULL, Value::NULL) => true,
+            (Value::NULL, _) | (_, Value::NULL) => false,
+            (Value::Bytes(x), Value::Bytes(y)) => x == y,
+            (Value::Bytes(bytes), typed) | (typed, Value::Bytes(bytes)) => {
+                match parse_as(bytes, typed) {
+                    Some(parsed) => typed_eq(&parsed, typed),
+                    None => false,
+                }
+            }
+            (x, y) => typed_eq(x, y),
+        }
+    }
+}
+
+/// Compares two typed (non-`NULL`, non-bytes) values.
+fn typed_eq(x: &Value, y: &Value) -> bool {
+    use Value::*;
+
+    match (x, y) {
+        (Int(x), Int(y)) => x == y,
+        (UInt(x), UInt(y)) => x == y,
+        (Int(x), UInt(y)) | (UInt(y), Int(x)) => i128::from(*x) == i128::from(*y),
+        (Float(x), Float(y)) => x == y,
+        (Double(x), Double(y)) => x == y,
+        (Float(x), Double(y)) | (Double(y), Float(x)) => *x == *y as f32,
+        (Int(x), Float(y)) | (Float(y), Int(x)) => *x as f64 == f64::from(*y),
+        (Int(x), Double(y)) | (Double(y), Int(x)) => *x as f64 == *y,
+        (UInt(x), Float(y)) | (Float(y), UInt(x)) => *x as f64 == f64::from(*y),
+        (UInt(x), Double(y)) | (Double(y), UInt(x)) => *x as f64 == *y,
+        (Date(..), Date(..)) | (Time(..), Time(..)) => x == y,
+        _ => false,
+    }
+}
+
+/// Parses text representation of a value as a value of the same type as `typed`.
+fn parse_as(bytes: &[u8], typed: &Value) -> Option<Value> {
+    let text = std::str::from_utf8(bytes).ok()?;
+    match typed {
+        Value::Int(_) | Value::UInt(_) => match text.parse::<i64>() {
+            Ok(x) => Some(Value::Int(x)),
+            Err(_) => match text.parse::<u64>() {
+                Ok(x) => Some(Value::UInt(x)),
+                Err(_) => text.parse::<f64>().ok().map(Value::Double),
+            },
+        },
+        Value::Float(_) => text.parse::<f32>().ok().map(Value::Float),
+        Value::Double(_) => text.parse::<f64>().ok().map(Value::Double),
+        Value::Date(..) => parse_date(text),
+        Value::Time(..) => parse_time(text),
+        Value::NULL | Value::Bytes(_) => None,
+    }
+}
+
+/// Parses `YYYY-MM-DD[ hh:mm:ss[.ffffff]]`.
+fn parse_date(text: &str) -> Option<Value> {
+    let (date, time) = match text.split_once(' ') {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut parts = date.splitn(3, '-');
+    let year = parse_fixed(parts.next()?, 4)?;
+    let month = parse_fixed(parts.next()?, 2)?;
+    let day = parse_fixed(parts.next()?, 2)?;
+
+    let (hour, minute, second, micros) = match time {
+        Some(time) => {
+            let (hms, micros) = split_fraction(time)?;
+            let mut parts = hms.splitn(3, ':');
+            (
+                parse_fixed(parts.next()?, 2)?,
+                parse_fixed(parts.next()?, 2)?,
+                parse_fixed(parts.next()?, 2)?,
+                micros,
+            )
+        }
+        None => (0, 0, 0, 0),
+    };
+
+    Some(Value::Date(
+        year as u16,
+        month as u8,
+        day as u8,
+        hour as u8,
+        minute as u8,
+        second as u8,
+        micros,
+    ))
+}
+
+/// Parses `[-]h:mm:ss[.ffffff]`.
+fn parse_time(text: &str) -> Option<Value> {
+    let (is_neg, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+
+    let (hms, micros) = split_fraction(text)?;
+    let mut parts = hms.splitn(3, ':');
+    let hours = parts.next()?;
+    if hours.is_empty() || !hours.bytes().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let hours = hours.parse::<u32>().ok()?;
+    let minute = parse_fixed(parts.next()?, 2)?;
+    let second = parse_fixed(parts.next()?, 2)?;
+
+    Some(Value::Time(
+        is_neg,
+        hours / 24,
+        (hours % 24) as u8,
+        minute as u8,
+        second as u8,
+        micros,
+    ))
+}
+
+/// Splits `text` into the integral part and microseconds of an optional fraction.
+fn split_fraction(text: &str) -> Option<(&str, u32)> {
+    match text.split_once('.') {
+        Some((integral, fraction)) => {
+            if fraction.is_empty()
+                || fraction.len() > 6
+                || !fraction.bytes().all(|x| x.is_ascii_digit())
+            {
+                return None;
+            }
+            let micros = fraction.parse::<u32>().ok()? * 10_u32.pow(6 - fraction.len() as u32);
+            Some((integral, micros))
+        }
+        None => Some((text, 0)),
+    }
+}
+
+/// Parses a number of exactly `len` digits.
+fn parse_fixed(text: &str, len: usize) -> Option<u32> {
+    if text.len() == len && text.bytes().all(|x| x.is_ascii_digit()) {
+        text.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql_common::value::Value::{self, *};
+
+    use super::ValueExt;
+
+    fn text(x: &str) -> Value {
+        Bytes(x.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn should_compare_numbers_loosely() {
+        assert!(Int(-42).loose_eq(&text("-42")));
+        assert!(text("42").loose_eq(&UInt(42)));
+        assert!(UInt(u64::MAX).loose_eq(&text("18446744073709551615")));
+        assert!(Int(42).loose_eq(&text("42.0")));
+        assert!(Int(42).loose_eq(&UInt(42)));
+        assert!(!Int(-1).loose_eq(&UInt(u64::MAX)));
+        assert!(Int(1).loose_eq(&Double(1.0)));
+        assert!(Float(0.1).loose_eq(&text("0.1")));
+        assert!(Float(0.1).loose_eq(&Double(0.1)));
+        assert!(Double(0.1).loose_eq(&text("0.1")));
+        assert!(Double(1.5).loose_eq(&text("1.50")));
+        assert!(!Int(42).loose_eq(&text("foo")));
+        assert!(!Int(42).loose_eq(&text("43")));
+    }
+
+    #[test]
+    fn should_compare_temporal_values_loosely() {
+        assert!(Date(2023, 1, 2, 0, 0, 0, 0).loose_eq(&text("2023-01-02")));
+        assert!(Date(2023, 1, 2, 3, 4, 5, 0).loose_eq(&text("2023-01-02 03:04:05")));
+        assert!(Date(2023, 1, 2, 3, 4, 5, 600).loose_eq(&text("2023-01-02 03:04:05.000600")));
+        assert!(Date(0, 0, 0, 0, 0, 0, 0).loose_eq(&text("0000-00-00 00:00:00")));
+        assert!(!Date(2023, 1, 2, 3, 4, 5, 0).loose_eq(&text("2023-01-02")));
+        assert!(!Date(2023, 1, 2, 0, 0, 0, 0).loose_eq(&text("2023-1-2")));
+
+        assert!(Time(false, 0, 1, 2, 3, 0).loose_eq(&text("01:02:03")));
+        assert!(Time(true, 1, 2, 3, 4, 500_000).loose_eq(&text("-26:03:04.5")));
+        assert!(Time(false, 34, 22, 59, 59, 0).loose_eq(&text("838:59:59")));
+        assert!(!Time(false, 0, 1, 2, 3, 0).loose_eq(&text("01:02")));
+    }
+
+    #[test]
+    fn should_compare_other_values_strictly() {
+        assert!(NULL.loose_eq(&NULL));
+        assert!(!NULL.loose_eq(&text("")));
+        assert!(!Int(0).loose_eq(&NULL));
+        assert!(text("1").loose_eq(&text("1")));
+        assert!(!text("1").loose_eq(&text("1.0")));
+        assert!(!Bytes(vec![0xff]).loose_eq(&Int(1)));
+        assert!(!Int(1).loose_eq(&Date(1, 0, 0, 0, 0, 0, 0)));
+    }
+}
@@ -4,17 +4,27 @@ use mysql_async::{
     futures::{DisconnectPool, GetConn},
     params,
     prelude::{
-        BatchQuery, FromRow, FromValue, GlobalHandler, Protocol, Query, Queryable, StatementLike,
-        ToValue,
+        BatchQuery, FromResultSets, FromRow, FromValue, GlobalHandler, MySqlEnum, Protocol, Query,
+        Queryable, RowExt, StatementLike, ToValue, ValueExt,
     },
-    BinaryProtocol, Column, Conn, Deserialized, DriverError, Error, FromRowError, FromValueError,
-    GnoInterval, Gtids, IoError, IsolationLevel, OkPacket, Opts, OptsBuilder, Params, ParseError,
-    Pool, PoolConstraints, PoolOpts, QueryResult, Result, Row, Schema, Serialized, ServerError,
-    SessionStateChange, SessionStateInfo, Sid, SslOpts, Statement, SystemVariable, TextProtocol,
+    quote_identifier, quote_string, AlterProgress, BatchFailure, BatchOpts, BatchProgress,
+    BatchReport, BinaryProtocol, BitValue, BufferStrategy, CloseReason, Collated, Column, Columns,
+    Conn, ConnectStats, CredentialProvider, CsvOpts, CsvQuoting, Cursor, Deserialized, DriverError,
+    EndpointAddr, EnumValue, Error, FlushOption, FromRowError, FromValueError, GnoInterval, Gtids,
+    IoError, IsolationLevel, LongData, OkPacket, Opts, OptsBuilder, Params, ParseError, Pool,
+    PoolBuilder, PoolConstraints, PoolEvent, PoolEventHandler, PoolMetrics, PoolOpts, PoolStatus,
+    QueryResult, ReaderSelection, ReplicatedPool, Result, Row, Schema, Serialized, ServerError,
+    ServerFlavor, SessionProfile, SessionStateChange, SessionStateInfo, SetValue, Sid,
+    SignedDuration, SlowQueryHandler, SslOpts, Statement, SystemVariable, TextProtocol,
     Transaction, TransactionCharacteristics, TransactionState, TxOpts, Unsupported, UrlError,
-    Value, WhiteListFsHandler, DEFAULT_INACTIVE_CONNECTION_TTL, DEFAULT_TTL_CHECK_INTERVAL,
+    Value, Warning, WarningLevel, WhiteListFsHandler, CON_COUNT_ERROR_BACKOFF,
+    CON_COUNT_ERROR_RETRIES, DEFAULT_COMPRESSION_THRESHOLD, DEFAULT_INACTIVE_CONNECTION_TTL,
+    DEFAULT_TTL_CHECK_INTERVAL,
 };
 
 #[cfg(feature = "binlog")]
 #[allow(unused_imports)]
-use mysql_async::{binlog, BinlogStream, BinlogStreamRequest};
+use mysql_async::{
+    binlog, BinlogStream, BinlogStreamEvent, BinlogStreamRequest, ReconnectPolicy,
+    ResilientBinlogStream, RowChange,
+};